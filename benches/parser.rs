@@ -0,0 +1,113 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use iced_rs_test::benchmarks::{
+    parse_game_template_file, CompilerStatistics, GameTemplate, GameTime, TokenIterator, TokenKind,
+    ValueTokenKind,
+};
+
+const ACTION_COUNT: usize = 2000;
+/// The tokens of each action of [`synthetic_template`], where string values are split into words.
+const TOKENS_PER_ACTION: usize = 37;
+
+/// A template with many independent actions, each using the common kinds of values.
+fn synthetic_template(action_count: usize) -> String {
+    (0..action_count)
+        .map(|index| {
+            format!(
+                "ACTION bench_action_{index}
+name Bench action {index}
+progressive benchmarking
+simple_past benchmarked
+type TRAIN
+str 0.5
+dex 0.5
+duration 1h+30m
+currency 10
+activation and(level_geq({level}), action_count(3, wait))
+deactivation never
+
+",
+                level = index % 100,
+            )
+        })
+        .collect()
+}
+
+async fn count_tokens(input: &[u8]) -> usize {
+    let mut tokens = TokenIterator::new(input);
+    let mut count = 0;
+    while tokens.next().await.unwrap().is_some() {
+        count += 1;
+    }
+    count
+}
+
+async fn parse(input: &[u8]) -> GameTemplate {
+    let mut game_template = GameTemplate::default();
+    parse_game_template_file(&mut game_template, input)
+        .await
+        .unwrap();
+    game_template
+}
+
+async fn parse_time(input: &[u8]) -> GameTime {
+    let mut tokens = TokenIterator::new(input);
+    tokens.next().await.unwrap();
+    match tokens
+        .next()
+        .await
+        .unwrap()
+        .map(|token| token.kind().clone())
+    {
+        Some(TokenKind::Value(ValueTokenKind::Time(time))) => time,
+        other => panic!("Expected a time, but got {other:?}"),
+    }
+}
+
+fn tokenizer(criterion: &mut Criterion) {
+    let template = synthetic_template(ACTION_COUNT);
+    let token_count = async_std::task::block_on(count_tokens(template.as_bytes()));
+    assert_eq!(token_count, ACTION_COUNT * TOKENS_PER_ACTION);
+
+    let mut group = criterion.benchmark_group("tokenizer");
+    group.throughput(Throughput::Bytes(template.len() as u64));
+    group.bench_function("synthetic_template", |bencher| {
+        bencher.iter(|| async_std::task::block_on(count_tokens(black_box(template.as_bytes()))))
+    });
+    group.finish();
+}
+
+fn parser(criterion: &mut Criterion) {
+    let template = synthetic_template(ACTION_COUNT);
+    let game_template = async_std::task::block_on(parse(template.as_bytes()));
+    assert_eq!(
+        CompilerStatistics::new(&game_template).actions,
+        ACTION_COUNT
+    );
+
+    let mut group = criterion.benchmark_group("parser");
+    group.throughput(Throughput::Bytes(template.len() as u64));
+    group.bench_function("synthetic_template", |bencher| {
+        bencher.iter(|| async_std::task::block_on(parse(black_box(template.as_bytes()))))
+    });
+    group.finish();
+}
+
+fn game_time(criterion: &mut Criterion) {
+    let input = "duration 1y+2w+3d+4h+5m+6s";
+    assert_eq!(
+        async_std::task::block_on(parse_time(input.as_bytes())),
+        GameTime::from_years(1)
+            + GameTime::from_weeks(2)
+            + GameTime::from_days(3)
+            + GameTime::from_hours(4)
+            + GameTime::from_minutes(5)
+            + GameTime::from_seconds(6)
+    );
+
+    criterion.bench_function("game_time", |bencher| {
+        bencher.iter(|| async_std::task::block_on(parse_time(black_box(input.as_bytes()))))
+    });
+}
+
+criterion_group!(benches, tokenizer, parser, game_time);
+criterion_main!(benches);
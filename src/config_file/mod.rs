@@ -0,0 +1,158 @@
+use crate::RunConfiguration;
+use async_std::path::PathBuf;
+use serde::Deserialize;
+use std::path::Path;
+
+/// The options of [`RunConfiguration`] that can be given in a JSON configuration file.
+/// Options that are missing from the file keep their command line value or default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    savegame_file: Option<String>,
+    savegame_compression_level: Option<u32>,
+    preferences_file: Option<String>,
+    compiled_game_data_file: Option<String>,
+    compiled_game_data_url: Option<String>,
+    compiled_game_data_directory: Option<String>,
+    static_prefix_directory: Option<String>,
+    static_prefix_url: Option<String>,
+    target_fps: Option<f32>,
+    window_width: Option<u32>,
+    window_height: Option<u32>,
+    resizable: Option<bool>,
+    language: Option<String>,
+    profile: Option<bool>,
+    long_update_seconds: Option<f64>,
+    bulk_update_seconds: Option<f64>,
+    record: Option<String>,
+    replay: Option<String>,
+    sound_command: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigFileError {
+    IoError(std::io::Error),
+    JsonError(serde_json::Error),
+}
+
+impl ConfigFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigFileError> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Overwrites the options of the configuration with the options of this file,
+    /// except for those for which `given_on_command_line` returns true.
+    /// It receives the id of the command line argument, which is the kebab-case name of the field.
+    pub fn apply(
+        self,
+        configuration: &mut RunConfiguration,
+        given_on_command_line: impl Fn(&str) -> bool,
+    ) {
+        let merge = |id: &str| !given_on_command_line(id);
+        if let Some(savegame_file) = self.savegame_file.filter(|_| merge("savegame-file")) {
+            configuration.savegame_file = PathBuf::from(savegame_file);
+        }
+        if let Some(savegame_compression_level) = self
+            .savegame_compression_level
+            .filter(|_| merge("savegame-compression-level"))
+        {
+            configuration.savegame_compression_level = Some(savegame_compression_level);
+        }
+        if let Some(preferences_file) = self.preferences_file.filter(|_| merge("preferences-file"))
+        {
+            configuration.preferences_file = PathBuf::from(preferences_file);
+        }
+        if let Some(compiled_game_data_file) = self
+            .compiled_game_data_file
+            .filter(|_| merge("compiled-game-data-file"))
+        {
+            configuration.compiled_game_data_file = PathBuf::from(compiled_game_data_file);
+        }
+        if let Some(compiled_game_data_url) = self
+            .compiled_game_data_url
+            .filter(|_| merge("compiled-game-data-url"))
+        {
+            configuration.compiled_game_data_url = compiled_game_data_url;
+        }
+        if let Some(compiled_game_data_directory) = self
+            .compiled_game_data_directory
+            .filter(|_| merge("compiled-game-data-directory"))
+        {
+            configuration.compiled_game_data_directory =
+                Some(PathBuf::from(compiled_game_data_directory));
+        }
+        if let Some(static_prefix_directory) = self
+            .static_prefix_directory
+            .filter(|_| merge("static-prefix-directory"))
+        {
+            configuration.static_prefix_directory = PathBuf::from(static_prefix_directory);
+        }
+        if let Some(static_prefix_url) = self
+            .static_prefix_url
+            .filter(|_| merge("static-prefix-url"))
+        {
+            configuration.static_prefix_url = static_prefix_url;
+        }
+        if let Some(target_fps) = self.target_fps.filter(|_| merge("target-fps")) {
+            configuration.target_fps = target_fps;
+        }
+        if let Some(window_width) = self.window_width.filter(|_| merge("window-width")) {
+            configuration.window_width = window_width;
+        }
+        if let Some(window_height) = self.window_height.filter(|_| merge("window-height")) {
+            configuration.window_height = window_height;
+        }
+        if let Some(resizable) = self.resizable.filter(|_| merge("resizable")) {
+            configuration.resizable = resizable;
+        }
+        if let Some(language) = self.language.filter(|_| merge("language")) {
+            configuration.language = Some(language);
+        }
+        if let Some(profile) = self.profile.filter(|_| merge("profile")) {
+            configuration.profile = profile;
+        }
+        if let Some(long_update_seconds) = self
+            .long_update_seconds
+            .filter(|_| merge("long-update-seconds"))
+        {
+            configuration.long_update_seconds = long_update_seconds;
+        }
+        if let Some(bulk_update_seconds) = self
+            .bulk_update_seconds
+            .filter(|_| merge("bulk-update-seconds"))
+        {
+            configuration.bulk_update_seconds = bulk_update_seconds;
+        }
+        if let Some(record) = self.record.filter(|_| merge("record")) {
+            configuration.record = Some(PathBuf::from(record));
+        }
+        if let Some(replay) = self.replay.filter(|_| merge("replay")) {
+            configuration.replay = Some(PathBuf::from(replay));
+        }
+        if let Some(sound_command) = self.sound_command.filter(|_| merge("sound-command")) {
+            configuration.sound_command = sound_command;
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigFileError {
+    fn from(error: std::io::Error) -> Self {
+        Self::IoError(error)
+    }
+}
+
+impl From<serde_json::Error> for ConfigFileError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::JsonError(error)
+    }
+}
+
+impl std::fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigFileError::IoError(error) => write!(f, "could not read config file: {error}"),
+            ConfigFileError::JsonError(error) => write!(f, "invalid config file: {error}"),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::game_state::time::GameTime;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A temporary effect on the character that lasts until it expires.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Sequence, Eq, PartialEq)]
+pub enum BuffKind {
+    Inspired,
+    Empowered,
+}
+
+/// A buff granted by an exploration event or an item, lasting for the given duration.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Buff {
+    pub kind: BuffKind,
+    pub duration: GameTime,
+}
+
+impl BuffKind {
+    /// Multiplies all attribute progress while the buff is active.
+    pub fn progress_multiplier(&self) -> f64 {
+        match self {
+            BuffKind::Inspired => 1.5,
+            BuffKind::Empowered => 1.0,
+        }
+    }
+
+    /// Multiplies the damage output of the character while the buff is active.
+    pub fn damage_multiplier(&self) -> f64 {
+        match self {
+            BuffKind::Inspired => 1.0,
+            BuffKind::Empowered => 1.5,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuffKind::Inspired => "INSPIRED",
+            BuffKind::Empowered => "EMPOWERED",
+        }
+    }
+}
+
+impl ToString for BuffKind {
+    fn to_string(&self) -> String {
+        match self {
+            BuffKind::Inspired => "Inspired".to_string(),
+            BuffKind::Empowered => "Empowered".to_string(),
+        }
+    }
+}
+
+impl FromStr for BuffKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        enum_iterator::all::<Self>()
+            .find(|buff_kind| buff_kind.as_str() == s)
+            .ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Buff, BuffKind};
+    use crate::game_state::player_actions::ACTION_EXPLORE;
+    use crate::game_state::tests::{create_test_game_state_with_templates, real_milliseconds};
+    use crate::game_state::time::GameTime;
+    use enum_iterator::all;
+
+    const BUFF_TEMPLATE: &str = "
+ACTION buff_study
+name Study
+progressive studying
+simple_past studied
+type TRAIN
+int 1.0
+duration 1h
+currency 0
+activation none
+deactivation never
+
+LOCATION buff_shrine
+name Shrine
+events (1, buff_blessing)
+activation none
+deactivation never
+
+EXPLORATION_EVENT buff_blessing
+name Blessing
+progressive receiving a blessing
+simple_past received a blessing
+buff INSPIRED
+buff_duration 3h
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_buff_kind_round_trip() {
+        for buff_kind in all::<BuffKind>() {
+            assert_eq!(buff_kind.as_str().parse(), Ok(buff_kind));
+        }
+        assert_eq!("inspired".parse::<BuffKind>(), Err(()));
+    }
+
+    #[test]
+    fn test_buff_boosts_progress_until_expiry() {
+        let mut game_state = create_test_game_state_with_templates(&[BUFF_TEMPLATE]);
+        let unbuffed_multiplier = game_state.progress_multiplier();
+        game_state.grant_buff(Buff {
+            kind: BuffKind::Inspired,
+            duration: GameTime::from_hours(2),
+        });
+        let expiry = game_state.current_time + GameTime::from_hours(2);
+        assert_eq!(game_state.buffs, vec![(BuffKind::Inspired, expiry)]);
+        assert_eq!(
+            game_state.progress_multiplier(),
+            unbuffed_multiplier * BuffKind::Inspired.progress_multiplier()
+        );
+        assert_eq!(game_state.damage_multiplier(), 1.0);
+
+        game_state.update_offline(real_milliseconds(GameTime::from_hours(1)));
+        assert!(game_state.current_time < expiry);
+        assert_eq!(game_state.buffs.len(), 1);
+
+        game_state.update_offline(real_milliseconds(GameTime::from_hours(2)));
+        assert!(game_state.current_time > expiry);
+        assert!(game_state.buffs.is_empty());
+        assert_eq!(game_state.progress_multiplier(), unbuffed_multiplier);
+    }
+
+    #[test]
+    fn test_buffed_action_gains_more_progress() {
+        let mut unbuffed = create_test_game_state_with_templates(&[BUFF_TEMPLATE]);
+        let study = unbuffed
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "buff_study")
+            .unwrap()
+            .id;
+        unbuffed.actions.selected_action = study;
+        let mut buffed = unbuffed.clone();
+        buffed.grant_buff(Buff {
+            kind: BuffKind::Inspired,
+            duration: GameTime::from_days(10),
+        });
+
+        for game_state in [&mut unbuffed, &mut buffed] {
+            game_state.update_offline(real_milliseconds(GameTime::from_days(1)));
+        }
+        assert!(
+            buffed.character.total_attribute_progress()
+                > unbuffed.character.total_attribute_progress()
+        );
+    }
+
+    #[test]
+    fn test_regranting_extends_buff() {
+        let mut game_state = create_test_game_state_with_templates(&[BUFF_TEMPLATE]);
+        let now = game_state.current_time;
+        game_state.grant_buff(Buff {
+            kind: BuffKind::Empowered,
+            duration: GameTime::from_hours(3),
+        });
+        game_state.grant_buff(Buff {
+            kind: BuffKind::Empowered,
+            duration: GameTime::from_hours(1),
+        });
+        assert_eq!(
+            game_state.buffs,
+            vec![(BuffKind::Empowered, now + GameTime::from_hours(3))]
+        );
+        assert_eq!(
+            game_state.damage_multiplier(),
+            BuffKind::Empowered.damage_multiplier()
+        );
+    }
+
+    #[test]
+    fn test_exploration_event_grants_buff() {
+        let mut game_state = create_test_game_state_with_templates(&[BUFF_TEMPLATE]);
+        let shrine = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "buff_shrine")
+            .unwrap()
+            .id;
+        assert!(game_state.world.select_location(shrine));
+        game_state.actions.selected_action = ACTION_EXPLORE;
+
+        let mut granted = false;
+        for _ in 0..48 {
+            game_state.update_offline(real_milliseconds(GameTime::from_minutes(30)));
+            granted |= game_state
+                .buffs
+                .iter()
+                .any(|(buff_kind, _)| *buff_kind == BuffKind::Inspired);
+        }
+        assert!(granted);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::game_state::time::{
+    GameTime, FIRST_DAY_OF_MONTH, MILLISECONDS_PER_DAY, MILLISECONDS_PER_YEAR,
+};
+use crate::game_state::translations::Translator;
+use crate::game_template::IdMaps;
+use serde::{Deserialize, Serialize};
+
+/// When the game clock jumps further than this many years in one update, older occurrences are skipped.
+pub const MAX_CALENDAR_YEARS_PER_UPDATE: i128 = 1000;
+
+/// An event that recurs every year on the same day, e.g. a festival on the 1st of the 5th month.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub id_str: String,
+    pub name: String,
+    /// The month of the event, starting from one.
+    pub month: usize,
+    /// The day of the month of the event, starting from one.
+    pub day: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompiledCalendarEvent {
+    pub id: CalendarEventId,
+    pub id_str: String,
+    pub name: String,
+    /// The day of the year of the event, starting from zero.
+    pub day_of_year: i128,
+}
+
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, Default, Eq, PartialEq, Hash, Ord, PartialOrd,
+)]
+pub struct CalendarEventId(pub usize);
+
+/// The recurring events of the calendar, and how far the game clock has been checked for their occurrences.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Calendar {
+    events: Vec<CompiledCalendarEvent>,
+    checked_until: Option<GameTime>,
+}
+
+impl CalendarEvent {
+    pub fn compile(self, id_maps: &IdMaps) -> CompiledCalendarEvent {
+        CompiledCalendarEvent {
+            id: *id_maps.calendar_events.get(&self.id_str).unwrap(),
+            id_str: self.id_str,
+            name: self.name,
+            day_of_year: FIRST_DAY_OF_MONTH[self.month - 1] + self.day as i128 - 1,
+        }
+    }
+}
+
+impl CompiledCalendarEvent {
+    /// The start of the day of this event in the given year, or `None` if it is beyond the end of time.
+    pub fn occurrence(&self, year: i128) -> Option<GameTime> {
+        year.checked_mul(MILLISECONDS_PER_YEAR)
+            .and_then(|milliseconds| {
+                milliseconds.checked_add(self.day_of_year * MILLISECONDS_PER_DAY)
+            })
+            .map(GameTime::from_milliseconds)
+    }
+}
+
+impl Calendar {
+    pub fn new(events: Vec<CompiledCalendarEvent>) -> Self {
+        Self {
+            events,
+            checked_until: None,
+        }
+    }
+
+    pub fn event(&self, id: CalendarEventId) -> &CompiledCalendarEvent {
+        &self.events[id.0]
+    }
+
+    pub fn translate(&mut self, translator: &Translator) {
+        for event in &mut self.events {
+            translator.translate(&mut event.name);
+        }
+    }
+
+    /// Returns the events that occurred after the previous call, up to and including the given time,
+    /// once per occurrence and in chronological order.
+    /// The first call only starts the calendar and returns no events.
+    pub fn advance(&mut self, time: GameTime) -> Vec<CalendarEventId> {
+        let checked_until = match self.checked_until {
+            Some(checked_until) if checked_until < time => checked_until,
+            Some(_) => return Vec::new(),
+            None => {
+                self.checked_until = Some(time);
+                return Vec::new();
+            }
+        };
+        self.checked_until = Some(time);
+
+        let first_year = checked_until
+            .years()
+            .max(time.years() - MAX_CALENDAR_YEARS_PER_UPDATE);
+        let mut occurrences: Vec<_> = (first_year..=time.years())
+            .flat_map(|year| {
+                self.events
+                    .iter()
+                    .filter_map(move |event| Some((event.occurrence(year)?, event.id)))
+            })
+            .filter(|(occurrence, _)| checked_until < *occurrence && *occurrence <= time)
+            .collect();
+        occurrences.sort();
+        occurrences.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+impl From<usize> for CalendarEventId {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Calendar, CalendarEventId, CompiledCalendarEvent};
+    use crate::game_state::tests::{create_test_game_state_with_templates, real_milliseconds};
+    use crate::game_state::time::GameTime;
+
+    const CALENDAR_TEMPLATE: &str = "
+CALENDAR_EVENT calendar_festival
+name Festival
+month 5
+day 1
+
+ACTION calendar_celebrate
+name Celebrate
+progressive celebrating
+simple_past celebrated
+type TRAIN
+chr 1.0
+duration 1h
+currency 0
+activation calendar_event_count(3, calendar_festival)
+deactivation never
+";
+
+    fn compiled_event(id: usize, day_of_year: i128) -> CompiledCalendarEvent {
+        CompiledCalendarEvent {
+            id: CalendarEventId(id),
+            id_str: format!("event_{id}"),
+            name: format!("Event {id}"),
+            day_of_year,
+        }
+    }
+
+    #[test]
+    fn test_yearly_event_in_multi_year_advance() {
+        let mut calendar = Calendar::new(vec![compiled_event(0, 120)]);
+        assert!(calendar.advance(GameTime::from_years(100)).is_empty());
+        assert_eq!(
+            calendar.advance(GameTime::from_years(110)),
+            vec![CalendarEventId(0); 10]
+        );
+        assert!(calendar.advance(GameTime::from_years(110)).is_empty());
+        assert_eq!(
+            calendar.advance(GameTime::from_years(110) + GameTime::from_days(120)),
+            vec![CalendarEventId(0)]
+        );
+    }
+
+    #[test]
+    fn test_occurrences_are_chronological() {
+        let mut calendar = Calendar::new(vec![compiled_event(0, 200), compiled_event(1, 10)]);
+        calendar.advance(GameTime::from_days(100));
+        assert_eq!(
+            calendar.advance(GameTime::from_years(2)),
+            vec![CalendarEventId(0), CalendarEventId(1), CalendarEventId(0)]
+        );
+    }
+
+    #[test]
+    fn test_festival_triggers_across_bulk_update() {
+        let mut game_state = create_test_game_state_with_templates(&[CALENDAR_TEMPLATE]);
+        let festival = game_state.calendar.event(CalendarEventId(0));
+        assert_eq!(festival.id_str, "calendar_festival");
+        assert_eq!(festival.day_of_year, 120);
+        let celebrate_available = |game_state: &crate::game_state::GameState| {
+            game_state
+                .actions
+                .list_choosable()
+                .any(|action| action.id_str == "calendar_celebrate")
+        };
+
+        game_state.update_offline(real_milliseconds(GameTime::from_years(2)));
+        assert!(!celebrate_available(&game_state));
+        game_state.update_offline(real_milliseconds(GameTime::from_years(1)));
+        assert!(celebrate_available(&game_state));
+    }
+}
@@ -1,10 +1,16 @@
+use crate::game_state::currency::Currency;
 use crate::game_state::time::GameTime;
 use crate::game_state::triggers::CompiledGameEvent;
 use enum_iterator::Sequence;
 use rand_distr::num_traits::Zero;
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::str::FromStr;
 use std::{iter, ops};
 
+/// The cost of a respec per attribute level, see [`Character::respec_cost`].
+pub const RESPEC_COST_PER_ATTRIBUTE_LEVEL: Currency = Currency::from_copper(10);
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Character {
     pub name: String,
@@ -83,18 +89,52 @@ impl Character {
         }
     }
 
+    /// Renames the character, ignoring surrounding whitespace.
+    /// Returns false and keeps the old name if the new name is empty.
+    pub fn rename(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() {
+            false
+        } else {
+            self.name = name.to_string();
+            true
+        }
+    }
+
     pub fn add_attribute_progress(
         &mut self,
         progress: CharacterAttributeProgress,
     ) -> impl Iterator<Item = CompiledGameEvent> {
         let events = iter::empty();
-        let progress = progress * self.race.attribute_progress_factors();
+        let progress = self.effective_attribute_progress(progress);
         self.attribute_progress += progress;
         let events = events.chain(self.attributes.check_progress(&mut self.attribute_progress));
 
         events.chain(self.add_level_progress(progress.sum()))
     }
 
+    /// The progress that [`add_attribute_progress`](Self::add_attribute_progress) adds for the given progress.
+    pub fn effective_attribute_progress(
+        &self,
+        progress: CharacterAttributeProgress,
+    ) -> CharacterAttributeProgress {
+        progress * self.race.attribute_progress_factors()
+    }
+
+    /// The effective attribute progress per game hour when gaining the given progress over the given time.
+    /// Its sum is the level progress per game hour.
+    pub fn attribute_progress_per_hour(
+        &self,
+        progress: CharacterAttributeProgress,
+        time: GameTime,
+    ) -> CharacterAttributeProgress {
+        if time <= GameTime::zero() {
+            return CharacterAttributeProgress::zero();
+        }
+        let hours = time.milliseconds() as f64 / GameTime::from_hours(1).milliseconds() as f64;
+        self.effective_attribute_progress(progress * hours.recip())
+    }
+
     pub fn add_level_progress(&mut self, progress: u64) -> impl Iterator<Item = CompiledGameEvent> {
         self.level_progress += progress;
         let mut level_event = None;
@@ -122,6 +162,156 @@ impl Character {
         &self.attribute_progress
     }
 
+    /// All attribute progress gained since the character was created, including the progress spent on attribute levels.
+    pub fn total_attribute_progress(&self) -> u64 {
+        let starting_attributes = self.race.starting_basic_attributes().to_array();
+        self.attributes
+            .to_array()
+            .into_iter()
+            .zip(starting_attributes)
+            .map(|(level, starting_level)| {
+                (starting_level..level)
+                    .map(CharacterAttributes::required_attribute_progress)
+                    .sum::<u64>()
+            })
+            .sum::<u64>()
+            + self.attribute_progress.sum()
+    }
+
+    /// The level and the progress towards the next level of the attribute with the largest of the given factors.
+    /// Ties go to the first attribute in the order strength, stamina, dexterity, intelligence, wisdom, charisma.
+    pub fn trained_attribute_standing(
+        &self,
+        factor: CharacterAttributeProgressFactor,
+    ) -> (u64, u64) {
+        let factors = factor.to_array();
+        let attribute = (0..factors.len()).fold(0, |trained, attribute| {
+            if factors[attribute] > factors[trained] {
+                attribute
+            } else {
+                trained
+            }
+        });
+        (
+            self.attributes.to_array()[attribute],
+            self.attribute_progress.to_array()[attribute],
+        )
+    }
+
+    /// The currency required for a respec, proportional to the total attribute levels.
+    pub fn respec_cost(&self) -> Currency {
+        RESPEC_COST_PER_ATTRIBUTE_LEVEL * self.attributes.sum() as f64
+    }
+
+    /// Resets the attributes to the starting attributes of the race and spends the total attribute progress again.
+    /// Each level goes to the attribute that is furthest below its target level.
+    /// Progress that does not suffice for the next level is kept for the attribute that would be raised next,
+    /// and progress beyond the target goes to the attribute with the highest target level.
+    pub fn respec(
+        &mut self,
+        target: &CharacterAttributes,
+    ) -> impl Iterator<Item = CompiledGameEvent> {
+        let previous_attributes = self.attributes;
+        let target = target.to_array();
+        let mut remaining_progress = self.total_attribute_progress();
+        let mut attributes = self.race.starting_basic_attributes().to_array();
+        let mut progress = [0; 6];
+
+        loop {
+            let (index, missing_levels) = (0..attributes.len())
+                .map(|index| (index, target[index].saturating_sub(attributes[index])))
+                .max_by_key(|(index, missing_levels)| (*missing_levels, Reverse(*index)))
+                .unwrap();
+            let required_progress =
+                CharacterAttributes::required_attribute_progress(attributes[index]);
+            if missing_levels > 0 && remaining_progress >= required_progress {
+                remaining_progress -= required_progress;
+                attributes[index] += 1;
+                continue;
+            }
+
+            let index = if missing_levels > 0 {
+                index
+            } else {
+                (0..target.len())
+                    .max_by_key(|index| (target[*index], Reverse(*index)))
+                    .unwrap()
+            };
+            progress[index] = remaining_progress;
+            break;
+        }
+
+        self.attributes = CharacterAttributes::from_array(attributes);
+        self.attribute_progress = CharacterAttributeProgress::from_array(progress);
+        self.attributes
+            .check_progress(&mut self.attribute_progress)
+            .for_each(drop);
+
+        self.attribute_change_events(previous_attributes)
+    }
+
+    /// Raises the attributes by the given levels without spending attribute progress.
+    pub fn add_attribute_levels(
+        &mut self,
+        levels: &CharacterAttributes,
+    ) -> impl Iterator<Item = CompiledGameEvent> {
+        let previous_attributes = self.attributes;
+        let mut attributes = self.attributes.to_array();
+        for (attribute, levels) in attributes.iter_mut().zip(levels.to_array()) {
+            *attribute += levels;
+        }
+        self.attributes = CharacterAttributes::from_array(attributes);
+
+        self.attribute_change_events(previous_attributes)
+    }
+
+    /// The events for the attributes that differ from the given previous attributes.
+    fn attribute_change_events(
+        &self,
+        previous_attributes: CharacterAttributes,
+    ) -> impl Iterator<Item = CompiledGameEvent> {
+        let attributes = self.attributes;
+        [
+            (previous_attributes.strength != attributes.strength).then_some(
+                CompiledGameEvent::PlayerStrengthChanged {
+                    value: attributes.strength,
+                },
+            ),
+            (previous_attributes.stamina != attributes.stamina).then_some(
+                CompiledGameEvent::PlayerStaminaChanged {
+                    value: attributes.stamina,
+                },
+            ),
+            (previous_attributes.dexterity != attributes.dexterity).then_some(
+                CompiledGameEvent::PlayerDexterityChanged {
+                    value: attributes.dexterity,
+                },
+            ),
+            (previous_attributes.intelligence != attributes.intelligence).then_some(
+                CompiledGameEvent::PlayerIntelligenceChanged {
+                    value: attributes.intelligence,
+                },
+            ),
+            (previous_attributes.wisdom != attributes.wisdom).then_some(
+                CompiledGameEvent::PlayerWisdomChanged {
+                    value: attributes.wisdom,
+                },
+            ),
+            (previous_attributes.charisma != attributes.charisma).then_some(
+                CompiledGameEvent::PlayerCharismaChanged {
+                    value: attributes.charisma,
+                },
+            ),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The damage the character can take in combat before being overwhelmed.
+    pub fn hitpoints(&self) -> f64 {
+        10.0 + 10.0 * self.attributes().stamina as f64
+    }
+
     pub fn damage_output(&self) -> f64 {
         let attributes = self.attributes();
         match self.selected_combat_style {
@@ -222,6 +412,12 @@ impl CharacterRace {
             CharacterRace::Dwarf => CombatStyle::CloseContact,
         }
     }
+
+    /// The url of the portrait of the race relative to the static directory.
+    /// The asset is optional, the race name is shown instead if it cannot be loaded.
+    pub fn image_url(&self) -> String {
+        format!("races/{}.png", self.to_string().to_lowercase())
+    }
 }
 
 impl ToString for CharacterRace {
@@ -322,6 +518,27 @@ impl CharacterAttributes {
     pub fn required_attribute_progress(attribute_level: u64) -> u64 {
         attribute_level * GameTime::from_hours(1).milliseconds() as u64
     }
+
+    pub fn sum(&self) -> u64 {
+        self.to_array().into_iter().sum()
+    }
+
+    fn to_array(self) -> [u64; 6] {
+        [
+            self.strength,
+            self.stamina,
+            self.dexterity,
+            self.intelligence,
+            self.wisdom,
+            self.charisma,
+        ]
+    }
+
+    fn from_array(
+        [strength, stamina, dexterity, intelligence, wisdom, charisma]: [u64; 6],
+    ) -> Self {
+        Self::new(strength, stamina, dexterity, intelligence, wisdom, charisma)
+    }
 }
 
 #[allow(dead_code)]
@@ -357,6 +574,23 @@ impl CharacterAttributeProgress {
         Default::default()
     }
 
+    fn to_array(self) -> [u64; 6] {
+        [
+            self.strength,
+            self.stamina,
+            self.dexterity,
+            self.intelligence,
+            self.wisdom,
+            self.charisma,
+        ]
+    }
+
+    fn from_array(
+        [strength, stamina, dexterity, intelligence, wisdom, charisma]: [u64; 6],
+    ) -> Self {
+        Self::new(strength, stamina, dexterity, intelligence, wisdom, charisma)
+    }
+
     pub fn from_strength(strength: u64) -> Self {
         let mut result = Self::zero();
         result.strength = strength;
@@ -392,6 +626,25 @@ impl CharacterAttributeProgress {
         result.charisma = charisma;
         result
     }
+
+    /// Subtracts the given progress from this progress, clamping each attribute at zero.
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self {
+            strength: self.strength.saturating_sub(rhs.strength),
+            stamina: self.stamina.saturating_sub(rhs.stamina),
+            dexterity: self.dexterity.saturating_sub(rhs.dexterity),
+            intelligence: self.intelligence.saturating_sub(rhs.intelligence),
+            wisdom: self.wisdom.saturating_sub(rhs.wisdom),
+            charisma: self.charisma.saturating_sub(rhs.charisma),
+        }
+    }
+
+    /// Scales each attribute by the given non-negative factor, rounding to the nearest integer.
+    /// Halfway cases are rounded away from zero.
+    pub fn scale(self, factor: f64) -> Self {
+        assert!(factor.is_finite());
+        self * factor
+    }
 }
 
 #[allow(dead_code)]
@@ -461,6 +714,40 @@ impl CharacterAttributeProgressFactor {
         result
     }
 
+    fn to_array(self) -> [f64; 6] {
+        [
+            self.strength,
+            self.stamina,
+            self.dexterity,
+            self.intelligence,
+            self.wisdom,
+            self.charisma,
+        ]
+    }
+
+    /// Scales the factors such that they sum up to one, keeping their ratios.
+    /// All-zero factors are returned unchanged.
+    pub fn normalized(self) -> Self {
+        let sum = self.strength
+            + self.stamina
+            + self.dexterity
+            + self.intelligence
+            + self.wisdom
+            + self.charisma;
+        if sum.is_zero() {
+            return self;
+        }
+
+        Self {
+            strength: self.strength / sum,
+            stamina: self.stamina / sum,
+            dexterity: self.dexterity / sum,
+            intelligence: self.intelligence / sum,
+            wisdom: self.wisdom / sum,
+            charisma: self.charisma / sum,
+        }
+    }
+
     pub fn into_progress(self, time: GameTime) -> CharacterAttributeProgress {
         CharacterAttributeProgress {
             strength: (self.strength * time.milliseconds() as f64).round() as u64,
@@ -497,6 +784,14 @@ impl ops::Mul<CharacterAttributeProgressFactor> for CharacterAttributeProgress {
     }
 }
 
+impl ops::Mul<f64> for CharacterAttributeProgress {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        self * CharacterAttributeProgressFactor::new(rhs, rhs, rhs, rhs, rhs, rhs)
+    }
+}
+
 impl ops::Add for CharacterAttributeProgress {
     type Output = Self;
 
@@ -518,6 +813,33 @@ impl ops::AddAssign for CharacterAttributeProgress {
     }
 }
 
+impl ops::Sub for CharacterAttributeProgress {
+    type Output = Self;
+
+    /// Subtracts the given progress from this progress.
+    /// Underflows are a bug and panic in debug builds, while release builds clamp at zero.
+    fn sub(self, rhs: Self) -> Self::Output {
+        debug_assert!(
+            self.strength >= rhs.strength
+                && self.stamina >= rhs.stamina
+                && self.dexterity >= rhs.dexterity
+                && self.intelligence >= rhs.intelligence
+                && self.wisdom >= rhs.wisdom
+                && self.charisma >= rhs.charisma,
+            "attribute progress underflow: {:?} - {:?}",
+            self,
+            rhs
+        );
+        self.saturating_sub(rhs)
+    }
+}
+
+impl ops::SubAssign for CharacterAttributeProgress {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
 impl PartialEq for CharacterAttributeProgressFactor {
     fn eq(&self, other: &Self) -> bool {
         self.assert_float_normal();
@@ -533,6 +855,42 @@ impl PartialEq for CharacterAttributeProgressFactor {
 
 impl Eq for CharacterAttributeProgressFactor {}
 
+impl CombatStyle {
+    /// Splits training progress across the attributes of this style in the ratio they contribute to its damage output.
+    /// The factors sum up to one.
+    pub fn attribute_progress_factor(&self) -> CharacterAttributeProgressFactor {
+        match self {
+            CombatStyle::CloseContact => {
+                CharacterAttributeProgressFactor::new(0.45, 0.45, 0.1, 0.0, 0.0, 0.0)
+            }
+            CombatStyle::Ranged => {
+                CharacterAttributeProgressFactor::new(0.1, 0.1, 0.8, 0.0, 0.0, 0.0)
+            }
+            CombatStyle::Magic => {
+                CharacterAttributeProgressFactor::new(0.0, 0.0, 0.0, 0.4, 0.6, 0.0)
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CombatStyle::CloseContact => "CLOSE_CONTACT",
+            CombatStyle::Ranged => "RANGED",
+            CombatStyle::Magic => "MAGIC",
+        }
+    }
+}
+
+impl FromStr for CombatStyle {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        enum_iterator::all::<Self>()
+            .find(|combat_style| combat_style.as_str() == s)
+            .ok_or(())
+    }
+}
+
 impl ToString for CombatStyle {
     fn to_string(&self) -> String {
         match self {
@@ -542,3 +900,203 @@ impl ToString for CombatStyle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Character, CharacterAttributeProgress, CharacterAttributeProgressFactor,
+        CharacterAttributes, CharacterRace, CombatStyle,
+    };
+    use crate::game_state::time::GameTime;
+    use crate::game_state::triggers::CompiledGameEvent;
+    use enum_iterator::all;
+
+    #[test]
+    fn test_combat_style_round_trip() {
+        for combat_style in all::<CombatStyle>() {
+            assert_eq!(combat_style.as_str().parse(), Ok(combat_style));
+            let factor = combat_style.attribute_progress_factor();
+            let sum = factor.strength
+                + factor.stamina
+                + factor.dexterity
+                + factor.intelligence
+                + factor.wisdom
+                + factor.charisma;
+            assert!((sum - 1.0).abs() < 1e-9);
+        }
+        assert_eq!("magic".parse::<CombatStyle>(), Err(()));
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut character =
+            Character::new("Hugo".to_string(), "He".to_string(), CharacterRace::Human);
+        assert!(character.rename("  Hilda "));
+        assert_eq!(character.name, "Hilda");
+    }
+
+    #[test]
+    fn test_rename_rejects_empty_name() {
+        let mut character =
+            Character::new("Hugo".to_string(), "He".to_string(), CharacterRace::Human);
+        assert!(!character.rename(""));
+        assert!(!character.rename(" \t "));
+        assert_eq!(character.name, "Hugo");
+    }
+
+    #[test]
+    fn test_normalized_preserves_ratios() {
+        let factor =
+            CharacterAttributeProgressFactor::new(3.0, 1.0, 0.0, 2.0, 0.0, 2.0).normalized();
+        let sum = factor.strength
+            + factor.stamina
+            + factor.dexterity
+            + factor.intelligence
+            + factor.wisdom
+            + factor.charisma;
+        assert!((sum - 1.0).abs() < 1e-12);
+        assert!((factor.strength - 0.375).abs() < 1e-12);
+        assert!((factor.strength / factor.stamina - 3.0).abs() < 1e-12);
+        assert!((factor.intelligence / factor.charisma - 1.0).abs() < 1e-12);
+        assert_eq!(factor.dexterity, 0.0);
+        assert_eq!(factor.wisdom, 0.0);
+    }
+
+    #[test]
+    fn test_normalized_all_zero() {
+        let factor = CharacterAttributeProgressFactor::zero().normalized();
+        for value in [
+            factor.strength,
+            factor.stamina,
+            factor.dexterity,
+            factor.intelligence,
+            factor.wisdom,
+            factor.charisma,
+        ] {
+            assert_eq!(value, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_respec_redistributes_progress() {
+        let mut character =
+            Character::new("Hugo".to_string(), "He".to_string(), CharacterRace::Human);
+        // one level each of strength and stamina
+        let _ = character.add_attribute_progress(CharacterAttributeProgress::new(
+            CharacterAttributes::required_attribute_progress(1),
+            CharacterAttributes::required_attribute_progress(1),
+            0,
+            0,
+            0,
+            0,
+        ));
+        assert_eq!(
+            character.attributes(),
+            &CharacterAttributes::new(2, 2, 1, 1, 1, 2)
+        );
+        let total_attribute_progress = character.total_attribute_progress();
+        assert_eq!(
+            total_attribute_progress,
+            2 * CharacterAttributes::required_attribute_progress(1)
+        );
+
+        let events: Vec<_> = character
+            .respec(&CharacterAttributes::new(1, 1, 2, 1, 1, 2))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                CompiledGameEvent::PlayerStrengthChanged { value: 1 },
+                CompiledGameEvent::PlayerStaminaChanged { value: 1 },
+                CompiledGameEvent::PlayerDexterityChanged { value: 2 },
+            ]
+        );
+        // the remaining progress is kept for dexterity, which has the highest target
+        assert_eq!(
+            character.attributes(),
+            &CharacterAttributes::new(1, 1, 2, 1, 1, 2)
+        );
+        assert_eq!(
+            character.attribute_progress().sum(),
+            CharacterAttributes::required_attribute_progress(1)
+        );
+        assert_eq!(
+            character.total_attribute_progress(),
+            total_attribute_progress
+        );
+    }
+
+    #[test]
+    fn test_attribute_progress_per_hour() {
+        let factor = CharacterAttributeProgressFactor::new(1.0, 0.5, 0.0, 0.0, 0.0, 2.0);
+        let hour = GameTime::from_hours(1).milliseconds() as u64;
+        for (race, expected) in [
+            (
+                CharacterRace::Human,
+                CharacterAttributeProgress::new(hour, hour / 2, 0, 0, 0, hour * 22 / 10),
+            ),
+            (
+                CharacterRace::Orc,
+                CharacterAttributeProgress::new(hour * 11 / 10, hour * 55 / 100, 0, 0, 0, hour * 2),
+            ),
+        ] {
+            let character = Character::new("Hugo".to_string(), "He".to_string(), race);
+            let progress_per_hour = character.attribute_progress_per_hour(
+                factor.into_progress(GameTime::from_hours(4)),
+                GameTime::from_hours(4),
+            );
+            assert_eq!(progress_per_hour, expected);
+        }
+
+        let character = Character::new("Hugo".to_string(), "He".to_string(), CharacterRace::Human);
+        assert_eq!(
+            character.attribute_progress_per_hour(
+                factor.into_progress(GameTime::from_hours(1)),
+                GameTime::zero()
+            ),
+            CharacterAttributeProgress::zero()
+        );
+    }
+
+    #[test]
+    fn test_attribute_progress_subtraction() {
+        let progress = CharacterAttributeProgress::new(10, 5, 0, 7, 3, 1);
+        let subtrahend = CharacterAttributeProgress::new(4, 6, 1, 7, 0, 2);
+        assert_eq!(
+            progress.saturating_sub(subtrahend),
+            CharacterAttributeProgress::new(6, 0, 0, 0, 3, 0)
+        );
+
+        let mut progress = CharacterAttributeProgress::new(10, 5, 1, 7, 3, 2);
+        progress -= CharacterAttributeProgress::new(4, 5, 1, 0, 3, 1);
+        assert_eq!(progress, CharacterAttributeProgress::new(6, 0, 0, 7, 0, 1));
+        assert_eq!(
+            progress + CharacterAttributeProgress::from_wisdom(2)
+                - CharacterAttributeProgress::from_wisdom(2),
+            progress
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "attribute progress underflow")]
+    fn test_attribute_progress_subtraction_underflow() {
+        let _ = CharacterAttributeProgress::from_strength(1)
+            - CharacterAttributeProgress::from_strength(2);
+    }
+
+    #[test]
+    fn test_attribute_progress_scale() {
+        let progress = CharacterAttributeProgress::new(10, 3, 5, 1, 0, 1000);
+        assert_eq!(
+            progress.scale(0.25),
+            CharacterAttributeProgress::new(3, 1, 1, 0, 0, 250)
+        );
+        assert_eq!(
+            progress.scale(0.5),
+            CharacterAttributeProgress::new(5, 2, 3, 1, 0, 500)
+        );
+        assert_eq!(progress.scale(1.0), progress);
+        assert_eq!(progress.scale(0.0), CharacterAttributeProgress::zero());
+    }
+}
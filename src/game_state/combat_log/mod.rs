@@ -0,0 +1,244 @@
+use crate::game_state::player_actions::PlayerActionInProgress;
+use crate::game_state::time::GameTime;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// The number of most recent hits of the current fight that are kept.
+pub const COMBAT_LOG_LENGTH: usize = 8;
+/// The time between two exchanges of blows in a fight.
+pub const HIT_INTERVAL: GameTime = GameTime::from_minutes(1);
+/// The chance of a hit to miss and deal no damage.
+pub const MISS_CHANCE: f64 = 0.1;
+/// The chance of a hit to be critical and deal double damage.
+pub const CRITICAL_CHANCE: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CombatHit {
+    /// The time since the start of the fight.
+    pub elapsed: GameTime,
+    pub attacker: CombatSide,
+    pub outcome: HitOutcome,
+    pub damage: f64,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub enum CombatSide {
+    Character,
+    Monster,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub enum HitOutcome {
+    Hit,
+    Miss,
+    Critical,
+}
+
+/// The most recent hits of the fight in progress, cleared when the fight ends.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CombatLog {
+    /// The start of the logged fight.
+    fight_start: Option<GameTime>,
+    /// The number of exchanges of blows of the logged fight that were resolved so far.
+    exchange_count: u64,
+    hits: VecDeque<CombatHit>,
+}
+
+impl CombatHit {
+    /// Resolves a hit with the given regular damage from the roll with the given index of the seeded fight.
+    pub fn roll(
+        seed: u64,
+        index: u64,
+        elapsed: GameTime,
+        attacker: CombatSide,
+        damage: f64,
+    ) -> Self {
+        let roll = hit_roll(seed, index);
+        let (outcome, damage) = if roll < MISS_CHANCE {
+            (HitOutcome::Miss, 0.0)
+        } else if roll >= 1.0 - CRITICAL_CHANCE {
+            (HitOutcome::Critical, 2.0 * damage)
+        } else {
+            (HitOutcome::Hit, damage)
+        };
+        Self {
+            elapsed,
+            attacker,
+            outcome,
+            damage,
+        }
+    }
+}
+
+impl CombatLog {
+    /// Adds the hits of the given action up to the given time if it is a fight, and clears the log otherwise.
+    /// The log is restarted when a new fight starts, including the fight against the next monster of a pack.
+    pub fn update(&mut self, action: &PlayerActionInProgress, current_time: GameTime) {
+        let (fight_start, combat_hitpoints) =
+            if let Some((_, fight_start, combat_hitpoints)) = action.current_fight(current_time) {
+                (fight_start, combat_hitpoints)
+            } else {
+                self.clear();
+                return;
+            };
+        if self.fight_start != Some(fight_start) {
+            self.clear();
+            self.fight_start = Some(fight_start);
+        }
+
+        let exchange_count = combat_hitpoints.exchange_count(current_time - fight_start);
+        // older exchanges would be dropped right away
+        let first_exchange = self
+            .exchange_count
+            .max(exchange_count.saturating_sub(COMBAT_LOG_LENGTH as u64));
+        for exchange in first_exchange..exchange_count {
+            for hit in combat_hitpoints.exchange(exchange) {
+                while self.hits.len() >= COMBAT_LOG_LENGTH {
+                    self.hits.pop_front();
+                }
+                self.hits.push_back(hit);
+            }
+        }
+        self.exchange_count = self.exchange_count.max(exchange_count);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hits.is_empty()
+    }
+
+    /// Iterates the logged hits, newest first.
+    pub fn iter_rev(&self) -> impl Iterator<Item = &CombatHit> {
+        self.hits.iter().rev()
+    }
+}
+
+/// A number in `[0, 1)` that is uniformly distributed over the indices of a seed, computed with SplitMix64.
+/// Unlike a random number generator, this resolves any hit of a fight without resolving the hits before it.
+fn hit_roll(seed: u64, index: u64) -> f64 {
+    let mut z = seed.wrapping_add((index + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CombatSide, HitOutcome, COMBAT_LOG_LENGTH, HIT_INTERVAL};
+    use crate::game_state::player_actions::{
+        CombatPolicy, PlayerActionInProgressKind, ACTION_EXPLORE,
+    };
+    use crate::game_state::tests::{create_test_game_state_with_templates, real_milliseconds};
+    use crate::game_state::time::GameTime;
+    use crate::game_state::world::monsters::CombatHitpoints;
+    use crate::game_state::GameState;
+
+    const COMBAT_LOG_TEMPLATE: &str = "
+LOCATION combat_log_arena
+name Combat log arena
+monster_pool (1, combat_log_ogre)
+activation none
+deactivation never
+
+MONSTER combat_log_ogre
+name Ogre
+hitpoints 2000
+activation none
+deactivation never
+";
+
+    fn is_fighting(game_state: &GameState) -> bool {
+        matches!(
+            game_state.actions.in_progress().kind,
+            PlayerActionInProgressKind::Combat(_)
+        )
+    }
+
+    #[test]
+    fn test_resolved_fight_hits_for_seed() {
+        let combat_hitpoints = CombatHitpoints {
+            monster: 100.0,
+            character: 1000.0,
+            damage: 10.0,
+            enrage: 0.1,
+            seed: 7,
+        };
+        assert_eq!(
+            combat_hitpoints.fight_duration(),
+            GameTime::from_minutes(10)
+        );
+        assert_eq!(combat_hitpoints.exchange_count(GameTime::from_hours(1)), 10);
+
+        let hits: Vec<_> = (0..10)
+            .flat_map(|exchange| combat_hitpoints.exchange(exchange))
+            .collect();
+        let outcomes = |attacker| {
+            hits.iter()
+                .filter(|hit| hit.attacker == attacker)
+                .map(|hit| hit.outcome)
+                .collect::<Vec<_>>()
+        };
+        use HitOutcome::{Critical, Hit, Miss};
+        assert_eq!(
+            outcomes(CombatSide::Character),
+            vec![Hit, Critical, Hit, Hit, Hit, Hit, Critical, Hit, Hit, Hit]
+        );
+        assert_eq!(
+            outcomes(CombatSide::Monster),
+            vec![Miss, Hit, Hit, Hit, Hit, Critical, Hit, Hit, Hit, Hit]
+        );
+
+        assert_eq!(hits[0].elapsed, HIT_INTERVAL);
+        assert_eq!(hits[0].damage, 10.0);
+        assert_eq!(hits[1].damage, 0.0);
+        assert_eq!(hits[2].damage, 20.0);
+        assert_eq!(hits[19].elapsed, GameTime::from_minutes(10));
+
+        // the same seed resolves the same fight
+        let replayed: Vec<_> = (0..10)
+            .flat_map(|exchange| combat_hitpoints.exchange(exchange))
+            .collect();
+        assert_eq!(hits, replayed);
+    }
+
+    #[test]
+    fn test_log_follows_fight_in_progress() {
+        let mut game_state = create_test_game_state_with_templates(&[COMBAT_LOG_TEMPLATE]);
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "combat_log_arena")
+            .unwrap()
+            .id;
+        assert!(game_state.world.select_location(arena));
+        game_state.actions.selected_action = ACTION_EXPLORE;
+        game_state.set_combat_policy(CombatPolicy::Continuous);
+        while !is_fighting(&game_state) {
+            assert!(game_state.combat_log.is_empty());
+            game_state.update_offline(real_milliseconds(HIT_INTERVAL));
+        }
+
+        game_state.update_offline(real_milliseconds(GameTime::from_minutes(30)));
+        assert!(is_fighting(&game_state));
+        let in_progress = game_state.actions.in_progress();
+        let combat_hitpoints = in_progress.combat_hitpoints.unwrap();
+        let exchange_count =
+            combat_hitpoints.exchange_count(game_state.current_time - in_progress.start);
+        assert!(exchange_count >= 30);
+        let mut expected: Vec<_> = (exchange_count - COMBAT_LOG_LENGTH as u64..exchange_count)
+            .flat_map(|exchange| combat_hitpoints.exchange(exchange))
+            .collect();
+        expected.reverse();
+        let logged: Vec<_> = game_state.combat_log.iter_rev().copied().collect();
+        assert_eq!(logged, expected);
+
+        game_state.cancel_current_action();
+        game_state.update(0);
+        assert!(!is_fighting(&game_state));
+        assert!(game_state.combat_log.is_empty());
+    }
+}
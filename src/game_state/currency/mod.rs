@@ -98,6 +98,14 @@ impl ops::SubAssign for Currency {
     }
 }
 
+impl ops::Mul<f64> for Currency {
+    type Output = Currency;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::from_copper_f64(self.amount as f64 * rhs)
+    }
+}
+
 impl ops::Neg for Currency {
     type Output = Currency;
 
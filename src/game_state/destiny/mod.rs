@@ -0,0 +1,198 @@
+use crate::game_state::character::CharacterAttributes;
+use crate::game_state::currency::Currency;
+use crate::game_state::inventory::item::{CompiledExpectedItemCount, ExpectedItemCount};
+use crate::game_state::triggers::CompiledGameEvent;
+use crate::game_state::GameState;
+use crate::game_template::IdMaps;
+use crate::utils::weighted::{weighted_choice, WeightedChoiceError};
+use log::debug;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A starting bonus that may be granted to a new character by the destiny roll, see [`roll_destiny`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Destiny {
+    pub id_str: String,
+    pub name: String,
+    pub weight: f64,
+    pub currency: Currency,
+    /// The attribute levels added to the starting attributes.
+    pub attributes: CharacterAttributes,
+    pub items: Vec<ExpectedItemCount>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompiledDestiny {
+    pub id_str: String,
+    pub name: String,
+    pub weight: f64,
+    pub currency: Currency,
+    /// The attribute levels added to the starting attributes.
+    pub attributes: CharacterAttributes,
+    pub items: Vec<CompiledExpectedItemCount>,
+}
+
+impl Destiny {
+    pub fn compile(self, id_maps: &IdMaps) -> CompiledDestiny {
+        CompiledDestiny {
+            id_str: self.id_str,
+            name: self.name,
+            weight: self.weight,
+            currency: self.currency,
+            attributes: self.attributes,
+            items: self
+                .items
+                .into_iter()
+                .map(|item| item.compile(id_maps))
+                .collect(),
+        }
+    }
+}
+
+/// Chooses one of the destinies with a probability proportional to its weight.
+/// Returns `None` if there are no destinies.
+pub fn roll_destiny<'destinies>(
+    destinies: &'destinies [CompiledDestiny],
+    rng: &mut impl Rng,
+) -> Option<&'destinies CompiledDestiny> {
+    let weighted_destinies: Vec<_> = destinies
+        .iter()
+        .map(|destiny| (destiny.weight, destiny))
+        .collect();
+    match weighted_choice(&weighted_destinies, rng) {
+        Ok(destiny) => Some(*destiny),
+        Err(WeightedChoiceError::NoItems) => None,
+        Err(error) => panic!("Error: {error:?}"),
+    }
+}
+
+impl GameState {
+    /// Grants the bonus of a destiny rolled from the given destinies with the random number generator of the game.
+    pub(super) fn apply_destiny_roll(&mut self, destinies: &[CompiledDestiny]) {
+        let destiny = if let Some(destiny) = roll_destiny(destinies, &mut self.rng) {
+            destiny
+        } else {
+            return;
+        };
+        debug!("Rolled destiny {}", destiny.id_str);
+        self.destiny = Some(destiny.name.clone());
+
+        self.inventory.currency += destiny.currency;
+        let mut game_events: Vec<_> = self
+            .character
+            .add_attribute_levels(&destiny.attributes)
+            .collect();
+        game_events.push(CompiledGameEvent::CurrencyChanged {
+            value: self.inventory.currency,
+        });
+        let items: Vec<_> = destiny
+            .items
+            .iter()
+            .map(|item| item.spawn(&mut self.rng))
+            .collect();
+        game_events.extend(self.inventory.add_multiple(items.into_iter()));
+
+        self.dispatch_events(&game_events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{roll_destiny, CompiledDestiny};
+    use crate::game_state::character::{CharacterAttributes, CharacterRace};
+    use crate::game_state::currency::Currency;
+    use crate::game_state::difficulty::Difficulty;
+    use crate::game_state::tests::create_test_game_template;
+    use crate::game_state::{GameState, GameStateInitialisation};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro512PlusPlus;
+
+    const DESTINY_TEMPLATE: &str = "
+DESTINY destiny_wealth
+name Born rich
+weight 0
+currency 500
+
+DESTINY destiny_strength
+name Born strong
+weight 2.5
+str 1
+";
+
+    fn create_destinies(weights: &[f64]) -> Vec<CompiledDestiny> {
+        weights
+            .iter()
+            .enumerate()
+            .map(|(index, weight)| CompiledDestiny {
+                id_str: format!("destiny_{index}"),
+                name: format!("Destiny {index}"),
+                weight: *weight,
+                currency: Currency::zero(),
+                attributes: CharacterAttributes::new(0, 0, 0, 0, 0, 0),
+                items: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn roll_sequence(destinies: &[CompiledDestiny], seed: u64) -> Vec<String> {
+        let mut rng = Xoshiro512PlusPlus::seed_from_u64(seed);
+        (0..100)
+            .map(|_| roll_destiny(destinies, &mut rng).unwrap().id_str.clone())
+            .collect()
+    }
+
+    fn create_game_state(destiny_roll: bool) -> GameState {
+        GameState::new(
+            create_test_game_template(&[DESTINY_TEMPLATE]),
+            GameStateInitialisation {
+                savegame_file: "test_savegame.json".into(),
+                name: "Tester".to_string(),
+                pronoun: "they".to_string(),
+                race: CharacterRace::default(),
+                difficulty: Difficulty::default(),
+                destiny_roll,
+            },
+        )
+    }
+
+    #[test]
+    fn test_roll_is_reproducible_for_seed() {
+        let destinies = create_destinies(&[1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(roll_sequence(&destinies, 5), roll_sequence(&destinies, 5));
+        assert_ne!(roll_sequence(&destinies, 5), roll_sequence(&destinies, 6));
+        assert!(roll_destiny(&[], &mut Xoshiro512PlusPlus::seed_from_u64(5)).is_none());
+    }
+
+    #[test]
+    fn test_roll_respects_weights() {
+        let destinies = create_destinies(&[1.0, 3.0, 0.0, 6.0]);
+        let mut rng = Xoshiro512PlusPlus::seed_from_u64(0);
+        let mut counts = [0_i32; 4];
+        for _ in 0..10_000 {
+            let destiny = roll_destiny(&destinies, &mut rng).unwrap();
+            counts[destinies
+                .iter()
+                .position(|candidate| candidate.id_str == destiny.id_str)
+                .unwrap()] += 1;
+        }
+
+        assert_eq!(counts[2], 0);
+        for (count, expected) in [(counts[0], 1_000), (counts[1], 3_000), (counts[3], 6_000)] {
+            assert!((count - expected).abs() < 200, "{counts:?}");
+        }
+    }
+
+    #[test]
+    fn test_destiny_roll_at_new_game() {
+        let plain = create_game_state(false);
+        assert_eq!(plain.destiny, None);
+
+        let rolled = create_game_state(true);
+        assert_eq!(rolled.destiny.as_deref(), Some("Born strong"));
+        assert_eq!(
+            rolled.character.attributes().strength,
+            plain.character.attributes().strength + 1
+        );
+        assert_eq!(rolled.inventory.currency, plain.inventory.currency);
+    }
+}
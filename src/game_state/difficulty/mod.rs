@@ -0,0 +1,166 @@
+use crate::game_state::currency::Currency;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+/// Global multipliers chosen when creating a new game.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, Sequence, Eq, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Multiplies the hitpoints of all monsters.
+    pub fn monster_hitpoint_multiplier(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Multiplies the currency gained from actions, but not the currency spent on them.
+    pub fn currency_reward_multiplier(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 1.25,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.75,
+        }
+    }
+
+    /// Multiplies all attribute progress.
+    pub fn progress_multiplier(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 1.25,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.75,
+        }
+    }
+
+    pub fn scale_currency_reward(&self, currency_reward: Currency) -> Currency {
+        if currency_reward > Currency::zero() {
+            currency_reward * self.currency_reward_multiplier()
+        } else {
+            currency_reward
+        }
+    }
+}
+
+impl ToString for Difficulty {
+    fn to_string(&self) -> String {
+        match self {
+            Difficulty::Easy => "Easy".to_string(),
+            Difficulty::Normal => "Normal".to_string(),
+            Difficulty::Hard => "Hard".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Difficulty;
+    use crate::game_state::currency::Currency;
+    use crate::game_state::player_actions::{PlayerActionInProgressSource, ACTION_EXPLORE};
+    use crate::game_state::tests::create_test_game_state_with_templates;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::{GameState, GAME_TIME_PER_MILLISECOND};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro512PlusPlus;
+
+    const DIFFICULTY_TEMPLATE: &str = "
+ACTION difficulty_work
+name Work hard
+progressive working hard
+simple_past worked hard
+type WORK
+duration 1h
+currency 100
+activation none
+deactivation never
+
+LOCATION difficulty_arena
+name Difficulty arena
+monster_pool (1, difficulty_rat)
+activation none
+deactivation never
+
+MONSTER difficulty_rat
+name Difficulty rat
+hitpoints 10.0
+activation none
+deactivation never
+";
+
+    /// Plays the given action for three days on normal and on hard difficulty.
+    fn play_normal_and_hard(action_id_str: &str) -> (GameState, GameState) {
+        let mut normal = create_test_game_state_with_templates(&[DIFFICULTY_TEMPLATE]);
+        let action = normal
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == action_id_str)
+            .unwrap()
+            .id;
+        normal.actions.selected_action = action;
+        let mut hard = normal.clone();
+        hard.difficulty = Difficulty::Hard;
+
+        for game_state in [&mut normal, &mut hard] {
+            game_state.update_offline(
+                (GameTime::from_days(3).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                    as i64,
+            );
+        }
+        (normal, hard)
+    }
+
+    #[test]
+    fn test_hard_scales_monster_hitpoints_up() {
+        let game_state = create_test_game_state_with_templates(&[DIFFICULTY_TEMPLATE]);
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "difficulty_arena")
+            .unwrap();
+        let monster = game_state.world.monster(arena.monsters[0].id);
+        let character = &game_state.character;
+        assert_eq!(monster.hitpoints(character, Difficulty::Normal), 10.0);
+        assert_eq!(monster.hitpoints(character, Difficulty::Hard), 15.0);
+        assert!(monster.hitpoints(character, Difficulty::Easy) < 10.0);
+
+        let [normal, hard] = [Difficulty::Normal, Difficulty::Hard].map(|difficulty| {
+            monster.spawn_combat(
+                &mut Xoshiro512PlusPlus::seed_from_u64(0),
+                GameTime::zero(),
+                character,
+                Currency::zero(),
+                Vec::new(),
+                PlayerActionInProgressSource::Action(ACTION_EXPLORE),
+                arena.id,
+                difficulty,
+                1.0,
+            )
+        });
+        assert!(normal.success && hard.success);
+        assert!(hard.length() > normal.length());
+    }
+
+    #[test]
+    fn test_hard_scales_rewards_down() {
+        assert_eq!(
+            Difficulty::Hard.scale_currency_reward(Currency::from_copper(100)),
+            Currency::from_copper(75)
+        );
+        assert_eq!(
+            Difficulty::Hard.scale_currency_reward(Currency::from_copper(-100)),
+            Currency::from_copper(-100)
+        );
+
+        let (normal, hard) = play_normal_and_hard("difficulty_work");
+        assert!(hard.inventory.currency < normal.inventory.currency);
+
+        let (normal, hard) = play_normal_and_hard("train_str");
+        assert!(hard.character.attributes().strength < normal.character.attributes().strength);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::game_state::story::quests::QuestState;
+use crate::game_state::GameState;
+use std::fmt::{Display, Formatter};
+
+/// A compact textual summary of the key fields of a game state for debugging.
+/// It is deterministic, so dumps of equal game states are equal and can be compared field by field.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameStateDump {
+    /// The fields in a fixed order, each with its rendered value.
+    fields: Vec<(&'static str, String)>,
+}
+
+/// A field whose value differs between two dumps, see [`GameState::diff`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+impl GameState {
+    pub fn dump(&self) -> GameStateDump {
+        let attributes = self.character.attributes();
+        let quests = |filter: fn(&QuestState) -> bool| {
+            let mut quests: Vec<_> = self
+                .story
+                .iter_all_quests()
+                .filter(|quest| filter(quest.state()))
+                .map(|quest| quest.id_str.as_str())
+                .collect();
+            quests.sort_unstable();
+            quests.join(", ")
+        };
+        let mut items: Vec<_> = self
+            .inventory
+            .iter_owned()
+            .map(|(item, count)| format!("{} {count}", item.id_str))
+            .collect();
+        items.sort_unstable();
+
+        GameStateDump {
+            fields: vec![
+                ("level", self.character.level.to_string()),
+                ("strength", attributes.strength.to_string()),
+                ("stamina", attributes.stamina.to_string()),
+                ("dexterity", attributes.dexterity.to_string()),
+                ("intelligence", attributes.intelligence.to_string()),
+                ("wisdom", attributes.wisdom.to_string()),
+                ("charisma", attributes.charisma.to_string()),
+                ("currency", self.inventory.currency.copper().to_string()),
+                (
+                    "selected_action",
+                    self.actions
+                        .action(self.actions.selected_action)
+                        .id_str
+                        .clone(),
+                ),
+                ("active_quests", quests(QuestState::is_active)),
+                ("completed_quests", quests(QuestState::is_completed)),
+                ("failed_quests", quests(QuestState::is_failed)),
+                ("items", items.join(", ")),
+            ],
+        }
+    }
+
+    /// The fields of the dump of this game state that differ in the dump of the other game state.
+    pub fn diff(&self, other: &GameState) -> Vec<FieldChange> {
+        self.dump().diff(&other.dump())
+    }
+}
+
+impl GameStateDump {
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The fields that differ in the other dump, in the order of the dump.
+    pub fn diff(&self, other: &GameStateDump) -> Vec<FieldChange> {
+        self.fields
+            .iter()
+            .zip(&other.fields)
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((field, old), (_, new))| FieldChange {
+                field,
+                old: old.clone(),
+                new: new.clone(),
+            })
+            .collect()
+    }
+}
+
+impl Display for GameStateDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (field, value) in &self.fields {
+            writeln!(f, "{field}: {value}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldChange;
+    use crate::game_state::currency::Currency;
+    use crate::game_state::tests::create_test_game_state;
+
+    #[test]
+    fn test_diff_against_itself_is_empty() {
+        let mut game_state = create_test_game_state();
+        game_state.update(1_000_000);
+        assert!(game_state.diff(&game_state.clone()).is_empty());
+        assert_eq!(game_state.dump(), game_state.clone().dump());
+        assert_eq!(
+            game_state.dump().get("level"),
+            Some(game_state.character.level.to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn test_diff_detects_single_changed_field() {
+        let game_state = create_test_game_state();
+        let mut changed = game_state.clone();
+        changed.inventory.currency += Currency::from_copper(25);
+
+        let old = game_state.inventory.currency.copper();
+        let diff = game_state.diff(&changed);
+        assert_eq!(
+            diff,
+            vec![FieldChange {
+                field: "currency",
+                old: old.to_string(),
+                new: (old + 25).to_string(),
+            }]
+        );
+        assert_eq!(
+            diff[0].to_string(),
+            format!("currency: {old} -> {}", old + 25)
+        );
+    }
+}
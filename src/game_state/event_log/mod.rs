@@ -1,5 +1,9 @@
-use crate::game_state::player_actions::PlayerActionInProgress;
+use crate::game_state::player_actions::{
+    PlayerActionInProgress, PlayerActionInProgressKind, PlayerActionInProgressSource,
+    PlayerActionType, PlayerActions,
+};
 use crate::game_state::time::GameTime;
+use enum_iterator::Sequence;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
@@ -21,6 +25,52 @@ impl EventLog {
     pub fn iter_rev(&self) -> impl Iterator<Item = &GameEvent> {
         self.events.iter().rev()
     }
+
+    /// Iterates the events matching the given search query, newest first.
+    pub fn iter_rev_matching<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> impl 'a + Iterator<Item = &'a GameEvent> {
+        self.iter_rev()
+            .filter(move |event| event.matches_search(query))
+    }
+
+    /// The earliest event that happened at or after the given time.
+    pub fn first_event_at_or_after(&self, time: GameTime) -> Option<&GameEvent> {
+        let index = self.events.partition_point(|event| event.time < time);
+        self.events.get(index)
+    }
+}
+
+impl GameEvent {
+    /// True if the description of this event contains the given query, ignoring case.
+    /// An empty query matches all events.
+    pub fn matches_search(&self, query: &str) -> bool {
+        let description = match &self.kind {
+            GameEventKind::Action(action) => &action.verb_simple_past,
+        };
+        description.to_lowercase().contains(&query.to_lowercase())
+    }
+
+    /// The category of this event, by which the player can filter the event log.
+    pub fn category(&self, actions: &PlayerActions) -> GameEventCategory {
+        match &self.kind {
+            GameEventKind::Action(action) => match (&action.kind, &action.source) {
+                (PlayerActionInProgressKind::Combat(_), _) => GameEventCategory::Combat,
+                (_, PlayerActionInProgressSource::Exploration(_)) => GameEventCategory::Exploration,
+                (_, PlayerActionInProgressSource::Action(action_id)) => {
+                    match actions.action(*action_id).action_type {
+                        PlayerActionType::Wait
+                        | PlayerActionType::Sleep
+                        | PlayerActionType::Tavern => GameEventCategory::Rest,
+                        PlayerActionType::Train => GameEventCategory::Training,
+                        PlayerActionType::Work => GameEventCategory::Work,
+                        PlayerActionType::Explore => GameEventCategory::Exploration,
+                    }
+                }
+            },
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -34,6 +84,28 @@ pub enum GameEventKind {
     Action(PlayerActionInProgress),
 }
 
+/// The categories of logged events that can be shown or hidden in the event log.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Sequence)]
+pub enum GameEventCategory {
+    Rest,
+    Training,
+    Work,
+    Exploration,
+    Combat,
+}
+
+impl ToString for GameEventCategory {
+    fn to_string(&self) -> String {
+        match self {
+            GameEventCategory::Rest => "Rest".to_string(),
+            GameEventCategory::Training => "Training".to_string(),
+            GameEventCategory::Work => "Work".to_string(),
+            GameEventCategory::Exploration => "Exploration".to_string(),
+            GameEventCategory::Combat => "Combat".to_string(),
+        }
+    }
+}
+
 impl From<PlayerActionInProgress> for GameEvent {
     fn from(action: PlayerActionInProgress) -> Self {
         Self {
@@ -42,3 +114,60 @@ impl From<PlayerActionInProgress> for GameEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::EventLog;
+    use crate::game_state::tests::create_test_game_state;
+    use crate::game_state::time::GameTime;
+
+    fn synthetic_log() -> EventLog {
+        let game_state = create_test_game_state();
+        let mut log = EventLog::default();
+        for (day, verb) in [
+            (1, "fought a rat"),
+            (1, "slept"),
+            (2, "trained strength"),
+            (4, "fought a Hare"),
+        ] {
+            let mut action = game_state.actions.in_progress().clone();
+            action.verb_simple_past = verb.to_string();
+            action.end = GameTime::from_days(day) + GameTime::from_hours(12);
+            log.log(action);
+        }
+        log
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let log = synthetic_log();
+        let matching_days = |query| {
+            log.iter_rev_matching(query)
+                .map(|event| event.time.days())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matching_days("fought"), vec![4, 1]);
+        assert_eq!(matching_days("HARE"), vec![4]);
+        assert_eq!(matching_days("dragon"), Vec::<i128>::new());
+        assert_eq!(matching_days("").len(), 4);
+    }
+
+    #[test]
+    fn test_first_event_at_or_after() {
+        let log = synthetic_log();
+        let first_day = |time| {
+            log.first_event_at_or_after(time)
+                .map(|event| event.time.days())
+        };
+
+        assert_eq!(first_day(GameTime::zero()), Some(1));
+        assert_eq!(first_day(GameTime::from_days(2)), Some(2));
+        assert_eq!(first_day(GameTime::from_days(3)), Some(4));
+        assert_eq!(
+            first_day(GameTime::from_days(4) + GameTime::from_hours(12)),
+            Some(4)
+        );
+        assert_eq!(first_day(GameTime::from_days(5)), None);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::game_state::character::Character;
+use serde::{Deserialize, Serialize};
+
+/// An arithmetic expression over character values, used by numeric template fields that opt in.
+///
+/// Constants are serialised as plain numbers, so fields that used to be numbers stay readable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Expression {
+    Constant(f64),
+    Variable(ExpressionVariable),
+    Binary {
+        operator: BinaryOperator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    Negation {
+        operand: Box<Expression>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ExpressionVariable {
+    Level,
+    Strength,
+    Stamina,
+    Dexterity,
+    Intelligence,
+    Wisdom,
+    Charisma,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Expression {
+    pub fn binary(operator: BinaryOperator, left: Expression, right: Expression) -> Self {
+        Self::Binary {
+            operator,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn negation(operand: Expression) -> Self {
+        Self::Negation {
+            operand: Box::new(operand),
+        }
+    }
+
+    pub fn evaluate(&self, character: &Character) -> f64 {
+        match self {
+            Expression::Constant(constant) => *constant,
+            Expression::Variable(variable) => variable.evaluate(character),
+            Expression::Negation { operand } => -operand.evaluate(character),
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let left = left.evaluate(character);
+                let right = right.evaluate(character);
+                match operator {
+                    BinaryOperator::Add => left + right,
+                    BinaryOperator::Subtract => left - right,
+                    BinaryOperator::Multiply => left * right,
+                    BinaryOperator::Divide => left / right,
+                }
+            }
+        }
+    }
+}
+
+impl ExpressionVariable {
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        Some(match identifier {
+            "level" => Self::Level,
+            "strength" => Self::Strength,
+            "stamina" => Self::Stamina,
+            "dexterity" => Self::Dexterity,
+            "intelligence" => Self::Intelligence,
+            "wisdom" => Self::Wisdom,
+            "charisma" => Self::Charisma,
+            _ => return None,
+        })
+    }
+
+    pub fn evaluate(&self, character: &Character) -> f64 {
+        let attributes = character.attributes();
+        (match self {
+            ExpressionVariable::Level => character.level,
+            ExpressionVariable::Strength => attributes.strength,
+            ExpressionVariable::Stamina => attributes.stamina,
+            ExpressionVariable::Dexterity => attributes.dexterity,
+            ExpressionVariable::Intelligence => attributes.intelligence,
+            ExpressionVariable::Wisdom => attributes.wisdom,
+            ExpressionVariable::Charisma => attributes.charisma,
+        }) as f64
+    }
+}
@@ -0,0 +1,199 @@
+use crate::game_state::player_actions::ACTION_EXPLORE;
+use crate::game_state::GameState;
+use std::fmt::{Display, Formatter};
+
+/// A violated consistency requirement of the game state, see [`GameState::validate_invariants`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum InvariantViolation {
+    ActionSetMismatch {
+        id_str: String,
+        state: String,
+        set: &'static str,
+        contained: bool,
+    },
+    ActionSetSize {
+        set: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    SelectedActionNotActive {
+        id_str: String,
+        state: String,
+    },
+    SelectedLocationNotActive {
+        id_str: String,
+    },
+    QuestSetMismatch {
+        id_str: String,
+        state: String,
+        set: &'static str,
+        contained: bool,
+    },
+    QuestSetSize {
+        set: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    QuestStageOutOfRange {
+        id_str: String,
+        stage: usize,
+        stage_count: usize,
+    },
+    QuestStageStateMismatch {
+        quest_id_str: String,
+        quest_state: String,
+        stage_id_str: String,
+        stage_state: String,
+    },
+}
+
+impl GameState {
+    /// Checks that the bookkeeping of actions and quests is consistent and that the selected action is valid.
+    pub fn validate_invariants(&self) -> Result<(), InvariantViolation> {
+        self.actions.validate_invariants()?;
+        self.story.validate_invariants()?;
+
+        let selected_action = self.actions.action(self.actions.selected_action);
+        if !selected_action.state.is_active() {
+            return Err(InvariantViolation::SelectedActionNotActive {
+                id_str: selected_action.id_str.clone(),
+                state: format!("{:?}", selected_action.state),
+            });
+        }
+        if self.actions.selected_action == ACTION_EXPLORE {
+            let selected_location = self.world.location(self.world.selected_location);
+            if !selected_location.state.is_active() {
+                return Err(InvariantViolation::SelectedLocationNotActive {
+                    id_str: selected_location.id_str.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for InvariantViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let contained_str = |contained: &bool| if *contained { "" } else { "not " };
+        match self {
+            InvariantViolation::ActionSetMismatch {
+                id_str,
+                state,
+                set,
+                contained,
+            } => write!(
+                f,
+                "action {id_str} in state {state} is {}contained in the {set} actions",
+                contained_str(contained)
+            ),
+            InvariantViolation::ActionSetSize {
+                set,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "the {set} actions contain {actual} entries, but {expected} actions are {set}"
+            ),
+            InvariantViolation::SelectedActionNotActive { id_str, state } => {
+                write!(f, "the selected action {id_str} is not active but {state}")
+            }
+            InvariantViolation::SelectedLocationNotActive { id_str } => {
+                write!(f, "the selected location {id_str} is not active")
+            }
+            InvariantViolation::QuestSetMismatch {
+                id_str,
+                state,
+                set,
+                contained,
+            } => write!(
+                f,
+                "quest {id_str} in state {state} is {}contained in the {set} quests",
+                contained_str(contained)
+            ),
+            InvariantViolation::QuestSetSize {
+                set,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "the {set} quests contain {actual} entries, but {expected} quests are {set}"
+            ),
+            InvariantViolation::QuestStageOutOfRange {
+                id_str,
+                stage,
+                stage_count,
+            } => write!(
+                f,
+                "quest {id_str} refers to stage {stage}, but has only {stage_count} stages"
+            ),
+            InvariantViolation::QuestStageStateMismatch {
+                quest_id_str,
+                quest_state,
+                stage_id_str,
+                stage_state,
+            } => write!(
+                f,
+                "stage {stage_id_str} of quest {quest_id_str} in state {quest_state} is in state {stage_state}"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InvariantViolation;
+    use crate::game_state::player_actions::{PlayerActionState, ACTION_WAIT};
+    use crate::game_state::tests::create_test_game_state;
+    use crate::game_state::time::GameTime;
+
+    #[test]
+    fn test_valid_game_state() {
+        let mut game_state = create_test_game_state();
+        assert_eq!(game_state.validate_invariants(), Ok(()));
+        game_state.update(1_000_000);
+        assert_eq!(game_state.validate_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_corrupted_action_state() {
+        let mut game_state = create_test_game_state();
+        game_state.actions.action_mut(ACTION_WAIT).state = PlayerActionState::Inactive;
+
+        let error = game_state.validate_invariants().unwrap_err();
+        assert!(matches!(
+            &error,
+            InvariantViolation::ActionSetMismatch {
+                set: "inactive",
+                contained: false,
+                ..
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "action WAIT in state Inactive is not contained in the inactive actions"
+        );
+    }
+
+    #[test]
+    fn test_deactivated_selected_action() {
+        let mut game_state = create_test_game_state();
+        let action = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "train_str")
+            .unwrap()
+            .id;
+        game_state.actions.selected_action = action;
+        let _ = game_state
+            .actions
+            .deactivate_action(action, GameTime::zero());
+        game_state.actions.selected_action = action;
+
+        let error = game_state.validate_invariants().unwrap_err();
+        assert!(matches!(
+            &error,
+            InvariantViolation::SelectedActionNotActive { id_str, .. } if id_str == "train_str"
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::game_state::buffs::Buff;
 use crate::game_state::currency::Currency;
 use crate::game_state::time::GameTime;
 use crate::game_template::parser::ExpectedIdentifierCount;
@@ -13,6 +14,8 @@ pub struct Item {
     pub name: String,
     pub description: String,
     pub value: Currency,
+    pub max_count: Option<usize>,
+    pub buff: Option<Buff>,
     pub activation_condition: String,
     pub deactivation_condition: String,
 }
@@ -25,6 +28,12 @@ pub struct CompiledItem {
     pub name: String,
     pub description: String,
     pub value: Currency,
+    /// The inventory never holds more than this many of the item.
+    #[serde(default)]
+    pub max_count: Option<usize>,
+    /// Granted to the character whenever the item is gained.
+    #[serde(default)]
+    pub buff: Option<Buff>,
     pub activation_condition: TriggerHandle,
     pub deactivation_condition: TriggerHandle,
 }
@@ -74,6 +83,8 @@ impl Item {
             name: self.name,
             description: self.description,
             value: self.value,
+            max_count: self.max_count,
+            buff: self.buff,
             activation_condition: *id_maps.triggers.get(&self.activation_condition).unwrap(),
             deactivation_condition: *id_maps.triggers.get(&self.deactivation_condition).unwrap(),
         }
@@ -1,11 +1,13 @@
 use crate::game_state::currency::Currency;
 use crate::game_state::inventory::item::{CompiledItem, ItemCount, ItemId, ItemState};
 use crate::game_state::time::GameTime;
+use crate::game_state::translations::Translator;
 use crate::game_state::triggers::CompiledGameEvent;
 use hashbag::HashBag;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::iter;
+use std::mem;
 
 pub mod item;
 
@@ -16,6 +18,11 @@ pub struct Inventory {
     owned: HashBag<ItemId>,
 
     pub currency: Currency,
+
+    /// Items that were not added because of their [`CompiledItem::max_count`]
+    /// since the last call to [`Inventory::take_wasted_items`].
+    #[serde(skip)]
+    wasted_items: Vec<ItemCount>,
 }
 
 impl Inventory {
@@ -26,10 +33,10 @@ impl Inventory {
             owned: Default::default(),
 
             currency: Currency::zero(),
+            wasted_items: Vec::new(),
         }
     }
 
-    #[allow(dead_code)]
     pub fn item(&self, item_id: ItemId) -> &CompiledItem {
         &self.items[item_id.0]
     }
@@ -38,13 +45,48 @@ impl Inventory {
         &mut self.items[item_id.0]
     }
 
+    pub fn translate(&mut self, translator: &Translator) {
+        for item in &mut self.items {
+            translator.translate(&mut item.name);
+            translator.translate(&mut item.description);
+        }
+    }
+
+    pub fn count(&self, item_id: ItemId) -> usize {
+        self.owned.contains(&item_id)
+    }
+
+    /// The owned items with their counts, in no particular order.
+    pub fn iter_owned(&self) -> impl Iterator<Item = (&CompiledItem, usize)> {
+        self.owned
+            .set_iter()
+            .map(|(item_id, count)| (self.item(*item_id), count))
+    }
+
+    /// Adds the items to the inventory, up to the [`CompiledItem::max_count`] of the item.
+    /// Items beyond that are recorded as wasted.
     pub fn add(
         &mut self,
         item_id: ItemId,
         count: usize,
     ) -> impl Iterator<Item = CompiledGameEvent> {
-        let new_count = self.owned.insert_many(item_id, count);
-        assert!(new_count >= count);
+        let old_count = self.count(item_id);
+        let count = match self.item(item_id).max_count {
+            Some(max_count) => {
+                let added_count = count.min(max_count.saturating_sub(old_count));
+                if added_count < count {
+                    self.wasted_items.push(ItemCount {
+                        id: item_id,
+                        count: count - added_count,
+                    });
+                }
+                added_count
+            }
+            None => count,
+        };
+
+        self.owned.insert_many(item_id, count);
+        let new_count = old_count + count;
         if count > 0 {
             Some(CompiledGameEvent::ItemCountChanged {
                 id: item_id,
@@ -67,6 +109,10 @@ impl Inventory {
         events.into_iter()
     }
 
+    pub fn take_wasted_items(&mut self) -> Vec<ItemCount> {
+        mem::take(&mut self.wasted_items)
+    }
+
     /*/// Remove some items from the inventory.
     /// If more are supposed to be removed than there are, then that is ignored.
     pub fn remove(
@@ -125,3 +171,76 @@ impl Inventory {
         iter::empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Inventory;
+    use crate::game_state::currency::Currency;
+    use crate::game_state::inventory::item::{CompiledItem, ItemCount, ItemId, ItemState};
+    use crate::game_state::triggers::CompiledGameEvent;
+    use event_trigger_action_system::TriggerHandle;
+
+    fn create_inventory(max_count: Option<usize>) -> Inventory {
+        Inventory::new(vec![CompiledItem {
+            id: ItemId(0),
+            id_str: "pelt".to_string(),
+            state: ItemState::Inactive,
+            name: "Pelt".to_string(),
+            description: "The pelt of a rat.".to_string(),
+            value: Currency::from_copper(1),
+            max_count,
+            buff: None,
+            activation_condition: TriggerHandle::from(0),
+            deactivation_condition: TriggerHandle::from(0),
+        }])
+    }
+
+    fn added_counts(inventory: &mut Inventory, count: usize) -> Vec<usize> {
+        inventory
+            .add(ItemId(0), count)
+            .map(|event| match event {
+                CompiledGameEvent::ItemCountChanged { count, .. } => count,
+                event => panic!("Unexpected event {event:?}"),
+            })
+            .collect()
+    }
+
+    fn wasted_counts(inventory: &mut Inventory) -> Vec<usize> {
+        inventory
+            .take_wasted_items()
+            .into_iter()
+            .map(|ItemCount { count, .. }| count)
+            .collect()
+    }
+
+    #[test]
+    fn test_add_without_max_count() {
+        let mut inventory = create_inventory(None);
+        assert_eq!(added_counts(&mut inventory, 1000), vec![1000]);
+        assert_eq!(added_counts(&mut inventory, 0), Vec::<usize>::new());
+        assert_eq!(inventory.count(ItemId(0)), 1000);
+        assert!(wasted_counts(&mut inventory).is_empty());
+    }
+
+    #[test]
+    fn test_add_up_to_max_count() {
+        let mut inventory = create_inventory(Some(5));
+        assert_eq!(added_counts(&mut inventory, 3), vec![3]);
+        assert_eq!(added_counts(&mut inventory, 2), vec![5]);
+        assert_eq!(inventory.count(ItemId(0)), 5);
+        assert!(wasted_counts(&mut inventory).is_empty());
+    }
+
+    #[test]
+    fn test_add_beyond_max_count() {
+        let mut inventory = create_inventory(Some(5));
+        assert_eq!(added_counts(&mut inventory, 4), vec![4]);
+        assert_eq!(added_counts(&mut inventory, 3), vec![5]);
+        assert_eq!(wasted_counts(&mut inventory), vec![2]);
+
+        assert_eq!(added_counts(&mut inventory, 2), Vec::<usize>::new());
+        assert_eq!(inventory.count(ItemId(0)), 5);
+        assert_eq!(wasted_counts(&mut inventory), vec![2]);
+        assert!(wasted_counts(&mut inventory).is_empty());
+    }
+}
@@ -1,14 +1,27 @@
-use crate::game_state::character::{Character, CharacterRace};
+use crate::game_state::buffs::{Buff, BuffKind};
+use crate::game_state::calendar::Calendar;
+use crate::game_state::character::{
+    Character, CharacterAttributeProgress, CharacterAttributes, CharacterRace, CombatStyle,
+};
+use crate::game_state::combat_log::CombatLog;
 use crate::game_state::currency::Currency;
+use crate::game_state::difficulty::Difficulty;
 use crate::game_state::event_log::EventLog;
 use crate::game_state::inventory::Inventory;
+use crate::game_state::observers::{GameEventObservers, SharedGameEventObservers};
 use crate::game_state::player_actions::{
-    PlayerActionInProgressKind, PlayerActionInProgressSource, PlayerActions, ACTION_EXPLORE,
-    ACTION_SLEEP, ACTION_TAVERN, ACTION_WAIT,
+    CombatPolicy, CompiledPlayerAction, PlayerActionId, PlayerActionInProgress,
+    PlayerActionInProgressKind, PlayerActionInProgressSource, PlayerActionType, PlayerActions,
+    TrainingPolicy, ACTION_EXPLORE, ACTION_SLEEP, ACTION_TAVERN, ACTION_WAIT,
 };
+use crate::game_state::prestige::Prestige;
+use crate::game_state::story::quests::{CompiledQuest, QuestId};
 use crate::game_state::story::Story;
+use crate::game_state::tavern::TavernQuality;
 use crate::game_state::time::GameTime;
+use crate::game_state::translations::Translations;
 use crate::game_state::triggers::{CompiledGameAction, CompiledGameEvent};
+use crate::game_state::world::locations::LocationId;
 use crate::game_state::world::World;
 use crate::game_template::CompiledGameTemplate;
 use crate::io::pathbuf_serde::PathBufSerde;
@@ -20,22 +33,41 @@ use rand::Rng;
 use rand::SeedableRng;
 use rand_xoshiro::Xoshiro512PlusPlus;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
 use std::iter;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
 
+pub mod buffs;
+pub mod calendar;
 pub mod character;
+pub mod combat_log;
 pub mod currency;
+pub mod destiny;
+pub mod difficulty;
+pub mod dump;
 pub mod event_log;
+pub mod expression;
+pub mod invariants;
 pub mod inventory;
+pub mod observers;
+pub mod offline_progress;
 pub mod player_actions;
+pub mod prestige;
 pub mod story;
+pub mod tavern;
 pub mod time;
+pub mod translations;
 pub mod triggers;
 pub mod world;
 
 pub const GAME_TIME_PER_MILLISECOND: GameTime = GameTime::from_milliseconds(900);
 pub const MIN_COMBAT_DURATION: GameTime = GameTime::from_minutes(10);
 pub const MAX_COMBAT_DURATION: GameTime = GameTime::from_hours(4);
+/// Updates spanning more real time than this are assumed to cover a pause, and do not count as played time.
+pub const MAX_PLAYED_TIME_PER_UPDATE: GameTime = GameTime::from_seconds(10);
+/// At most this many quests can be pinned to the goal tracker.
+pub const MAX_PINNED_QUESTS: usize = 3;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GameState {
@@ -43,6 +75,11 @@ pub struct GameState {
     pub rng: Xoshiro512PlusPlus,
     pub character: Character,
     pub current_time: GameTime,
+    /// `None` only in savegames from before it was saved, until [`GameState::complete_loaded_savegame`] is called.
+    #[serde(default)]
+    starting_time: Option<GameTime>,
+    #[serde(default)]
+    pub real_time_played: GameTime,
     pub last_update: DateTime<Utc>,
     pub log: EventLog,
     pub actions: PlayerActions,
@@ -50,6 +87,90 @@ pub struct GameState {
     pub world: World,
     pub inventory: Inventory,
     pub triggers: CompiledTriggers<CompiledGameEvent>,
+    #[serde(default)]
+    combat_policy: CombatPolicy,
+    /// The start of the first combat since exploring was selected, used by [`CombatPolicy::ForDuration`].
+    #[serde(default)]
+    combat_streak_start: Option<GameTime>,
+    #[serde(default)]
+    training_policy: TrainingPolicy,
+    /// The start of waiting since the player last changed the action, used by [`CompiledGameEvent::PlayerIdle`].
+    #[serde(default)]
+    idle_start: Option<GameTime>,
+    /// Actions pinned by the player to the top of the action list.
+    #[serde(default)]
+    favorite_actions: HashSet<PlayerActionId>,
+    /// Actions to select one after the other whenever the selected action completes.
+    #[serde(default)]
+    action_queue: VecDeque<PlayerActionId>,
+    /// Quests pinned by the player to the goal tracker, in the order they were pinned.
+    #[serde(default)]
+    pinned_quests: Vec<QuestId>,
+    #[serde(default)]
+    pub prestige: Prestige,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub tavern_quality: TavernQuality,
+    /// The active buffs with their expiry times.
+    #[serde(default)]
+    pub buffs: Vec<(BuffKind, GameTime)>,
+    #[serde(default)]
+    pub calendar: Calendar,
+    #[serde(default)]
+    pub translations: Translations,
+    #[serde(default)]
+    pub combat_log: CombatLog,
+    /// The name of the destiny rolled when the game was started, if any.
+    #[serde(default)]
+    pub destiny: Option<String>,
+    /// Log state transitions, see [`RunConfiguration::profile`](crate::RunConfiguration).
+    #[serde(skip)]
+    pub profile: bool,
+    /// Quest activations, completions and failures since the last call to [`GameState::take_quest_events`].
+    #[serde(skip)]
+    quest_events: Vec<CompiledGameEvent>,
+    /// Actions completed while playing since the last call to [`GameState::take_completed_actions`].
+    #[serde(skip)]
+    completed_actions: Vec<PlayerActionId>,
+    /// The events dispatched during [`GameState::update_with_events`], `None` outside of it.
+    #[serde(skip)]
+    emitted_events: Option<Vec<CompiledGameEvent>>,
+    /// Called with each dispatched event, see [`GameState::register_observer`].
+    #[serde(skip)]
+    observers: GameEventObservers,
+    /// See [`GameState::revision`].
+    #[serde(skip, default = "next_revision")]
+    revision: u64,
+}
+
+/// Returns a revision that was never returned before, so that revisions of different game states never collide.
+fn next_revision() -> u64 {
+    static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A change of the game state requested by the player.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum GameStateMessage {
+    ActionChanged(PlayerActionId),
+    ActionChangedExplore(LocationId),
+    ExplorationLocationChanged(LocationId),
+    CombatStyleChanged(CombatStyle),
+    CombatPolicyChanged(CombatPolicy),
+    TrainingPolicyChanged(TrainingPolicy),
+    CancelCurrentAction,
+    RenameCharacter(String),
+    ToggleFavorite(PlayerActionId),
+    EnqueueAction(PlayerActionId),
+    /// Swaps the queued action at the given index with the one before it.
+    MoveQueuedActionUp(usize),
+    RemoveQueuedAction(usize),
+    ClearActionQueue,
+    TogglePinnedQuest(QuestId),
+    SkipToActionCompletion,
+    AcceptQuest(QuestId),
+    PurchaseTavernQuality(TavernQuality),
 }
 
 #[derive(Clone, Debug)]
@@ -58,6 +179,9 @@ pub struct GameStateInitialisation {
     pub name: String,
     pub pronoun: String,
     pub race: CharacterRace,
+    pub difficulty: Difficulty,
+    /// Grant a random starting bonus from the destinies of the game template, see [`destiny::roll_destiny`].
+    pub destiny_roll: bool,
 }
 
 impl GameState {
@@ -74,6 +198,8 @@ impl GameState {
                 initialisation.race,
             ),
             current_time: game_template.initialisation.starting_time,
+            starting_time: Some(game_template.initialisation.starting_time),
+            real_time_played: GameTime::zero(),
             last_update: Utc::now(),
             log: EventLog::default(),
             actions: game_template.actions,
@@ -81,36 +207,392 @@ impl GameState {
             world: game_template.world,
             inventory: game_template.inventory,
             triggers: game_template.triggers,
+            combat_policy: Default::default(),
+            combat_streak_start: None,
+            training_policy: Default::default(),
+            idle_start: None,
+            favorite_actions: Default::default(),
+            action_queue: Default::default(),
+            pinned_quests: Default::default(),
+            prestige: Prestige::new(game_template.initialisation.prestige_quest),
+            difficulty: initialisation.difficulty,
+            tavern_quality: Default::default(),
+            buffs: Vec::new(),
+            calendar: game_template.calendar,
+            translations: game_template.translations,
+            combat_log: Default::default(),
+            destiny: None,
+            profile: false,
+            quest_events: Vec::new(),
+            completed_actions: Vec::new(),
+            emitted_events: None,
+            observers: Default::default(),
+            revision: next_revision(),
         };
+        if initialisation.destiny_roll {
+            result.apply_destiny_roll(&game_template.destinies);
+        }
         result.execute_all_triggered_actions();
+        result.world.ensure_active_selected_location();
         result.update(0);
         result
     }
 
+    /// Shows the display strings in the given language where translated, and in the default language otherwise.
+    pub fn select_language(&mut self, language: Option<&str>) {
+        let translator = self.translations.translator(language);
+        self.actions.translate(&translator);
+        self.story.translate(&translator);
+        self.world.translate(&translator);
+        self.inventory.translate(&translator);
+        self.calendar.translate(&translator);
+        self.revision = next_revision();
+    }
+
+    /// Updates the game state while the game is being played.
     pub fn update(&mut self, passed_real_milliseconds: i64) {
+        let passed_real_time = GameTime::from_milliseconds(passed_real_milliseconds.into());
+        if passed_real_time >= GameTime::zero() && passed_real_time <= MAX_PLAYED_TIME_PER_UPDATE {
+            self.real_time_played += passed_real_time;
+        } else {
+            debug!("Not counting {passed_real_milliseconds}ms as played time");
+        }
+
+        self.advance(passed_real_milliseconds);
+    }
+
+    /// Updates the game state like [`GameState::update`] and returns all events that were dispatched to the triggers meanwhile,
+    /// in the order they were dispatched.
+    pub fn update_with_events(&mut self, passed_real_milliseconds: i64) -> Vec<CompiledGameEvent> {
+        self.emitted_events = Some(Vec::new());
+        self.update(passed_real_milliseconds);
+        self.emitted_events.take().unwrap_or_default()
+    }
+
+    /// Registers a callback that is called with each event dispatched to the triggers, in the order they are dispatched.
+    /// Observers cannot change the game state, and are not saved.
+    pub fn register_observer(
+        &mut self,
+        observer: impl Fn(&CompiledGameEvent) + Send + Sync + 'static,
+    ) {
+        self.observers.register(observer);
+    }
+
+    /// Registers each of the given observers, see [`GameState::register_observer`].
+    pub fn register_shared_observers(&mut self, observers: &SharedGameEventObservers) {
+        self.observers.register_shared(observers);
+    }
+
+    /// Updates the game state for time that passed while the game was not running.
+    /// This does not count as played time, and the completed actions and wasted items are not reported.
+    pub fn update_offline(&mut self, passed_real_milliseconds: i64) {
+        self.advance(passed_real_milliseconds);
+        self.completed_actions.clear();
+        self.inventory.take_wasted_items();
+    }
+
+    /// Applies a change requested by the player.
+    /// Returns false if the change was rejected and the game state was left unchanged.
+    pub fn handle_message(&mut self, message: &GameStateMessage) -> bool {
+        match message {
+            GameStateMessage::ActionChanged(action) => {
+                if !self.actions.action(*action).state.is_active() {
+                    warn!("Ignoring selection of inactive action {action:?}");
+                    return false;
+                }
+                if !self.can_afford_action(*action) {
+                    warn!("Ignoring selection of unaffordable action {action:?}");
+                    return false;
+                }
+                self.actions.selected_action = *action;
+                self.idle_start = None;
+            }
+            GameStateMessage::ActionChangedExplore(location) => {
+                if !self.world.select_location(*location) {
+                    warn!("Ignoring selection of inactive location {location:?}");
+                    return false;
+                }
+                self.actions.selected_action = ACTION_EXPLORE;
+                self.idle_start = None;
+            }
+            GameStateMessage::ExplorationLocationChanged(location) => {
+                if !self.world.select_location(*location) {
+                    warn!("Ignoring selection of inactive location {location:?}");
+                    return false;
+                }
+            }
+            GameStateMessage::CombatStyleChanged(combat_style) => {
+                self.character.selected_combat_style = *combat_style;
+            }
+            GameStateMessage::CombatPolicyChanged(combat_policy) => {
+                self.set_combat_policy(*combat_policy);
+            }
+            GameStateMessage::TrainingPolicyChanged(training_policy) => {
+                self.training_policy = *training_policy;
+            }
+            GameStateMessage::CancelCurrentAction => {
+                self.cancel_current_action();
+            }
+            GameStateMessage::RenameCharacter(name) => {
+                if !self.character.rename(name) {
+                    warn!("Ignoring empty character name {name:?}");
+                    return false;
+                }
+            }
+            GameStateMessage::ToggleFavorite(action) => {
+                self.toggle_favorite_action(*action);
+            }
+            GameStateMessage::EnqueueAction(action) => {
+                if !self.enqueue_action(*action) {
+                    warn!("Ignoring enqueueing of inactive action {action:?}");
+                    return false;
+                }
+            }
+            GameStateMessage::MoveQueuedActionUp(index) => {
+                if *index == 0 || *index >= self.action_queue.len() {
+                    warn!("Ignoring move of queued action {index}");
+                    return false;
+                }
+                self.action_queue.swap(*index - 1, *index);
+            }
+            GameStateMessage::RemoveQueuedAction(index) => {
+                if self.action_queue.remove(*index).is_none() {
+                    warn!("Ignoring removal of queued action {index}");
+                    return false;
+                }
+            }
+            GameStateMessage::ClearActionQueue => {
+                self.action_queue.clear();
+            }
+            GameStateMessage::TogglePinnedQuest(quest_id) => {
+                if !self.toggle_pinned_quest(*quest_id) {
+                    warn!("Ignoring pinning of quest {quest_id:?}");
+                    return false;
+                }
+            }
+            GameStateMessage::SkipToActionCompletion => {
+                if !self.skip_to_action_completion() {
+                    warn!("Ignoring skip to the completion of the current action");
+                    return false;
+                }
+            }
+            GameStateMessage::AcceptQuest(quest_id) => {
+                if !self.accept_quest(*quest_id) {
+                    warn!("Ignoring acceptance of unavailable quest {quest_id:?}");
+                    return false;
+                }
+            }
+            GameStateMessage::PurchaseTavernQuality(tavern_quality) => {
+                if !self.purchase_tavern_quality(*tavern_quality) {
+                    warn!("Ignoring purchase of tavern quality {tavern_quality:?}");
+                    return false;
+                }
+            }
+        }
+        self.revision = next_revision();
+        true
+    }
+
+    /// Changes whenever events are dispatched or the player changes the game state, but not when only time passes.
+    /// Views can rebuild what they derive from the game state only when the revision changed.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Ends the action in progress early, granting its rewards pro-rated by the elapsed time.
+    /// Partial combat actions do not count as a kill.
+    pub fn cancel_current_action(&mut self) {
+        if !self.actions.has_action_in_progress() {
+            return;
+        }
+
+        let mut in_progress = self.actions.in_progress().clone();
+        let elapsed = (self.current_time - in_progress.start)
+            .max(GameTime::zero())
+            .min(in_progress.length());
+        let fraction = if in_progress.length() > GameTime::zero() {
+            elapsed.milliseconds() as f64 / in_progress.length().milliseconds() as f64
+        } else {
+            1.0
+        };
+
+        let mut game_events = Vec::new();
+        in_progress.attribute_progress = in_progress.attribute_progress * fraction;
+        game_events.extend(
+            self.character.add_attribute_progress(
+                in_progress.attribute_progress * self.progress_multiplier(),
+            ),
+        );
+        if in_progress.success {
+            in_progress.currency_reward = in_progress.currency_reward * fraction;
+            in_progress.items.clear();
+
+            self.inventory.currency += in_progress.currency_reward;
+            if in_progress.currency_reward != Currency::zero() {
+                game_events.push(CompiledGameEvent::CurrencyChanged {
+                    value: self.inventory.currency,
+                })
+            }
+        }
+
+        in_progress.end = in_progress.start + elapsed;
+        self.actions.set_in_progress(in_progress);
+        self.actions.selected_action = ACTION_WAIT;
+        self.idle_start = None;
+        self.log.log(self.actions.in_progress().clone());
+
+        self.dispatch_events(&game_events);
+
+        let game_events: Vec<_> = self
+            .next_player_action(self.actions.in_progress().end)
+            .collect();
+        self.dispatch_events(&game_events);
+        debug!("New action: {:?}", self.actions.in_progress());
+    }
+
+    /// Advances the game time just past the end of the action in progress in one step, completing it like waiting would.
+    /// Combats are resolved from the seeded random number generator when they start, so skipping does not change their outcome.
+    /// The skipped time does not count as played time.
+    /// Returns false and leaves the game state unchanged if there is no action in progress or the end of time is reached.
+    pub fn skip_to_action_completion(&mut self) -> bool {
+        if !self.actions.has_action_in_progress() {
+            return false;
+        }
+
+        match self
+            .actions
+            .in_progress()
+            .end
+            .checked_add(GameTime::from_milliseconds(1))
+        {
+            Some(current_time) => {
+                self.advance_to(current_time.max(self.current_time));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redistributes the character's attribute progress towards the target attributes, see [`Character::respec`].
+    /// Returns false and leaves the game state unchanged if the player cannot afford [`Character::respec_cost`].
+    pub fn respec(&mut self, target: CharacterAttributes) -> bool {
+        let cost = self.character.respec_cost();
+        if self.inventory.currency < cost {
+            return false;
+        }
+
+        self.inventory.currency -= cost;
+        let mut game_events: Vec<_> = self.character.respec(&target).collect();
+        game_events.push(CompiledGameEvent::CurrencyChanged {
+            value: self.inventory.currency,
+        });
+
+        self.dispatch_events(&game_events);
+        true
+    }
+
+    /// Upgrades the tavern quality for its price.
+    /// Returns false and leaves the game state unchanged if it is not an upgrade or the player cannot afford it.
+    pub fn purchase_tavern_quality(&mut self, tavern_quality: TavernQuality) -> bool {
+        let price = tavern_quality.price();
+        if tavern_quality <= self.tavern_quality || self.inventory.currency < price {
+            return false;
+        }
+
+        self.inventory.currency -= price;
+        self.tavern_quality = tavern_quality;
+        let game_event = CompiledGameEvent::CurrencyChanged {
+            value: self.inventory.currency,
+        };
+        self.dispatch_events(&[game_event]);
+        true
+    }
+
+    /// True if the player can pay for the given action when it completes, or if the action allows debt.
+    /// Actions that do not cost anything can always be afforded, even if the player is in debt.
+    pub fn can_afford_action(&self, action_id: PlayerActionId) -> bool {
+        let action = self.actions.action(action_id);
+        let currency_reward = self
+            .difficulty
+            .scale_currency_reward(action.currency_reward);
+        action.allow_debt
+            || currency_reward >= Currency::zero()
+            || self.inventory.currency + currency_reward >= Currency::zero()
+    }
+
+    /// Spawns a visit of the tavern with the benefits and costs of the current tavern quality.
+    pub fn spawn_tavern_visit(&mut self, start_time: GameTime) -> PlayerActionInProgress {
+        let mut tavern_visit = self.actions.action(ACTION_TAVERN).spawn(
+            &mut self.rng,
+            start_time,
+            self.world.selected_location,
+        );
+        self.tavern_quality.apply(&mut tavern_visit);
+        tavern_visit
+    }
+
+    /// The in-game time that passed since the game was started.
+    /// Saturates if it does not fit into a [`GameTime`].
+    pub fn game_time_played(&self) -> GameTime {
+        self.current_time
+            .saturating_sub(self.starting_time.unwrap_or(self.current_time))
+    }
+
+    /// Fills in the fields that savegames from older versions do not contain.
+    /// Their time played is counted from the time they are loaded on.
+    pub fn complete_loaded_savegame(&mut self) {
+        self.starting_time.get_or_insert(self.current_time);
+    }
+
+    fn advance(&mut self, passed_real_milliseconds: i64) {
         if passed_real_milliseconds < 0 {
             warn!("Attempting to update with negative duration: {passed_real_milliseconds}; last_update: {}", self.last_update.naive_local());
             return;
         }
 
-        let passed_game_time = passed_real_milliseconds * GAME_TIME_PER_MILLISECOND;
-        self.current_time += passed_game_time;
+        match GAME_TIME_PER_MILLISECOND
+            .checked_mul(passed_real_milliseconds)
+            .and_then(|passed_game_time| self.current_time.checked_add(passed_game_time))
+        {
+            Some(current_time) => self.advance_to(current_time),
+            None => {
+                warn!("Attempting to update beyond the end of time: {passed_real_milliseconds}; current_time: {:?}", self.current_time);
+                return;
+            }
+        }
+
+        self.last_update += Duration::milliseconds(passed_real_milliseconds);
+    }
+
+    /// Completes all actions that end before the given game time.
+    fn advance_to(&mut self, current_time: GameTime) {
+        self.current_time = current_time;
 
         if !self.actions.has_action_in_progress() {
-            let game_events = self.next_player_action(self.current_time);
-            self.triggers.execute_owned_events(game_events);
-            self.execute_all_triggered_actions();
+            let game_events: Vec<_> = self.next_player_action(self.current_time).collect();
+            self.dispatch_events(&game_events);
             debug!("New action: {:?}", self.actions.in_progress());
         }
 
         while self.actions.in_progress().end < self.current_time {
+            self.fail_expired_quests(self.actions.in_progress().end);
+            self.prune_buffs(self.actions.in_progress().end);
+            self.fire_calendar_events(self.actions.in_progress().end);
             let mut game_events = Vec::new();
+            // fled combats still give their partial attribute progress
+            game_events.extend(self.character.add_attribute_progress(
+                self.actions.in_progress().attribute_progress * self.progress_multiplier(),
+            ));
+            game_events.extend(
+                self.actions
+                    .in_progress()
+                    .pack_kills
+                    .iter()
+                    .map(|kill| CompiledGameEvent::MonsterKilled { id: kill.monster }),
+            );
             if self.actions.in_progress().success {
-                game_events.extend(
-                    self.character
-                        .add_attribute_progress(self.actions.in_progress().attribute_progress),
-                );
                 self.inventory.currency += self.actions.in_progress().currency_reward;
+                self.grant_buffs_of_completed_action();
                 game_events.extend(
                     self.inventory
                         .add_multiple(self.actions.in_progress().items.iter().copied()),
@@ -124,6 +606,8 @@ impl GameState {
                 game_events.push(CompiledGameEvent::ActionCompleted {
                     id: self.actions.in_progress().source.action_id(),
                 });
+                self.completed_actions
+                    .push(self.actions.in_progress().source.action_id());
                 if self.actions.in_progress().source.action_id() == ACTION_EXPLORE {
                     game_events.push(CompiledGameEvent::ExplorationCompleted {
                         id: self.actions.in_progress().location,
@@ -138,12 +622,20 @@ impl GameState {
                 match self.actions.in_progress().source {
                     PlayerActionInProgressSource::Action(_) => {}
                     PlayerActionInProgressSource::Exploration(exploration_event) => {
+                        self.world.complete_exploration_event(exploration_event);
                         game_events.push(CompiledGameEvent::ExplorationEventCompleted {
                             id: exploration_event,
                         });
                     }
                 }
             } else {
+                // fled packs still give the currency of the monsters killed before fleeing
+                if self.actions.in_progress().currency_reward != Currency::zero() {
+                    self.inventory.currency += self.actions.in_progress().currency_reward;
+                    game_events.push(CompiledGameEvent::CurrencyChanged {
+                        value: self.inventory.currency,
+                    })
+                }
                 match self.actions.in_progress().kind {
                     PlayerActionInProgressKind::Combat(monster) => {
                         game_events.push(CompiledGameEvent::MonsterFailed { id: monster });
@@ -153,15 +645,296 @@ impl GameState {
             }
 
             self.log.log(self.actions.in_progress().deref().clone());
+            self.apply_combat_policy();
+            game_events.extend(self.idle_event());
 
-            self.triggers.execute_events(game_events.iter());
-            self.execute_all_triggered_actions();
+            self.dispatch_events(&game_events);
+            self.apply_action_queue();
+            self.apply_action_chain();
+            self.apply_training_policy();
 
-            game_events.extend(self.next_player_action(self.actions.in_progress().end));
+            let game_events: Vec<_> = self
+                .next_player_action(self.actions.in_progress().end)
+                .collect();
+            self.dispatch_events(&game_events);
             debug!("New action: {:?}", self.actions.in_progress());
         }
+        self.fail_expired_quests(self.current_time);
+        self.prune_buffs(self.current_time);
+        self.fire_calendar_events(self.current_time);
+        self.combat_log
+            .update(self.actions.in_progress(), self.current_time);
 
-        self.last_update += Duration::milliseconds(passed_real_milliseconds);
+        #[cfg(debug_assertions)]
+        if let Err(violation) = self.validate_invariants() {
+            panic!("Invalid game state after update: {violation}");
+        }
+    }
+
+    /// Multiplies all attribute progress, combining prestige, difficulty and buffs.
+    pub fn progress_multiplier(&self) -> f64 {
+        self.buffs
+            .iter()
+            .map(|(buff_kind, _)| buff_kind.progress_multiplier())
+            .product::<f64>()
+            * self.prestige.multiplier
+            * self.difficulty.progress_multiplier()
+    }
+
+    /// Multiplies the damage output of the character in combat, combining all buffs.
+    pub fn damage_multiplier(&self) -> f64 {
+        self.buffs
+            .iter()
+            .map(|(buff_kind, _)| buff_kind.damage_multiplier())
+            .product()
+    }
+
+    /// Grants the buff from now on for its duration.
+    /// If the buff is already active, it expires at the later of the two expiry times.
+    pub fn grant_buff(&mut self, buff: Buff) {
+        self.grant_buff_at(buff, self.current_time);
+    }
+
+    fn grant_buff_at(&mut self, buff: Buff, time: GameTime) {
+        let expiry = time + buff.duration;
+        if let Some((_, active_expiry)) = self
+            .buffs
+            .iter_mut()
+            .find(|(buff_kind, _)| *buff_kind == buff.kind)
+        {
+            *active_expiry = (*active_expiry).max(expiry);
+        } else {
+            debug!("Granting buff {:?} until {expiry:?}", buff.kind);
+            self.buffs.push((buff.kind, expiry));
+        }
+    }
+
+    /// Grants the buffs of the exploration event and the items of the successfully completed action in progress.
+    fn grant_buffs_of_completed_action(&mut self) {
+        let in_progress = self.actions.in_progress();
+        let time = in_progress.end;
+        let event_buff = match in_progress.source {
+            PlayerActionInProgressSource::Action(_) => None,
+            PlayerActionInProgressSource::Exploration(exploration_event) => {
+                self.world.event(exploration_event).buff
+            }
+        };
+        let buffs: Vec<_> = event_buff
+            .into_iter()
+            .chain(
+                in_progress
+                    .items
+                    .iter()
+                    .filter(|item| item.count > 0)
+                    .filter_map(|item| self.inventory.item(item.id).buff),
+            )
+            .collect();
+        for buff in buffs {
+            self.grant_buff_at(buff, time);
+        }
+    }
+
+    /// Removes all buffs that expired at or before the given time.
+    fn prune_buffs(&mut self, time: GameTime) {
+        self.buffs.retain(|(_, expiry)| *expiry > time);
+    }
+
+    /// The attribute progress per game hour of the action in progress, including all multipliers.
+    /// Its sum is the level progress per game hour.
+    pub fn attribute_progress_per_hour(&self) -> CharacterAttributeProgress {
+        if !self.actions.has_action_in_progress() {
+            return CharacterAttributeProgress::zero();
+        }
+        let in_progress = self.actions.in_progress();
+        self.character.attribute_progress_per_hour(
+            in_progress.attribute_progress * self.progress_multiplier(),
+            in_progress.length(),
+        )
+    }
+
+    pub fn combat_policy(&self) -> CombatPolicy {
+        self.combat_policy
+    }
+
+    pub fn set_combat_policy(&mut self, combat_policy: CombatPolicy) {
+        self.combat_policy = combat_policy;
+        self.combat_streak_start = None;
+    }
+
+    pub fn training_policy(&self) -> TrainingPolicy {
+        self.training_policy
+    }
+
+    pub fn is_favorite_action(&self, action: PlayerActionId) -> bool {
+        self.favorite_actions.contains(&action)
+    }
+
+    pub fn toggle_favorite_action(&mut self, action: PlayerActionId) {
+        if !self.favorite_actions.remove(&action) {
+            self.favorite_actions.insert(action);
+        }
+    }
+
+    pub fn action_queue(&self) -> &VecDeque<PlayerActionId> {
+        &self.action_queue
+    }
+
+    /// Appends the action to the action queue.
+    /// Returns false and leaves the queue unchanged if the action is not active.
+    pub fn enqueue_action(&mut self, action: PlayerActionId) -> bool {
+        if !self.actions.action(action).state.is_active() {
+            return false;
+        }
+        self.action_queue.push_back(action);
+        true
+    }
+
+    /// The pinned quests that are still active, in the order they were pinned.
+    pub fn pinned_quests(&self) -> impl Iterator<Item = &'_ CompiledQuest> {
+        self.pinned_quests
+            .iter()
+            .map(|quest_id| self.story.quest(*quest_id))
+            .filter(|quest| quest.state().is_active())
+    }
+
+    pub fn is_pinned_quest(&self, quest_id: QuestId) -> bool {
+        self.pinned_quests.contains(&quest_id)
+    }
+
+    /// Unpins the quest if it is pinned, and pins it otherwise.
+    /// Returns false if the quest is not active or [`MAX_PINNED_QUESTS`] active quests are pinned already.
+    /// Pins of quests that are no longer active are dropped first, so those cannot be unpinned.
+    pub fn toggle_pinned_quest(&mut self, quest_id: QuestId) -> bool {
+        let story = &self.story;
+        self.pinned_quests
+            .retain(|pinned| story.quest(*pinned).state().is_active());
+        if let Some(index) = self
+            .pinned_quests
+            .iter()
+            .position(|pinned| *pinned == quest_id)
+        {
+            self.pinned_quests.remove(index);
+            return true;
+        }
+
+        if !self.story.quest(quest_id).state().is_active()
+            || self.pinned_quests.len() >= MAX_PINNED_QUESTS
+        {
+            return false;
+        }
+        self.pinned_quests.push(quest_id);
+        true
+    }
+
+    /// Moves favorite actions to the front, keeping the relative order of the actions otherwise.
+    pub fn sort_favorite_actions_first(&self, actions: &mut [&CompiledPlayerAction]) {
+        actions.sort_by_key(|action| !self.is_favorite_action(action.id));
+    }
+
+    /// Stops exploring if the combat policy is fulfilled by the action that just completed.
+    fn apply_combat_policy(&mut self) {
+        if self.actions.selected_action != ACTION_EXPLORE {
+            self.combat_streak_start = None;
+            return;
+        }
+
+        let in_progress = self.actions.in_progress();
+        if let PlayerActionInProgressKind::Combat(_) = in_progress.kind {
+            let streak_start = *self.combat_streak_start.get_or_insert(in_progress.start);
+            let stop = match self.combat_policy {
+                CombatPolicy::Continuous => false,
+                CombatPolicy::FightOne => true,
+                CombatPolicy::UntilDefeat => !in_progress.success,
+                CombatPolicy::ForDuration(duration) => in_progress.end >= streak_start + duration,
+            };
+
+            if stop {
+                self.actions.selected_action = ACTION_WAIT;
+                self.combat_streak_start = None;
+            }
+        }
+    }
+
+    /// Returns how long waiting has been selected if it is selected while the action completes.
+    fn idle_event(&mut self) -> Option<CompiledGameEvent> {
+        if self.actions.selected_action != ACTION_WAIT {
+            self.idle_start = None;
+            return None;
+        }
+
+        let in_progress = self.actions.in_progress();
+        // waiting selected during another action only counts from the end of that action
+        let waiting_start = if in_progress.source.action_id() == ACTION_WAIT {
+            in_progress.start
+        } else {
+            in_progress.end
+        };
+        let idle_start = *self.idle_start.get_or_insert(waiting_start);
+        Some(CompiledGameEvent::PlayerIdle {
+            duration: in_progress.end - idle_start,
+        })
+    }
+
+    /// Selects the follow-up of the completed action if it is still selected and the follow-up is active.
+    /// Each completion advances the chain by at most one action, so cyclic chains rotate.
+    fn apply_action_chain(&mut self) {
+        let in_progress = self.actions.in_progress();
+        let completed_action = in_progress.source.action_id();
+        if !in_progress.success || self.actions.selected_action != completed_action {
+            return;
+        }
+
+        if let Some(then) = self.actions.action(completed_action).then {
+            if self.actions.action(then).state.is_active() {
+                self.actions.selected_action = then;
+            }
+        }
+    }
+
+    /// Selects the training action for the lowest attribute if the focus training policy is set
+    /// and the training action that just completed is still selected.
+    fn apply_training_policy(&mut self) {
+        let completed_action = self.actions.in_progress().source.action_id();
+        if self.training_policy != TrainingPolicy::Focus
+            || self.actions.selected_action != completed_action
+            || self.actions.action(completed_action).action_type != PlayerActionType::Train
+        {
+            return;
+        }
+
+        if let Some(action) = self
+            .actions
+            .list_choosable()
+            .filter(|action| {
+                action.action_type == PlayerActionType::Train && self.can_afford_action(action.id)
+            })
+            .min_by_key(|action| {
+                (
+                    self.character
+                        .trained_attribute_standing(action.attribute_progress_factor),
+                    action.id,
+                )
+            })
+            .map(|action| action.id)
+        {
+            self.actions.selected_action = action;
+        }
+    }
+
+    /// Selects the next queued action that is still active if the selected action just completed.
+    /// Queued actions that became inactive are dropped from the queue.
+    fn apply_action_queue(&mut self) {
+        if self.actions.in_progress().source.action_id() != self.actions.selected_action {
+            return;
+        }
+
+        while let Some(action) = self.action_queue.pop_front() {
+            if self.actions.action(action).state.is_active() {
+                self.actions.selected_action = action;
+                return;
+            }
+            debug!("Skipping inactive queued action {action:?}");
+        }
     }
 
     fn next_player_action(
@@ -177,9 +950,12 @@ impl GameState {
         let hour_of_day = start_time.hour_of_day();
         let time_of_day = start_time.time_of_day();
 
-        let tavern_currency_gain = self.actions.action(ACTION_TAVERN).currency_reward;
+        let tavern_currency_gain = self
+            .tavern_quality
+            .scale_visit_cost(self.actions.action(ACTION_TAVERN).currency_reward);
+        let damage_multiplier = self.damage_multiplier();
 
-        let action = if !(6..22).contains(&hour_of_day) {
+        let mut action = if !(6..22).contains(&hour_of_day) {
             // sleep until 6 in the morning
             let end_time = if hour_of_day < 6 {
                 start_time.floor_day()
@@ -193,18 +969,25 @@ impl GameState {
             action_in_progress.end = end_time;
             action_in_progress
         } else if self.inventory.currency >= -tavern_currency_gain
-            && rand::thread_rng()
+            && self
+                .rng
                 .gen_range(earliest_tavern_time.seconds()..=latest_tavern_time.seconds())
                 <= time_of_day.seconds()
         {
-            let action = self.actions.action(ACTION_TAVERN);
-            action.spawn(&mut self.rng, start_time, self.world.selected_location)
+            self.spawn_tavern_visit(start_time)
         } else {
             let action = self.actions.action(self.actions.selected_action);
 
             if action.id == ACTION_EXPLORE {
                 self.world
-                    .explore(&mut self.rng, start_time, action.duration, &self.character)
+                    .explore(
+                        &mut self.rng,
+                        start_time,
+                        action.duration,
+                        &self.character,
+                        self.difficulty,
+                        damage_multiplier,
+                    )
                     .unwrap_or_else(|| {
                         self.actions.action(ACTION_WAIT).spawn(
                             &mut self.rng,
@@ -212,11 +995,27 @@ impl GameState {
                             self.world.selected_location,
                         )
                     })
+            } else if !self.can_afford_action(action.id) {
+                debug!(
+                    "Waiting instead of starting unaffordable action {:?}",
+                    action.id
+                );
+                self.world.break_exploration_chain();
+                self.actions.action(ACTION_WAIT).spawn(
+                    &mut self.rng,
+                    start_time,
+                    self.world.selected_location,
+                )
             } else {
+                self.world.break_exploration_chain();
                 action.spawn(&mut self.rng, start_time, self.world.selected_location)
             }
         };
 
+        action.currency_reward = self
+            .difficulty
+            .scale_currency_reward(action.currency_reward);
+
         assert!(
             self.actions
                 .action(action.source.action_id())
@@ -242,11 +1041,146 @@ impl GameState {
 
     fn execute_all_triggered_actions(&mut self) {
         while let Some(game_action) = self.triggers.consume_action() {
-            let game_events = self.execute_game_action(game_action);
-            self.triggers.execute_owned_events(game_events);
+            let game_action_event = game_action.clone().into();
+            self.emit_events(iter::once(&game_action_event));
+            let game_events: Vec<_> = self.execute_game_action(game_action).collect();
+            self.emit_events(game_events.iter());
+            self.record_quest_events(iter::once(&game_action_event).chain(game_events.iter()));
+            self.triggers.execute_events(game_events.iter());
+            self.observers
+                .notify(iter::once(&game_action_event).chain(game_events.iter()));
         }
     }
 
+    /// Activates a quest with manual activation that was offered to the player.
+    /// Returns false and leaves the game state unchanged if the quest is not available.
+    pub fn accept_quest(&mut self, quest_id: QuestId) -> bool {
+        if !self.story.accept_quest(quest_id) {
+            return false;
+        }
+
+        let game_events: Vec<_> = iter::once(CompiledGameEvent::Action(
+            CompiledGameAction::ActivateQuest { id: quest_id },
+        ))
+        .chain(self.story.activate_quest(quest_id, self.current_time))
+        .collect();
+        self.record_quest_events(game_events.iter());
+        self.dispatch_events(&game_events);
+        true
+    }
+
+    /// Fails all quests whose time limit elapsed at or before the given time, each at its deadline.
+    fn fail_expired_quests(&mut self, time: GameTime) {
+        let expired_quests: Vec<_> = self.story.expired_quests(time).collect();
+        for (quest_id, deadline) in expired_quests {
+            let game_events: Vec<_> =
+                iter::once(CompiledGameEvent::Action(CompiledGameAction::FailQuest {
+                    id: quest_id,
+                }))
+                .chain(self.story.fail_quest(quest_id, deadline))
+                .collect();
+            self.record_quest_events(game_events.iter());
+            self.dispatch_events(&game_events);
+        }
+    }
+
+    /// Emits an event for each occurrence of a calendar event since the last call, up to the given time.
+    fn fire_calendar_events(&mut self, time: GameTime) {
+        let game_events: Vec<_> = self
+            .calendar
+            .advance(time)
+            .into_iter()
+            .map(|id| CompiledGameEvent::CalendarEventOccurred { id })
+            .collect();
+        for game_event in game_events {
+            self.dispatch_events(&[game_event]);
+        }
+    }
+
+    fn record_quest_events<'events>(
+        &mut self,
+        events: impl IntoIterator<Item = &'events CompiledGameEvent>,
+    ) {
+        self.quest_events.extend(
+            events
+                .into_iter()
+                .filter(|event| {
+                    matches!(
+                        event,
+                        CompiledGameEvent::Action(CompiledGameAction::ActivateQuest { .. })
+                            | CompiledGameEvent::Action(CompiledGameAction::OfferQuest { .. })
+                            | CompiledGameEvent::Action(CompiledGameAction::FailQuest { .. })
+                            | CompiledGameEvent::QuestCompleted { .. }
+                    )
+                })
+                .cloned(),
+        );
+    }
+
+    /// Returns the quest activations, offers, completions and failures that happened since the last call.
+    pub fn take_quest_events(&mut self) -> Vec<CompiledGameEvent> {
+        std::mem::take(&mut self.quest_events)
+    }
+
+    /// Returns the actions that were completed while playing since the last call, in order of completion.
+    pub fn take_completed_actions(&mut self) -> Vec<PlayerActionId> {
+        std::mem::take(&mut self.completed_actions)
+    }
+
+    /// Dispatches the events to the triggers and then to the observers, and executes the actions they trigger.
+    fn dispatch_events(&mut self, game_events: &[CompiledGameEvent]) {
+        self.emit_events(game_events);
+        self.triggers.execute_events(game_events);
+        self.observers.notify(game_events);
+        self.execute_all_triggered_actions();
+    }
+
+    /// Logs the state transitions among the events when profiling and records the events during [`GameState::update_with_events`].
+    fn emit_events<'events>(
+        &mut self,
+        events: impl IntoIterator<Item = &'events CompiledGameEvent>,
+    ) {
+        for event in events {
+            self.revision = next_revision();
+            if self.profile {
+                if let Some(description) = self.state_transition_description(event) {
+                    debug!("{description}");
+                }
+            }
+            if let Some(emitted_events) = &mut self.emitted_events {
+                emitted_events.push(event.clone());
+            }
+        }
+    }
+
+    /// Describes the given event if it is a state transition that should be logged when profiling.
+    pub fn state_transition_description(&self, event: &CompiledGameEvent) -> Option<String> {
+        let transition = match event {
+            CompiledGameEvent::Action(CompiledGameAction::ActivateQuest { id }) => {
+                format!("activated quest {}", self.story.quest(*id).id_str)
+            }
+            CompiledGameEvent::QuestCompleted { id } => {
+                format!("completed quest {}", self.story.quest(*id).id_str)
+            }
+            CompiledGameEvent::Action(CompiledGameAction::ActivateAction { id }) => {
+                format!("activated action {}", self.actions.action(*id).id_str)
+            }
+            CompiledGameEvent::Action(CompiledGameAction::DeactivateAction { id }) => {
+                format!("deactivated action {}", self.actions.action(*id).id_str)
+            }
+            CompiledGameEvent::PlayerLevelChanged { value } => format!("reached level {value}"),
+            _ => return None,
+        };
+
+        Some(format!(
+            "[{}y {}d {:02}:{:02}] State transition: {transition}",
+            self.current_time.years(),
+            self.current_time.day_of_year(),
+            self.current_time.hour_of_day(),
+            self.current_time.minute_of_hour(),
+        ))
+    }
+
     fn execute_game_action(
         &mut self,
         game_action: CompiledGameAction,
@@ -263,6 +1197,9 @@ impl GameState {
                     self.current_time,
                 ))
             }
+            CompiledGameAction::OfferQuest { id } => {
+                Box::new(self.story.offer_quest(id, self.current_time))
+            }
             CompiledGameAction::FailQuest { id } => {
                 Box::new(self.story.fail_quest(id, self.current_time))
             }
@@ -314,3 +1251,1273 @@ impl GameState {
         }
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::{
+        GameState, GameStateInitialisation, GameStateMessage, GAME_TIME_PER_MILLISECOND,
+        MAX_PLAYED_TIME_PER_UPDATE,
+    };
+    use crate::game_state::character::{
+        CharacterAttributeProgress, CharacterAttributes, CharacterRace,
+    };
+    use crate::game_state::currency::Currency;
+    use crate::game_state::difficulty::Difficulty;
+    use crate::game_state::event_log::GameEventKind;
+    use crate::game_state::observers::{GameEventObserver, SharedGameEventObservers};
+    use crate::game_state::player_actions::{
+        CombatPolicy, PlayerActionId, PlayerActionInProgress, PlayerActionInProgressKind,
+        TrainingPolicy, ACTION_EXPLORE, ACTION_WAIT,
+    };
+    use crate::game_state::story::quests::QuestId;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::triggers::{CompiledGameAction, CompiledGameEvent};
+    use crate::game_state::world::monsters::MonsterId;
+    use crate::game_template::parser::parse_game_template_file;
+    use crate::game_template::{CompiledGameTemplate, GameTemplate};
+    use pot::Value;
+    use serde::Serialize;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    const TEMPLATE_FILES: [&str; 3] = [
+        include_str!("../../data/builtin.tpl"),
+        include_str!("../../data/game.tpl"),
+        include_str!("../../data/village.tpl"),
+    ];
+
+    pub fn create_test_game_state() -> GameState {
+        create_test_game_state_with_templates(&[])
+    }
+
+    pub fn create_test_game_state_with_templates(additional_template_files: &[&str]) -> GameState {
+        GameState::new(
+            create_test_game_template(additional_template_files),
+            GameStateInitialisation {
+                savegame_file: "test_savegame.json".into(),
+                name: "Tester".to_string(),
+                pronoun: "they".to_string(),
+                race: CharacterRace::default(),
+                difficulty: Difficulty::default(),
+                destiny_roll: false,
+            },
+        )
+    }
+
+    pub fn create_test_game_template(additional_template_files: &[&str]) -> CompiledGameTemplate {
+        let mut game_template = GameTemplate::default();
+        for template_file in TEMPLATE_FILES
+            .iter()
+            .chain(additional_template_files.iter())
+        {
+            async_std::task::block_on(parse_game_template_file(
+                &mut game_template,
+                template_file.as_bytes(),
+            ))
+            .unwrap();
+        }
+
+        game_template.compile().unwrap()
+    }
+
+    /// The real time in which the given game time passes.
+    pub fn real_milliseconds(time: GameTime) -> i64 {
+        (time.milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds()) as i64
+    }
+
+    /// Serialises the value in a form that does not depend on the iteration order of hash maps.
+    pub fn canonical_pot(value: &impl Serialize) -> String {
+        fn canonical_value(value: &Value<'_>) -> String {
+            let sorted = |mut entries: Vec<String>| {
+                entries.sort();
+                entries.join(",")
+            };
+            match value {
+                Value::Sequence(values) => {
+                    format!("[{}]", sorted(values.iter().map(canonical_value).collect()))
+                }
+                Value::Mappings(mappings) => format!(
+                    "{{{}}}",
+                    sorted(
+                        mappings
+                            .iter()
+                            .map(|(key, value)| {
+                                format!("{}:{}", canonical_value(key), canonical_value(value))
+                            })
+                            .collect()
+                    )
+                ),
+                value => format!("{value:?}"),
+            }
+        }
+
+        let serialised = pot::to_vec(value).unwrap();
+        canonical_value(&pot::from_slice(&serialised).unwrap())
+    }
+
+    pub fn execute_test_event(game_state: &mut GameState, event: CompiledGameEvent) {
+        game_state.triggers.execute_event(&event);
+        game_state.execute_all_triggered_actions();
+    }
+
+    const CANCEL_TEMPLATE: &str = "
+ACTION after_rat_kill
+name After rat kill
+progressive resting after the fight
+simple_past rested after the fight
+type TRAIN
+duration 1h
+strength 1.0
+currency 0
+activation monster_killed_count(1, rat)
+deactivation never
+";
+
+    const ARENA_TEMPLATE: &str = "
+LOCATION arena
+name Arena
+events (1.0, fight_weakling), (0.0, fight_giant)
+activation none
+deactivation never
+
+EXPLORATION_EVENT fight_weakling
+monster weakling
+currency 0
+activation none
+deactivation never
+
+EXPLORATION_EVENT fight_giant
+monster giant
+currency 0
+activation none
+deactivation never
+
+MONSTER weakling
+name Weakling
+hitpoints 20 * level
+activation none
+deactivation never
+
+MONSTER giant
+name Giant
+hitpoints 1000000
+activation none
+deactivation never
+";
+
+    /// Explores the arena from 06:00 to 18:30 and returns the resolved combats.
+    fn explore_arena(combat_policy: CombatPolicy, giant: bool) -> Vec<PlayerActionInProgress> {
+        let arena_template = if giant {
+            ARENA_TEMPLATE.replace(
+                "(1.0, fight_weakling), (0.0, fight_giant)",
+                "(1.0, fight_giant)",
+            )
+        } else {
+            ARENA_TEMPLATE.to_string()
+        };
+        let mut game_state = create_test_game_state_with_templates(&[&arena_template]);
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "arena")
+            .unwrap()
+            .id;
+        assert!(game_state.world.select_location(arena));
+        game_state.actions.selected_action = ACTION_EXPLORE;
+        game_state.set_combat_policy(combat_policy);
+
+        assert_eq!(game_state.current_time.hour_of_day(), 0);
+        let start = game_state.current_time + GameTime::from_hours(6);
+        let end = game_state.current_time + GameTime::from_hours(18) + GameTime::from_minutes(30);
+        while game_state.current_time < end {
+            game_state.update_offline(
+                (GameTime::from_minutes(1).milliseconds()
+                    / GAME_TIME_PER_MILLISECOND.milliseconds()) as i64,
+            );
+        }
+
+        game_state
+            .log
+            .iter_rev()
+            .map(|event| match &event.kind {
+                GameEventKind::Action(action) => action.clone(),
+            })
+            .filter(|action| {
+                matches!(action.kind, PlayerActionInProgressKind::Combat(_))
+                    && action.start >= start
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_combat_policy_continuous() {
+        let combats = explore_arena(CombatPolicy::Continuous, false);
+        assert!(combats.len() > 6, "{}", combats.len());
+
+        let combats = explore_arena(CombatPolicy::Continuous, true);
+        assert_eq!(combats.len(), 3);
+        assert!(combats.iter().all(|combat| !combat.success));
+    }
+
+    #[test]
+    fn test_combat_policy_fight_one() {
+        assert_eq!(explore_arena(CombatPolicy::FightOne, false).len(), 1);
+        assert_eq!(explore_arena(CombatPolicy::FightOne, true).len(), 1);
+    }
+
+    #[test]
+    fn test_combat_policy_until_defeat() {
+        let combats = explore_arena(CombatPolicy::UntilDefeat, false);
+        assert!(combats.len() > 6, "{}", combats.len());
+        assert!(combats.iter().all(|combat| combat.success));
+
+        let combats = explore_arena(CombatPolicy::UntilDefeat, true);
+        assert_eq!(combats.len(), 1);
+        assert!(!combats[0].success);
+    }
+
+    #[test]
+    fn test_combat_policy_for_duration() {
+        let duration = GameTime::from_hours(2);
+        let combats = explore_arena(CombatPolicy::ForDuration(duration), false);
+        let first_start = combats.iter().map(|combat| combat.start).min().unwrap();
+        let last_end = combats.iter().map(|combat| combat.end).max().unwrap();
+        assert!(last_end >= first_start + duration);
+        assert_eq!(
+            combats
+                .iter()
+                .filter(|combat| combat.end >= first_start + duration)
+                .count(),
+            1
+        );
+        assert!(combats.len() < explore_arena(CombatPolicy::Continuous, false).len());
+    }
+
+    fn start_test_action(game_state: &mut GameState, id_str: &str) -> PlayerActionInProgress {
+        let action_id = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == id_str)
+            .unwrap()
+            .id;
+        game_state.actions.selected_action = action_id;
+        let mut in_progress = game_state.actions.action(action_id).spawn(
+            &mut game_state.rng,
+            game_state.current_time,
+            game_state.world.selected_location,
+        );
+        in_progress.currency_reward = Currency::from_copper(10);
+        game_state.actions.set_in_progress(in_progress.clone());
+        in_progress
+    }
+
+    #[test]
+    fn test_attribute_progress_per_hour() {
+        let mut game_state = create_test_game_state();
+        game_state.prestige.multiplier = 2.0;
+        start_test_action(&mut game_state, "train_str");
+        let hour = GameTime::from_hours(1).milliseconds() as u64;
+        assert_eq!(
+            game_state.attribute_progress_per_hour(),
+            CharacterAttributeProgress::from_strength(2 * hour)
+        );
+        assert_eq!(game_state.attribute_progress_per_hour().sum(), 2 * hour);
+    }
+
+    #[test]
+    fn test_cancel_current_action() {
+        let mut game_state = create_test_game_state();
+        let in_progress = start_test_action(&mut game_state, "train_str");
+        let full_progress = (in_progress.attribute_progress
+            * game_state.character.race.attribute_progress_factors())
+        .strength;
+        let strength_progress = game_state.character.attribute_progress().strength;
+        let currency = game_state.inventory.currency;
+
+        game_state.current_time = in_progress.start + GameTime::from_minutes(30);
+        game_state.cancel_current_action();
+
+        let granted_progress =
+            game_state.character.attribute_progress().strength - strength_progress;
+        assert!(granted_progress.abs_diff(full_progress / 2) <= 1);
+        assert_eq!(
+            game_state.inventory.currency,
+            currency + Currency::from_copper(5)
+        );
+        assert_eq!(game_state.actions.selected_action, ACTION_WAIT);
+        assert_eq!(
+            game_state.actions.in_progress().start,
+            game_state.current_time
+        );
+        assert_eq!(
+            game_state.log.iter_rev().next().unwrap().time,
+            game_state.current_time
+        );
+    }
+
+    #[test]
+    fn test_cancel_combat_action_grants_no_kill() {
+        let mut game_state = create_test_game_state_with_templates(&[CANCEL_TEMPLATE]);
+        let rat = (0..)
+            .map(MonsterId)
+            .find(|&monster| game_state.world.monster(monster).id_str == "rat")
+            .unwrap();
+        let mut in_progress = start_test_action(&mut game_state, "train_str");
+        in_progress.kind = PlayerActionInProgressKind::Combat(rat);
+        game_state.actions.set_in_progress(in_progress.clone());
+
+        game_state.current_time = in_progress.start + GameTime::from_minutes(30);
+        game_state.cancel_current_action();
+
+        assert!(!game_state
+            .actions
+            .list_choosable()
+            .any(|action| action.id_str == "after_rat_kill"));
+    }
+
+    #[test]
+    fn test_real_time_played() {
+        let mut game_state = create_test_game_state();
+        assert_eq!(game_state.real_time_played, GameTime::zero());
+
+        for _ in 0..10 {
+            game_state.update(16);
+        }
+        assert_eq!(
+            game_state.real_time_played,
+            GameTime::from_milliseconds(160)
+        );
+
+        // a long gap between updates means the game was paused
+        game_state.update(
+            (MAX_PLAYED_TIME_PER_UPDATE + GameTime::from_milliseconds(1)).milliseconds() as i64,
+        );
+        assert_eq!(
+            game_state.real_time_played,
+            GameTime::from_milliseconds(160)
+        );
+
+        game_state.update(MAX_PLAYED_TIME_PER_UPDATE.milliseconds() as i64);
+        assert_eq!(
+            game_state.real_time_played,
+            GameTime::from_milliseconds(160) + MAX_PLAYED_TIME_PER_UPDATE
+        );
+    }
+
+    #[test]
+    fn test_offline_time_is_not_played() {
+        let mut game_state = create_test_game_state();
+        let starting_time = game_state.current_time;
+        game_state.update(1_000);
+        game_state.update_offline(GameTime::from_hours(1).milliseconds() as i64);
+        game_state.update(1_000);
+
+        assert_eq!(game_state.real_time_played, GameTime::from_seconds(2));
+        assert_eq!(game_state.starting_time, Some(starting_time));
+        assert!(game_state.game_time_played() > GameTime::from_hours(1));
+    }
+
+    #[test]
+    fn test_savegame_without_time_played() {
+        let mut game_state = create_test_game_state();
+        game_state.update(1_000_000);
+        game_state.starting_time = None;
+        game_state.complete_loaded_savegame();
+        assert_eq!(game_state.game_time_played(), GameTime::zero());
+        game_state.update(1_000);
+        assert_eq!(
+            game_state.game_time_played(),
+            GameTime::from_milliseconds(GAME_TIME_PER_MILLISECOND.milliseconds() * 1_000)
+        );
+    }
+
+    const EMITTED_QUEST_TEMPLATE: &str = "
+ACTION emitted_study
+name Study
+progressive studying
+simple_past studied
+type TRAIN
+duration 1h
+int 1.0
+currency 0
+activation none
+deactivation never
+
+QUEST emitted_quest
+title Study once
+activation none
+failure never
+BEGIN
+    QUEST_STAGE emitted_stage
+    task Study once.
+    completion action_count(1, emitted_study)
+END
+";
+
+    #[test]
+    fn test_update_with_events_training() {
+        let mut game_state = create_test_game_state();
+        let in_progress = start_test_action(&mut game_state, "train_str");
+        let events = game_state.update_with_events(real_milliseconds(in_progress.length()) + 1);
+        assert!(game_state.emitted_events.is_none());
+
+        let completed_actions: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                CompiledGameEvent::ActionCompleted { id } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(completed_actions, vec![in_progress.source.action_id()]);
+        assert_eq!(completed_actions, game_state.take_completed_actions());
+        assert_eq!(
+            events.iter().rev().find_map(|event| match event {
+                CompiledGameEvent::CurrencyChanged { value } => Some(*value),
+                _ => None,
+            }),
+            Some(game_state.inventory.currency)
+        );
+
+        // events are only collected while updating with events
+        game_state.update(real_milliseconds(GameTime::from_hours(2)));
+        assert!(game_state.update_with_events(0).is_empty());
+    }
+
+    #[test]
+    fn test_observer_receives_training_events() {
+        let mut game_state = create_test_game_state();
+        let observed_events = Arc::new(Mutex::new(Vec::new()));
+        let observer_events = observed_events.clone();
+        game_state.register_observer(move |event| {
+            observer_events.lock().unwrap().push(event.clone());
+        });
+
+        let in_progress = start_test_action(&mut game_state, "train_str");
+        let events = game_state.update_with_events(real_milliseconds(in_progress.length()) + 1);
+
+        let observed_events = observed_events.lock().unwrap();
+        assert!(
+            observed_events.contains(&CompiledGameEvent::ActionCompleted {
+                id: in_progress.source.action_id()
+            })
+        );
+        assert_eq!(*observed_events, events);
+    }
+
+    #[test]
+    fn test_clones_do_not_notify_observers() {
+        let mut game_state = create_test_game_state();
+        let observed_events = Arc::new(Mutex::new(Vec::new()));
+        let observer_events = observed_events.clone();
+        game_state.register_observer(move |event| {
+            observer_events.lock().unwrap().push(event.clone());
+        });
+
+        let in_progress = start_test_action(&mut game_state, "train_str");
+        let mut autosave = game_state.clone();
+        autosave.update(real_milliseconds(in_progress.length()) + 1);
+        assert!(observed_events.lock().unwrap().is_empty());
+
+        game_state.update(real_milliseconds(in_progress.length()) + 1);
+        assert!(!observed_events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_shared_observers_are_kept_by_clones() {
+        let observed_events = Arc::new(Mutex::new(Vec::new()));
+        let observer_events = observed_events.clone();
+        let observer: GameEventObserver = Arc::new(move |event: &CompiledGameEvent| {
+            observer_events.lock().unwrap().push(event.clone());
+        });
+        let observers = SharedGameEventObservers::from(vec![observer]).clone();
+
+        let mut game_state = create_test_game_state();
+        game_state.register_shared_observers(&observers);
+        let in_progress = start_test_action(&mut game_state, "train_str");
+        let events = game_state.update_with_events(real_milliseconds(in_progress.length()) + 1);
+
+        assert!(!events.is_empty());
+        assert_eq!(*observed_events.lock().unwrap(), events);
+    }
+
+    #[test]
+    fn test_update_with_events_quest_completion() {
+        let mut game_state = create_test_game_state_with_templates(&[EMITTED_QUEST_TEMPLATE]);
+        let quest_id = (0..)
+            .map(QuestId)
+            .find(|&quest| game_state.story.quest(quest).id_str == "emitted_quest")
+            .unwrap();
+        assert!(game_state.story.quest(quest_id).state().is_active());
+        let in_progress = start_test_action(&mut game_state, "emitted_study");
+        game_state.take_quest_events();
+
+        let events = game_state.update_with_events(real_milliseconds(in_progress.length()) + 1);
+        assert!(game_state.story.quest(quest_id).state().is_completed());
+        let position = |expected: &CompiledGameEvent| {
+            events
+                .iter()
+                .position(|event| event == expected)
+                .unwrap_or_else(|| panic!("{expected:?} was not emitted"))
+        };
+        assert!(
+            position(&CompiledGameEvent::ActionCompleted {
+                id: in_progress.source.action_id()
+            }) < position(&CompiledGameEvent::QuestCompleted { id: quest_id })
+        );
+        assert!(game_state
+            .take_quest_events()
+            .contains(&CompiledGameEvent::QuestCompleted { id: quest_id }));
+    }
+
+    /// Skips the action in progress and compares the result to waiting for it frame by frame.
+    fn assert_skip_matches_waiting(mut game_state: GameState) {
+        let mut waiting = game_state.clone();
+        let in_progress = game_state.actions.in_progress().clone();
+        assert!(game_state.handle_message(&GameStateMessage::SkipToActionCompletion));
+        assert_eq!(
+            game_state.current_time,
+            in_progress.end + GameTime::from_milliseconds(1)
+        );
+        assert_eq!(game_state.actions.in_progress().start, in_progress.end);
+
+        while waiting.actions.in_progress().start == in_progress.start {
+            waiting.update(16);
+        }
+        assert_eq!(game_state.rng, waiting.rng);
+        assert_eq!(
+            canonical_pot(&game_state.character),
+            canonical_pot(&waiting.character)
+        );
+        assert_eq!(
+            canonical_pot(&game_state.inventory),
+            canonical_pot(&waiting.inventory)
+        );
+        assert_eq!(
+            canonical_pot(&game_state.actions),
+            canonical_pot(&waiting.actions)
+        );
+        assert_eq!(canonical_pot(&game_state.log), canonical_pot(&waiting.log));
+        assert_eq!(
+            canonical_pot(&game_state.triggers),
+            canonical_pot(&waiting.triggers)
+        );
+        assert_eq!(
+            game_state.take_completed_actions(),
+            waiting.take_completed_actions()
+        );
+    }
+
+    #[test]
+    fn test_skip_to_action_completion() {
+        let mut game_state = create_test_game_state();
+        start_test_action(&mut game_state, "train_str");
+        assert_skip_matches_waiting(game_state);
+    }
+
+    #[test]
+    fn test_skip_to_combat_completion() {
+        let mut game_state = create_test_game_state_with_templates(&[ARENA_TEMPLATE]);
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "arena")
+            .unwrap()
+            .id;
+        assert!(game_state.world.select_location(arena));
+        game_state.actions.selected_action = ACTION_EXPLORE;
+        assert!(game_state.skip_to_action_completion());
+        assert!(matches!(
+            game_state.actions.in_progress().kind,
+            PlayerActionInProgressKind::Combat(_)
+        ));
+
+        assert_skip_matches_waiting(game_state);
+    }
+
+    #[test]
+    fn test_update_beyond_end_of_time() {
+        let mut game_state = create_test_game_state();
+        game_state.current_time = GameTime::from_milliseconds(i128::MAX - 1000);
+        game_state.starting_time = Some(GameTime::from_milliseconds(i128::MIN));
+        let current_time = game_state.current_time;
+
+        game_state.update_offline(i64::MAX);
+        game_state.update_offline(2);
+        assert_eq!(game_state.current_time, current_time);
+        assert_eq!(
+            game_state.game_time_played(),
+            GameTime::from_milliseconds(i128::MAX)
+        );
+    }
+
+    #[test]
+    fn test_state_transition_description() {
+        let mut game_state = create_test_game_state();
+        game_state.current_time = GameTime::from_years(5000)
+            + GameTime::from_days(120)
+            + GameTime::from_hours(7)
+            + GameTime::from_minutes(5);
+        let quest_id_str = game_state.story.quest(QuestId(0)).id_str.clone();
+        let wait_id_str = game_state.actions.action(ACTION_WAIT).id_str.clone();
+
+        let events = [
+            CompiledGameEvent::Action(CompiledGameAction::ActivateQuest { id: QuestId(0) }),
+            CompiledGameEvent::QuestCompleted { id: QuestId(0) },
+            CompiledGameEvent::Action(CompiledGameAction::ActivateAction { id: ACTION_WAIT }),
+            CompiledGameEvent::Action(CompiledGameAction::DeactivateAction { id: ACTION_WAIT }),
+            CompiledGameEvent::PlayerLevelChanged { value: 3 },
+            CompiledGameEvent::PlayerStrengthChanged { value: 3 },
+        ];
+        let descriptions: Vec<_> = events
+            .iter()
+            .map(|event| game_state.state_transition_description(event))
+            .collect();
+
+        assert_eq!(
+            descriptions,
+            vec![
+                Some(format!(
+                    "[5000y 120d 07:05] State transition: activated quest {quest_id_str}"
+                )),
+                Some(format!(
+                    "[5000y 120d 07:05] State transition: completed quest {quest_id_str}"
+                )),
+                Some(format!(
+                    "[5000y 120d 07:05] State transition: activated action {wait_id_str}"
+                )),
+                Some(format!(
+                    "[5000y 120d 07:05] State transition: deactivated action {wait_id_str}"
+                )),
+                Some("[5000y 120d 07:05] State transition: reached level 3".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_toggle_favorite_action() {
+        let mut game_state = create_test_game_state();
+        assert!(!game_state.is_favorite_action(ACTION_WAIT));
+
+        assert!(game_state.handle_message(&GameStateMessage::ToggleFavorite(ACTION_WAIT)));
+        assert!(game_state.is_favorite_action(ACTION_WAIT));
+        assert!(!game_state.is_favorite_action(ACTION_EXPLORE));
+
+        let loaded: GameState = pot::from_slice(&pot::to_vec(&game_state).unwrap()).unwrap();
+        assert!(loaded.is_favorite_action(ACTION_WAIT));
+
+        game_state.toggle_favorite_action(ACTION_WAIT);
+        assert!(!game_state.is_favorite_action(ACTION_WAIT));
+    }
+
+    #[test]
+    fn test_sort_favorite_actions_first() {
+        let mut game_state = create_test_game_state();
+        let order: Vec<_> = game_state
+            .actions
+            .list_choosable()
+            .map(|action| action.id)
+            .collect();
+        assert!(order.len() >= 4);
+        game_state.toggle_favorite_action(order[3]);
+        game_state.toggle_favorite_action(order[1]);
+
+        let mut actions: Vec<_> = game_state.actions.list_choosable().collect();
+        game_state.sort_favorite_actions_first(&mut actions);
+        let sorted: Vec<_> = actions.iter().map(|action| action.id).collect();
+        let expected: Vec<_> = [order[1], order[3]]
+            .into_iter()
+            .chain(
+                order
+                    .iter()
+                    .copied()
+                    .filter(|&action| action != order[1] && action != order[3]),
+            )
+            .collect();
+        assert_eq!(sorted, expected);
+    }
+
+    const ACTION_CHAIN_TEMPLATE: &str = "
+ACTION chain_a
+name Chain A
+progressive doing A
+simple_past did A
+type TRAIN
+duration 1h
+currency 0
+then chain_b
+activation none
+deactivation never
+
+ACTION chain_b
+name Chain B
+progressive doing B
+simple_past did B
+type TRAIN
+duration 1h
+currency 0
+then chain_a
+activation none
+deactivation never
+";
+
+    /// Advances the game state hour by hour and returns the chain actions that were started, without repetitions.
+    fn run_chain(
+        game_state: &mut GameState,
+        hours: usize,
+        chain: &[PlayerActionId],
+    ) -> Vec<PlayerActionId> {
+        let mut started: Vec<PlayerActionId> = Vec::new();
+        for _ in 0..hours {
+            game_state.update_offline(
+                (GameTime::from_hours(1).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                    as i64,
+            );
+            let action = game_state.actions.in_progress().source.action_id();
+            if chain.contains(&action) && started.last() != Some(&action) {
+                started.push(action);
+            }
+        }
+        started
+    }
+
+    #[test]
+    fn test_action_chain() {
+        let mut game_state = create_test_game_state_with_templates(&[ACTION_CHAIN_TEMPLATE]);
+        let chain: Vec<_> = ["chain_a", "chain_b"]
+            .into_iter()
+            .map(|id_str| {
+                game_state
+                    .actions
+                    .list_choosable()
+                    .find(|action| action.id_str == id_str)
+                    .unwrap()
+                    .id
+            })
+            .collect();
+        let (chain_a, chain_b) = (chain[0], chain[1]);
+        assert_eq!(game_state.actions.action(chain_a).then, Some(chain_b));
+        assert_eq!(game_state.actions.action(chain_b).then, Some(chain_a));
+
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(chain_a)));
+        let started = run_chain(&mut game_state, 48, &chain);
+        assert!(started.len() >= 6);
+        for (index, action) in started.iter().enumerate() {
+            assert_eq!(*action, chain[index % 2]);
+        }
+
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(ACTION_WAIT)));
+        run_chain(&mut game_state, 2, &chain);
+        assert!(run_chain(&mut game_state, 48, &chain).is_empty());
+        assert_eq!(game_state.actions.selected_action, ACTION_WAIT);
+    }
+
+    const ACTION_QUEUE_TEMPLATE: &str = "
+ACTION queue_a
+name Queue A
+progressive doing A
+simple_past did A
+type TRAIN
+duration 1h
+currency 0
+activation none
+deactivation never
+
+ACTION queue_b
+name Queue B
+progressive doing B
+simple_past did B
+type TRAIN
+duration 1h
+currency 0
+activation none
+deactivation never
+
+ACTION queue_c
+name Queue C
+progressive doing C
+simple_past did C
+type TRAIN
+duration 1h
+currency 0
+activation none
+deactivation never
+
+ACTION queue_expiring
+name Queue expiring
+progressive doing the expiring
+simple_past did the expiring
+type TRAIN
+duration 1h
+currency 0
+activation none
+deactivation action_count(1, queue_a)
+";
+
+    fn queue_test_actions(game_state: &GameState) -> Vec<PlayerActionId> {
+        ["queue_a", "queue_b", "queue_c", "queue_expiring"]
+            .into_iter()
+            .map(|id_str| {
+                game_state
+                    .actions
+                    .list_choosable()
+                    .find(|action| action.id_str == id_str)
+                    .unwrap()
+                    .id
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_action_queue() {
+        let mut game_state = create_test_game_state_with_templates(&[ACTION_QUEUE_TEMPLATE]);
+        let queue = queue_test_actions(&game_state);
+        start_test_action(&mut game_state, "train_str");
+        for action in [queue[0], queue[2], queue[0]] {
+            assert!(game_state.handle_message(&GameStateMessage::EnqueueAction(action)));
+        }
+        assert!(game_state.handle_message(&GameStateMessage::MoveQueuedActionUp(2)));
+        assert!(!game_state.handle_message(&GameStateMessage::MoveQueuedActionUp(0)));
+        assert_eq!(
+            game_state.action_queue(),
+            &VecDeque::from([queue[0], queue[0], queue[2]])
+        );
+        assert!(game_state.handle_message(&GameStateMessage::RemoveQueuedAction(1)));
+        assert!(game_state.handle_message(&GameStateMessage::EnqueueAction(queue[1])));
+        assert!(game_state.handle_message(&GameStateMessage::MoveQueuedActionUp(2)));
+
+        // long enough to include a night of sleep
+        let started = run_chain(&mut game_state, 24, &queue);
+        assert_eq!(started, queue[..3].to_vec());
+        assert!(game_state.action_queue().is_empty());
+        assert_eq!(game_state.actions.selected_action, queue[2]);
+    }
+
+    #[test]
+    fn test_action_queue_skips_inactive_actions() {
+        let mut game_state = create_test_game_state_with_templates(&[ACTION_QUEUE_TEMPLATE]);
+        let queue = queue_test_actions(&game_state);
+        start_test_action(&mut game_state, "train_str");
+        for action in [queue[0], queue[3], queue[2]] {
+            assert!(game_state.handle_message(&GameStateMessage::EnqueueAction(action)));
+        }
+
+        // completing queue_a deactivates queue_expiring
+        let started = run_chain(&mut game_state, 8, &queue);
+        assert_eq!(started, vec![queue[0], queue[2]]);
+        assert!(!game_state.handle_message(&GameStateMessage::EnqueueAction(queue[3])));
+        assert!(game_state.handle_message(&GameStateMessage::ClearActionQueue));
+        assert!(game_state.action_queue().is_empty());
+    }
+
+    const FOCUS_TRAINING_TEMPLATE: &str = "
+ACTION focus_sta
+name Run
+progressive running
+simple_past ran
+type TRAIN
+duration 1h
+sta 1.0
+currency 0
+activation none
+deactivation never
+
+ACTION focus_dex
+name Juggle
+progressive juggling
+simple_past juggled
+type TRAIN
+duration 1h
+dex 1.0
+currency 0
+activation none
+deactivation never
+";
+
+    /// The actions training strength, stamina and dexterity.
+    fn focus_test_actions(game_state: &GameState) -> Vec<PlayerActionId> {
+        ["train_str", "focus_sta", "focus_dex"]
+            .into_iter()
+            .map(|id_str| {
+                game_state
+                    .actions
+                    .list_choosable()
+                    .find(|action| action.id_str == id_str)
+                    .unwrap()
+                    .id
+            })
+            .collect()
+    }
+
+    /// Trains strength for the given hours and returns the strength, stamina and dexterity levels.
+    fn train_with_policy(training_policy: TrainingPolicy, hours: usize) -> [u64; 3] {
+        let mut game_state = create_test_game_state_with_templates(&[FOCUS_TRAINING_TEMPLATE]);
+        let actions = focus_test_actions(&game_state);
+        assert!(
+            game_state.handle_message(&GameStateMessage::TrainingPolicyChanged(training_policy))
+        );
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(actions[0])));
+        run_chain(&mut game_state, hours, &actions);
+
+        let attributes = game_state.character.attributes();
+        [
+            attributes.strength,
+            attributes.stamina,
+            attributes.dexterity,
+        ]
+    }
+
+    #[test]
+    fn test_focus_training_selects_lagging_attribute() {
+        let mut game_state = create_test_game_state_with_templates(&[FOCUS_TRAINING_TEMPLATE]);
+        let actions = focus_test_actions(&game_state);
+        let _ = game_state
+            .character
+            .add_attribute_levels(&CharacterAttributes::new(3, 2, 0, 0, 0, 0))
+            .count();
+        assert!(
+            game_state.handle_message(&GameStateMessage::TrainingPolicyChanged(
+                TrainingPolicy::Focus
+            ))
+        );
+        assert_eq!(game_state.training_policy(), TrainingPolicy::Focus);
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(actions[0])));
+
+        // dexterity is trained first, until it catches up with stamina
+        let started = run_chain(&mut game_state, 24, &actions);
+        assert_eq!(started[..2], [actions[0], actions[2]]);
+        assert!(started.contains(&actions[1]));
+        assert!(game_state.character.attributes().dexterity >= 3);
+    }
+
+    #[test]
+    fn test_focus_training_converges_to_balance() {
+        let manual = train_with_policy(TrainingPolicy::Manual, 24 * 5);
+        assert!(manual[0] > 5, "{manual:?}");
+        assert_eq!(manual[1..], [1, 1]);
+
+        let focus = train_with_policy(TrainingPolicy::Focus, 24 * 5);
+        let lowest = *focus.iter().min().unwrap();
+        let highest = *focus.iter().max().unwrap();
+        assert!(lowest > 3, "{focus:?}");
+        assert!(highest - lowest <= 1, "{focus:?}");
+        assert!(highest < manual[0], "{focus:?}");
+    }
+
+    const PINNED_QUEST_TEMPLATE: &str = "
+QUEST pinned_a
+title Pinned A
+activation none
+failure never
+BEGIN
+    QUEST_STAGE pinned_a_stage
+    task Wait forever.
+    completion never
+END
+
+QUEST pinned_b
+title Pinned B
+activation none
+failure never
+BEGIN
+    QUEST_STAGE pinned_b_stage
+    task Wait forever.
+    completion never
+END
+
+QUEST pinned_c
+title Pinned C
+activation none
+failure never
+BEGIN
+    QUEST_STAGE pinned_c_stage
+    task Wait forever.
+    completion never
+END
+";
+
+    fn pinned_quest_ids(game_state: &GameState) -> Vec<String> {
+        game_state
+            .pinned_quests()
+            .map(|quest| quest.id_str.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_pin_quests() {
+        let mut game_state =
+            create_test_game_state_with_templates(&[EMITTED_QUEST_TEMPLATE, PINNED_QUEST_TEMPLATE]);
+        let quest = |id_str: &str| {
+            (0..)
+                .map(QuestId)
+                .find(|&quest| game_state.story.quest(quest).id_str == id_str)
+                .unwrap()
+        };
+        let [a, b, c, emitted] = ["pinned_a", "pinned_b", "pinned_c", "emitted_quest"].map(quest);
+
+        for quest_id in [b, emitted, a] {
+            assert!(game_state.handle_message(&GameStateMessage::TogglePinnedQuest(quest_id)));
+        }
+        assert!(!game_state.handle_message(&GameStateMessage::TogglePinnedQuest(c)));
+        assert!(!game_state.is_pinned_quest(c));
+        assert_eq!(
+            pinned_quest_ids(&game_state),
+            ["pinned_b", "emitted_quest", "pinned_a"]
+        );
+
+        // unpinning keeps the order of the other quests and frees a slot
+        assert!(game_state.handle_message(&GameStateMessage::TogglePinnedQuest(b)));
+        assert!(game_state.handle_message(&GameStateMessage::TogglePinnedQuest(c)));
+        assert_eq!(
+            pinned_quest_ids(&game_state),
+            ["emitted_quest", "pinned_a", "pinned_c"]
+        );
+        let loaded: GameState = pot::from_slice(&pot::to_vec(&game_state).unwrap()).unwrap();
+        assert_eq!(pinned_quest_ids(&loaded), pinned_quest_ids(&game_state));
+    }
+
+    #[test]
+    fn test_completed_quests_free_their_pin() {
+        let mut game_state =
+            create_test_game_state_with_templates(&[EMITTED_QUEST_TEMPLATE, PINNED_QUEST_TEMPLATE]);
+        let quest = |id_str: &str| {
+            (0..)
+                .map(QuestId)
+                .find(|&quest| game_state.story.quest(quest).id_str == id_str)
+                .unwrap()
+        };
+        let [a, b, c, emitted] = ["pinned_a", "pinned_b", "pinned_c", "emitted_quest"].map(quest);
+        for quest_id in [a, emitted, b] {
+            assert!(game_state.toggle_pinned_quest(quest_id));
+        }
+
+        let in_progress = start_test_action(&mut game_state, "emitted_study");
+        game_state.update(real_milliseconds(in_progress.length()) + 1);
+        assert!(game_state.story.quest(emitted).state().is_completed());
+        assert_eq!(pinned_quest_ids(&game_state), ["pinned_a", "pinned_b"]);
+        assert!(!game_state.toggle_pinned_quest(emitted));
+
+        assert!(game_state.toggle_pinned_quest(c));
+        assert!(!game_state.is_pinned_quest(emitted));
+        assert_eq!(
+            pinned_quest_ids(&game_state),
+            ["pinned_a", "pinned_b", "pinned_c"]
+        );
+    }
+
+    fn trained_game_state() -> GameState {
+        let mut game_state = create_test_game_state();
+        let train_str = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "train_str")
+            .unwrap()
+            .id;
+        game_state.actions.selected_action = train_str;
+        game_state.update_offline(
+            (GameTime::from_days(5).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                as i64,
+        );
+        game_state
+    }
+
+    #[test]
+    fn test_respec() {
+        let mut game_state = trained_game_state();
+        let strength = game_state.character.attributes().strength;
+        assert!(strength > 5);
+        let total_attribute_progress = game_state.character.total_attribute_progress();
+        let cost = game_state.character.respec_cost();
+        game_state.inventory.currency = cost + Currency::from_copper(3);
+        let level = game_state.character.level;
+
+        assert!(game_state.respec(CharacterAttributes::new(1, 1, 1, 1, 1, strength)));
+        let attributes = *game_state.character.attributes();
+        assert_eq!(attributes.strength, 1);
+        // charisma starts higher, so reaching the same level takes less progress
+        assert!(attributes.charisma >= strength);
+        assert_eq!(
+            game_state.character.total_attribute_progress(),
+            total_attribute_progress
+        );
+        assert_eq!(game_state.character.level, level);
+        assert_eq!(game_state.inventory.currency, Currency::from_copper(3));
+    }
+
+    #[test]
+    fn test_unaffordable_respec() {
+        let mut game_state = trained_game_state();
+        let attributes = *game_state.character.attributes();
+        let cost = game_state.character.respec_cost();
+        game_state.inventory.currency = cost - Currency::from_copper(1);
+
+        assert!(!game_state.respec(CharacterAttributes::new(1, 1, 1, 1, 1, 10)));
+        assert_eq!(*game_state.character.attributes(), attributes);
+        assert_eq!(
+            game_state.inventory.currency,
+            cost - Currency::from_copper(1)
+        );
+    }
+
+    const DEBT_TEMPLATE: &str = "
+ACTION debt_costly
+name Costly
+progressive paying
+simple_past paid
+type WORK
+duration 1h
+currency 0
+activation none
+deactivation never
+
+ACTION debt_allowed
+name Borrowing
+progressive borrowing
+simple_past borrowed
+type WORK
+duration 1h
+currency 0
+allow_debt true
+activation none
+deactivation never
+";
+
+    /// A game state where both actions of [`DEBT_TEMPLATE`] cost ten copper, and the player has none.
+    fn broke_game_state() -> (GameState, PlayerActionId, PlayerActionId) {
+        let mut game_state = create_test_game_state_with_templates(&[DEBT_TEMPLATE]);
+        let find_action = |game_state: &GameState, id_str| {
+            game_state
+                .actions
+                .list_choosable()
+                .find(|action| action.id_str == id_str)
+                .unwrap()
+                .id
+        };
+        let costly = find_action(&game_state, "debt_costly");
+        let allowed = find_action(&game_state, "debt_allowed");
+        assert!(!game_state.actions.action(costly).allow_debt);
+        assert!(game_state.actions.action(allowed).allow_debt);
+        for action in [costly, allowed] {
+            game_state.actions.action_mut(action).currency_reward = Currency::from_copper(-10);
+        }
+        game_state.inventory.currency = Currency::zero();
+        (game_state, costly, allowed)
+    }
+
+    #[test]
+    fn test_costly_action_blocked_when_broke() {
+        let (mut game_state, costly, allowed) = broke_game_state();
+        assert!(!game_state.can_afford_action(costly));
+        assert!(game_state.can_afford_action(allowed));
+        assert!(!game_state.handle_message(&GameStateMessage::ActionChanged(costly)));
+        assert_ne!(game_state.actions.selected_action, costly);
+
+        game_state.inventory.currency = Currency::from_copper(10);
+        assert!(game_state.can_afford_action(costly));
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(costly)));
+
+        // losing the money after selecting the action makes the player wait instead
+        game_state.inventory.currency = Currency::zero();
+        game_state.update_offline(real_milliseconds(GameTime::from_days(1)));
+        assert!(game_state.log.iter_rev().all(|event| !matches!(
+            &event.kind,
+            GameEventKind::Action(action) if action.source.action_id() == costly
+        )));
+        assert_eq!(game_state.inventory.currency, Currency::zero());
+    }
+
+    #[test]
+    fn test_costly_action_allowed_with_debt() {
+        let (mut game_state, _, allowed) = broke_game_state();
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(allowed)));
+        game_state.update_offline(real_milliseconds(GameTime::from_days(1)));
+        assert!(game_state.log.iter_rev().any(|event| matches!(
+            &event.kind,
+            GameEventKind::Action(action) if action.source.action_id() == allowed
+        )));
+        assert!(game_state.inventory.currency < Currency::zero());
+    }
+
+    #[test]
+    fn test_player_in_debt_can_still_work() {
+        let (mut game_state, costly, _) = broke_game_state();
+        game_state.inventory.currency = Currency::from_copper(-20);
+        assert!(!game_state.can_afford_action(costly));
+
+        for currency_reward in [Currency::zero(), Currency::from_copper(10)] {
+            game_state.actions.action_mut(costly).currency_reward = currency_reward;
+            assert!(game_state.can_afford_action(costly));
+        }
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(costly)));
+        game_state.update_offline(real_milliseconds(GameTime::from_days(1)));
+        assert!(game_state.inventory.currency > Currency::from_copper(-20));
+    }
+
+    const IDLE_TEMPLATE: &str = "
+ACTION idle_work
+name Work
+progressive working
+simple_past worked
+type WORK
+duration 1h
+currency 1
+activation none
+deactivation never
+
+ACTION idle_reward
+name Daydream
+progressive daydreaming
+simple_past daydreamed
+type TRAIN
+int 1.0
+duration 1h
+currency 0
+activation idle_for(3h)
+deactivation never
+";
+
+    fn idle_reward_available(game_state: &GameState) -> bool {
+        game_state
+            .actions
+            .list_choosable()
+            .any(|action| action.id_str == "idle_reward")
+    }
+
+    #[test]
+    fn test_sustained_waiting_fires_idle_event() {
+        let mut game_state = create_test_game_state_with_templates(&[IDLE_TEMPLATE]);
+        assert_eq!(game_state.actions.selected_action, ACTION_WAIT);
+        assert!(!idle_reward_available(&game_state));
+
+        let events = game_state.update_with_events(real_milliseconds(GameTime::from_days(1)));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            CompiledGameEvent::PlayerIdle { duration } if *duration >= GameTime::from_hours(3)
+        )));
+        assert!(idle_reward_available(&game_state));
+    }
+
+    #[test]
+    fn test_changing_action_resets_idle_time() {
+        let mut game_state = create_test_game_state_with_templates(&[IDLE_TEMPLATE]);
+        let work = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "idle_work")
+            .unwrap()
+            .id;
+        let select_for = |game_state: &mut GameState, action, hours| {
+            assert!(game_state.handle_message(&GameStateMessage::ActionChanged(action)));
+            game_state.update_offline(real_milliseconds(GameTime::from_hours(hours)));
+        };
+
+        select_for(&mut game_state, work, 8);
+        select_for(&mut game_state, ACTION_WAIT, 2);
+        select_for(&mut game_state, work, 1);
+        select_for(&mut game_state, ACTION_WAIT, 2);
+        assert!(!idle_reward_available(&game_state));
+
+        game_state.update_offline(real_milliseconds(GameTime::from_hours(2)));
+        assert!(idle_reward_available(&game_state));
+    }
+}
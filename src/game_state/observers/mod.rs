@@ -0,0 +1,74 @@
+use crate::game_state::triggers::CompiledGameEvent;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// A callback of external code that is called with each event dispatched by the game state.
+pub type GameEventObserver = Arc<dyn Fn(&CompiledGameEvent) + Send + Sync>;
+
+/// The observers registered with [`GameState::register_observer`](crate::game_state::GameState::register_observer).
+/// They are not saved, so they have to be registered again after loading a game.
+/// They are not cloned either, so that copies of the game state such as autosaves do not notify them.
+#[derive(Default)]
+pub struct GameEventObservers {
+    observers: Vec<GameEventObserver>,
+}
+
+impl GameEventObservers {
+    pub fn register(&mut self, observer: impl Fn(&CompiledGameEvent) + Send + Sync + 'static) {
+        self.observers.push(Arc::new(observer));
+    }
+
+    pub fn register_shared(&mut self, observers: &SharedGameEventObservers) {
+        self.observers.extend(observers.iter().cloned());
+    }
+
+    pub fn notify<'events>(&self, events: impl IntoIterator<Item = &'events CompiledGameEvent>) {
+        if self.observers.is_empty() {
+            return;
+        }
+
+        for event in events {
+            for observer in &self.observers {
+                observer(event);
+            }
+        }
+    }
+}
+
+impl Clone for GameEventObservers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Debug for GameEventObservers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GameEventObservers({})", self.observers.len())
+    }
+}
+
+/// The observers given to [`run_with_observers`](crate::run_with_observers),
+/// which are registered with each game state when it starts being played.
+/// Unlike [`GameEventObservers`], they are kept when cloned, since they belong to the run configuration.
+#[derive(Clone, Default)]
+pub struct SharedGameEventObservers {
+    observers: Vec<GameEventObserver>,
+}
+
+impl SharedGameEventObservers {
+    pub fn iter(&self) -> impl '_ + Iterator<Item = &GameEventObserver> {
+        self.observers.iter()
+    }
+}
+
+impl From<Vec<GameEventObserver>> for SharedGameEventObservers {
+    fn from(observers: Vec<GameEventObserver>) -> Self {
+        Self { observers }
+    }
+}
+
+impl Debug for SharedGameEventObservers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SharedGameEventObservers({})", self.observers.len())
+    }
+}
@@ -0,0 +1,160 @@
+use crate::game_state::character::CharacterAttributes;
+use crate::game_state::currency::Currency;
+use crate::game_state::story::quests::QuestId;
+use crate::game_state::time::GameTime;
+use crate::game_state::GameState;
+use std::collections::HashSet;
+
+/// The parts of the game state that are compared to summarise offline progress.
+#[derive(Debug, Clone)]
+pub struct ProgressSnapshot {
+    time: GameTime,
+    level: u64,
+    currency: Currency,
+    attributes: CharacterAttributes,
+    completed_quests: HashSet<QuestId>,
+}
+
+/// The net change of the game state over an offline interval.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OfflineProgressSummary {
+    pub passed_time: GameTime,
+    pub levels_gained: u64,
+    pub currency_earned: Currency,
+    /// Completed quests in order of completion.
+    pub completed_quests: Vec<QuestId>,
+    pub attribute_gains: CharacterAttributes,
+}
+
+impl ProgressSnapshot {
+    pub fn new(game_state: &GameState) -> Self {
+        Self {
+            time: game_state.current_time,
+            level: game_state.character.level,
+            currency: game_state.inventory.currency,
+            attributes: *game_state.character.attributes(),
+            completed_quests: game_state
+                .story
+                .iter_completed_quests_by_completion_time()
+                .map(|quest| quest.id)
+                .collect(),
+        }
+    }
+
+    /// Summarises the progress from this snapshot to the given game state.
+    pub fn summary(&self, game_state: &GameState) -> OfflineProgressSummary {
+        let attributes = game_state.character.attributes();
+        OfflineProgressSummary {
+            passed_time: game_state.current_time - self.time,
+            levels_gained: game_state.character.level - self.level,
+            currency_earned: game_state.inventory.currency - self.currency,
+            completed_quests: game_state
+                .story
+                .iter_completed_quests_by_completion_time()
+                .map(|quest| quest.id)
+                .filter(|quest_id| !self.completed_quests.contains(quest_id))
+                .collect(),
+            attribute_gains: CharacterAttributes {
+                strength: attributes.strength - self.attributes.strength,
+                stamina: attributes.stamina - self.attributes.stamina,
+                dexterity: attributes.dexterity - self.attributes.dexterity,
+                intelligence: attributes.intelligence - self.attributes.intelligence,
+                wisdom: attributes.wisdom - self.attributes.wisdom,
+                charisma: attributes.charisma - self.attributes.charisma,
+            },
+        }
+    }
+}
+
+impl OfflineProgressSummary {
+    /// True if nothing worth reporting happened.
+    pub fn is_empty(&self) -> bool {
+        self.levels_gained == 0
+            && self.currency_earned == Currency::zero()
+            && self.completed_quests.is_empty()
+            && self.attribute_gains
+                == CharacterAttributes {
+                    strength: 0,
+                    stamina: 0,
+                    dexterity: 0,
+                    intelligence: 0,
+                    wisdom: 0,
+                    charisma: 0,
+                }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProgressSnapshot;
+    use crate::game_state::tests::create_test_game_state;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::GAME_TIME_PER_MILLISECOND;
+
+    #[test]
+    fn test_offline_summary_matches_state_difference() {
+        let mut game_state = create_test_game_state();
+        let train_str = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "train_str")
+            .unwrap()
+            .id;
+        game_state.actions.selected_action = train_str;
+        let before = game_state.clone();
+        let snapshot = ProgressSnapshot::new(&game_state);
+
+        let offline_time = GameTime::from_days(30);
+        for _ in 0..30 {
+            game_state.update_offline(
+                (GameTime::from_days(1).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                    as i64,
+            );
+        }
+        let summary = snapshot.summary(&game_state);
+
+        assert_eq!(summary.passed_time, offline_time);
+        assert!(summary.levels_gained > 0);
+        assert!(summary.attribute_gains.strength > 0);
+        assert!(!summary.completed_quests.is_empty());
+        assert_eq!(
+            summary.levels_gained,
+            game_state.character.level - before.character.level
+        );
+        assert_eq!(
+            summary.currency_earned,
+            game_state.inventory.currency - before.inventory.currency
+        );
+        assert_eq!(
+            summary.attribute_gains.strength,
+            game_state.character.attributes().strength - before.character.attributes().strength
+        );
+        assert_eq!(
+            summary.attribute_gains.charisma,
+            game_state.character.attributes().charisma - before.character.attributes().charisma
+        );
+        for quest_id in &summary.completed_quests {
+            assert!(!before.story.quest(*quest_id).state().is_completed());
+            assert!(game_state.story.quest(*quest_id).state().is_completed());
+        }
+        assert_eq!(
+            summary.completed_quests.len(),
+            game_state
+                .story
+                .iter_completed_quests_by_completion_time()
+                .count()
+                - before
+                    .story
+                    .iter_completed_quests_by_completion_time()
+                    .count()
+        );
+    }
+
+    #[test]
+    fn test_empty_offline_summary() {
+        let game_state = create_test_game_state();
+        let summary = ProgressSnapshot::new(&game_state).summary(&game_state);
+        assert_eq!(summary.passed_time, GameTime::zero());
+        assert!(summary.is_empty());
+    }
+}
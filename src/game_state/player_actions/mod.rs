@@ -1,18 +1,20 @@
 use crate::game_state::character::{CharacterAttributeProgress, CharacterAttributeProgressFactor};
 use crate::game_state::currency::Currency;
+use crate::game_state::invariants::InvariantViolation;
 use crate::game_state::inventory::item::{CompiledExpectedItemCount, ExpectedItemCount, ItemCount};
 use crate::game_state::time::GameTime;
+use crate::game_state::translations::Translator;
 use crate::game_state::triggers::CompiledGameEvent;
 use crate::game_state::world::events::ExplorationEventId;
 use crate::game_state::world::locations::LocationId;
-use crate::game_state::world::monsters::MonsterId;
+use crate::game_state::world::monsters::{CombatHitpoints, MonsterId, PackKill};
 use crate::game_template::parser::error::{ParserError, ParserErrorKind};
 use crate::game_template::IdMaps;
-use enum_iterator::Sequence;
+use enum_iterator::{all, Sequence};
 use event_trigger_action_system::TriggerHandle;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::iter;
 use std::str::FromStr;
 
@@ -129,16 +131,55 @@ pub enum PlayerActionType {
     Explore,
 }
 
+/// Decides when exploring stops after combat, switching to waiting.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum CombatPolicy {
+    #[default]
+    Continuous,
+    FightOne,
+    UntilDefeat,
+    /// Stop after the first combat that ends this long after the first combat in the streak started.
+    ForDuration(GameTime),
+}
+
+/// The combat policies offered to the player.
+pub const COMBAT_POLICY_CHOICES: [CombatPolicy; 6] = [
+    CombatPolicy::Continuous,
+    CombatPolicy::FightOne,
+    CombatPolicy::UntilDefeat,
+    CombatPolicy::ForDuration(GameTime::from_hours(1)),
+    CombatPolicy::ForDuration(GameTime::from_hours(4)),
+    CombatPolicy::ForDuration(GameTime::from_days(1)),
+];
+
+/// Decides which training action is selected when the selected training action completes.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq, Sequence)]
+pub enum TrainingPolicy {
+    /// Keep training the selected attribute.
+    #[default]
+    Manual,
+    /// Select the training action for the attribute that is lowest among the active training actions.
+    Focus,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlayerAction {
     pub id_str: String,
     pub name: String,
+    /// The url of a sound that is played when the action is completed.
+    pub sound: Option<String>,
+    /// The group of the action in the action picker.
+    pub category: Option<String>,
     pub verb_progressive: String,
     pub verb_simple_past: String,
     pub action_type: PlayerActionType,
     pub duration: GameTime,
+    /// The action that is selected when this action is completed while selected.
+    pub then: Option<String>,
     pub attribute_progress_factor: CharacterAttributeProgressFactor,
     pub currency_reward: Currency,
+    /// If true, the action can be started even if the player cannot pay for it.
+    pub allow_debt: bool,
     pub items: Vec<ExpectedItemCount>,
     pub activation_condition: String,
     pub deactivation_condition: String,
@@ -150,15 +191,27 @@ pub struct CompiledPlayerAction {
     pub id_str: String,
     pub state: PlayerActionState,
     pub name: String,
+    #[serde(default)]
+    pub sound: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
     pub verb_progressive: String,
     pub verb_simple_past: String,
     pub action_type: PlayerActionType,
     pub duration: GameTime,
+    #[serde(default)]
+    pub then: Option<PlayerActionId>,
     pub attribute_progress_factor: CharacterAttributeProgressFactor,
     pub currency_reward: Currency,
+    /// If true, the action can be started even if the player cannot pay for it.
+    #[serde(default)]
+    pub allow_debt: bool,
     pub items: Vec<CompiledExpectedItemCount>,
     pub activation_condition: TriggerHandle,
     pub deactivation_condition: TriggerHandle,
+    /// Describes what unlocks the action, if it is not available from the start.
+    #[serde(default)]
+    pub unlock_description: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -186,6 +239,11 @@ pub struct PlayerActionInProgress {
     pub items: Vec<ItemCount>,
     pub location: LocationId,
     pub success: bool,
+    #[serde(default)]
+    pub combat_hitpoints: Option<CombatHitpoints>,
+    /// The monsters of a pack that were killed before the fight of [`combat_hitpoints`](Self::combat_hitpoints).
+    #[serde(default)]
+    pub pack_kills: Vec<PackKill>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -264,6 +322,12 @@ impl PlayerActions {
         &mut self.actions[action_id.0]
     }
 
+    pub fn translate(&mut self, translator: &Translator) {
+        for action in &mut self.actions {
+            translator.translate(&mut action.name);
+        }
+    }
+
     pub fn has_action_in_progress(&self) -> bool {
         self.in_progress.is_some()
     }
@@ -284,6 +348,14 @@ impl PlayerActions {
             .map(|action_id| self.action(action_id))
     }
 
+    /// Lists the actions that were never active but can still be unlocked.
+    pub fn list_locked(&self) -> impl '_ + Iterator<Item = &'_ CompiledPlayerAction> {
+        self.inactive_actions
+            .iter()
+            .map(|action_id| self.action(*action_id))
+            .filter(|action| action.unlock_description.is_some())
+    }
+
     pub fn activate_action(
         &mut self,
         action_id: PlayerActionId,
@@ -299,6 +371,59 @@ impl PlayerActions {
         iter::empty()
     }
 
+    pub fn validate_invariants(&self) -> Result<(), InvariantViolation> {
+        let sets = [
+            ("inactive", &self.inactive_actions),
+            ("active", &self.active_actions),
+            ("deactivated", &self.deactivated_actions),
+        ];
+        for action in &self.actions {
+            let expected_set = match action.state {
+                PlayerActionState::Inactive => "inactive",
+                PlayerActionState::Active { .. } => "active",
+                PlayerActionState::Deactivated { .. } => "deactivated",
+            };
+            for (set, action_ids) in sets {
+                let contained = action_ids.contains(&action.id);
+                if contained != (set == expected_set) {
+                    return Err(InvariantViolation::ActionSetMismatch {
+                        id_str: action.id_str.clone(),
+                        state: format!("{:?}", action.state),
+                        set,
+                        contained,
+                    });
+                }
+            }
+        }
+
+        // Every action is in exactly one set, so the sizes only differ if a set contains unknown actions.
+        let expected = [
+            self.actions
+                .iter()
+                .filter(|action| action.state.is_inactive())
+                .count(),
+            self.actions
+                .iter()
+                .filter(|action| action.state.is_active())
+                .count(),
+            self.actions
+                .iter()
+                .filter(|action| action.state.is_deactivated())
+                .count(),
+        ];
+        for ((set, action_ids), expected) in sets.into_iter().zip(expected) {
+            if action_ids.len() != expected {
+                return Err(InvariantViolation::ActionSetSize {
+                    set,
+                    expected,
+                    actual: action_ids.len(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn deactivate_action(
         &mut self,
         action_id: PlayerActionId,
@@ -334,12 +459,21 @@ impl PlayerAction {
             id_str: self.id_str,
             state: PlayerActionState::Inactive,
             name: self.name,
+            sound: self.sound,
+            category: self.category,
             verb_progressive: self.verb_progressive,
             verb_simple_past: self.verb_simple_past,
             action_type: self.action_type,
             duration: self.duration,
+            then: self.then.map(|then| {
+                *id_maps
+                    .actions
+                    .get(&then)
+                    .unwrap_or_else(|| panic!("Did not find action {then:?} in id_map"))
+            }),
             attribute_progress_factor: self.attribute_progress_factor,
             currency_reward: self.currency_reward,
+            allow_debt: self.allow_debt,
             items: self
                 .items
                 .into_iter()
@@ -347,6 +481,7 @@ impl PlayerAction {
                 .collect(),
             activation_condition: *id_maps.triggers.get(&self.activation_condition).unwrap(),
             deactivation_condition: *id_maps.triggers.get(&self.deactivation_condition).unwrap(),
+            unlock_description: None,
         }
     }
 }
@@ -370,8 +505,34 @@ impl CompiledPlayerAction {
             items: self.items.iter().map(|item| item.spawn(rng)).collect(),
             location,
             success: true,
+            combat_hitpoints: None,
+            pack_kills: Vec::new(),
+        }
+    }
+}
+
+/// Groups the given actions by their category, keeping their order within each category.
+/// The categories are ordered by name, followed by the uncategorized actions if there are any.
+pub fn group_actions_by_category<'actions>(
+    actions: impl IntoIterator<Item = &'actions CompiledPlayerAction>,
+) -> Vec<(Option<&'actions str>, Vec<&'actions CompiledPlayerAction>)> {
+    let mut categorized: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    let mut uncategorized = Vec::new();
+    for action in actions {
+        match &action.category {
+            Some(category) => categorized
+                .entry(category.as_str())
+                .or_default()
+                .push(action),
+            None => uncategorized.push(action),
         }
     }
+
+    categorized
+        .into_iter()
+        .map(|(category, actions)| (Some(category), actions))
+        .chain((!uncategorized.is_empty()).then_some((None, uncategorized)))
+        .collect()
 }
 
 impl PlayerActionInProgressSource {
@@ -408,6 +569,44 @@ impl PlayerActionInProgress {
     pub fn length(&self) -> GameTime {
         self.end - self.start
     }
+
+    /// The monster, start and hitpoints of the fight that is ongoing at the given time, or `None` if this is not a combat.
+    /// In a pack, this is the fight against the first monster that is not yet killed at that time.
+    pub fn current_fight(&self, time: GameTime) -> Option<(MonsterId, GameTime, &CombatHitpoints)> {
+        let monster = match self.kind {
+            PlayerActionInProgressKind::Combat(monster) => monster,
+            PlayerActionInProgressKind::None => return None,
+        };
+        let combat_hitpoints = self.combat_hitpoints.as_ref()?;
+        let mut fight_start = self.start;
+        for kill in &self.pack_kills {
+            if time < kill.end {
+                return Some((kill.monster, fight_start, &kill.combat_hitpoints));
+            }
+            fight_start = kill.end;
+        }
+        Some((monster, fight_start, combat_hitpoints))
+    }
+}
+
+impl ToString for CombatPolicy {
+    fn to_string(&self) -> String {
+        match self {
+            CombatPolicy::Continuous => "Fight continuously".to_string(),
+            CombatPolicy::FightOne => "Fight one monster".to_string(),
+            CombatPolicy::UntilDefeat => "Fight until defeated".to_string(),
+            CombatPolicy::ForDuration(duration) => format!("Fight for {}h", duration.hours()),
+        }
+    }
+}
+
+impl ToString for TrainingPolicy {
+    fn to_string(&self) -> String {
+        match self {
+            TrainingPolicy::Manual => "Train the selected attribute".to_string(),
+            TrainingPolicy::Focus => "Focus the lowest attribute".to_string(),
+        }
+    }
 }
 
 impl From<usize> for PlayerActionId {
@@ -416,18 +615,140 @@ impl From<usize> for PlayerActionId {
     }
 }
 
+impl PlayerActionType {
+    pub fn all() -> impl Iterator<Item = Self> {
+        all::<Self>()
+    }
+
+    /// The keyword of this type in game templates.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerActionType::Wait => "WAIT",
+            PlayerActionType::Sleep => "SLEEP",
+            PlayerActionType::Tavern => "TAVERN",
+            PlayerActionType::Train => "TRAIN",
+            PlayerActionType::Work => "WORK",
+            PlayerActionType::Explore => "EXPLORE",
+        }
+    }
+
+    /// Builtin actions have these types, and only builtin actions can have them.
+    pub fn is_builtin(&self) -> bool {
+        match self {
+            PlayerActionType::Wait
+            | PlayerActionType::Sleep
+            | PlayerActionType::Tavern
+            | PlayerActionType::Explore => true,
+            PlayerActionType::Train | PlayerActionType::Work => false,
+        }
+    }
+}
+
+impl ToString for PlayerActionType {
+    fn to_string(&self) -> String {
+        self.as_str().to_string()
+    }
+}
+
 impl FromStr for PlayerActionType {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "WAIT" => PlayerActionType::Wait,
-            "SLEEP" => PlayerActionType::Sleep,
-            "TAVERN" => PlayerActionType::Tavern,
-            "TRAIN" => PlayerActionType::Train,
-            "WORK" => PlayerActionType::Work,
-            "EXPLORE" => PlayerActionType::Explore,
-            _ => return Err(()),
-        })
+        Self::all()
+            .find(|action_type| action_type.as_str() == s)
+            .ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{group_actions_by_category, PlayerActionType};
+    use crate::game_state::tests::create_test_game_state_with_templates;
+
+    #[test]
+    fn test_action_type_round_trip() {
+        assert_eq!(PlayerActionType::all().count(), 6);
+        for action_type in PlayerActionType::all() {
+            assert_eq!(action_type.as_str().parse(), Ok(action_type.clone()));
+            assert_eq!(action_type.to_string(), action_type.as_str());
+        }
+        assert_eq!("train".parse::<PlayerActionType>(), Err(()));
+        assert_eq!(
+            PlayerActionType::all()
+                .filter(PlayerActionType::is_builtin)
+                .map(|action_type| action_type.as_str())
+                .collect::<Vec<_>>(),
+            vec!["WAIT", "SLEEP", "TAVERN", "EXPLORE"]
+        );
+    }
+
+    const CATEGORY_TEMPLATE: &str = "
+ACTION category_chop_wood
+name Chop wood
+category Gathering
+progressive chopping wood
+simple_past chopped wood
+type WORK
+duration 1h
+currency 1
+activation none
+deactivation never
+
+ACTION category_sharpen_sword
+name Sharpen sword
+category Combat prep
+progressive sharpening the sword
+simple_past sharpened the sword
+type TRAIN
+str 1.0
+duration 1h
+currency 0
+activation none
+deactivation never
+
+ACTION category_pick_berries
+name Pick berries
+category Gathering
+progressive picking berries
+simple_past picked berries
+type WORK
+duration 1h
+currency 1
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_group_actions_by_category() {
+        let game_state = create_test_game_state_with_templates(&[CATEGORY_TEMPLATE]);
+        let mut actions: Vec<_> = game_state.actions.list_choosable().collect();
+        actions.sort_by_key(|action| &action.name);
+        let mut groups = group_actions_by_category(actions);
+
+        let (category, uncategorized) = groups.pop().unwrap();
+        assert_eq!(category, None);
+        assert!(!uncategorized.is_empty());
+        assert!(uncategorized.iter().all(|action| action.category.is_none()));
+        let groups: Vec<_> = groups
+            .into_iter()
+            .map(|(category, actions)| {
+                let id_strs: Vec<_> = actions
+                    .into_iter()
+                    .map(|action| action.id_str.as_str())
+                    .collect();
+                (category, id_strs)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Some("Combat prep"), vec!["category_sharpen_sword"]),
+                (
+                    Some("Gathering"),
+                    vec!["category_chop_wood", "category_pick_berries"]
+                ),
+            ]
+        );
+        assert!(group_actions_by_category(Vec::new()).is_empty());
     }
 }
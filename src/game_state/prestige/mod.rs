@@ -0,0 +1,206 @@
+use crate::game_state::story::quests::QuestId;
+use crate::game_state::{GameState, GameStateInitialisation};
+use crate::game_template::CompiledGameTemplate;
+use serde::{Deserialize, Serialize};
+
+/// The attribute progress multiplier gained per level reached before a prestige.
+pub const PRESTIGE_MULTIPLIER_PER_LEVEL: f64 = 0.02;
+
+/// Progress that persists when the game is reset, see [`GameState::prestige`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Prestige {
+    /// Prestige is available once this quest is completed, and never if there is none.
+    pub required_quest: Option<QuestId>,
+    pub count: u64,
+    /// Multiplies all attribute progress.
+    pub multiplier: f64,
+}
+
+impl Prestige {
+    pub fn new(required_quest: Option<QuestId>) -> Self {
+        Self {
+            required_quest,
+            count: 0,
+            multiplier: 1.0,
+        }
+    }
+
+    /// The increase of the multiplier when prestiging at the given level.
+    pub fn multiplier_gain(level: u64) -> f64 {
+        level.saturating_sub(1) as f64 * PRESTIGE_MULTIPLIER_PER_LEVEL
+    }
+}
+
+impl Default for Prestige {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl GameState {
+    pub fn can_prestige(&self) -> bool {
+        self.prestige.required_quest.map_or(false, |quest_id| {
+            self.story.quest(quest_id).state().is_completed()
+        })
+    }
+
+    /// Resets the game to the given template, keeping the character's identity and the prestige progress.
+    /// The multiplier increases by [`Prestige::multiplier_gain`] of the current level.
+    /// Returns false and leaves the game state unchanged if prestige is not available.
+    pub fn prestige(&mut self, game_template: CompiledGameTemplate) -> bool {
+        if !self.can_prestige() {
+            return false;
+        }
+
+        let mut prestige = self.prestige.clone();
+        prestige.count += 1;
+        prestige.multiplier += Prestige::multiplier_gain(self.character.level);
+
+        let mut game_state = GameState::new(
+            game_template,
+            GameStateInitialisation {
+                savegame_file: self.savegame_file.clone().into(),
+                name: self.character.name.clone(),
+                pronoun: self.character.pronoun.clone(),
+                race: self.character.race,
+                difficulty: self.difficulty,
+                destiny_roll: false,
+            },
+        );
+        game_state.prestige = prestige;
+        game_state.real_time_played = self.real_time_played;
+        game_state.favorite_actions = std::mem::take(&mut self.favorite_actions);
+        game_state.combat_policy = self.combat_policy;
+        game_state.profile = self.profile;
+        game_state.observers = std::mem::take(&mut self.observers);
+        *self = game_state;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Prestige;
+    use crate::game_state::story::quests::QuestId;
+    use crate::game_state::tests::{create_test_game_state, create_test_game_template};
+    use crate::game_state::time::GameTime;
+    use crate::game_state::{GameState, GAME_TIME_PER_MILLISECOND};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn progressed_game_state(days: i128) -> GameState {
+        let mut game_state = create_test_game_state();
+        game_state.prestige.required_quest = Some(QuestId(0));
+        let train_str = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "train_str")
+            .unwrap()
+            .id;
+        game_state.actions.selected_action = train_str;
+        for _ in 0..days {
+            game_state.update_offline(
+                (GameTime::from_days(1).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                    as i64,
+            );
+        }
+        game_state
+    }
+
+    #[test]
+    fn test_prestige_unavailable() {
+        let mut game_state = create_test_game_state();
+        assert!(!game_state.can_prestige());
+        assert!(!game_state.prestige(create_test_game_template(&[])));
+
+        game_state.prestige.required_quest = Some(QuestId(0));
+        assert!(!game_state.story.quest(QuestId(0)).state().is_completed());
+        assert!(!game_state.can_prestige());
+        assert!(!game_state.prestige(create_test_game_template(&[])));
+        assert_eq!(game_state.prestige.count, 0);
+    }
+
+    #[test]
+    fn test_prestige_resets_progress() {
+        let mut game_state = progressed_game_state(30);
+        assert!(game_state.can_prestige());
+        let level = game_state.character.level;
+        assert!(level > 1);
+        let fresh = create_test_game_state();
+
+        game_state.character.rename("Prestiged");
+        assert!(game_state.prestige(create_test_game_template(&[])));
+
+        assert_eq!(game_state.character.name, "Prestiged");
+        assert_eq!(game_state.character.level, 1);
+        assert_eq!(
+            game_state.character.attributes(),
+            fresh.character.attributes()
+        );
+        assert_eq!(game_state.current_time, fresh.current_time);
+        assert!(!game_state.story.quest(QuestId(0)).state().is_completed());
+        assert_eq!(game_state.prestige.required_quest, Some(QuestId(0)));
+        assert_eq!(game_state.prestige.count, 1);
+        assert_eq!(
+            game_state.prestige.multiplier,
+            1.0 + Prestige::multiplier_gain(level)
+        );
+
+        let serialised = pot::to_vec(&game_state).unwrap();
+        let loaded: GameState = pot::from_slice(&serialised).unwrap();
+        assert_eq!(loaded.prestige.count, 1);
+        assert_eq!(loaded.prestige.multiplier, game_state.prestige.multiplier);
+    }
+
+    #[test]
+    fn test_prestige_keeps_observers() {
+        let mut game_state = progressed_game_state(30);
+        let observed_events = Arc::new(AtomicUsize::new(0));
+        let observer_events = observed_events.clone();
+        game_state.register_observer(move |_| {
+            observer_events.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert!(game_state.prestige(create_test_game_template(&[])));
+        let events = game_state.update_with_events(
+            (GameTime::from_days(1).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                as i64,
+        );
+
+        assert!(!events.is_empty());
+        assert_eq!(observed_events.load(Ordering::Relaxed), events.len());
+    }
+
+    #[test]
+    fn test_prestige_multiplier_increases_with_progress() {
+        let mut short = progressed_game_state(10);
+        let mut long = progressed_game_state(60);
+        assert!(long.character.level > short.character.level);
+
+        assert!(short.prestige(create_test_game_template(&[])));
+        assert!(long.prestige(create_test_game_template(&[])));
+        assert!(long.prestige.multiplier > short.prestige.multiplier);
+        assert!(short.prestige.multiplier > 1.0);
+    }
+
+    #[test]
+    fn test_prestige_multiplier_accelerates_progress() {
+        let mut normal = create_test_game_state();
+        let mut prestiged = normal.clone();
+        prestiged.prestige.multiplier = 2.0;
+        for game_state in [&mut normal, &mut prestiged] {
+            let train_str = game_state
+                .actions
+                .list_choosable()
+                .find(|action| action.id_str == "train_str")
+                .unwrap()
+                .id;
+            game_state.actions.selected_action = train_str;
+            game_state.update_offline(
+                (GameTime::from_days(5).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                    as i64,
+            );
+        }
+        assert!(prestiged.character.attributes().strength > normal.character.attributes().strength);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::game_state::currency::Currency;
+use crate::game_state::invariants::InvariantViolation;
 use crate::game_state::inventory::Inventory;
-use crate::game_state::story::quests::{CompiledQuest, QuestId};
+use crate::game_state::story::quests::{CompiledQuest, QuestId, QuestState};
 use crate::game_state::time::GameTime;
+use crate::game_state::translations::Translator;
 use crate::game_state::triggers::CompiledGameEvent;
 use log::debug;
 use quests::quest_stages::QuestStageId;
@@ -9,6 +11,7 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeSet, HashSet};
 use std::fmt::Debug;
+use std::iter;
 
 pub mod quests;
 
@@ -26,6 +29,9 @@ pub struct Story {
     active_failed_quests_by_failure_time: BTreeSet<(GameTime, QuestId)>,
     failed_quests: HashSet<QuestId>,
     failed_quests_by_failure_time: BTreeSet<(GameTime, QuestId)>,
+    /// Inactive quests with manual activation that were offered to the player, by offer time.
+    #[serde(default)]
+    available_quests_by_offer_time: BTreeSet<(GameTime, QuestId)>,
 }
 
 impl Story {
@@ -44,6 +50,7 @@ impl Story {
             active_failed_quests_by_failure_time: Default::default(),
             failed_quests: Default::default(),
             failed_quests_by_failure_time: Default::default(),
+            available_quests_by_offer_time: Default::default(),
         }
     }
 
@@ -55,6 +62,26 @@ impl Story {
         &mut self.quests[quest_id.0]
     }
 
+    pub fn translate(&mut self, translator: &Translator) {
+        for quest in &mut self.quests {
+            quest.translate(translator);
+        }
+    }
+
+    pub fn iter_available_quests_by_offer_time(
+        &self,
+    ) -> impl Iterator<Item = &'_ CompiledQuest> + DoubleEndedIterator {
+        self.available_quests_by_offer_time
+            .iter()
+            .map(|(_, quest_id)| self.quest(*quest_id))
+    }
+
+    pub fn is_available(&self, quest_id: QuestId) -> bool {
+        self.available_quests_by_offer_time
+            .iter()
+            .any(|(_, available_quest_id)| *available_quest_id == quest_id)
+    }
+
     pub fn iter_active_quests_by_activation_time(
         &self,
     ) -> impl Iterator<Item = &'_ CompiledQuest> + DoubleEndedIterator {
@@ -83,6 +110,117 @@ impl Story {
         self.quests.iter()
     }
 
+    /// The active quests whose deadline is at or before the given time, with their deadlines.
+    pub fn expired_quests(&self, time: GameTime) -> impl Iterator<Item = (QuestId, GameTime)> + '_ {
+        self.iter_active_quests_by_activation_time()
+            .filter_map(move |quest| {
+                quest
+                    .deadline()
+                    .filter(|deadline| *deadline <= time)
+                    .map(|deadline| (quest.id, deadline))
+            })
+    }
+
+    pub fn validate_invariants(&self) -> Result<(), InvariantViolation> {
+        let sets = [
+            ("inactive", &self.inactive_quests),
+            ("active", &self.active_quests),
+            ("completed", &self.completed_quests),
+            ("inactive failed", &self.inactive_failed_quests),
+            ("active failed", &self.active_failed_quests),
+            ("failed", &self.failed_quests),
+        ];
+        let timed_sets = [
+            (
+                "active by activation time",
+                &self.active_quests_by_activation_time,
+            ),
+            (
+                "completed by completion time",
+                &self.completed_quests_by_completion_time,
+            ),
+            (
+                "inactive failed by failure time",
+                &self.inactive_failed_quests_by_failure_time,
+            ),
+            (
+                "active failed by failure time",
+                &self.active_failed_quests_by_failure_time,
+            ),
+            (
+                "failed by failure time",
+                &self.failed_quests_by_failure_time,
+            ),
+        ];
+        let mut expected_sizes = [0; 6];
+        let mut expected_timed_sizes = [0; 5];
+
+        for quest in &self.quests {
+            let (expected_sets, expected_timed_sets): (&[usize], Vec<(usize, GameTime)>) =
+                match *quest.state() {
+                    QuestState::Inactive => (&[0], vec![]),
+                    QuestState::Active {
+                        activation_time, ..
+                    } => (&[1], vec![(0, activation_time)]),
+                    QuestState::Completed {
+                        completion_time, ..
+                    } => (&[2], vec![(1, completion_time)]),
+                    QuestState::FailedWhileInactive { failure_time } => {
+                        (&[3, 5], vec![(2, failure_time), (4, failure_time)])
+                    }
+                    QuestState::FailedWhileActive { failure_time, .. } => {
+                        (&[4, 5], vec![(3, failure_time), (4, failure_time)])
+                    }
+                };
+
+            let mismatch = |set, contained| InvariantViolation::QuestSetMismatch {
+                id_str: quest.id_str.clone(),
+                state: format!("{:?}", quest.state()),
+                set,
+                contained,
+            };
+            for (index, (set, quest_ids)) in sets.iter().enumerate() {
+                let contained = quest_ids.contains(&quest.id);
+                if contained != expected_sets.contains(&index) {
+                    return Err(mismatch(set, contained));
+                }
+            }
+            for &index in expected_sets {
+                expected_sizes[index] += 1;
+            }
+            for (index, time) in expected_timed_sets {
+                let (set, entries) = timed_sets[index];
+                if !entries.contains(&(time, quest.id)) {
+                    return Err(mismatch(set, false));
+                }
+                expected_timed_sizes[index] += 1;
+            }
+
+            quest.validate_stages()?;
+        }
+
+        for ((set, quest_ids), expected) in sets.into_iter().zip(expected_sizes) {
+            if quest_ids.len() != expected {
+                return Err(InvariantViolation::QuestSetSize {
+                    set,
+                    expected,
+                    actual: quest_ids.len(),
+                });
+            }
+        }
+        for ((set, entries), expected) in timed_sets.into_iter().zip(expected_timed_sizes) {
+            if entries.len() != expected {
+                return Err(InvariantViolation::QuestSetSize {
+                    set,
+                    expected,
+                    actual: entries.len(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn activate_quest(
         &mut self,
         quest_id: QuestId,
@@ -93,6 +231,7 @@ impl Story {
         if !quest.state().is_failed() {
             let result = quest.activate(time);
             assert!(self.inactive_quests.remove(&quest_id));
+            self.accept_quest(quest_id);
             assert!(self.active_quests.insert(quest_id));
             assert!(self
                 .active_quests_by_activation_time
@@ -105,6 +244,29 @@ impl Story {
         .flatten()
     }
 
+    /// Makes the quest available to be accepted by the player, if it is still inactive.
+    pub fn offer_quest(
+        &mut self,
+        quest_id: QuestId,
+        time: GameTime,
+    ) -> impl Iterator<Item = CompiledGameEvent> {
+        let quest = self.quest(quest_id);
+        if quest.state() == &QuestState::Inactive && !self.is_available(quest_id) {
+            debug!("Offering quest {}", quest.id_str);
+            self.available_quests_by_offer_time.insert((time, quest_id));
+        }
+        iter::empty()
+    }
+
+    /// Removes the quest from the available quests.
+    /// Returns false if it was not available.
+    pub fn accept_quest(&mut self, quest_id: QuestId) -> bool {
+        let previous_len = self.available_quests_by_offer_time.len();
+        self.available_quests_by_offer_time
+            .retain(|(_, available_quest_id)| *available_quest_id != quest_id);
+        self.available_quests_by_offer_time.len() < previous_len
+    }
+
     pub fn complete_quest_stage(
         &mut self,
         rng: &mut impl Rng,
@@ -164,8 +326,12 @@ impl Story {
         time: GameTime,
     ) -> impl Iterator<Item = CompiledGameEvent> {
         let quest = self.quests.get_mut(quest_id.0).unwrap();
+        if quest.state().is_failed() {
+            // A quest with a time limit may already have failed when its failure condition triggers.
+            return None.into_iter().flatten();
+        }
         debug!("Failing quest {}", quest.id_str);
-        quest.fail(time, |activation_time| {
+        Some(quest.fail(time, |activation_time| {
             if let Some(activation_time) = activation_time {
                 assert!(self.active_quests.remove(&quest_id));
                 assert!(self
@@ -178,6 +344,8 @@ impl Story {
                 assert!(self.failed_quests.insert(quest_id));
                 assert!(self.failed_quests_by_failure_time.insert((time, quest_id)));
             } else {
+                self.available_quests_by_offer_time
+                    .retain(|(_, available_quest_id)| *available_quest_id != quest_id);
                 assert!(self.inactive_quests.remove(&quest_id));
                 assert!(self.inactive_failed_quests.insert(quest_id));
                 assert!(self
@@ -186,6 +354,294 @@ impl Story {
                 assert!(self.failed_quests.insert(quest_id));
                 assert!(self.failed_quests_by_failure_time.insert((time, quest_id)));
             }
-        })
+        }))
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::invariants::InvariantViolation;
+    use crate::game_state::story::quests::{QuestId, QuestState};
+    use crate::game_state::tests::{
+        create_test_game_state, create_test_game_state_with_templates, real_milliseconds,
+    };
+    use crate::game_state::time::GameTime;
+    use crate::game_state::{GameState, GameStateMessage};
+
+    const TIMED_QUEST_TEMPLATE: &str = "
+QUEST timed_quest
+title Hurry up
+activation none
+failure never
+time_limit 2d
+BEGIN
+    QUEST_STAGE timed_stage
+    task Do the impossible.
+    completion never
+END
+
+QUEST consolation_quest
+title Try again
+activation quest_failed(timed_quest)
+failure never
+BEGIN
+    QUEST_STAGE consolation_stage
+    task Do something easier.
+    completion never
+END
+";
+
+    const MANUAL_QUEST_TEMPLATE: &str = "
+QUEST manual_quest
+title Help the farmer
+activation none
+manual_activation true
+failure never
+BEGIN
+    QUEST_STAGE manual_stage
+    task Plough the field.
+    completion never
+END
+";
+
+    const QUEST_STATE_CONDITION_TEMPLATE: &str = "
+ACTION gating_study
+name Study
+progressive studying
+simple_past studied
+type TRAIN
+duration 1h
+int 1.0
+currency 0
+activation none
+deactivation never
+
+QUEST gating_quest
+title Study once
+activation none
+manual_activation true
+failure never
+BEGIN
+    QUEST_STAGE gating_stage
+    task Study once.
+    completion action_count(1, gating_study)
+END
+
+QUEST after_activation
+title Report for study
+activation quest_activated(gating_quest)
+failure never
+BEGIN
+    QUEST_STAGE after_activation_stage
+    task Wait for the lesson.
+    completion never
+END
+
+QUEST after_completion
+title Show what you learned
+activation quest_completed(gating_quest)
+failure never
+BEGIN
+    QUEST_STAGE after_completion_stage
+    task Wait for the exam.
+    completion never
+END
+";
+
+    #[test]
+    fn test_corrupted_quest_sets() {
+        let mut game_state = create_test_game_state();
+        assert!(game_state.story.quest(QuestId(0)).state().is_active());
+        game_state.story.active_quests.remove(&QuestId(0));
+
+        let error = game_state.validate_invariants().unwrap_err();
+        assert!(matches!(
+            &error,
+            InvariantViolation::QuestSetMismatch {
+                set: "active",
+                contained: false,
+                ..
+            }
+        ));
+        assert!(error.to_string().starts_with(&format!(
+            "quest {} in state Active",
+            game_state.story.quest(QuestId(0)).id_str
+        )));
+    }
+
+    #[test]
+    fn test_unknown_quest_in_set() {
+        let mut game_state = create_test_game_state();
+        game_state.story.completed_quests.insert(QuestId(10_000));
+
+        assert_eq!(
+            game_state.validate_invariants(),
+            Err(InvariantViolation::QuestSetSize {
+                set: "completed",
+                expected: 0,
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_quest_time_limit() {
+        let mut game_state = create_test_game_state_with_templates(&[TIMED_QUEST_TEMPLATE]);
+        let quest = game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.id_str == "timed_quest")
+            .unwrap();
+        let quest_id = quest.id;
+        let activation_time = quest.state().activation_time().unwrap();
+        let deadline = activation_time + GameTime::from_days(2);
+        let consolation_quest_id = game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.id_str == "consolation_quest")
+            .unwrap()
+            .id;
+        assert_eq!(quest.deadline(), Some(deadline));
+
+        game_state.update_offline(real_milliseconds(GameTime::from_days(1)));
+        let quest = game_state.story.quest(quest_id);
+        assert!(quest.state().is_active());
+        assert_eq!(
+            quest.remaining_time(game_state.current_time),
+            Some(deadline - game_state.current_time)
+        );
+        assert_eq!(
+            quest.remaining_time(activation_time + GameTime::from_hours(30)),
+            Some(GameTime::from_hours(18))
+        );
+
+        game_state.update_offline(real_milliseconds(deadline - game_state.current_time) - 1);
+        assert!(game_state.current_time < deadline);
+        assert!(game_state.story.quest(quest_id).state().is_active());
+        assert!(!game_state
+            .story
+            .quest(consolation_quest_id)
+            .state()
+            .is_active());
+
+        game_state.update_offline(2);
+        assert!(game_state.current_time >= deadline);
+        let quest = game_state.story.quest(quest_id);
+        assert_eq!(
+            quest.state(),
+            &QuestState::FailedWhileActive {
+                activation_time,
+                failure_time: deadline,
+                failed_stage: 0,
+            }
+        );
+        assert_eq!(quest.remaining_time(game_state.current_time), None);
+        // quests can depend on the failure of the timed quest
+        assert!(game_state
+            .story
+            .quest(consolation_quest_id)
+            .state()
+            .is_active());
+    }
+
+    #[test]
+    fn test_manual_quest_activation() {
+        let mut game_state = create_test_game_state_with_templates(&[MANUAL_QUEST_TEMPLATE]);
+        let quest_id = game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.id_str == "manual_quest")
+            .unwrap()
+            .id;
+        game_state.update_offline(real_milliseconds(GameTime::from_hours(1)));
+        assert_eq!(
+            game_state.story.quest(quest_id).state(),
+            &QuestState::Inactive
+        );
+        assert!(game_state.story.is_available(quest_id));
+        assert!(game_state
+            .story
+            .iter_available_quests_by_offer_time()
+            .any(|quest| quest.id == quest_id));
+
+        assert!(game_state.handle_message(&GameStateMessage::AcceptQuest(quest_id)));
+        assert!(game_state.story.quest(quest_id).state().is_active());
+        assert!(!game_state.story.is_available(quest_id));
+        assert_eq!(game_state.validate_invariants(), Ok(()));
+
+        assert!(!game_state.handle_message(&GameStateMessage::AcceptQuest(quest_id)));
+        assert!(game_state.story.quest(quest_id).state().is_active());
+    }
+
+    #[test]
+    fn test_accept_unavailable_quest() {
+        let mut game_state = create_test_game_state_with_templates(&[TIMED_QUEST_TEMPLATE]);
+        let quest_id = game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.state() == &QuestState::Inactive)
+            .unwrap()
+            .id;
+
+        assert!(!game_state.handle_message(&GameStateMessage::AcceptQuest(quest_id)));
+        assert_eq!(
+            game_state.story.quest(quest_id).state(),
+            &QuestState::Inactive
+        );
+    }
+    #[test]
+    fn test_quest_state_conditions() {
+        let mut game_state =
+            create_test_game_state_with_templates(&[QUEST_STATE_CONDITION_TEMPLATE]);
+        let quest_id = |game_state: &GameState, id_str: &str| {
+            game_state
+                .story
+                .iter_all_quests()
+                .find(|quest| quest.id_str == id_str)
+                .unwrap()
+                .id
+        };
+        let gating_quest = quest_id(&game_state, "gating_quest");
+        let after_activation = quest_id(&game_state, "after_activation");
+        let after_completion = quest_id(&game_state, "after_completion");
+        let states = |game_state: &GameState| {
+            [gating_quest, after_activation, after_completion]
+                .map(|quest| game_state.story.quest(quest).state().clone())
+        };
+
+        // neither condition is fulfilled while the quest is inactive
+        game_state.update_offline(real_milliseconds(GameTime::from_hours(1)));
+        assert_eq!(
+            states(&game_state),
+            [
+                QuestState::Inactive,
+                QuestState::Inactive,
+                QuestState::Inactive
+            ]
+        );
+
+        // only the activation condition is fulfilled while the quest is active
+        assert!(game_state.handle_message(&GameStateMessage::AcceptQuest(gating_quest)));
+        let [gating_state, after_activation_state, after_completion_state] = states(&game_state);
+        assert!(gating_state.is_active());
+        assert!(after_activation_state.is_active());
+        assert_eq!(after_completion_state, QuestState::Inactive);
+
+        // both conditions stay fulfilled once the quest is completed
+        let gating_study = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "gating_study")
+            .unwrap()
+            .id;
+        game_state.actions.selected_action = gating_study;
+        game_state.update_offline(real_milliseconds(GameTime::from_days(1)));
+        let [gating_state, after_activation_state, after_completion_state] = states(&game_state);
+        assert!(gating_state.is_completed());
+        assert!(after_activation_state.is_active());
+        assert!(after_completion_state.is_active());
+        assert_eq!(game_state.validate_invariants(), Ok(()));
     }
 }
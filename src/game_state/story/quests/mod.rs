@@ -1,7 +1,9 @@
 use crate::game_state::currency::Currency;
+use crate::game_state::invariants::InvariantViolation;
 use crate::game_state::inventory::item::{CompiledExpectedItemCount, ExpectedItemCount};
 use crate::game_state::inventory::Inventory;
 use crate::game_state::time::GameTime;
+use crate::game_state::translations::Translator;
 use crate::game_state::triggers::CompiledGameEvent;
 use crate::game_template::IdMaps;
 use event_trigger_action_system::TriggerHandle;
@@ -9,6 +11,7 @@ use log::debug;
 use quest_stages::{CompiledQuestStage, QuestStage, QuestStageId, QuestStageState};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::iter;
 
 /*pub fn init_quests() -> Vec<Quest> {
@@ -40,7 +43,13 @@ pub struct Quest {
     pub currency_reward: Currency,
     pub items: Vec<ExpectedItemCount>,
     pub activation_condition: String,
+    /// The quest is offered to the player when its activation condition is fulfilled, and activated when accepted.
+    #[serde(default)]
+    pub manual_activation: bool,
     pub failure_condition: String,
+    /// The quest fails if it is not completed within this time after its activation.
+    #[serde(default)]
+    pub time_limit: Option<GameTime>,
     pub stages: Vec<QuestStage>,
 }
 
@@ -53,7 +62,11 @@ pub struct CompiledQuest {
     pub currency_reward: Currency,
     pub items: Vec<CompiledExpectedItemCount>,
     pub activation_condition: TriggerHandle,
+    #[serde(default)]
+    pub manual_activation: bool,
     pub failure_condition: TriggerHandle,
+    #[serde(default)]
+    pub time_limit: Option<GameTime>,
     stages: Vec<CompiledQuestStage>,
     state: QuestState,
 }
@@ -108,7 +121,9 @@ impl Quest {
                 .map(|item| item.compile(id_maps))
                 .collect(),
             activation_condition: *id_maps.triggers.get(&self.activation_condition).unwrap(),
+            manual_activation: self.manual_activation,
             failure_condition: *id_maps.triggers.get(&self.failure_condition).unwrap(),
+            time_limit: self.time_limit,
             stages: self
                 .stages
                 .into_iter()
@@ -136,6 +151,15 @@ impl CompiledQuest {
         }
     }
 
+    pub fn translate(&mut self, translator: &Translator) {
+        translator.translate(&mut self.title);
+        translator.translate_option(&mut self.description);
+        for stage in &mut self.stages {
+            translator.translate_option(&mut stage.description);
+            translator.translate(&mut stage.task);
+        }
+    }
+
     pub fn failed_stage(&self) -> Option<&CompiledQuestStage> {
         match self.state {
             QuestState::FailedWhileInactive { .. } => Some(self.stages.first().unwrap()),
@@ -146,10 +170,77 @@ impl CompiledQuest {
         }
     }
 
+    /// Checks that the states of the stages match the state of the quest.
+    pub fn validate_stages(&self) -> Result<(), InvariantViolation> {
+        if let QuestState::Active {
+            active_stage: stage,
+            ..
+        }
+        | QuestState::FailedWhileActive {
+            failed_stage: stage,
+            ..
+        } = self.state
+        {
+            if stage >= self.stages.len() {
+                return Err(InvariantViolation::QuestStageOutOfRange {
+                    id_str: self.id_str.clone(),
+                    stage,
+                    stage_count: self.stages.len(),
+                });
+            }
+        }
+
+        for (index, stage) in self.stages.iter().enumerate() {
+            let is_valid = match self.state {
+                QuestState::Inactive => stage.state.is_inactive(),
+                QuestState::Active { active_stage, .. } => match index.cmp(&active_stage) {
+                    Ordering::Less => stage.state.is_completed(),
+                    Ordering::Equal => stage.state.is_active(),
+                    Ordering::Greater => stage.state.is_inactive(),
+                },
+                QuestState::Completed { .. } => stage.state.is_completed(),
+                QuestState::FailedWhileInactive { .. } | QuestState::FailedWhileActive { .. } => {
+                    true
+                }
+            };
+            if !is_valid {
+                return Err(InvariantViolation::QuestStageStateMismatch {
+                    quest_id_str: self.id_str.clone(),
+                    quest_state: format!("{:?}", self.state),
+                    stage_id_str: stage.id_str.clone(),
+                    stage_state: format!("{:?}", stage.state),
+                });
+            }
+        }
+        Ok(())
+    }
+
     pub fn state(&self) -> &QuestState {
         &self.state
     }
 
+    /// The time at which this quest fails if it has a time limit and is active.
+    pub fn deadline(&self) -> Option<GameTime> {
+        match self.state {
+            QuestState::Active {
+                activation_time, ..
+            } => self
+                .time_limit
+                .map(|time_limit| activation_time + time_limit),
+            _ => None,
+        }
+    }
+
+    /// The time left until the [`deadline`](Self::deadline), or `None` if there is none.
+    pub fn remaining_time(&self, current_time: GameTime) -> Option<GameTime> {
+        self.deadline()
+            .map(|deadline| (deadline - current_time).max(GameTime::zero()))
+    }
+
+    pub fn stage_count(&self) -> usize {
+        self.stages.len()
+    }
+
     pub fn completed_stages(&self) -> impl Iterator<Item = &'_ CompiledQuestStage> {
         self.stages.iter().take(match self.state {
             QuestState::Inactive => 0,
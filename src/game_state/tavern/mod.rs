@@ -0,0 +1,167 @@
+use crate::game_state::character::CharacterAttributeProgressFactor;
+use crate::game_state::currency::Currency;
+use crate::game_state::player_actions::PlayerActionInProgress;
+use enum_iterator::{next, Sequence};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// The quality of the rooms the character rents when visiting the tavern, bought with currency.
+#[derive(
+    Clone, Copy, Debug, Serialize, Deserialize, Default, Sequence, Eq, PartialEq, Ord, PartialOrd,
+)]
+pub enum TavernQuality {
+    #[default]
+    Shabby,
+    Comfortable,
+    Luxurious,
+}
+
+impl TavernQuality {
+    /// The one-time price of upgrading to this quality.
+    pub fn price(&self) -> Currency {
+        match self {
+            TavernQuality::Shabby => Currency::zero(),
+            TavernQuality::Comfortable => Currency::from_silver(50),
+            TavernQuality::Luxurious => Currency::from_gold(5),
+        }
+    }
+
+    /// The next better quality, if any.
+    pub fn upgrade(&self) -> Option<Self> {
+        next(self)
+    }
+
+    /// Multiplies the currency spent on each visit, but not the currency gained.
+    pub fn visit_cost_multiplier(&self) -> f64 {
+        match self {
+            TavernQuality::Shabby => 1.0,
+            TavernQuality::Comfortable => 1.5,
+            TavernQuality::Luxurious => 2.0,
+        }
+    }
+
+    /// Additional attribute progress per hour spent resting in the tavern.
+    pub fn rest_attribute_progress_factor(&self) -> CharacterAttributeProgressFactor {
+        match self {
+            TavernQuality::Shabby => CharacterAttributeProgressFactor::zero(),
+            TavernQuality::Comfortable => CharacterAttributeProgressFactor::from_stamina(0.5),
+            TavernQuality::Luxurious => {
+                CharacterAttributeProgressFactor::new(0.0, 1.0, 0.0, 0.0, 0.0, 0.5)
+            }
+        }
+    }
+
+    pub fn scale_visit_cost(&self, currency_reward: Currency) -> Currency {
+        if currency_reward < Currency::zero() {
+            currency_reward * self.visit_cost_multiplier()
+        } else {
+            currency_reward
+        }
+    }
+
+    /// Applies the benefits and costs of this quality to a visit of the tavern.
+    pub fn apply(&self, tavern_visit: &mut PlayerActionInProgress) {
+        tavern_visit.attribute_progress += self
+            .rest_attribute_progress_factor()
+            .into_progress(tavern_visit.length());
+        tavern_visit.currency_reward = self.scale_visit_cost(tavern_visit.currency_reward);
+    }
+}
+
+impl Display for TavernQuality {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TavernQuality::Shabby => write!(f, "Shabby"),
+            TavernQuality::Comfortable => write!(f, "Comfortable"),
+            TavernQuality::Luxurious => write!(f, "Luxurious"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TavernQuality;
+    use crate::game_state::currency::Currency;
+    use crate::game_state::player_actions::{PlayerActionInProgressSource, ACTION_TAVERN};
+    use crate::game_state::tests::create_test_game_state;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::GameStateMessage;
+
+    #[test]
+    fn test_upgrades_are_ordered() {
+        let mut quality = TavernQuality::default();
+        while let Some(upgrade) = quality.upgrade() {
+            assert!(upgrade > quality);
+            assert!(upgrade.price() > quality.price());
+            assert!(
+                upgrade
+                    .rest_attribute_progress_factor()
+                    .into_progress(GameTime::from_hours(1))
+                    .sum()
+                    > 0
+            );
+            quality = upgrade;
+        }
+        assert_eq!(quality, TavernQuality::Luxurious);
+    }
+
+    #[test]
+    fn test_purchase_changes_tavern_reward() {
+        let mut game_state = create_test_game_state();
+        let start_time = game_state.current_time;
+        let shabby_visit = game_state.spawn_tavern_visit(start_time);
+        assert!(matches!(
+            shabby_visit.source,
+            PlayerActionInProgressSource::Action(action) if action == ACTION_TAVERN
+        ));
+
+        let price = TavernQuality::Comfortable.price();
+        game_state.inventory.currency = price + Currency::from_copper(7);
+        assert!(
+            game_state.handle_message(&GameStateMessage::PurchaseTavernQuality(
+                TavernQuality::Comfortable
+            ))
+        );
+        assert_eq!(game_state.tavern_quality, TavernQuality::Comfortable);
+        assert_eq!(game_state.inventory.currency, Currency::from_copper(7));
+
+        let comfortable_visit = game_state.spawn_tavern_visit(start_time);
+        assert!(comfortable_visit.attribute_progress.sum() > shabby_visit.attribute_progress.sum());
+        assert!(comfortable_visit.currency_reward <= shabby_visit.currency_reward);
+    }
+
+    #[test]
+    fn test_unaffordable_purchase() {
+        let mut game_state = create_test_game_state();
+        let price = TavernQuality::Luxurious.price();
+        game_state.inventory.currency = price - Currency::from_copper(1);
+
+        assert!(
+            !game_state.handle_message(&GameStateMessage::PurchaseTavernQuality(
+                TavernQuality::Luxurious
+            ))
+        );
+        assert_eq!(game_state.tavern_quality, TavernQuality::Shabby);
+        assert_eq!(
+            game_state.inventory.currency,
+            price - Currency::from_copper(1)
+        );
+    }
+
+    #[test]
+    fn test_no_downgrade() {
+        let mut game_state = create_test_game_state();
+        game_state.inventory.currency = TavernQuality::Luxurious.price();
+        assert!(game_state.purchase_tavern_quality(TavernQuality::Luxurious));
+        assert_eq!(game_state.inventory.currency, Currency::zero());
+
+        game_state.inventory.currency = TavernQuality::Comfortable.price();
+        assert!(!game_state.purchase_tavern_quality(TavernQuality::Comfortable));
+        assert!(!game_state.purchase_tavern_quality(TavernQuality::Luxurious));
+        assert_eq!(game_state.tavern_quality, TavernQuality::Luxurious);
+        assert_eq!(
+            game_state.inventory.currency,
+            TavernQuality::Comfortable.price()
+        );
+    }
+}
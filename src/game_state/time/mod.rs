@@ -1,13 +1,21 @@
 #![allow(dead_code)]
 
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
 use std::ops;
+use std::str::FromStr;
 
 #[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct GameTime {
     time: i128,
 }
 
+/// Serialises a [`GameTime`] as its human readable [`Display`] string instead of a millisecond count,
+/// e.g. for savegames that are meant to be read and edited by hand.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct GameTimeString(pub GameTime);
+
 pub const MILLISECONDS_PER_SECOND: i128 = 1000;
 pub const SECONDS_PER_MINUTE: i128 = 60;
 pub const MINUTES_PER_HOUR: i128 = 60;
@@ -40,6 +48,16 @@ pub const MILLISECONDS_PER_MONTH: [i128; 12] = [
 ];
 pub const MILLISECONDS_PER_YEAR: i128 = MILLISECONDS_PER_DAY * DAYS_PER_YEAR;
 
+/// The units of the display string of a game time, largest first.
+const DISPLAY_UNITS: [(&str, i128); 6] = [
+    ("y", MILLISECONDS_PER_YEAR),
+    ("d", MILLISECONDS_PER_DAY),
+    ("h", MILLISECONDS_PER_HOUR),
+    ("m", MILLISECONDS_PER_MINUTE),
+    ("s", MILLISECONDS_PER_SECOND),
+    ("ms", 1),
+];
+
 impl GameTime {
     pub const fn zero() -> Self {
         Self { time: 0 }
@@ -407,6 +425,37 @@ impl GameTime {
             _ => unreachable!(),
         }
     }
+
+    /// Adds the times, returning `None` on overflow.
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.time.checked_add(rhs.time) {
+            Some(time) => Some(Self { time }),
+            None => None,
+        }
+    }
+
+    /// Subtracts the times, returning `None` on overflow.
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.time.checked_sub(rhs.time) {
+            Some(time) => Some(Self { time }),
+            None => None,
+        }
+    }
+
+    /// Subtracts the times, saturating at the numeric bounds instead of overflowing.
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self {
+            time: self.time.saturating_sub(rhs.time),
+        }
+    }
+
+    /// Scales the time, returning `None` on overflow.
+    pub const fn checked_mul(self, rhs: i64) -> Option<Self> {
+        match self.time.checked_mul(rhs as i128) {
+            Some(time) => Some(Self { time }),
+            None => None,
+        }
+    }
 }
 
 impl ops::Add for GameTime {
@@ -461,12 +510,202 @@ impl ops::Mul<GameTime> for i64 {
     }
 }
 
+impl ops::Mul<f64> for GameTime {
+    type Output = Self;
+
+    /// Scales the time, rounding to the nearest millisecond.
+    /// Returns the unscaled time if the result is not finite.
+    fn mul(self, rhs: f64) -> Self::Output {
+        let result = self.time as f64 * rhs;
+        debug_assert!(
+            result.is_finite(),
+            "Scaling {self:?} by {rhs} is not finite"
+        );
+        if result.is_finite() {
+            Self::from_milliseconds_f64(result)
+        } else {
+            self
+        }
+    }
+}
+
+impl ops::Mul<GameTime> for f64 {
+    type Output = GameTime;
+
+    fn mul(self, rhs: GameTime) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl ops::Div<f64> for GameTime {
+    type Output = Self;
+
+    /// Scales the time, rounding to the nearest millisecond.
+    /// Returns the unscaled time if the result is not finite.
+    fn div(self, rhs: f64) -> Self::Output {
+        let result = self.time as f64 / rhs;
+        debug_assert!(
+            result.is_finite(),
+            "Dividing {self:?} by {rhs} is not finite"
+        );
+        if result.is_finite() {
+            Self::from_milliseconds_f64(result)
+        } else {
+            self
+        }
+    }
+}
+
+/// Displays the time as its nonzero components, e.g. `1y 2d 3h 4m 5s 6ms`, or `0ms` if it is zero.
+impl Display for GameTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.time == 0 {
+            return write!(f, "0ms");
+        }
+        if self.time < 0 {
+            write!(f, "-")?;
+        }
+
+        let mut remainder = self.time.unsigned_abs();
+        let mut separator = "";
+        for (unit, milliseconds) in DISPLAY_UNITS {
+            let count = remainder / milliseconds as u128;
+            remainder %= milliseconds as u128;
+            if count > 0 {
+                write!(f, "{separator}{count}{unit}")?;
+                separator = " ";
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the [`Display`] string of a game time.
+/// The components may be in any order and also contain weeks, e.g. `2w 12h`.
+impl FromStr for GameTime {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sign, s) = match s.trim().strip_prefix('-') {
+            Some(s) => (-1, s),
+            None => (1, s.trim()),
+        };
+
+        let mut time = GameTime::zero();
+        for component in s.split_whitespace() {
+            let unit_index = component
+                .find(|character: char| !character.is_ascii_digit())
+                .ok_or(())?;
+            let (count, unit) = component.split_at(unit_index);
+            let count: i128 = count.parse().map_err(|_| ())?;
+            let milliseconds = match unit {
+                "w" => MILLISECONDS_PER_WEEK,
+                unit => {
+                    DISPLAY_UNITS
+                        .iter()
+                        .find(|(display_unit, _)| *display_unit == unit)
+                        .ok_or(())?
+                        .1
+                }
+            };
+            let component = count
+                .checked_mul(milliseconds)
+                .and_then(|component| component.checked_mul(sign))
+                .ok_or(())?;
+            time = time
+                .checked_add(GameTime::from_milliseconds(component))
+                .ok_or(())?;
+        }
+
+        if s.trim().is_empty() {
+            Err(())
+        } else {
+            Ok(time)
+        }
+    }
+}
+
+impl Serialize for GameTimeString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GameTimeString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        string
+            .parse()
+            .map(Self)
+            .map_err(|_| D::Error::custom(format!("malformed game time {string:?}")))
+    }
+}
+
+impl From<GameTime> for GameTimeString {
+    fn from(value: GameTime) -> Self {
+        Self(value)
+    }
+}
+
+impl From<GameTimeString> for GameTime {
+    fn from(value: GameTimeString) -> Self {
+        value.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::game_state::time::{
-        GameTime, DAYS_PER_MONTH, FIRST_DAY_OF_MONTH, FIRST_YEAR_OF_ERA, YEARS_PER_FINISHED_ERA,
+        GameTime, GameTimeString, DAYS_PER_MONTH, FIRST_DAY_OF_MONTH, FIRST_YEAR_OF_ERA,
+        YEARS_PER_FINISHED_ERA,
     };
 
+    #[test]
+    fn test_checked_arithmetic() {
+        let max = GameTime::from_milliseconds(i128::MAX);
+        let min = GameTime::from_milliseconds(i128::MIN);
+        let one = GameTime::from_milliseconds(1);
+
+        assert_eq!(
+            GameTime::from_hours(1).checked_add(GameTime::from_minutes(30)),
+            Some(GameTime::from_minutes(90))
+        );
+        assert_eq!((max - one).checked_add(one), Some(max));
+        assert_eq!(max.checked_add(one), None);
+        assert_eq!(max.checked_add(max), None);
+
+        assert_eq!(
+            GameTime::from_hours(1).checked_sub(GameTime::from_minutes(30)),
+            Some(GameTime::from_minutes(30))
+        );
+        assert_eq!((min + one).checked_sub(one), Some(min));
+        assert_eq!(min.checked_sub(one), None);
+        assert_eq!(GameTime::zero().checked_sub(min), None);
+        assert_eq!(GameTime::zero().saturating_sub(min), max);
+        assert_eq!(min.saturating_sub(one), min);
+
+        assert_eq!(
+            GameTime::from_hours(2).checked_mul(3),
+            Some(GameTime::from_hours(6))
+        );
+        assert_eq!(
+            GameTime::from_milliseconds(i128::MAX / 2).checked_mul(2),
+            Some(GameTime::from_milliseconds(i128::MAX - 1))
+        );
+        assert_eq!(max.checked_mul(2), None);
+        assert_eq!(min.checked_mul(-1), None);
+        assert_eq!(
+            GameTime::from_milliseconds(i128::MAX / 1000).checked_mul(i64::MAX),
+            None
+        );
+    }
+
     #[test]
     fn test_first_days_of_months() {
         let first_day_of_month: Vec<_> = DAYS_PER_MONTH
@@ -624,4 +863,89 @@ mod tests {
             10
         );
     }
+
+    #[test]
+    fn test_scale_by_f64() {
+        let duration = GameTime::from_hours(1) + GameTime::from_milliseconds(3);
+        assert_eq!(
+            duration * 0.5,
+            GameTime::from_minutes(30) + GameTime::from_milliseconds(2)
+        );
+        assert_eq!(
+            duration * 1.5,
+            GameTime::from_minutes(90) + GameTime::from_milliseconds(5)
+        );
+        assert_eq!(1.5 * duration, duration * 1.5);
+        assert_eq!(
+            duration / 2.0,
+            GameTime::from_minutes(30) + GameTime::from_milliseconds(2)
+        );
+        assert_eq!(
+            GameTime::from_milliseconds(10) / 3.0,
+            GameTime::from_milliseconds(3)
+        );
+        assert_eq!(
+            GameTime::from_milliseconds(11) / 3.0,
+            GameTime::from_milliseconds(4)
+        );
+        assert_eq!(duration * 0.0, GameTime::zero());
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn test_scale_by_non_finite_f64() {
+        let duration = GameTime::from_hours(1);
+        assert_eq!(duration * f64::NAN, duration);
+        assert_eq!(duration * f64::INFINITY, duration);
+        assert_eq!(duration / 0.0, duration);
+    }
+
+    #[test]
+    fn test_display_string() {
+        assert_eq!(GameTime::zero().to_string(), "0ms");
+        assert_eq!(GameTime::from_minutes(90).to_string(), "1h 30m");
+        assert_eq!(GameTime::from_weeks(1).to_string(), "7d");
+        assert_eq!(
+            (GameTime::from_years(1)
+                + GameTime::from_days(2)
+                + GameTime::from_hours(3)
+                + GameTime::from_minutes(4)
+                + GameTime::from_seconds(5)
+                + GameTime::from_milliseconds(6))
+            .to_string(),
+            "1y 2d 3h 4m 5s 6ms"
+        );
+        assert_eq!(GameTime::from_seconds(-61).to_string(), "-1m 1s");
+
+        assert_eq!("2w 12h".parse(), Ok(GameTime::from_hours(348)));
+        assert_eq!(" 30m  1h ".parse(), Ok(GameTime::from_minutes(90)));
+        for malformed in ["", "-", "1", "h", "1x", "1.5h", "1h-30m"] {
+            assert_eq!(malformed.parse::<GameTime>(), Err(()), "{malformed:?}");
+        }
+    }
+
+    #[test]
+    fn test_string_serialisation_round_trip() {
+        for time in [
+            GameTime::zero(),
+            GameTime::from_milliseconds(1),
+            GameTime::from_hours(25) + GameTime::from_milliseconds(999),
+            GameTime::from_years(2344) + GameTime::from_days(364) + GameTime::from_seconds(59),
+            GameTime::from_minutes(-90),
+            GameTime::from_milliseconds(i128::MAX),
+            GameTime::from_milliseconds(i128::MIN + 1),
+        ] {
+            let serialised = serde_json::to_string(&GameTimeString(time)).unwrap();
+            assert_eq!(serialised, format!("{:?}", time.to_string()));
+            let deserialised: GameTimeString = serde_json::from_str(&serialised).unwrap();
+            assert_eq!(deserialised.0, time);
+        }
+
+        assert_eq!(
+            serde_json::to_string(&GameTimeString(GameTime::zero())).unwrap(),
+            "\"0ms\""
+        );
+        assert!(serde_json::from_str::<GameTimeString>("\"1 hour\"").is_err());
+        assert!(serde_json::from_str::<GameTimeString>("3600000").is_err());
+    }
 }
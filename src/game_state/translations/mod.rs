@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Language-tagged variants of the display strings of the game data, given in templates as e.g. `name.de`.
+/// The untagged strings are the default language, which is shown where a translation is missing.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Translations {
+    /// The variants of each default string by language.
+    strings: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Replaces display strings in any language with their variant in a single language.
+pub struct Translator<'translations> {
+    variants: HashMap<&'translations str, &'translations str>,
+}
+
+impl Translations {
+    pub fn add(&mut self, default: &str, language: &str, translation: String) {
+        self.strings
+            .entry(default.to_string())
+            .or_default()
+            .insert(language.to_string(), translation);
+    }
+
+    /// The variant of the default string in the given language, or the default string if it is not translated.
+    /// Returns the default string if no language is given.
+    pub fn get<'string>(
+        &'string self,
+        default: &'string str,
+        language: Option<&str>,
+    ) -> &'string str {
+        language
+            .and_then(|language| self.strings.get(default)?.get(language))
+            .map(String::as_str)
+            .unwrap_or(default)
+    }
+
+    /// Creates a translator to the given language, or back to the default language if none is given.
+    /// Since the translator knows all variants, it also translates strings that were translated before.
+    pub fn translator(&self, language: Option<&str>) -> Translator<'_> {
+        let mut variants = HashMap::new();
+        for (default, translations) in &self.strings {
+            let target = self.get(default, language);
+            variants.insert(default.as_str(), target);
+            for translation in translations.values() {
+                variants.insert(translation.as_str(), target);
+            }
+        }
+        Translator { variants }
+    }
+}
+
+impl Translator<'_> {
+    pub fn translate(&self, string: &mut String) {
+        if let Some(&variant) = self.variants.get(string.as_str()) {
+            if variant != string {
+                *string = variant.to_string();
+            }
+        }
+    }
+
+    pub fn translate_option(&self, string: &mut Option<String>) {
+        if let Some(string) = string {
+            self.translate(string);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Translations;
+    use crate::game_state::tests::create_test_game_state_with_templates;
+    use crate::game_state::GameState;
+    use crate::game_template::parser::error::ParserErrorKind;
+    use crate::game_template::parser::parse_game_template_file;
+    use crate::game_template::GameTemplate;
+
+    const TRANSLATED_TEMPLATE: &str = "
+ACTION translated_work
+name Work
+name.de Arbeiten
+name.fr Travailler
+progressive working
+simple_past worked
+type WORK
+duration 1h
+currency 1
+activation none
+deactivation never
+
+QUEST translated_quest
+title Errand
+title.de Botengang
+description Run an errand.
+activation none
+failure never
+BEGIN
+QUEST_STAGE translated_stage
+task Deliver the letter.
+task.de Liefere den Brief.
+completion action_count(1, translated_work)
+END
+";
+
+    fn action_name(game_state: &GameState) -> &str {
+        &game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "translated_work")
+            .unwrap()
+            .name
+    }
+
+    #[test]
+    fn test_get_falls_back_to_default() {
+        let mut translations = Translations::default();
+        translations.add("Work", "de", "Arbeiten".to_string());
+        assert_eq!(translations.get("Work", Some("de")), "Arbeiten");
+        assert_eq!(translations.get("Work", Some("fr")), "Work");
+        assert_eq!(translations.get("Work", None), "Work");
+        assert_eq!(translations.get("Sleep", Some("de")), "Sleep");
+    }
+
+    #[test]
+    fn test_translation_requires_default() {
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut GameTemplate::default(),
+            "ACTION work\nname.de Arbeiten\n".as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::MissingField { field, .. } if field == "name"
+        ));
+    }
+
+    #[test]
+    fn test_select_language() {
+        let mut game_state = create_test_game_state_with_templates(&[TRANSLATED_TEMPLATE]);
+        assert_eq!(action_name(&game_state), "Work");
+        let quest = game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.id_str == "translated_quest")
+            .unwrap()
+            .id;
+
+        game_state.select_language(Some("de"));
+        assert_eq!(action_name(&game_state), "Arbeiten");
+        assert_eq!(game_state.story.quest(quest).title, "Botengang");
+        assert_eq!(
+            game_state.story.quest(quest).description.as_deref(),
+            Some("Run an errand.")
+        );
+        assert_eq!(
+            game_state.story.quest(quest).active_stage().unwrap().task,
+            "Liefere den Brief."
+        );
+
+        // missing translations fall back to the default language
+        game_state.select_language(Some("fr"));
+        assert_eq!(action_name(&game_state), "Travailler");
+        assert_eq!(game_state.story.quest(quest).title, "Errand");
+
+        game_state.select_language(None);
+        assert_eq!(action_name(&game_state), "Work");
+    }
+}
@@ -1,78 +1,223 @@
+use crate::game_state::calendar::CalendarEventId;
 use crate::game_state::currency::Currency;
 use crate::game_state::inventory::item::ItemId;
 use crate::game_state::player_actions::PlayerActionId;
 use crate::game_state::story::quests::quest_stages::QuestStageId;
 use crate::game_state::story::quests::QuestId;
+use crate::game_state::time::GameTime;
 use crate::game_state::world::events::ExplorationEventId;
 use crate::game_state::world::locations::LocationId;
 use crate::game_state::world::monsters::MonsterId;
 use crate::game_template::IdMaps;
-use event_trigger_action_system::{TriggerAction, TriggerEvent, TriggerIdentifier};
+use event_trigger_action_system::{
+    geq, TriggerAction, TriggerCondition, TriggerEvent, TriggerIdentifier,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub enum GameEvent {
     Action(GameAction),
-    QuestStageActivated { quest_id: String, stage_id: String },
-    QuestStageFailed { quest_id: String, stage_id: String },
-    QuestCompleted { id: String },
-    CurrencyChanged { value: Currency },
-    PlayerLevelChanged { value: u64 },
-    PlayerStrengthChanged { value: u64 },
-    PlayerStaminaChanged { value: u64 },
-    PlayerDexterityChanged { value: u64 },
-    PlayerIntelligenceChanged { value: u64 },
-    PlayerWisdomChanged { value: u64 },
-    PlayerCharismaChanged { value: u64 },
-    ActionStarted { id: String },
-    ActionCompleted { id: String },
-    ExplorationStarted { id: String },
-    ExplorationCompleted { id: String },
-    MonsterKilled { id: String },
-    MonsterFailed { id: String },
-    ExplorationEventCompleted { id: String },
-    ItemCountChanged { id: String, count: usize },
+    QuestStageActivated {
+        quest_id: String,
+        stage_id: String,
+    },
+    QuestStageFailed {
+        quest_id: String,
+        stage_id: String,
+    },
+    QuestCompleted {
+        id: String,
+    },
+    CurrencyChanged {
+        value: Currency,
+    },
+    /// Reference event for conditions on the currency dropping to or below a value.
+    CurrencyLeq {
+        value: Currency,
+    },
+    PlayerLevelChanged {
+        value: u64,
+    },
+    /// Reference event for conditions on the level dropping to or below a value.
+    PlayerLevelLeq {
+        value: u64,
+    },
+    PlayerStrengthChanged {
+        value: u64,
+    },
+    PlayerStaminaChanged {
+        value: u64,
+    },
+    PlayerDexterityChanged {
+        value: u64,
+    },
+    PlayerIntelligenceChanged {
+        value: u64,
+    },
+    PlayerWisdomChanged {
+        value: u64,
+    },
+    PlayerCharismaChanged {
+        value: u64,
+    },
+    ActionStarted {
+        id: String,
+    },
+    ActionCompleted {
+        id: String,
+    },
+    ExplorationStarted {
+        id: String,
+    },
+    ExplorationCompleted {
+        id: String,
+    },
+    MonsterKilled {
+        id: String,
+    },
+    MonsterFailed {
+        id: String,
+    },
+    ExplorationEventCompleted {
+        id: String,
+    },
+    ItemCountChanged {
+        id: String,
+        count: usize,
+    },
+    CalendarEventOccurred {
+        id: String,
+    },
+    /// The player has had waiting selected for the given duration without changing the action.
+    PlayerIdle {
+        duration: GameTime,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub enum GameAction {
-    ActivateQuest { id: String },
-    CompleteQuestStage { quest_id: String, stage_id: String },
-    FailQuest { id: String },
-    ActivateAction { id: String },
-    DeactivateAction { id: String },
-    ActivateLocation { id: String },
-    DeactivateLocation { id: String },
-    ActivateExplorationEvent { id: String },
-    DeactivateExplorationEvent { id: String },
-    ActivateMonster { id: String },
-    DeactivateMonster { id: String },
-    ActivateItem { id: String },
-    DeactivateItem { id: String },
+    ActivateQuest {
+        id: String,
+    },
+    CompleteQuestStage {
+        quest_id: String,
+        stage_id: String,
+    },
+    FailQuest {
+        id: String,
+    },
+    ActivateAction {
+        id: String,
+    },
+    DeactivateAction {
+        id: String,
+    },
+    ActivateLocation {
+        id: String,
+    },
+    DeactivateLocation {
+        id: String,
+    },
+    ActivateExplorationEvent {
+        id: String,
+    },
+    DeactivateExplorationEvent {
+        id: String,
+    },
+    ActivateMonster {
+        id: String,
+    },
+    DeactivateMonster {
+        id: String,
+    },
+    ActivateItem {
+        id: String,
+    },
+    DeactivateItem {
+        id: String,
+    },
+    /// Makes a quest with manual activation available to be accepted by the player.
+    OfferQuest {
+        id: String,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub enum CompiledGameEvent {
     Action(CompiledGameAction),
-    QuestStageActivated { id: QuestStageId },
-    QuestStageFailed { id: QuestStageId },
-    QuestCompleted { id: QuestId },
-    CurrencyChanged { value: Currency },
-    PlayerLevelChanged { value: u64 },
-    PlayerStrengthChanged { value: u64 },
-    PlayerStaminaChanged { value: u64 },
-    PlayerDexterityChanged { value: u64 },
-    PlayerIntelligenceChanged { value: u64 },
-    PlayerWisdomChanged { value: u64 },
-    PlayerCharismaChanged { value: u64 },
-    ActionStarted { id: PlayerActionId },
-    ActionCompleted { id: PlayerActionId },
-    ExplorationStarted { id: LocationId },
-    ExplorationCompleted { id: LocationId },
-    MonsterKilled { id: MonsterId },
-    MonsterFailed { id: MonsterId },
-    ExplorationEventCompleted { id: ExplorationEventId },
-    ItemCountChanged { id: ItemId, count: usize },
+    QuestStageActivated {
+        id: QuestStageId,
+    },
+    QuestStageFailed {
+        id: QuestStageId,
+    },
+    QuestCompleted {
+        id: QuestId,
+    },
+    CurrencyChanged {
+        value: Currency,
+    },
+    /// Reference event for conditions on the currency dropping to or below a value.
+    CurrencyLeq {
+        value: Currency,
+    },
+    PlayerLevelChanged {
+        value: u64,
+    },
+    /// Reference event for conditions on the level dropping to or below a value.
+    PlayerLevelLeq {
+        value: u64,
+    },
+    PlayerStrengthChanged {
+        value: u64,
+    },
+    PlayerStaminaChanged {
+        value: u64,
+    },
+    PlayerDexterityChanged {
+        value: u64,
+    },
+    PlayerIntelligenceChanged {
+        value: u64,
+    },
+    PlayerWisdomChanged {
+        value: u64,
+    },
+    PlayerCharismaChanged {
+        value: u64,
+    },
+    ActionStarted {
+        id: PlayerActionId,
+    },
+    ActionCompleted {
+        id: PlayerActionId,
+    },
+    ExplorationStarted {
+        id: LocationId,
+    },
+    ExplorationCompleted {
+        id: LocationId,
+    },
+    MonsterKilled {
+        id: MonsterId,
+    },
+    MonsterFailed {
+        id: MonsterId,
+    },
+    ExplorationEventCompleted {
+        id: ExplorationEventId,
+    },
+    ItemCountChanged {
+        id: ItemId,
+        count: usize,
+    },
+    CalendarEventOccurred {
+        id: CalendarEventId,
+    },
+    /// The player has had waiting selected for the given duration without changing the action.
+    PlayerIdle {
+        duration: GameTime,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq)]
@@ -97,6 +242,8 @@ pub enum CompiledGameEventIdentifier {
     MonsterFailed { id: MonsterId },
     ExplorationEventCompleted { id: ExplorationEventId },
     ItemCountChanged { id: ItemId },
+    CalendarEventOccurred { id: CalendarEventId },
+    PlayerIdle,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq)]
@@ -114,6 +261,7 @@ pub enum CompiledGameAction {
     DeactivateMonster { id: MonsterId },
     ActivateItem { id: ItemId },
     DeactivateItem { id: ItemId },
+    OfferQuest { id: QuestId },
 }
 
 impl GameEvent {
@@ -134,9 +282,11 @@ impl GameEvent {
                 id: *id_maps.quests.get(&id).unwrap(),
             },
             GameEvent::CurrencyChanged { value } => CompiledGameEvent::CurrencyChanged { value },
+            GameEvent::CurrencyLeq { value } => CompiledGameEvent::CurrencyLeq { value },
             GameEvent::PlayerLevelChanged { value } => {
                 CompiledGameEvent::PlayerLevelChanged { value }
             }
+            GameEvent::PlayerLevelLeq { value } => CompiledGameEvent::PlayerLevelLeq { value },
             GameEvent::PlayerStrengthChanged { value } => {
                 CompiledGameEvent::PlayerStrengthChanged { value }
             }
@@ -182,6 +332,10 @@ impl GameEvent {
                 id: *id_maps.items.get(&id).unwrap(),
                 count,
             },
+            GameEvent::CalendarEventOccurred { id } => CompiledGameEvent::CalendarEventOccurred {
+                id: *id_maps.calendar_events.get(&id).unwrap(),
+            },
+            GameEvent::PlayerIdle { duration } => CompiledGameEvent::PlayerIdle { duration },
         }
     }
 }
@@ -237,6 +391,9 @@ impl GameAction {
             GameAction::DeactivateItem { id } => CompiledGameAction::DeactivateItem {
                 id: *id_maps.items.get(&id).unwrap(),
             },
+            GameAction::OfferQuest { id } => CompiledGameAction::OfferQuest {
+                id: *id_maps.quests.get(&id).unwrap(),
+            },
         }
     }
 }
@@ -259,10 +416,11 @@ impl TriggerEvent for CompiledGameEvent {
             CompiledGameEvent::QuestCompleted { id } => {
                 CompiledGameEventIdentifier::QuestCompleted { id: *id }
             }
-            CompiledGameEvent::CurrencyChanged { .. } => {
+            CompiledGameEvent::CurrencyChanged { .. } | CompiledGameEvent::CurrencyLeq { .. } => {
                 CompiledGameEventIdentifier::CurrencyChanged
             }
-            CompiledGameEvent::PlayerLevelChanged { .. } => {
+            CompiledGameEvent::PlayerLevelChanged { .. }
+            | CompiledGameEvent::PlayerLevelLeq { .. } => {
                 CompiledGameEventIdentifier::PlayerLevelChanged
             }
             CompiledGameEvent::PlayerStrengthChanged { .. } => {
@@ -307,11 +465,19 @@ impl TriggerEvent for CompiledGameEvent {
             CompiledGameEvent::ItemCountChanged { id, .. } => {
                 CompiledGameEventIdentifier::ItemCountChanged { id: *id }
             }
+            CompiledGameEvent::CalendarEventOccurred { id } => {
+                CompiledGameEventIdentifier::CalendarEventOccurred { id: *id }
+            }
+            CompiledGameEvent::PlayerIdle { .. } => CompiledGameEventIdentifier::PlayerIdle,
         }
     }
 
     fn value_geq(&self, other: &Self) -> Option<bool> {
         match (self, other) {
+            (
+                _,
+                CompiledGameEvent::CurrencyLeq { .. } | CompiledGameEvent::PlayerLevelLeq { .. },
+            ) => self.value_leq(other),
             (
                 CompiledGameEvent::CurrencyChanged { value: value_lhs },
                 CompiledGameEvent::CurrencyChanged { value: value_rhs },
@@ -352,12 +518,24 @@ impl TriggerEvent for CompiledGameEvent {
                     count: count_rhs, ..
                 },
             ) => Some(count_lhs >= count_rhs),
+            (
+                CompiledGameEvent::PlayerIdle {
+                    duration: duration_lhs,
+                },
+                CompiledGameEvent::PlayerIdle {
+                    duration: duration_rhs,
+                },
+            ) => Some(duration_lhs >= duration_rhs),
             _ => None,
         }
     }
 
     fn value_geq_progress(&self, other: &Self) -> Option<f64> {
         match (self, other) {
+            (
+                _,
+                CompiledGameEvent::CurrencyLeq { .. } | CompiledGameEvent::PlayerLevelLeq { .. },
+            ) => self.value_leq_progress(other),
             (
                 CompiledGameEvent::CurrencyChanged { value: value_lhs },
                 CompiledGameEvent::CurrencyChanged { value: value_rhs },
@@ -398,11 +576,70 @@ impl TriggerEvent for CompiledGameEvent {
                     count: count_rhs, ..
                 },
             ) => Some(*count_lhs as f64 / *count_rhs as f64),
+            // changing the action resets the idle duration, but the progress must never decrease
+            (CompiledGameEvent::PlayerIdle { .. }, CompiledGameEvent::PlayerIdle { .. }) => {
+                Some(0.0)
+            }
             _ => None,
         }
     }
 }
 
+impl CompiledGameEvent {
+    /// The counterpart of [`TriggerEvent::value_geq`] for the `*Leq` reference events.
+    pub fn value_leq(&self, other: &Self) -> Option<bool> {
+        match (self, other) {
+            (
+                CompiledGameEvent::CurrencyChanged { value: value_lhs },
+                CompiledGameEvent::CurrencyLeq { value: value_rhs },
+            ) => Some(value_lhs <= value_rhs),
+            (
+                CompiledGameEvent::PlayerLevelChanged { value: value_lhs },
+                CompiledGameEvent::PlayerLevelLeq { value: value_rhs },
+            ) => Some(value_lhs <= value_rhs),
+            _ => None,
+        }
+    }
+
+    /// The counterpart of [`TriggerEvent::value_geq_progress`] for the `*Leq` reference events.
+    pub fn value_leq_progress(&self, other: &Self) -> Option<f64> {
+        match (self, other) {
+            (
+                CompiledGameEvent::CurrencyChanged { value: value_lhs },
+                CompiledGameEvent::CurrencyLeq { value: value_rhs },
+            ) => Some(leq_progress(
+                value_lhs.copper() as f64,
+                value_rhs.copper() as f64,
+            )),
+            (
+                CompiledGameEvent::PlayerLevelChanged { value: value_lhs },
+                CompiledGameEvent::PlayerLevelLeq { value: value_rhs },
+            ) => Some(leq_progress(*value_lhs as f64, *value_rhs as f64)),
+            _ => None,
+        }
+    }
+}
+
+fn leq_progress(value: f64, reference: f64) -> f64 {
+    if value <= reference {
+        1.0
+    } else if reference <= 0.0 {
+        0.0
+    } else {
+        reference / value
+    }
+}
+
+/// Creates a condition that is fulfilled once the value of the given event drops to or below the value of the given event.
+/// Only events that have a `*Leq` reference event are supported.
+pub fn leq(event: GameEvent) -> TriggerCondition<GameEvent> {
+    geq(match event {
+        GameEvent::CurrencyChanged { value } => GameEvent::CurrencyLeq { value },
+        GameEvent::PlayerLevelChanged { value } => GameEvent::PlayerLevelLeq { value },
+        event => panic!("Event does not support leq: {event:?}"),
+    })
+}
+
 impl TriggerAction for CompiledGameAction {}
 
 impl TriggerIdentifier for CompiledGameEventIdentifier {}
@@ -412,3 +649,299 @@ impl From<CompiledGameAction> for CompiledGameEvent {
         Self::Action(action)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CompiledGameAction, CompiledGameEvent};
+    use crate::game_state::calendar::CalendarEventId;
+    use crate::game_state::currency::Currency;
+    use crate::game_state::inventory::item::ItemId;
+    use crate::game_state::player_actions::PlayerActionId;
+    use crate::game_state::story::quests::quest_stages::QuestStageId;
+    use crate::game_state::story::quests::QuestId;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::world::events::ExplorationEventId;
+    use crate::game_state::world::locations::LocationId;
+    use crate::game_state::world::monsters::MonsterId;
+    use event_trigger_action_system::{geq, CompiledTriggers, Trigger, TriggerCondition};
+
+    /// The savegame representation of each action, changing this breaks existing savegames.
+    const GAME_ACTION_SNAPSHOT: &[&str] = &[
+        r#"{"ActivateQuest":{"id":1}}"#,
+        r#"{"CompleteQuestStage":{"id":{"quest_id":2,"stage_id":3}}}"#,
+        r#"{"FailQuest":{"id":4}}"#,
+        r#"{"ActivateAction":{"id":5}}"#,
+        r#"{"DeactivateAction":{"id":6}}"#,
+        r#"{"ActivateLocation":{"id":7}}"#,
+        r#"{"DeactivateLocation":{"id":8}}"#,
+        r#"{"ActivateExplorationEvent":{"id":9}}"#,
+        r#"{"DeactivateExplorationEvent":{"id":10}}"#,
+        r#"{"ActivateMonster":{"id":11}}"#,
+        r#"{"DeactivateMonster":{"id":12}}"#,
+        r#"{"ActivateItem":{"id":13}}"#,
+        r#"{"DeactivateItem":{"id":14}}"#,
+        r#"{"OfferQuest":{"id":15}}"#,
+    ];
+
+    /// The savegame representation of each event, changing this breaks existing savegames.
+    const GAME_EVENT_SNAPSHOT: &[&str] = &[
+        r#"{"Action":{"ActivateQuest":{"id":1}}}"#,
+        r#"{"QuestStageActivated":{"id":{"quest_id":2,"stage_id":3}}}"#,
+        r#"{"QuestStageFailed":{"id":{"quest_id":4,"stage_id":5}}}"#,
+        r#"{"QuestCompleted":{"id":6}}"#,
+        r#"{"CurrencyChanged":{"value":{"amount":-7}}}"#,
+        r#"{"CurrencyLeq":{"value":{"amount":80000}}}"#,
+        r#"{"PlayerLevelChanged":{"value":9}}"#,
+        r#"{"PlayerLevelLeq":{"value":10}}"#,
+        r#"{"PlayerStrengthChanged":{"value":11}}"#,
+        r#"{"PlayerStaminaChanged":{"value":12}}"#,
+        r#"{"PlayerDexterityChanged":{"value":13}}"#,
+        r#"{"PlayerIntelligenceChanged":{"value":14}}"#,
+        r#"{"PlayerWisdomChanged":{"value":15}}"#,
+        r#"{"PlayerCharismaChanged":{"value":16}}"#,
+        r#"{"ActionStarted":{"id":17}}"#,
+        r#"{"ActionCompleted":{"id":18}}"#,
+        r#"{"ExplorationStarted":{"id":19}}"#,
+        r#"{"ExplorationCompleted":{"id":20}}"#,
+        r#"{"MonsterKilled":{"id":21}}"#,
+        r#"{"MonsterFailed":{"id":22}}"#,
+        r#"{"ExplorationEventCompleted":{"id":23}}"#,
+        r#"{"ItemCountChanged":{"id":24,"count":25}}"#,
+        r#"{"CalendarEventOccurred":{"id":26}}"#,
+        r#"{"PlayerIdle":{"duration":{"time":27}}}"#,
+    ];
+
+    fn compile_single_trigger(
+        condition: TriggerCondition<CompiledGameEvent>,
+    ) -> CompiledTriggers<CompiledGameEvent> {
+        CompiledTriggers::new(vec![Trigger::new(
+            "test".to_string(),
+            condition,
+            vec![CompiledGameAction::ActivateQuest { id: QuestId(0) }],
+        )
+        .compile(&|event| event, &|action| action)])
+    }
+
+    #[test]
+    fn test_currency_decrease_fulfills_currency_leq() {
+        let mut triggers = compile_single_trigger(geq(CompiledGameEvent::CurrencyLeq {
+            value: Currency::from_copper(10),
+        }));
+
+        triggers.execute_event(&CompiledGameEvent::CurrencyChanged {
+            value: Currency::from_copper(100),
+        });
+        assert!(triggers.consume_action().is_none());
+        triggers.execute_event(&CompiledGameEvent::CurrencyChanged {
+            value: Currency::from_copper(11),
+        });
+        assert!(triggers.consume_action().is_none());
+        triggers.execute_event(&CompiledGameEvent::CurrencyChanged {
+            value: Currency::from_copper(5),
+        });
+        assert_eq!(
+            triggers.consume_action(),
+            Some(CompiledGameAction::ActivateQuest { id: QuestId(0) })
+        );
+    }
+
+    #[test]
+    fn test_level_leq() {
+        let mut triggers =
+            compile_single_trigger(geq(CompiledGameEvent::PlayerLevelLeq { value: 3 }));
+
+        triggers.execute_event(&CompiledGameEvent::PlayerLevelChanged { value: 4 });
+        assert!(triggers.consume_action().is_none());
+        triggers.execute_event(&CompiledGameEvent::PlayerLevelChanged { value: 3 });
+        assert!(triggers.consume_action().is_some());
+    }
+
+    #[test]
+    fn test_value_leq() {
+        let reference = CompiledGameEvent::CurrencyLeq {
+            value: Currency::from_copper(10),
+        };
+        for (copper, expected) in [(-5, true), (0, true), (10, true), (11, false)] {
+            let event = CompiledGameEvent::CurrencyChanged {
+                value: Currency::from_copper(copper),
+            };
+            assert_eq!(event.value_leq(&reference), Some(expected));
+        }
+        assert_eq!(
+            CompiledGameEvent::PlayerLevelChanged { value: 1 }.value_leq(&reference),
+            None
+        );
+    }
+
+    fn all_game_actions() -> Vec<CompiledGameAction> {
+        let actions = vec![
+            CompiledGameAction::ActivateQuest { id: QuestId(1) },
+            CompiledGameAction::CompleteQuestStage {
+                id: QuestStageId {
+                    quest_id: QuestId(2),
+                    stage_id: 3,
+                },
+            },
+            CompiledGameAction::FailQuest { id: QuestId(4) },
+            CompiledGameAction::ActivateAction {
+                id: PlayerActionId::from(5),
+            },
+            CompiledGameAction::DeactivateAction {
+                id: PlayerActionId::from(6),
+            },
+            CompiledGameAction::ActivateLocation { id: LocationId(7) },
+            CompiledGameAction::DeactivateLocation { id: LocationId(8) },
+            CompiledGameAction::ActivateExplorationEvent {
+                id: ExplorationEventId(9),
+            },
+            CompiledGameAction::DeactivateExplorationEvent {
+                id: ExplorationEventId(10),
+            },
+            CompiledGameAction::ActivateMonster { id: MonsterId(11) },
+            CompiledGameAction::DeactivateMonster { id: MonsterId(12) },
+            CompiledGameAction::ActivateItem { id: ItemId(13) },
+            CompiledGameAction::DeactivateItem { id: ItemId(14) },
+            CompiledGameAction::OfferQuest { id: QuestId(15) },
+        ];
+
+        // fails to compile when a variant is added, so that it gets added above as well
+        for action in &actions {
+            match action {
+                CompiledGameAction::ActivateQuest { .. }
+                | CompiledGameAction::CompleteQuestStage { .. }
+                | CompiledGameAction::FailQuest { .. }
+                | CompiledGameAction::ActivateAction { .. }
+                | CompiledGameAction::DeactivateAction { .. }
+                | CompiledGameAction::ActivateLocation { .. }
+                | CompiledGameAction::DeactivateLocation { .. }
+                | CompiledGameAction::ActivateExplorationEvent { .. }
+                | CompiledGameAction::DeactivateExplorationEvent { .. }
+                | CompiledGameAction::ActivateMonster { .. }
+                | CompiledGameAction::DeactivateMonster { .. }
+                | CompiledGameAction::ActivateItem { .. }
+                | CompiledGameAction::DeactivateItem { .. }
+                | CompiledGameAction::OfferQuest { .. } => {}
+            }
+        }
+        actions
+    }
+
+    fn all_game_events() -> Vec<CompiledGameEvent> {
+        let events = vec![
+            CompiledGameEvent::Action(CompiledGameAction::ActivateQuest { id: QuestId(1) }),
+            CompiledGameEvent::QuestStageActivated {
+                id: QuestStageId {
+                    quest_id: QuestId(2),
+                    stage_id: 3,
+                },
+            },
+            CompiledGameEvent::QuestStageFailed {
+                id: QuestStageId {
+                    quest_id: QuestId(4),
+                    stage_id: 5,
+                },
+            },
+            CompiledGameEvent::QuestCompleted { id: QuestId(6) },
+            CompiledGameEvent::CurrencyChanged {
+                value: Currency::from_copper(-7),
+            },
+            CompiledGameEvent::CurrencyLeq {
+                value: Currency::from_gold(8),
+            },
+            CompiledGameEvent::PlayerLevelChanged { value: 9 },
+            CompiledGameEvent::PlayerLevelLeq { value: 10 },
+            CompiledGameEvent::PlayerStrengthChanged { value: 11 },
+            CompiledGameEvent::PlayerStaminaChanged { value: 12 },
+            CompiledGameEvent::PlayerDexterityChanged { value: 13 },
+            CompiledGameEvent::PlayerIntelligenceChanged { value: 14 },
+            CompiledGameEvent::PlayerWisdomChanged { value: 15 },
+            CompiledGameEvent::PlayerCharismaChanged { value: 16 },
+            CompiledGameEvent::ActionStarted {
+                id: PlayerActionId::from(17),
+            },
+            CompiledGameEvent::ActionCompleted {
+                id: PlayerActionId::from(18),
+            },
+            CompiledGameEvent::ExplorationStarted { id: LocationId(19) },
+            CompiledGameEvent::ExplorationCompleted { id: LocationId(20) },
+            CompiledGameEvent::MonsterKilled { id: MonsterId(21) },
+            CompiledGameEvent::MonsterFailed { id: MonsterId(22) },
+            CompiledGameEvent::ExplorationEventCompleted {
+                id: ExplorationEventId(23),
+            },
+            CompiledGameEvent::ItemCountChanged {
+                id: ItemId(24),
+                count: 25,
+            },
+            CompiledGameEvent::CalendarEventOccurred {
+                id: CalendarEventId(26),
+            },
+            CompiledGameEvent::PlayerIdle {
+                duration: GameTime::from_milliseconds(27),
+            },
+        ];
+
+        // fails to compile when a variant is added, so that it gets added above as well
+        for event in &events {
+            match event {
+                CompiledGameEvent::Action(_)
+                | CompiledGameEvent::QuestStageActivated { .. }
+                | CompiledGameEvent::QuestStageFailed { .. }
+                | CompiledGameEvent::QuestCompleted { .. }
+                | CompiledGameEvent::CurrencyChanged { .. }
+                | CompiledGameEvent::CurrencyLeq { .. }
+                | CompiledGameEvent::PlayerLevelChanged { .. }
+                | CompiledGameEvent::PlayerLevelLeq { .. }
+                | CompiledGameEvent::PlayerStrengthChanged { .. }
+                | CompiledGameEvent::PlayerStaminaChanged { .. }
+                | CompiledGameEvent::PlayerDexterityChanged { .. }
+                | CompiledGameEvent::PlayerIntelligenceChanged { .. }
+                | CompiledGameEvent::PlayerWisdomChanged { .. }
+                | CompiledGameEvent::PlayerCharismaChanged { .. }
+                | CompiledGameEvent::ActionStarted { .. }
+                | CompiledGameEvent::ActionCompleted { .. }
+                | CompiledGameEvent::ExplorationStarted { .. }
+                | CompiledGameEvent::ExplorationCompleted { .. }
+                | CompiledGameEvent::MonsterKilled { .. }
+                | CompiledGameEvent::MonsterFailed { .. }
+                | CompiledGameEvent::ExplorationEventCompleted { .. }
+                | CompiledGameEvent::ItemCountChanged { .. }
+                | CompiledGameEvent::CalendarEventOccurred { .. }
+                | CompiledGameEvent::PlayerIdle { .. } => {}
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_game_action_serde_round_trip() {
+        let actions = all_game_actions();
+        let serialized: Vec<_> = actions
+            .iter()
+            .map(|action| serde_json::to_string(action).unwrap())
+            .collect();
+        assert_eq!(serialized, GAME_ACTION_SNAPSHOT);
+
+        let deserialized: Vec<CompiledGameAction> = serialized
+            .iter()
+            .map(|action| serde_json::from_str(action).unwrap())
+            .collect();
+        assert_eq!(deserialized, actions);
+    }
+
+    #[test]
+    fn test_game_event_serde_round_trip() {
+        let events = all_game_events();
+        let serialized: Vec<_> = events
+            .iter()
+            .map(|event| serde_json::to_string(event).unwrap())
+            .collect();
+        assert_eq!(serialized, GAME_EVENT_SNAPSHOT);
+
+        let deserialized: Vec<CompiledGameEvent> = serialized
+            .iter()
+            .map(|event| serde_json::from_str(event).unwrap())
+            .collect();
+        assert_eq!(deserialized, events);
+    }
+}
@@ -1,20 +1,18 @@
+use crate::game_state::buffs::Buff;
 use crate::game_state::character::{Character, CharacterAttributeProgress};
 use crate::game_state::currency::Currency;
+use crate::game_state::difficulty::Difficulty;
 use crate::game_state::inventory::item::{CompiledExpectedItemCount, ExpectedItemCount};
 use crate::game_state::player_actions::{
     PlayerActionInProgress, PlayerActionInProgressKind, PlayerActionInProgressSource,
 };
 use crate::game_state::time::GameTime;
 use crate::game_state::world::locations::LocationId;
-use crate::game_state::world::monsters::{CompiledMonster, MonsterId};
-use crate::game_state::{MAX_COMBAT_DURATION, MIN_COMBAT_DURATION};
+use crate::game_state::world::monsters::{spawn_pack_combat, CompiledMonster, MonsterId};
 use crate::game_template::parser::WeightedIdentifier;
 use crate::game_template::IdMaps;
-use crate::utils::text::a_or_an;
 use event_trigger_action_system::TriggerHandle;
-use rand::distributions::Distribution;
 use rand::Rng;
-use rand_distr::{Gamma, Normal};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -24,6 +22,8 @@ pub struct ExplorationEvent {
     pub attribute_progress: CharacterAttributeProgress,
     pub currency_reward: Currency,
     pub items: Vec<ExpectedItemCount>,
+    pub buff: Option<Buff>,
+    pub then: Option<String>,
     pub activation_condition: String,
     pub deactivation_condition: String,
 }
@@ -37,6 +37,12 @@ pub struct CompiledExplorationEvent {
     pub attribute_progress: CharacterAttributeProgress,
     pub currency_reward: Currency,
     pub items: Vec<CompiledExpectedItemCount>,
+    /// Granted to the character when the event is completed successfully.
+    #[serde(default)]
+    pub buff: Option<Buff>,
+    /// The event that is explored next after this event is completed successfully.
+    #[serde(default)]
+    pub then: Option<ExplorationEventId>,
     pub activation_condition: TriggerHandle,
     pub deactivation_condition: TriggerHandle,
 }
@@ -51,6 +57,10 @@ pub enum ExplorationEventKind {
     Monster {
         monster: String,
     },
+    /// Multiple monsters that are fought one after the other in a single combat.
+    Pack {
+        monsters: Vec<String>,
+    },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -63,6 +73,9 @@ pub enum CompiledExplorationEventKind {
     Monster {
         monster: MonsterId,
     },
+    Pack {
+        monsters: Vec<MonsterId>,
+    },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -111,6 +124,13 @@ impl ExplorationEvent {
                 .into_iter()
                 .map(|item| item.compile(id_maps))
                 .collect(),
+            buff: self.buff,
+            then: self.then.map(|then| {
+                *id_maps
+                    .exploration_events
+                    .get(&then)
+                    .unwrap_or_else(|| panic!("Did not find exploration event {then:?} in id_map"))
+            }),
             activation_condition: *id_maps.triggers.get(&self.activation_condition).unwrap(),
             deactivation_condition: *id_maps.triggers.get(&self.deactivation_condition).unwrap(),
         }
@@ -118,6 +138,7 @@ impl ExplorationEvent {
 }
 
 impl CompiledExplorationEvent {
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn(
         &self,
         rng: &mut impl Rng,
@@ -126,53 +147,42 @@ impl CompiledExplorationEvent {
         character: &Character,
         monsters: &[CompiledMonster],
         location: LocationId,
+        difficulty: Difficulty,
+        damage_multiplier: f64,
     ) -> PlayerActionInProgress {
         let items = self.items.iter().map(|item| item.spawn(rng)).collect();
 
         match &self.kind {
             CompiledExplorationEventKind::Monster {
                 monster: monster_id,
-            } => {
-                let monster = &monsters[monster_id.0];
-
-                let damage = character.damage_output();
-                let hitpoint_jitter = Normal::new(1.0, 0.1).unwrap().sample(rng);
-                let duration = GameTime::from_milliseconds(
-                    (monster.hitpoints * hitpoint_jitter / damage * 60_000.0).round() as i128,
-                )
-                .min(MAX_COMBAT_DURATION);
-                let success = duration < MAX_COMBAT_DURATION;
-
-                let currency_jitter = Gamma::new(2.0, 0.25).unwrap().sample(rng) + 0.5;
-                let currency_reward = if success {
-                    Currency::from_copper_f64(
-                        self.currency_reward.copper() as f64 * currency_jitter,
-                    )
-                } else {
-                    Currency::zero()
-                };
-
-                let attribute_progress = if success {
-                    character.evaluate_combat_attribute_progress(duration)
-                } else {
-                    CharacterAttributeProgress::zero()
-                };
-
-                let a_or_an = a_or_an(&monster.name);
-                PlayerActionInProgress {
-                    verb_progressive: format!("fighting {a_or_an} {}", monster.name.to_lowercase()),
-                    verb_simple_past: format!("fought {a_or_an} {}", monster.name.to_lowercase()),
-                    source: PlayerActionInProgressSource::Exploration(self.id),
-                    kind: PlayerActionInProgressKind::Combat(*monster_id),
-                    start: start_time,
-                    end: start_time + duration.max(MIN_COMBAT_DURATION),
-                    attribute_progress,
-                    currency_reward,
-                    items,
-                    location,
-                    success,
-                }
-            }
+            } => monsters[monster_id.0].spawn_combat(
+                rng,
+                start_time,
+                character,
+                self.currency_reward,
+                items,
+                PlayerActionInProgressSource::Exploration(self.id),
+                location,
+                difficulty,
+                damage_multiplier,
+            ),
+            CompiledExplorationEventKind::Pack {
+                monsters: monster_ids,
+            } => spawn_pack_combat(
+                &monster_ids
+                    .iter()
+                    .map(|monster_id| &monsters[monster_id.0])
+                    .collect::<Vec<_>>(),
+                rng,
+                start_time,
+                character,
+                self.currency_reward,
+                items,
+                PlayerActionInProgressSource::Exploration(self.id),
+                location,
+                difficulty,
+                damage_multiplier,
+            ),
             CompiledExplorationEventKind::Normal {
                 verb_progressive,
                 verb_simple_past,
@@ -189,6 +199,8 @@ impl CompiledExplorationEvent {
                 items,
                 location,
                 success: true,
+                combat_hitpoints: None,
+                pack_kills: Vec::new(),
             },
         }
     }
@@ -209,6 +221,12 @@ impl ExplorationEventKind {
             ExplorationEventKind::Monster { monster } => CompiledExplorationEventKind::Monster {
                 monster: *id_maps.monsters.get(&monster).unwrap(),
             },
+            ExplorationEventKind::Pack { monsters } => CompiledExplorationEventKind::Pack {
+                monsters: monsters
+                    .iter()
+                    .map(|monster| *id_maps.monsters.get(monster).unwrap())
+                    .collect(),
+            },
         }
     }
 }
@@ -255,3 +273,134 @@ impl From<WeightedIdentifier> for WeightedExplorationEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::event_log::GameEventKind;
+    use crate::game_state::player_actions::{PlayerActionInProgressSource, ACTION_EXPLORE};
+    use crate::game_state::tests::create_test_game_state_with_templates;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::world::MAX_EXPLORATION_EVENT_CHAIN_LENGTH;
+    use crate::game_state::GAME_TIME_PER_MILLISECOND;
+
+    const CHAIN_TEMPLATE: &str = "
+LOCATION chain_cave
+name Cave
+events (1.0, chain_entrance), (0.0, chain_tunnel)
+activation none
+deactivation never
+
+EXPLORATION_EVENT chain_entrance
+name Entrance
+progressive entering the cave
+simple_past entered the cave
+then chain_tunnel
+activation none
+deactivation never
+
+EXPLORATION_EVENT chain_tunnel
+name Tunnel
+progressive crawling through a tunnel
+simple_past crawled through a tunnel
+activation none
+deactivation never
+
+LOCATION cycle_maze
+name Maze
+events (1.0, cycle_entrance)
+activation none
+deactivation never
+
+EXPLORATION_EVENT cycle_entrance
+name Maze entrance
+progressive entering the maze
+simple_past entered the maze
+then cycle_left
+activation none
+deactivation never
+
+EXPLORATION_EVENT cycle_left
+name Left turn
+progressive turning left
+simple_past turned left
+then cycle_right
+activation none
+deactivation never
+
+EXPLORATION_EVENT cycle_right
+name Right turn
+progressive turning right
+simple_past turned right
+then cycle_left
+activation none
+deactivation never
+";
+
+    /// Explores the given location for two days and returns the explored events in order.
+    fn explored_events(location_id_str: &str) -> Vec<String> {
+        let mut game_state = create_test_game_state_with_templates(&[CHAIN_TEMPLATE]);
+        let location = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == location_id_str)
+            .unwrap()
+            .id;
+        assert!(game_state.world.select_location(location));
+        game_state.actions.selected_action = ACTION_EXPLORE;
+        for _ in 0..48 {
+            game_state.update_offline(
+                (GameTime::from_hours(1).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                    as i64,
+            );
+        }
+
+        let mut events: Vec<_> = game_state
+            .log
+            .iter_rev()
+            .filter_map(|event| match &event.kind {
+                GameEventKind::Action(action) => match action.source {
+                    PlayerActionInProgressSource::Exploration(event_id) => {
+                        Some(game_state.world.event(event_id).id_str.clone())
+                    }
+                    PlayerActionInProgressSource::Action(_) => None,
+                },
+            })
+            .collect();
+        events.reverse();
+        events
+    }
+
+    #[test]
+    fn test_follow_up_event_is_explored_next() {
+        let events = explored_events("chain_cave");
+        assert!(events.len() > 10, "{events:?}");
+        for (index, event) in events.iter().enumerate() {
+            let expected = if index % 2 == 0 {
+                "chain_entrance"
+            } else {
+                "chain_tunnel"
+            };
+            assert_eq!(event, expected, "{events:?}");
+        }
+    }
+
+    #[test]
+    fn test_cyclic_chain_is_broken() {
+        let events = explored_events("cycle_maze");
+        let entrances: Vec<_> = events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| *event == "cycle_entrance")
+            .map(|(index, _)| index)
+            .collect();
+        assert!(entrances.len() > 1, "{events:?}");
+        assert_eq!(entrances[0], 0);
+        for window in entrances.windows(2) {
+            assert_eq!(
+                window[1] - window[0],
+                MAX_EXPLORATION_EVENT_CHAIN_LENGTH + 1,
+                "{events:?}"
+            );
+        }
+    }
+}
@@ -3,12 +3,18 @@ use crate::game_state::world::events::{
     CompiledExplorationEvent, CompiledWeightedExplorationEvent, ExplorationEventId,
     WeightedExplorationEvent,
 };
+use crate::game_state::world::monsters::{
+    CompiledMonster, CompiledWeightedMonster, MonsterId, WeightedMonster,
+};
 use crate::game_template::IdMaps;
+use crate::utils::weighted::{weighted_choice, WeightedChoiceError};
 use event_trigger_action_system::TriggerHandle;
-use rand::distributions::WeightedError;
-use rand::seq::SliceRandom;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// The default probability that exploring a location with both events and monsters results in a combat.
+pub const DEFAULT_COMBAT_RATIO: f64 = 0.5;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Location {
@@ -16,6 +22,9 @@ pub struct Location {
     pub name: String,
     pub url: Option<String>,
     pub events: Vec<WeightedExplorationEvent>,
+    pub monsters: Vec<WeightedMonster>,
+    pub combat_ratio: f64,
+    pub tie_break: TieBreak,
     pub activation_condition: String,
     pub deactivation_condition: String,
 }
@@ -28,8 +37,39 @@ pub struct CompiledLocation {
     pub name: String,
     pub url: Option<String>,
     pub events: Vec<CompiledWeightedExplorationEvent>,
+    /// Monsters that are fought directly when exploring, independently of the exploration events.
+    #[serde(default)]
+    pub monsters: Vec<CompiledWeightedMonster>,
+    /// The probability that exploring results in a combat from the monsters if there are also active events.
+    #[serde(default = "default_combat_ratio")]
+    pub combat_ratio: f64,
+    #[serde(default)]
+    pub tie_break: TieBreak,
+    /// How often each event was chosen, used to break ties by source order.
+    #[serde(default)]
+    pub event_choice_counts: Vec<u64>,
     pub activation_condition: TriggerHandle,
     pub deactivation_condition: TriggerHandle,
+    /// Describes what unlocks the location, if it is not available from the start.
+    #[serde(default)]
+    pub unlock_description: Option<String>,
+}
+
+/// How to choose between exploration events of exactly equal weight.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum TieBreak {
+    /// Leave the choice to the random number generator.
+    #[default]
+    Random,
+    /// Choose the events in a round-robin in the order they are listed in the location.
+    SourceOrder,
+}
+
+/// The outcome of exploring a location, see [`CompiledLocation::choose_exploration`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExplorationChoice {
+    Event(ExplorationEventId),
+    Combat(MonsterId),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -57,36 +97,124 @@ impl Location {
             state: LocationState::Inactive,
             name: self.name,
             url: self.url,
+            event_choice_counts: vec![0; self.events.len()],
             events: self
                 .events
                 .into_iter()
                 .map(|event| event.compile(id_maps))
                 .collect(),
+            monsters: self
+                .monsters
+                .into_iter()
+                .map(|monster| monster.compile(id_maps))
+                .collect(),
+            combat_ratio: self.combat_ratio,
+            tie_break: self.tie_break,
             activation_condition: *id_maps.triggers.get(&self.activation_condition).unwrap(),
             deactivation_condition: *id_maps.triggers.get(&self.deactivation_condition).unwrap(),
+            unlock_description: None,
         }
     }
 }
 
 impl CompiledLocation {
+    /// Chooses between a combat against one of the active monsters and one of the active exploration events.
+    /// If only one of them is available, it is always chosen, otherwise a combat is chosen with [`CompiledLocation::combat_ratio`].
+    pub fn choose_exploration(
+        &mut self,
+        rng: &mut impl Rng,
+        exploration_events: &[CompiledExplorationEvent],
+        monsters: &[CompiledMonster],
+    ) -> Option<ExplorationChoice> {
+        let active_monsters: Vec<_> = self
+            .monsters
+            .iter()
+            .filter(|weighted_monster| {
+                weighted_monster.weight > 0.0 && monsters[weighted_monster.id.0].state.is_active()
+            })
+            .map(|weighted_monster| (weighted_monster.weight, weighted_monster.id))
+            .collect();
+        if active_monsters.is_empty() {
+            return self
+                .explore(rng, exploration_events)
+                .map(ExplorationChoice::Event);
+        }
+
+        let has_active_events = self
+            .events
+            .iter()
+            .any(|weighted_event| exploration_events[weighted_event.id.0].state.is_active());
+        if has_active_events && !rng.gen_bool(self.combat_ratio) {
+            return self
+                .explore(rng, exploration_events)
+                .map(ExplorationChoice::Event);
+        }
+
+        let monster = weighted_choice(&active_monsters, rng)
+            .unwrap_or_else(|error| panic!("Error: {:?}", error));
+        Some(ExplorationChoice::Combat(*monster))
+    }
+
     pub fn explore(
-        &self,
+        &mut self,
         rng: &mut impl Rng,
         exploration_events: &[CompiledExplorationEvent],
     ) -> Option<ExplorationEventId> {
         assert!(self.state.is_active());
+        if self.event_choice_counts.len() != self.events.len() {
+            self.event_choice_counts = vec![0; self.events.len()];
+        }
+
         let active_events: Vec<_> = self
             .events
             .iter()
-            .filter(|weighted_event| exploration_events[weighted_event.id.0].state.is_active())
+            .enumerate()
+            .filter(|(_, weighted_event)| exploration_events[weighted_event.id.0].state.is_active())
+            .map(|(index, active_event)| (active_event.weight, index))
             .collect();
-        match active_events.choose_weighted(rng, |active_event| active_event.weight) {
-            Ok(event) => Some(event.id),
+        let chosen_index = match self.tie_break {
+            TieBreak::Random => weighted_choice(&active_events, rng).copied(),
+            TieBreak::SourceOrder => {
+                let mut weight_groups: Vec<(f64, Vec<usize>)> = Vec::new();
+                for (weight, index) in &active_events {
+                    if let Some((_, indices)) = weight_groups
+                        .iter_mut()
+                        .find(|(group_weight, _)| group_weight == weight)
+                    {
+                        indices.push(*index);
+                    } else {
+                        weight_groups.push((*weight, vec![*index]));
+                    }
+                }
+
+                let weight_group = if weight_groups.len() == 1 && weight_groups[0].0 > 0.0 {
+                    Ok(&weight_groups[0].1)
+                } else {
+                    let weight_groups: Vec<_> = weight_groups
+                        .iter()
+                        .map(|(weight, indices)| (weight * indices.len() as f64, indices))
+                        .collect();
+                    weighted_choice(&weight_groups, rng).copied()
+                };
+                weight_group.map(|indices| {
+                    *indices
+                        .iter()
+                        .min_by_key(|index| self.event_choice_counts[**index])
+                        .unwrap()
+                })
+            }
+        };
+
+        match chosen_index {
+            Ok(index) => {
+                self.event_choice_counts[index] += 1;
+                Some(self.events[index].id)
+            }
             Err(error) => match error {
-                WeightedError::NoItem => None,
-                WeightedError::InvalidWeight
-                | WeightedError::TooMany
-                | WeightedError::AllWeightsZero => panic!("Error: {:?}", error),
+                WeightedChoiceError::NoItems => None,
+                WeightedChoiceError::IllegalWeight(_) | WeightedChoiceError::AllWeightsZero => {
+                    panic!("Error: {:?}", error)
+                }
             },
         }
     }
@@ -126,8 +254,215 @@ impl LocationState {
     }
 }
 
+fn default_combat_ratio() -> f64 {
+    DEFAULT_COMBAT_RATIO
+}
+
+impl FromStr for TieBreak {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "RANDOM" => TieBreak::Random,
+            "SOURCE_ORDER" => TieBreak::SourceOrder,
+            _ => return Err(()),
+        })
+    }
+}
+
 impl From<usize> for LocationId {
     fn from(value: usize) -> Self {
         Self(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CompiledLocation, ExplorationChoice, LocationId, LocationState, TieBreak,
+        DEFAULT_COMBAT_RATIO,
+    };
+    use crate::game_state::expression::Expression;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::world::events::{
+        CompiledExplorationEvent, CompiledExplorationEventKind, CompiledWeightedExplorationEvent,
+        ExplorationEventId, ExplorationEventState,
+    };
+    use crate::game_state::world::monsters::{
+        CompiledMonster, CompiledWeightedMonster, MonsterId, MonsterState,
+    };
+    use event_trigger_action_system::TriggerHandle;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro512PlusPlus;
+
+    fn create_exploration_events(event_count: usize) -> Vec<CompiledExplorationEvent> {
+        (0..event_count)
+            .map(|index| CompiledExplorationEvent {
+                id: ExplorationEventId(index),
+                id_str: format!("event_{index}"),
+                state: ExplorationEventState::Active {
+                    activation_time: GameTime::zero(),
+                },
+                kind: CompiledExplorationEventKind::Normal {
+                    name: format!("Event {index}"),
+                    verb_progressive: "exploring".to_string(),
+                    verb_simple_past: "explored".to_string(),
+                },
+                attribute_progress: Default::default(),
+                currency_reward: Default::default(),
+                items: Vec::new(),
+                buff: None,
+                then: None,
+                activation_condition: TriggerHandle::from(0),
+                deactivation_condition: TriggerHandle::from(0),
+            })
+            .collect()
+    }
+
+    fn create_location(weights: &[f64], tie_break: TieBreak) -> CompiledLocation {
+        CompiledLocation {
+            id: LocationId(0),
+            id_str: "location".to_string(),
+            state: LocationState::Active {
+                activation_time: GameTime::zero(),
+            },
+            name: "Location".to_string(),
+            url: None,
+            events: weights
+                .iter()
+                .enumerate()
+                .map(|(index, weight)| CompiledWeightedExplorationEvent {
+                    id: ExplorationEventId(index),
+                    weight: *weight,
+                })
+                .collect(),
+            monsters: Vec::new(),
+            combat_ratio: DEFAULT_COMBAT_RATIO,
+            tie_break,
+            event_choice_counts: vec![0; weights.len()],
+            activation_condition: TriggerHandle::from(0),
+            deactivation_condition: TriggerHandle::from(0),
+            unlock_description: None,
+        }
+    }
+
+    fn explore_ids(location: &mut CompiledLocation, seed: u64, count: usize) -> Vec<usize> {
+        let exploration_events = create_exploration_events(location.events.len());
+        let mut rng = Xoshiro512PlusPlus::seed_from_u64(seed);
+        (0..count)
+            .map(|_| location.explore(&mut rng, &exploration_events).unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn test_source_order_tie_break_is_round_robin() {
+        let mut location = create_location(&[1.0, 1.0, 1.0], TieBreak::SourceOrder);
+        assert_eq!(explore_ids(&mut location, 0, 7), vec![0, 1, 2, 0, 1, 2, 0]);
+
+        let mut location = create_location(&[1.0, 1.0, 1.0], TieBreak::SourceOrder);
+        assert_eq!(explore_ids(&mut location, 1, 7), vec![0, 1, 2, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_source_order_tie_break_within_equal_weights() {
+        let mut location = create_location(&[2.0, 1.0, 2.0], TieBreak::SourceOrder);
+        let ids = explore_ids(&mut location, 0, 100);
+        let heavy_ids: Vec<_> = ids.iter().copied().filter(|id| *id != 1).collect();
+        assert!(heavy_ids.len() > 50);
+        for (index, id) in heavy_ids.into_iter().enumerate() {
+            assert_eq!(id, [0, 2][index % 2]);
+        }
+    }
+
+    #[test]
+    fn test_random_tie_break_follows_seeded_rng() {
+        let mut location = create_location(&[1.0, 1.0, 1.0], TieBreak::Random);
+        let ids = explore_ids(&mut location, 0, 30);
+        let mut location = create_location(&[1.0, 1.0, 1.0], TieBreak::Random);
+        assert_eq!(explore_ids(&mut location, 0, 30), ids);
+
+        let round_robin: Vec<_> = (0..30).map(|index| index % 3).collect();
+        assert_ne!(ids, round_robin);
+        assert!((0..3).all(|id| ids.contains(&id)));
+    }
+
+    fn create_monsters(monster_count: usize) -> Vec<CompiledMonster> {
+        (0..monster_count)
+            .map(|index| CompiledMonster {
+                id: MonsterId(index),
+                id_str: format!("monster_{index}"),
+                state: MonsterState::Active {
+                    activation_time: GameTime::zero(),
+                },
+                name: format!("Monster {index}"),
+                hitpoints: Expression::Constant(10.0),
+                enrage: None,
+                activation_condition: TriggerHandle::from(0),
+                deactivation_condition: TriggerHandle::from(0),
+            })
+            .collect()
+    }
+
+    fn choose_explorations(
+        location: &mut CompiledLocation,
+        monster_weights: &[f64],
+        seed: u64,
+        count: usize,
+    ) -> Vec<ExplorationChoice> {
+        location.monsters = monster_weights
+            .iter()
+            .enumerate()
+            .map(|(index, weight)| CompiledWeightedMonster {
+                id: MonsterId(index),
+                weight: *weight,
+            })
+            .collect();
+        let exploration_events = create_exploration_events(location.events.len());
+        let monsters = create_monsters(monster_weights.len());
+        let mut rng = Xoshiro512PlusPlus::seed_from_u64(seed);
+        (0..count)
+            .map(|_| {
+                location
+                    .choose_exploration(&mut rng, &exploration_events, &monsters)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_monster_only_location_always_rolls_combats() {
+        let mut location = create_location(&[], TieBreak::Random);
+        let choices = choose_explorations(&mut location, &[1.0, 3.0], 0, 100);
+        assert!(choices
+            .iter()
+            .all(|choice| matches!(choice, ExplorationChoice::Combat(_))));
+        assert!(choices.contains(&ExplorationChoice::Combat(MonsterId(0))));
+        assert!(choices.contains(&ExplorationChoice::Combat(MonsterId(1))));
+    }
+
+    #[test]
+    fn test_mixed_location_respects_combat_ratio() {
+        for (combat_ratio, expected_combats) in [(0.0, 0), (0.25, 259), (0.8, 804), (1.0, 1000)] {
+            let mut location = create_location(&[1.0, 1.0], TieBreak::Random);
+            location.combat_ratio = combat_ratio;
+            let choices = choose_explorations(&mut location, &[1.0], 0, 1000);
+            let combats = choices
+                .iter()
+                .filter(|choice| matches!(choice, ExplorationChoice::Combat(_)))
+                .count();
+            assert_eq!(combats, expected_combats, "{combat_ratio}");
+        }
+    }
+
+    #[test]
+    fn test_location_without_monsters_only_rolls_events() {
+        let mut location = create_location(&[1.0, 1.0, 1.0], TieBreak::SourceOrder);
+        let choices = choose_explorations(&mut location, &[], 0, 4);
+        assert_eq!(
+            choices,
+            [0, 1, 2, 0]
+                .map(|id| ExplorationChoice::Event(ExplorationEventId(id)))
+                .to_vec()
+        );
+    }
+}
@@ -1,12 +1,21 @@
 use crate::game_state::character::Character;
-use crate::game_state::player_actions::PlayerActionInProgress;
+use crate::game_state::currency::Currency;
+use crate::game_state::difficulty::Difficulty;
+use crate::game_state::player_actions::{
+    PlayerActionInProgress, PlayerActionInProgressSource, ACTION_EXPLORE,
+};
 use crate::game_state::time::GameTime;
+use crate::game_state::translations::Translator;
 use crate::game_state::triggers::CompiledGameEvent;
 use crate::game_state::world::events::{
-    CompiledExplorationEvent, ExplorationEventId, ExplorationEventState,
+    CompiledExplorationEvent, CompiledExplorationEventKind, ExplorationEventId,
+    ExplorationEventState,
+};
+use crate::game_state::world::locations::{
+    CompiledLocation, ExplorationChoice, LocationId, LocationState,
 };
-use crate::game_state::world::locations::{CompiledLocation, LocationId, LocationState};
 use crate::game_state::world::monsters::{CompiledMonster, MonsterId, MonsterState};
+use log::warn;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -16,6 +25,9 @@ pub mod events;
 pub mod locations;
 pub mod monsters;
 
+/// At most this many follow-up events are explored in a row, such that cyclic chains of events end.
+pub const MAX_EXPLORATION_EVENT_CHAIN_LENGTH: usize = 8;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct World {
     pub selected_location: LocationId,
@@ -23,6 +35,12 @@ pub struct World {
     events: Vec<CompiledExplorationEvent>,
     monsters: Vec<CompiledMonster>,
     active_locations: HashSet<LocationId>,
+    /// The follow-up of the last completed exploration event, explored next instead of a random event.
+    #[serde(default)]
+    follow_up_event: Option<ExplorationEventId>,
+    /// The number of follow-up events explored in a row.
+    #[serde(default)]
+    chain_length: usize,
 }
 
 impl World {
@@ -38,6 +56,8 @@ impl World {
             events,
             monsters,
             active_locations: Default::default(),
+            follow_up_event: None,
+            chain_length: 0,
         }
     }
 
@@ -53,12 +73,53 @@ impl World {
         self.location(self.selected_location)
     }
 
+    /// Selects the given location for exploration.
+    /// Returns false and keeps the current selection if the location is not active.
+    pub fn select_location(&mut self, location_id: LocationId) -> bool {
+        if self.active_locations.contains(&location_id) {
+            if self.selected_location != location_id {
+                self.break_exploration_chain();
+            }
+            self.selected_location = location_id;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// If the selected location is not active, select the active location that was activated first.
+    /// Keeps the current selection if there are no active locations.
+    pub fn ensure_active_selected_location(&mut self) {
+        if self.active_locations.contains(&self.selected_location) {
+            return;
+        }
+
+        if let Some(location) = self
+            .active_locations()
+            .min_by_key(|location| (location.state.activation_time(), location.id))
+        {
+            warn!(
+                "Selected location {} is not active, selecting {} instead",
+                self.selected_location().id_str,
+                location.id_str
+            );
+            self.selected_location = location.id;
+        }
+    }
+
     pub fn active_locations(&self) -> impl '_ + Iterator<Item = &'_ CompiledLocation> {
         self.active_locations
             .iter()
             .map(|location_id| self.location(*location_id))
     }
 
+    /// Lists the locations that were never active but can still be unlocked.
+    pub fn locked_locations(&self) -> impl '_ + Iterator<Item = &'_ CompiledLocation> {
+        self.locations.iter().filter(|location| {
+            location.state.is_inactive() && location.unlock_description.is_some()
+        })
+    }
+
     pub fn event(&self, event_id: ExplorationEventId) -> &CompiledExplorationEvent {
         &self.events[event_id.0]
     }
@@ -67,7 +128,6 @@ impl World {
         &mut self.events[event_id.0]
     }
 
-    #[allow(dead_code)]
     pub fn monster(&self, monster_id: MonsterId) -> &CompiledMonster {
         &self.monsters[monster_id.0]
     }
@@ -76,6 +136,20 @@ impl World {
         &mut self.monsters[monster_id.0]
     }
 
+    pub fn translate(&mut self, translator: &Translator) {
+        for location in &mut self.locations {
+            translator.translate(&mut location.name);
+        }
+        for event in &mut self.events {
+            if let CompiledExplorationEventKind::Normal { name, .. } = &mut event.kind {
+                translator.translate(name);
+            }
+        }
+        for monster in &mut self.monsters {
+            translator.translate(&mut monster.name);
+        }
+    }
+
     pub fn activate_location(
         &mut self,
         location_id: LocationId,
@@ -179,35 +253,144 @@ impl World {
         iter::empty()
     }
 
+    /// Makes the follow-up of the completed exploration event the next event to explore,
+    /// unless [`MAX_EXPLORATION_EVENT_CHAIN_LENGTH`] follow-ups were explored in a row already.
+    pub fn complete_exploration_event(&mut self, event_id: ExplorationEventId) {
+        self.follow_up_event = self
+            .event(event_id)
+            .then
+            .filter(|_| self.chain_length < MAX_EXPLORATION_EVENT_CHAIN_LENGTH);
+    }
+
+    /// Lets the next exploration choose a random event, even if the last event has a follow-up.
+    pub fn break_exploration_chain(&mut self) {
+        self.follow_up_event = None;
+        self.chain_length = 0;
+    }
+
     pub fn explore(
-        &self,
+        &mut self,
         rng: &mut impl Rng,
         start_time: GameTime,
         default_duration: GameTime,
         character: &Character,
+        difficulty: Difficulty,
+        damage_multiplier: f64,
     ) -> Option<PlayerActionInProgress> {
+        let follow_up_event = self
+            .follow_up_event
+            .take()
+            .filter(|event_id| self.event(*event_id).state.is_active());
+        let choice = if let Some(event_id) = follow_up_event {
+            self.chain_length += 1;
+            ExplorationChoice::Event(event_id)
+        } else {
+            self.chain_length = 0;
+            let location = &mut self.locations[self.selected_location.0];
+            location.choose_exploration(rng, &self.events, &self.monsters)?
+        };
         let location = self.selected_location();
-        let event_id = location.explore(rng, &self.events)?;
-        let event = self.event(event_id);
-        Some(event.spawn(
-            rng,
-            start_time,
-            default_duration,
-            character,
-            self.monsters.as_slice(),
-            location.id,
-        ))
+        Some(match choice {
+            ExplorationChoice::Event(event_id) => self.event(event_id).spawn(
+                rng,
+                start_time,
+                default_duration,
+                character,
+                self.monsters.as_slice(),
+                location.id,
+                difficulty,
+                damage_multiplier,
+            ),
+            ExplorationChoice::Combat(monster_id) => self.monster(monster_id).spawn_combat(
+                rng,
+                start_time,
+                character,
+                Currency::zero(),
+                Vec::new(),
+                PlayerActionInProgressSource::Action(ACTION_EXPLORE),
+                location.id,
+                difficulty,
+                damage_multiplier,
+            ),
+        })
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-pub struct WeightedMonster {
-    monster: String,
-    weight: f64,
-}
-
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct WeightedMonsterModifier {
     monster_modifier: String,
     weight: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::World;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::world::locations::{
+        CompiledLocation, LocationId, LocationState, DEFAULT_COMBAT_RATIO,
+    };
+    use event_trigger_action_system::TriggerHandle;
+
+    fn create_world(location_count: usize) -> World {
+        World::new(
+            LocationId(0),
+            (0..location_count)
+                .map(|index| CompiledLocation {
+                    id: LocationId(index),
+                    id_str: format!("location_{index}"),
+                    state: LocationState::Inactive,
+                    name: format!("Location {index}"),
+                    url: None,
+                    events: Vec::new(),
+                    monsters: Vec::new(),
+                    combat_ratio: DEFAULT_COMBAT_RATIO,
+                    tie_break: Default::default(),
+                    event_choice_counts: Vec::new(),
+                    activation_condition: TriggerHandle::from(0),
+                    deactivation_condition: TriggerHandle::from(0),
+                    unlock_description: None,
+                })
+                .collect(),
+            Vec::new(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_select_location() {
+        let mut world = create_world(3);
+        world
+            .activate_location(LocationId(0), GameTime::zero())
+            .for_each(drop);
+        world
+            .activate_location(LocationId(1), GameTime::zero())
+            .for_each(drop);
+
+        assert!(!world.select_location(LocationId(2)));
+        assert_eq!(world.selected_location, LocationId(0));
+        assert!(world.select_location(LocationId(1)));
+        assert_eq!(world.selected_location, LocationId(1));
+
+        world
+            .deactivate_location(LocationId(0), GameTime::from_seconds(1))
+            .for_each(drop);
+        assert!(!world.select_location(LocationId(0)));
+        assert_eq!(world.selected_location, LocationId(1));
+    }
+
+    #[test]
+    fn test_ensure_active_selected_location() {
+        let mut world = create_world(3);
+        world
+            .activate_location(LocationId(2), GameTime::from_seconds(2))
+            .for_each(drop);
+        world
+            .activate_location(LocationId(1), GameTime::from_seconds(1))
+            .for_each(drop);
+
+        world.ensure_active_selected_location();
+        assert_eq!(world.selected_location, LocationId(1));
+        world.ensure_active_selected_location();
+        assert_eq!(world.selected_location, LocationId(1));
+    }
+}
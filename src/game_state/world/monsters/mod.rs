@@ -1,13 +1,34 @@
+use crate::game_state::character::{Character, CharacterAttributeProgress};
+use crate::game_state::combat_log::{CombatHit, CombatSide, HIT_INTERVAL};
+use crate::game_state::currency::Currency;
+use crate::game_state::difficulty::Difficulty;
+use crate::game_state::expression::Expression;
+use crate::game_state::inventory::item::ItemCount;
+use crate::game_state::player_actions::{
+    PlayerActionInProgress, PlayerActionInProgressKind, PlayerActionInProgressSource,
+};
 use crate::game_state::time::GameTime;
+use crate::game_state::world::locations::LocationId;
+use crate::game_state::{MAX_COMBAT_DURATION, MIN_COMBAT_DURATION};
+use crate::game_template::parser::WeightedIdentifier;
 use crate::game_template::IdMaps;
+use crate::utils::text::a_or_an;
 use event_trigger_action_system::TriggerHandle;
+use rand::distributions::Distribution;
+use rand::Rng;
+use rand_distr::{Gamma, Normal};
 use serde::{Deserialize, Serialize};
+use std::iter;
+
+/// The fraction of the attribute progress of a full fight that is awarded when the character flees.
+pub const FLED_COMBAT_PROGRESS_FACTOR: f64 = 0.5;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Monster {
     pub id_str: String,
     pub name: String,
-    pub hitpoints: f64,
+    pub hitpoints: Expression,
+    pub enrage: Option<f64>,
     pub activation_condition: String,
     pub deactivation_condition: String,
 }
@@ -18,7 +39,10 @@ pub struct CompiledMonster {
     pub id_str: String,
     pub state: MonsterState,
     pub name: String,
-    pub hitpoints: f64,
+    pub hitpoints: Expression,
+    /// If set, the monster deals damage to the character that grows by this much per minute of combat.
+    #[serde(default)]
+    pub enrage: Option<f64>,
     pub activation_condition: TriggerHandle,
     pub deactivation_condition: TriggerHandle,
 }
@@ -40,6 +64,41 @@ pub enum MonsterState {
 )]
 pub struct MonsterId(pub usize);
 
+/// The hitpoints of both sides of a combat, from which the remaining hitpoints at any point of the fight are estimated.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CombatHitpoints {
+    pub monster: f64,
+    pub character: f64,
+    /// The damage per minute dealt by the character.
+    pub damage: f64,
+    /// The growth of the damage per minute dealt by the monster, per minute of combat.
+    pub enrage: f64,
+    /// Determines the outcome of the individual hits of the fight.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// A monster of a pack that was killed before the last fight of the combat against the pack.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PackKill {
+    pub monster: MonsterId,
+    /// The end of the fight against this monster, which is the start of the fight against the next one.
+    pub end: GameTime,
+    pub combat_hitpoints: CombatHitpoints,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WeightedMonster {
+    pub monster: String,
+    pub weight: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CompiledWeightedMonster {
+    pub id: MonsterId,
+    pub weight: f64,
+}
+
 impl Monster {
     pub fn compile(self, id_maps: &IdMaps) -> CompiledMonster {
         CompiledMonster {
@@ -48,12 +107,258 @@ impl Monster {
             state: MonsterState::Inactive,
             name: self.name,
             hitpoints: self.hitpoints,
+            enrage: self.enrage,
             activation_condition: *id_maps.triggers.get(&self.activation_condition).unwrap(),
             deactivation_condition: *id_maps.triggers.get(&self.deactivation_condition).unwrap(),
         }
     }
 }
 
+impl CompiledMonster {
+    /// The hitpoints of this monster when fought by the given character.
+    pub fn hitpoints(&self, character: &Character, difficulty: Difficulty) -> f64 {
+        self.hitpoints.evaluate(character) * difficulty.monster_hitpoint_multiplier()
+    }
+
+    /// Spawns a fight against this monster that lasts until the character has dealt damage equal to its hitpoints.
+    /// If it would take longer than [`MAX_COMBAT_DURATION`], the character flees after fighting that long,
+    /// and gets only [`FLED_COMBAT_PROGRESS_FACTOR`] of the attribute progress and no other rewards.
+    /// An enraging monster additionally overwhelms the character once its growing damage exceeds the character's hitpoints,
+    /// which ends the fight in the same way.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn_combat(
+        &self,
+        rng: &mut impl Rng,
+        start_time: GameTime,
+        character: &Character,
+        currency_reward: Currency,
+        items: Vec<ItemCount>,
+        source: PlayerActionInProgressSource,
+        location: LocationId,
+        difficulty: Difficulty,
+        damage_multiplier: f64,
+    ) -> PlayerActionInProgress {
+        let hitpoint_jitter = Normal::new(1.0, 0.1).unwrap().sample(rng);
+        let combat_hitpoints = CombatHitpoints {
+            monster: self.hitpoints(character, difficulty) * hitpoint_jitter,
+            character: character.hitpoints(),
+            damage: character.damage_output() * damage_multiplier,
+            enrage: self.enrage.unwrap_or(0.0),
+            seed: start_time.milliseconds() as u64,
+        };
+        let kill_duration = combat_hitpoints.kill_duration();
+        let survival_duration = combat_hitpoints.capped_survival_duration();
+        let duration = kill_duration.min(survival_duration);
+        let success = kill_duration < survival_duration;
+
+        let currency_jitter = Gamma::new(2.0, 0.25).unwrap().sample(rng) + 0.5;
+        let currency_reward = if success {
+            Currency::from_copper_f64(currency_reward.copper() as f64 * currency_jitter)
+        } else {
+            Currency::zero()
+        };
+
+        let attribute_progress = if success {
+            character.evaluate_combat_attribute_progress(duration)
+        } else {
+            character.evaluate_combat_attribute_progress(duration) * FLED_COMBAT_PROGRESS_FACTOR
+        };
+
+        let a_or_an = a_or_an(&self.name);
+        let verb_simple_past = if success {
+            format!("fought {a_or_an} {}", self.name.to_lowercase())
+        } else {
+            format!("fled from {a_or_an} {}", self.name.to_lowercase())
+        };
+        PlayerActionInProgress {
+            verb_progressive: format!("fighting {a_or_an} {}", self.name.to_lowercase()),
+            verb_simple_past,
+            source,
+            kind: PlayerActionInProgressKind::Combat(self.id),
+            start: start_time,
+            end: start_time + duration.max(MIN_COMBAT_DURATION),
+            attribute_progress,
+            currency_reward,
+            items,
+            location,
+            success,
+            combat_hitpoints: Some(combat_hitpoints),
+            pack_kills: Vec::new(),
+        }
+    }
+}
+
+/// Spawns a combat against the monsters of a pack, which are fought one after the other like in [`CompiledMonster::spawn_combat`].
+/// The currency reward is granted for each killed monster, and the attribute progress of all fights is accumulated.
+/// If the character flees from a monster, the combat ends there, but the rewards for the monsters killed before are kept.
+/// The items are granted only if the whole pack is killed.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_pack_combat(
+    pack: &[&CompiledMonster],
+    rng: &mut impl Rng,
+    start_time: GameTime,
+    character: &Character,
+    currency_reward: Currency,
+    items: Vec<ItemCount>,
+    source: PlayerActionInProgressSource,
+    location: LocationId,
+    difficulty: Difficulty,
+    damage_multiplier: f64,
+) -> PlayerActionInProgress {
+    assert!(!pack.is_empty(), "Spawned a combat against an empty pack");
+    let mut pack_kills = Vec::new();
+    let mut attribute_progress = CharacterAttributeProgress::zero();
+    let mut total_currency_reward = Currency::zero();
+    let mut fight_start = start_time;
+
+    for (index, monster) in pack.iter().enumerate() {
+        let fight = monster.spawn_combat(
+            rng,
+            fight_start,
+            character,
+            currency_reward,
+            Vec::new(),
+            source.clone(),
+            location,
+            difficulty,
+            damage_multiplier,
+        );
+        attribute_progress += fight.attribute_progress;
+        total_currency_reward += fight.currency_reward;
+        if fight.success && index + 1 < pack.len() {
+            pack_kills.push(PackKill {
+                monster: monster.id,
+                end: fight.end,
+                combat_hitpoints: fight.combat_hitpoints.unwrap(),
+            });
+            fight_start = fight.end;
+            continue;
+        }
+
+        let names: Vec<_> = pack
+            .iter()
+            .map(|monster| indefinite_name(monster))
+            .collect();
+        let verb_simple_past = if fight.success {
+            format!("fought {}", join_names(&names))
+        } else if index == 0 {
+            format!("fled from {}", names[0])
+        } else {
+            format!(
+                "fought {} and fled from {}",
+                join_names(&names[..index]),
+                names[index]
+            )
+        };
+        return PlayerActionInProgress {
+            verb_progressive: format!("fighting {}", join_names(&names)),
+            verb_simple_past,
+            start: start_time,
+            attribute_progress,
+            currency_reward: total_currency_reward,
+            items: if fight.success { items } else { Vec::new() },
+            pack_kills,
+            ..fight
+        };
+    }
+    unreachable!()
+}
+
+/// The lowercase name of the monster with its indefinite article, e.g. "an ogre".
+fn indefinite_name(monster: &CompiledMonster) -> String {
+    format!("{} {}", a_or_an(&monster.name), monster.name.to_lowercase())
+}
+
+/// Joins the names into an enumeration, e.g. "a rat, a goblin and an ogre".
+fn join_names(names: &[String]) -> String {
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {last}", rest.join(", ")),
+        None => String::new(),
+    }
+}
+
+impl CombatHitpoints {
+    /// The duration after which the character has dealt damage equal to the hitpoints of the monster.
+    pub fn kill_duration(&self) -> GameTime {
+        GameTime::from_milliseconds((self.monster / self.damage * 60_000.0).round() as i128)
+    }
+
+    /// The duration after which the damage of an enraged monster exceeds the hitpoints of the character,
+    /// or `None` if the monster does not enrage.
+    pub fn survival_duration(&self) -> Option<GameTime> {
+        // the damage taken after t minutes is enrage * t^2 / 2
+        (self.enrage > 0.0)
+            .then(|| GameTime::from_minutes_f64((2.0 * self.character / self.enrage).sqrt()))
+    }
+
+    /// The duration after which the character stops fighting if the monster is still alive.
+    pub fn capped_survival_duration(&self) -> GameTime {
+        self.survival_duration()
+            .map_or(MAX_COMBAT_DURATION, |survival_duration| {
+                survival_duration.min(MAX_COMBAT_DURATION)
+            })
+    }
+
+    /// The duration until either side of the fight is down or the character flees.
+    pub fn fight_duration(&self) -> GameTime {
+        self.kill_duration().min(self.capped_survival_duration())
+    }
+
+    /// The number of exchanges of blows until the given duration of the fight, one per [`HIT_INTERVAL`].
+    pub fn exchange_count(&self, elapsed: GameTime) -> u64 {
+        let elapsed = elapsed.min(self.fight_duration()).max(GameTime::zero());
+        (elapsed.milliseconds() / HIT_INTERVAL.milliseconds()) as u64
+    }
+
+    /// The hits of the exchange of blows with the given index, starting from zero.
+    /// The character strikes first, and the monster strikes back only if it enrages.
+    /// Misses and critical hits balance out, so the hits deal the estimated damage on average.
+    pub fn exchange(&self, index: u64) -> impl Iterator<Item = CombatHit> {
+        let elapsed =
+            GameTime::from_milliseconds(HIT_INTERVAL.milliseconds() * (index + 1) as i128);
+        let interval_minutes = HIT_INTERVAL.milliseconds() as f64 / 60_000.0;
+        let character_hit = CombatHit::roll(
+            self.seed,
+            2 * index,
+            elapsed,
+            CombatSide::Character,
+            self.damage * interval_minutes,
+        );
+        let monster_hit = (self.enrage > 0.0).then(|| {
+            let minutes = elapsed.milliseconds() as f64 / 60_000.0;
+            CombatHit::roll(
+                self.seed,
+                2 * index + 1,
+                elapsed,
+                CombatSide::Monster,
+                self.enrage * minutes * interval_minutes,
+            )
+        });
+        iter::once(character_hit).chain(monster_hit)
+    }
+
+    /// The estimated hitpoints of the monster after fighting for the given duration.
+    pub fn remaining_monster_hitpoints(&self, elapsed: GameTime) -> f64 {
+        (self.monster - self.damage * elapsed.milliseconds() as f64 / 60_000.0).max(0.0)
+    }
+
+    /// The estimated hitpoints of the character after fighting for the given duration.
+    pub fn remaining_character_hitpoints(&self, elapsed: GameTime) -> f64 {
+        let minutes = elapsed.milliseconds() as f64 / 60_000.0;
+        (self.character - self.enrage * minutes * minutes / 2.0).max(0.0)
+    }
+}
+
+impl WeightedMonster {
+    pub fn compile(self, id_maps: &IdMaps) -> CompiledWeightedMonster {
+        CompiledWeightedMonster {
+            id: *id_maps.monsters.get(&self.monster).unwrap(),
+            weight: self.weight,
+        }
+    }
+}
+
 #[allow(dead_code)]
 impl MonsterState {
     pub fn is_inactive(&self) -> bool {
@@ -74,3 +379,368 @@ impl From<usize> for MonsterId {
         Self(value)
     }
 }
+
+impl From<WeightedIdentifier> for WeightedMonster {
+    fn from(weighted_identifier: WeightedIdentifier) -> Self {
+        Self {
+            monster: weighted_identifier.identifier,
+            weight: weighted_identifier.weight,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CombatHitpoints, FLED_COMBAT_PROGRESS_FACTOR};
+    use crate::game_state::character::CharacterAttributeProgress;
+    use crate::game_state::currency::Currency;
+    use crate::game_state::difficulty::Difficulty;
+    use crate::game_state::event_log::GameEventKind;
+    use crate::game_state::player_actions::{
+        CombatPolicy, PlayerActionInProgress, PlayerActionInProgressKind,
+        PlayerActionInProgressSource, ACTION_EXPLORE,
+    };
+    use crate::game_state::tests::create_test_game_state_with_templates;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::triggers::CompiledGameEvent;
+    use crate::game_state::world::events::CompiledExplorationEventKind;
+    use crate::game_state::{GameState, GAME_TIME_PER_MILLISECOND, MAX_COMBAT_DURATION};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro512PlusPlus;
+
+    const FLEE_TEMPLATE: &str = "
+LOCATION flee_arena
+name Flee arena
+monster_pool (1, flee_ogre)
+activation none
+deactivation never
+
+MONSTER flee_ogre
+name Ogre
+hitpoints 2000
+activation none
+deactivation never
+
+ACTION flee_recover
+name Recover
+progressive recovering from the fight
+simple_past recovered from the fight
+type TRAIN
+duration 1h
+strength 1.0
+currency 0
+activation game_event_count(1, monster_failed(flee_ogre))
+deactivation never
+";
+
+    const ENRAGE_TEMPLATE: &str = "
+LOCATION enrage_arena
+name Enrage arena
+monster_pool (1, enrage_rat), (1, enrage_troll)
+activation none
+deactivation never
+
+MONSTER enrage_rat
+name Rat
+hitpoints 5
+enrage 0.1
+activation none
+deactivation never
+
+MONSTER enrage_troll
+name Troll
+hitpoints 60
+enrage 0.1
+activation none
+deactivation never
+";
+
+    const PACK_TEMPLATE: &str = "
+LOCATION pack_arena
+name Pack arena
+events (1, fight_pack)
+activation none
+deactivation never
+
+EXPLORATION_EVENT fight_pack
+monster pack_rat, pack_ogre
+currency 10
+activation none
+deactivation never
+
+MONSTER pack_rat
+name Rat
+hitpoints 5
+activation none
+deactivation never
+
+MONSTER pack_ogre
+name Ogre
+hitpoints 2000
+activation none
+deactivation never
+";
+
+    fn fight_enraging(
+        game_state: &GameState,
+        id_str: &str,
+        enrage: bool,
+    ) -> PlayerActionInProgress {
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "enrage_arena")
+            .unwrap();
+        let mut monster = arena
+            .monsters
+            .iter()
+            .map(|monster| game_state.world.monster(monster.id))
+            .find(|monster| monster.id_str == id_str)
+            .unwrap()
+            .clone();
+        if !enrage {
+            monster.enrage = None;
+        }
+        monster.spawn_combat(
+            &mut Xoshiro512PlusPlus::seed_from_u64(0),
+            GameTime::zero(),
+            &game_state.character,
+            Currency::from_copper(10),
+            Vec::new(),
+            PlayerActionInProgressSource::Action(ACTION_EXPLORE),
+            arena.id,
+            Difficulty::Normal,
+            1.0,
+        )
+    }
+
+    fn fight_ogre(game_state: &GameState) -> PlayerActionInProgress {
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "flee_arena")
+            .unwrap();
+        game_state.world.monster(arena.monsters[0].id).spawn_combat(
+            &mut Xoshiro512PlusPlus::seed_from_u64(0),
+            GameTime::zero(),
+            &game_state.character,
+            Currency::from_copper(10),
+            Vec::new(),
+            PlayerActionInProgressSource::Action(ACTION_EXPLORE),
+            arena.id,
+            Difficulty::Normal,
+            1.0,
+        )
+    }
+
+    #[test]
+    fn test_underpowered_character_flees() {
+        let game_state = create_test_game_state_with_templates(&[FLEE_TEMPLATE]);
+        let combat = fight_ogre(&game_state);
+        assert!(!combat.success);
+        assert_eq!(combat.length(), MAX_COMBAT_DURATION);
+        assert_eq!(combat.currency_reward, Currency::zero());
+        assert_eq!(combat.verb_simple_past, "fled from an ogre");
+
+        let full_progress = game_state
+            .character
+            .evaluate_combat_attribute_progress(MAX_COMBAT_DURATION);
+        assert!(combat.attribute_progress.sum() > 0);
+        assert_eq!(
+            combat.attribute_progress,
+            full_progress * FLED_COMBAT_PROGRESS_FACTOR
+        );
+    }
+
+    #[test]
+    fn test_strong_character_wins() {
+        let mut game_state = create_test_game_state_with_templates(&[FLEE_TEMPLATE]);
+        let _ = game_state
+            .character
+            .add_attribute_progress(CharacterAttributeProgress::new(
+                10_000_000_000,
+                10_000_000_000,
+                10_000_000_000,
+                10_000_000_000,
+                10_000_000_000,
+                10_000_000_000,
+            ))
+            .count();
+        let combat = fight_ogre(&game_state);
+        assert!(combat.success);
+        assert!(combat.length() < MAX_COMBAT_DURATION);
+        assert!(combat.currency_reward > Currency::zero());
+        assert_eq!(combat.verb_simple_past, "fought an ogre");
+    }
+
+    #[test]
+    fn test_fleeing_fires_monster_failed_triggers() {
+        let mut game_state = create_test_game_state_with_templates(&[FLEE_TEMPLATE]);
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "flee_arena")
+            .unwrap()
+            .id;
+        assert!(game_state.world.select_location(arena));
+        game_state.actions.selected_action = ACTION_EXPLORE;
+        let recover_available = |game_state: &GameState| {
+            game_state
+                .actions
+                .list_choosable()
+                .any(|action| action.id_str == "flee_recover")
+        };
+        assert!(!recover_available(&game_state));
+        let attribute_progress = game_state.character.total_attribute_progress();
+
+        game_state.update_offline(
+            (GameTime::from_hours(12).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                as i64,
+        );
+        assert!(recover_available(&game_state));
+        assert!(game_state.character.total_attribute_progress() > attribute_progress);
+    }
+
+    #[test]
+    fn test_long_fight_against_enraging_monster_fails() {
+        let game_state = create_test_game_state_with_templates(&[ENRAGE_TEMPLATE]);
+
+        let calm_troll = fight_enraging(&game_state, "enrage_troll", false);
+        assert!(calm_troll.success);
+
+        let enraged_troll = fight_enraging(&game_state, "enrage_troll", true);
+        assert!(!enraged_troll.success);
+        assert_eq!(enraged_troll.currency_reward, Currency::zero());
+        let combat_hitpoints = enraged_troll.combat_hitpoints.unwrap();
+        assert_eq!(
+            enraged_troll.length(),
+            combat_hitpoints.survival_duration().unwrap()
+        );
+        assert!(enraged_troll.length() < calm_troll.length());
+        assert!(combat_hitpoints.remaining_monster_hitpoints(enraged_troll.length()) > 0.0);
+        assert!(combat_hitpoints.remaining_character_hitpoints(enraged_troll.length()) < 1e-6);
+
+        let enraged_rat = fight_enraging(&game_state, "enrage_rat", true);
+        assert!(enraged_rat.success);
+        assert!(enraged_rat.currency_reward > Currency::zero());
+    }
+
+    #[test]
+    fn test_enrage_is_deterministic() {
+        let game_state = create_test_game_state_with_templates(&[ENRAGE_TEMPLATE]);
+        let first = fight_enraging(&game_state, "enrage_troll", true);
+        let second = fight_enraging(&game_state, "enrage_troll", true);
+        assert_eq!(first.end, second.end);
+        assert_eq!(first.combat_hitpoints, second.combat_hitpoints);
+    }
+
+    #[test]
+    fn test_pack_keeps_rewards_of_monsters_killed_before_fleeing() {
+        let mut game_state = create_test_game_state_with_templates(&[PACK_TEMPLATE]);
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "pack_arena")
+            .unwrap()
+            .id;
+        assert!(game_state.world.select_location(arena));
+        game_state.actions.selected_action = ACTION_EXPLORE;
+        game_state.set_combat_policy(CombatPolicy::FightOne);
+
+        let mut events = Vec::new();
+        let combat = loop {
+            events.extend(game_state.update_with_events(
+                (GameTime::from_minutes(1).milliseconds()
+                    / GAME_TIME_PER_MILLISECOND.milliseconds()) as i64,
+            ));
+            if let Some(combat) = game_state
+                .log
+                .iter_rev()
+                .map(|event| match &event.kind {
+                    GameEventKind::Action(action) => action,
+                })
+                .find(|action| matches!(action.kind, PlayerActionInProgressKind::Combat(_)))
+            {
+                break combat.clone();
+            }
+        };
+
+        let (rat, ogre) = match &game_state
+            .world
+            .event(game_state.world.location(arena).events[0].id)
+            .kind
+        {
+            CompiledExplorationEventKind::Pack { monsters } => (monsters[0], monsters[1]),
+            kind => panic!("Expected a pack, but got {kind:?}"),
+        };
+        assert!(!combat.success);
+        assert_eq!(combat.pack_kills.len(), 1);
+        assert_eq!(combat.pack_kills[0].monster, rat);
+        assert!(combat.currency_reward > Currency::zero());
+        assert_eq!(
+            combat.verb_simple_past,
+            "fought a rat and fled from an ogre"
+        );
+        assert_eq!(
+            combat
+                .current_fight(combat.start)
+                .map(|(monster, _, _)| monster),
+            Some(rat)
+        );
+        assert_eq!(
+            combat
+                .current_fight(combat.end)
+                .map(|(monster, start, _)| (monster, start)),
+            Some((ogre, combat.pack_kills[0].end))
+        );
+
+        assert!(events.contains(&CompiledGameEvent::MonsterKilled { id: rat }));
+        assert!(!events.contains(&CompiledGameEvent::MonsterKilled { id: ogre }));
+        // the currency of the rat is granted together with the failure against the ogre
+        let failed = events
+            .iter()
+            .position(|event| *event == CompiledGameEvent::MonsterFailed { id: ogre })
+            .unwrap();
+        assert!(matches!(
+            events[failed - 1],
+            CompiledGameEvent::CurrencyChanged { .. }
+        ));
+    }
+
+    #[test]
+    fn test_remaining_hitpoints_estimate() {
+        let combat_hitpoints = CombatHitpoints {
+            monster: 100.0,
+            character: 20.0,
+            damage: 2.0,
+            enrage: 0.1,
+            seed: 0,
+        };
+        assert_eq!(
+            combat_hitpoints.survival_duration(),
+            Some(GameTime::from_minutes(20))
+        );
+        assert_eq!(
+            combat_hitpoints.remaining_monster_hitpoints(GameTime::from_minutes(10)),
+            80.0
+        );
+        assert_eq!(
+            combat_hitpoints.remaining_character_hitpoints(GameTime::from_minutes(10)),
+            15.0
+        );
+        assert_eq!(
+            combat_hitpoints.remaining_monster_hitpoints(GameTime::from_hours(2)),
+            0.0
+        );
+
+        let calm = CombatHitpoints {
+            enrage: 0.0,
+            ..combat_hitpoints
+        };
+        assert_eq!(calm.survival_duration(), None);
+        assert_eq!(
+            calm.remaining_character_hitpoints(GameTime::from_hours(2)),
+            20.0
+        );
+    }
+}
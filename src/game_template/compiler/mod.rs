@@ -1,23 +1,97 @@
-use crate::game_template::parser::error::ParserError;
-use crate::game_template::parser::parse_game_template_file;
-use crate::game_template::GameTemplate;
+use crate::game_state::player_actions::PlayerActionType;
+use crate::game_state::triggers::{GameAction, GameEvent};
+use crate::game_state::{GameState, GameStateInitialisation};
+use crate::game_template::parser::character_iterator::CharacterCoordinateRange;
+use crate::game_template::parser::error::{ParserError, ParserErrorKind};
+use crate::game_template::parser::parse_game_template_file_recovering;
+use crate::game_template::parser::tokenizer::{
+    KeyValueKind, SectionTokenKind, DEFAULT_MAX_NESTING_DEPTH,
+};
+use crate::game_template::{CompiledGameTemplate, GameTemplate, IdNamespace};
+use crate::io::{compress, decode_compressed_eager, LoadError, DEFAULT_COMPRESSION_LEVEL};
 use async_recursion::async_recursion;
 use async_std::fs::File;
 use async_std::io::{BufReader, WriteExt};
 use async_std::path::{Path, PathBuf};
 use async_std::stream::StreamExt;
-use clap::Args;
-use flate2::write::GzEncoder;
-use flate2::Compression;
+use clap::{Args, ValueEnum};
+use enum_iterator::all;
+use event_trigger_action_system::TriggerCondition;
 use log::{debug, info, warn};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
 use std::ffi::OsStr;
-use std::io::Write;
+use std::fmt::{Display, Formatter};
 
 #[derive(Debug)]
 pub enum CompilerError {
     Parser(ParserError),
+    /// All errors of a template file, see [`parse_game_template_file_recovering`].
+    ParserErrors(Vec<ParserError>),
     Pot(pot::Error),
     Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The compiled game data cannot be loaded or does not start a valid game.
+    InvalidGameData(LoadError),
+    /// The stage follows a stage that can never be completed.
+    UnreachableQuestStage {
+        quest_id_str: String,
+        stage_id_str: String,
+    },
+    /// The final stage of the quest can never be completed.
+    UncompletableQuest {
+        quest_id_str: String,
+    },
+    /// Two sections of the same kind have the same identifier.
+    DuplicateId {
+        kind: IdNamespace,
+        id: String,
+        first: CharacterCoordinateRange,
+        second: CharacterCoordinateRange,
+    },
+}
+
+/// A problem in the game data that does not prevent compilation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CompilerWarning {
+    MissingStaticAsset {
+        id_str: String,
+        url: String,
+        path: PathBuf,
+    },
+    /// The trigger requires the quest to be both completed and failed, so it can never be fulfilled.
+    ContradictoryCondition {
+        trigger_id_str: String,
+        quest_id_str: String,
+    },
+}
+
+/// A compiler error or warning in a machine-readable form for editor tooling.
+#[derive(Debug, Clone, Serialize, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    /// The name of the error variant, e.g. `IllegalKeyword`.
+    pub kind: String,
+    pub severity: Severity,
+    pub start_line: Option<usize>,
+    pub start_column: Option<usize>,
+    /// The line and column after the erroneous text.
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum ErrorFormat {
+    Human,
+    /// Print each error and warning as a JSON [`Diagnostic`] on its own line to stdout.
+    Json,
 }
 
 #[derive(Debug, Args)]
@@ -27,18 +101,149 @@ pub struct CompileConfiguration {
 
     #[clap(long, default_value = "data.bin.gz")]
     compiled_game_data: PathBuf,
+
+    /// The gzip compression level of the compiled game data from 0 to 9, trading speed for size.
+    #[clap(
+        long,
+        default_value_t = DEFAULT_COMPRESSION_LEVEL,
+        value_parser = clap::value_parser!(u32).range(0..=9)
+    )]
+    compression_level: u32,
+
+    /// Warn about `url`s that do not exist in the static directory.
+    #[clap(long)]
+    check_static_assets: bool,
+
+    #[clap(long, default_value = "static")]
+    static_prefix_directory: PathBuf,
+
+    /// Print statistics about the compiled game data.
+    #[clap(long)]
+    stats: bool,
+
+    #[clap(long, value_enum, default_value = "human")]
+    error_format: ErrorFormat,
+
+    /// Reject template files with sections nested in more `BEGIN` blocks than this.
+    #[clap(long, default_value_t = DEFAULT_MAX_NESTING_DEPTH)]
+    max_nesting_depth: usize,
+
+    /// Skip sections with unknown keywords with a warning instead of rejecting them.
+    /// Allows to partially compile templates written for newer versions.
+    #[clap(long)]
+    lenient: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyConfiguration {
+    #[clap(long, default_value = "data.bin.gz")]
+    compiled_game_data: PathBuf,
+}
+
+/// The size of a compiled game template.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CompilerStatistics {
+    pub actions: usize,
+    pub quests: usize,
+    pub quest_stages: usize,
+    pub locations: usize,
+    pub monsters: usize,
+    pub items: usize,
+    pub exploration_events: usize,
+    pub calendar_events: usize,
+    pub destinies: usize,
+    pub triggers: usize,
+    pub uncompressed_size: usize,
+    pub compressed_size: usize,
 }
 
 pub async fn compile(configuration: &CompileConfiguration) -> Result<(), CompilerError> {
+    let result = compile_game_data(configuration).await;
+    if let (Err(error), ErrorFormat::Json) = (&result, configuration.error_format) {
+        for diagnostic in error.diagnostics() {
+            println!("{}", serde_json::to_string(&diagnostic)?);
+        }
+    }
+    result
+}
+
+/// Checks that the compiled game data can be loaded and starts a valid game, printing OK or the first failure.
+pub async fn verify(configuration: &VerifyConfiguration) -> Result<(), CompilerError> {
+    info!(
+        "Verifying {}...",
+        configuration.compiled_game_data.to_string_lossy()
+    );
+    let compressed = async_std::fs::read(&configuration.compiled_game_data).await?;
+    match verify_game_data(&compressed) {
+        Ok(()) => {
+            println!("OK");
+            Ok(())
+        }
+        Err(error) => {
+            println!("{error}");
+            Err(CompilerError::InvalidGameData(error))
+        }
+    }
+}
+
+/// Decompresses and deserialises the compiled game data like the game does,
+/// and validates the invariants of a new game started from it.
+fn verify_game_data(compressed: &[u8]) -> Result<(), LoadError> {
+    let game_template: CompiledGameTemplate = decode_compressed_eager(compressed)?;
+    let game_state = GameState::new(
+        game_template,
+        GameStateInitialisation {
+            savegame_file: "verify_savegame.json".into(),
+            name: "Verifier".to_string(),
+            pronoun: "they".to_string(),
+            race: Default::default(),
+            difficulty: Default::default(),
+            destiny_roll: false,
+        },
+    );
+    game_state.validate_invariants()?;
+    Ok(())
+}
+
+async fn compile_game_data(configuration: &CompileConfiguration) -> Result<(), CompilerError> {
     let mut game_template = GameTemplate::default();
-    compile_directory(&mut game_template, &configuration.source_game_data).await?;
+    compile_directory(
+        &mut game_template,
+        &configuration.source_game_data,
+        configuration.max_nesting_depth,
+        configuration.lenient,
+    )
+    .await?;
+    let mut warnings = Vec::new();
+    if configuration.check_static_assets {
+        info!("Checking static assets...");
+        warnings.extend(
+            check_static_assets(&game_template, &configuration.static_prefix_directory).await,
+        );
+    }
+    warnings.extend(check_contradictions(&game_template));
+    for warning in warnings {
+        match configuration.error_format {
+            ErrorFormat::Human => warn!("{warning}"),
+            ErrorFormat::Json => {
+                println!("{}", serde_json::to_string(&warning.diagnostic())?)
+            }
+        }
+    }
+    validate_unique_ids(&game_template)?;
+    validate_builtin_actions(&game_template)?;
+    validate_quests(&game_template)?;
+    let mut statistics = CompilerStatistics::new(&game_template);
     info!("Compiling...");
     let game_template = game_template.compile()?;
     info!("Serialising...");
     let game_template_vec = pot::to_vec(&game_template)?;
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-    encoder.write_all(&game_template_vec)?;
-    let game_template_vec = encoder.finish()?;
+    statistics.uncompressed_size = game_template_vec.len();
+    let game_template_vec = compress(&game_template_vec, configuration.compression_level)?;
+    statistics.compressed_size = game_template_vec.len();
+    if configuration.stats {
+        println!("{statistics}");
+    }
 
     if configuration.compiled_game_data.exists().await {
         info!(
@@ -56,10 +261,242 @@ pub async fn compile(configuration: &CompileConfiguration) -> Result<(), Compile
     Ok(())
 }
 
+/// Checks that all `url`s referenced by the game template exist in the static directory.
+pub async fn check_static_assets(
+    game_template: &GameTemplate,
+    static_prefix_directory: &Path,
+) -> Vec<CompilerWarning> {
+    let mut warnings = Vec::new();
+    for location in &game_template.locations {
+        if let Some(url) = &location.url {
+            let path = static_prefix_directory.join(url);
+            if !path.is_file().await {
+                warnings.push(CompilerWarning::MissingStaticAsset {
+                    id_str: location.id_str.clone(),
+                    url: url.clone(),
+                    path,
+                });
+            }
+        }
+    }
+    warnings
+}
+
+impl CompilerStatistics {
+    /// Counts the sections of the game template. The output sizes are left at zero.
+    pub fn new(game_template: &GameTemplate) -> Self {
+        Self {
+            actions: game_template.actions.len(),
+            quests: game_template.quests.len(),
+            quest_stages: game_template
+                .quests
+                .iter()
+                .map(|quest| quest.stages.len())
+                .sum(),
+            locations: game_template.locations.len(),
+            monsters: game_template.monsters.len(),
+            items: game_template.items.len(),
+            exploration_events: game_template.exploration_events.len(),
+            calendar_events: game_template.calendar_events.len(),
+            destinies: game_template.destinies.len(),
+            triggers: game_template.triggers.len(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Checks that no two sections of the same kind have the same identifier.
+/// Sections of different kinds may share an identifier.
+pub fn validate_unique_ids(game_template: &GameTemplate) -> Result<(), CompilerError> {
+    let mut ids = HashMap::new();
+    for (kind, id, range) in &game_template.id_ranges {
+        if let Some(first) = ids.insert((*kind, id.as_str()), *range) {
+            return Err(CompilerError::DuplicateId {
+                kind: *kind,
+                id: id.clone(),
+                first,
+                second: *range,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the builtin actions `WAIT`, `SLEEP`, `TAVERN` and `EXPLORE` are declared with their builtin types.
+/// Normal actions with builtin types are already rejected by the parser.
+pub fn validate_builtin_actions(game_template: &GameTemplate) -> Result<(), ParserError> {
+    for (action_type, missing_action) in [
+        (PlayerActionType::Wait, ParserErrorKind::MissingActionWait),
+        (PlayerActionType::Sleep, ParserErrorKind::MissingActionSleep),
+        (
+            PlayerActionType::Tavern,
+            ParserErrorKind::MissingActionTavern,
+        ),
+        (
+            PlayerActionType::Explore,
+            ParserErrorKind::MissingActionExplore,
+        ),
+    ] {
+        let action = game_template
+            .actions
+            .iter()
+            .find(|action| action.id_str == action_type.as_str())
+            .ok_or_else(|| ParserError::without_coordinates(missing_action))?;
+        if action.action_type != action_type {
+            return Err(ParserError::without_coordinates(
+                ParserErrorKind::WrongBuiltinActionType {
+                    id_str: action.id_str.clone(),
+                    action_type: action.action_type.clone(),
+                },
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that all stages of each quest can be reached and that its final stage can be completed.
+pub fn validate_quests(game_template: &GameTemplate) -> Result<(), CompilerError> {
+    for quest in &game_template.quests {
+        let mut stages = quest.stages.iter().peekable();
+        while let Some(stage) = stages.next() {
+            let completion_trigger = game_template
+                .triggers
+                .iter()
+                .rev()
+                .find(|trigger| trigger.id_str == stage.completion_condition);
+            if !completion_trigger.map_or(false, |trigger| can_trigger(&trigger.condition)) {
+                return Err(if let Some(next_stage) = stages.peek() {
+                    CompilerError::UnreachableQuestStage {
+                        quest_id_str: quest.id_str.clone(),
+                        stage_id_str: next_stage.id_str.clone(),
+                    }
+                } else {
+                    CompilerError::UncompletableQuest {
+                        quest_id_str: quest.id_str.clone(),
+                    }
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// False if the condition can never be fulfilled, independent of the events that happen.
+fn can_trigger(condition: &TriggerCondition<GameEvent>) -> bool {
+    match condition {
+        TriggerCondition::Never => false,
+        TriggerCondition::Sequence { conditions } | TriggerCondition::And { conditions } => {
+            conditions.iter().all(can_trigger)
+        }
+        TriggerCondition::Or { conditions } => conditions.iter().any(can_trigger),
+        TriggerCondition::AnyN { conditions, n } => {
+            conditions
+                .iter()
+                .filter(|condition| can_trigger(condition))
+                .count()
+                >= *n
+        }
+        TriggerCondition::None
+        | TriggerCondition::EventCount { .. }
+        | TriggerCondition::Geq { .. } => true,
+    }
+}
+
+/// Warns about triggers that require a quest to be both completed and failed.
+pub fn check_contradictions(game_template: &GameTemplate) -> Vec<CompilerWarning> {
+    game_template
+        .triggers
+        .iter()
+        .filter_map(|trigger| {
+            let mut completed_quests = BTreeSet::new();
+            let mut failed_quests = BTreeSet::new();
+            collect_required_quest_outcomes(
+                &trigger.condition,
+                &mut completed_quests,
+                &mut failed_quests,
+            );
+            completed_quests
+                .intersection(&failed_quests)
+                .next()
+                .map(|quest_id_str| CompilerWarning::ContradictoryCondition {
+                    trigger_id_str: trigger.id_str.clone(),
+                    quest_id_str: quest_id_str.to_string(),
+                })
+        })
+        .collect()
+}
+
+/// Collects the quests that must be completed or failed for the condition to be fulfilled.
+/// The alternatives of `or` and `any_n` are not required individually, so they are skipped.
+fn collect_required_quest_outcomes<'condition>(
+    condition: &'condition TriggerCondition<GameEvent>,
+    completed_quests: &mut BTreeSet<&'condition str>,
+    failed_quests: &mut BTreeSet<&'condition str>,
+) {
+    match condition {
+        TriggerCondition::EventCount {
+            event: GameEvent::QuestCompleted { id },
+            ..
+        } => {
+            completed_quests.insert(id);
+        }
+        TriggerCondition::EventCount {
+            event: GameEvent::Action(GameAction::FailQuest { id }),
+            ..
+        } => {
+            failed_quests.insert(id);
+        }
+        TriggerCondition::Sequence { conditions } | TriggerCondition::And { conditions } => {
+            for condition in conditions {
+                collect_required_quest_outcomes(condition, completed_quests, failed_quests);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SectionGrammar {
+    section: &'static str,
+    keys: Vec<KeyGrammar>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeyGrammar {
+    key: &'static str,
+    aliases: &'static [&'static str],
+    value: KeyValueKind,
+}
+
+/// Describes all sections of the template language together with their allowed keys.
+pub fn describe_grammar() -> Vec<SectionGrammar> {
+    all::<SectionTokenKind>()
+        .map(|section| SectionGrammar {
+            section: section.keyword(),
+            keys: section
+                .allowed_keys()
+                .iter()
+                .map(|key| KeyGrammar {
+                    key: key.keyword(),
+                    aliases: &key.keywords()[1..],
+                    value: key.value_kind(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn print_grammar() -> Result<(), CompilerError> {
+    println!("{}", serde_json::to_string_pretty(&describe_grammar())?);
+    Ok(())
+}
+
 #[async_recursion]
 async fn compile_directory(
     game_template: &mut GameTemplate,
     directory: &Path,
+    max_nesting_depth: usize,
+    lenient_sections: bool,
 ) -> Result<(), CompilerError> {
     let mut read_dir = directory.read_dir().await?;
     while let Some(entry) = read_dir.next().await {
@@ -68,13 +505,19 @@ async fn compile_directory(
         if path.is_file().await {
             if path.extension().and_then(OsStr::to_str) == Some("tpl") {
                 info!("Parsing {}", path.to_string_lossy());
-                parse_game_template_file(game_template, BufReader::new(File::open(path).await?))
-                    .await?;
+                parse_game_template_file_recovering(
+                    game_template,
+                    BufReader::new(File::open(path).await?),
+                    max_nesting_depth,
+                    lenient_sections,
+                )
+                .await
+                .map_err(CompilerError::ParserErrors)?;
             } else {
                 debug!("Skipping {}", path.to_string_lossy());
             }
         } else if path.is_dir().await {
-            compile_directory(game_template, &path).await?;
+            compile_directory(game_template, &path, max_nesting_depth, lenient_sections).await?;
         } else {
             warn!(
                 "Found directory entry that is neither a file nor a directory: {:?}",
@@ -86,6 +529,74 @@ async fn compile_directory(
     Ok(())
 }
 
+impl Diagnostic {
+    fn new(
+        message: String,
+        kind: &impl std::fmt::Debug,
+        severity: Severity,
+        coordinates: Option<&CharacterCoordinateRange>,
+    ) -> Self {
+        let kind = format!("{kind:?}");
+        let kind = kind
+            .split(|character: char| !character.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Self {
+            message,
+            kind,
+            severity,
+            start_line: coordinates.map(|coordinates| coordinates.start().line_number()),
+            start_column: coordinates.map(|coordinates| coordinates.start().column_number()),
+            end_line: coordinates.map(|coordinates| coordinates.end().line_number()),
+            end_column: coordinates.map(|coordinates| coordinates.end().column_number()),
+        }
+    }
+}
+
+impl Diagnostic {
+    fn from_parser_error(error: &ParserError) -> Self {
+        Self::new(
+            format!("{:?}", error.kind),
+            &error.kind,
+            Severity::Error,
+            error.coordinates.as_ref(),
+        )
+    }
+}
+
+impl CompilerError {
+    /// The diagnostic of this error, or of the first error if there are several.
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            CompilerError::Parser(error) => Diagnostic::from_parser_error(error),
+            CompilerError::ParserErrors(errors) if !errors.is_empty() => {
+                Diagnostic::from_parser_error(&errors[0])
+            }
+            CompilerError::DuplicateId { second, .. } => {
+                Diagnostic::new(format!("{self:?}"), self, Severity::Error, Some(second))
+            }
+            error => Diagnostic::new(format!("{error:?}"), error, Severity::Error, None),
+        }
+    }
+
+    /// The diagnostics of all errors contained in this error.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            CompilerError::ParserErrors(errors) => {
+                errors.iter().map(Diagnostic::from_parser_error).collect()
+            }
+            error => vec![error.diagnostic()],
+        }
+    }
+}
+
+impl CompilerWarning {
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(self.to_string(), self, Severity::Warning, None)
+    }
+}
+
 impl From<ParserError> for CompilerError {
     fn from(error: ParserError) -> Self {
         Self::Parser(error)
@@ -103,3 +614,522 @@ impl From<std::io::Error> for CompilerError {
         Self::Io(error)
     }
 }
+
+impl From<serde_json::Error> for CompilerError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+impl Display for CompilerStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Actions: {}", self.actions)?;
+        writeln!(f, "Quests: {}", self.quests)?;
+        writeln!(f, "Quest stages: {}", self.quest_stages)?;
+        writeln!(f, "Locations: {}", self.locations)?;
+        writeln!(f, "Monsters: {}", self.monsters)?;
+        writeln!(f, "Items: {}", self.items)?;
+        writeln!(f, "Exploration events: {}", self.exploration_events)?;
+        writeln!(f, "Calendar events: {}", self.calendar_events)?;
+        writeln!(f, "Destinies: {}", self.destinies)?;
+        writeln!(f, "Triggers: {}", self.triggers)?;
+        writeln!(f, "Uncompressed size: {} bytes", self.uncompressed_size)?;
+        write!(f, "Compressed size: {} bytes", self.compressed_size)
+    }
+}
+
+impl Display for CompilerWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompilerWarning::MissingStaticAsset { id_str, url, path } => write!(
+                f,
+                "{id_str} references missing static asset {url} (expected at {})",
+                path.to_string_lossy()
+            ),
+            CompilerWarning::ContradictoryCondition {
+                trigger_id_str,
+                quest_id_str,
+            } => write!(
+                f,
+                "{trigger_id_str} can never trigger, because it requires quest {quest_id_str} to be both completed and failed"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_contradictions, check_static_assets, describe_grammar, validate_builtin_actions,
+        validate_quests, validate_unique_ids, verify, verify_game_data, CompilerError,
+        CompilerStatistics, CompilerWarning, Severity, VerifyConfiguration,
+    };
+    use crate::game_state::player_actions::PlayerActionType;
+    use crate::game_state::tests::create_test_game_template;
+    use crate::game_template::parser::error::ParserErrorKind;
+    use crate::game_template::parser::parse_game_template_file;
+    use crate::game_template::{GameTemplate, IdNamespace};
+    use crate::io::{compress, LoadError, DEFAULT_COMPRESSION_LEVEL};
+    use async_std::path::PathBuf;
+    use serde_json::{json, Value};
+
+    const ASSET_TEMPLATE: &str = "
+LOCATION present
+name Present
+url present.png
+events (1.0, rat)
+activation none
+deactivation never
+
+LOCATION absent
+name Absent
+url absent.png
+events (1.0, rat)
+activation none
+deactivation never
+
+LOCATION without_url
+name Without url
+events (1.0, rat)
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_check_static_assets() {
+        let static_prefix_directory = PathBuf::from(std::env::temp_dir())
+            .join(format!("iced-rs-test-static-{}", std::process::id()));
+        std::fs::create_dir_all(&static_prefix_directory).unwrap();
+        std::fs::write(static_prefix_directory.join("present.png"), []).unwrap();
+
+        let mut game_template = GameTemplate::default();
+        let warnings = async_std::task::block_on(async {
+            parse_game_template_file(&mut game_template, ASSET_TEMPLATE.as_bytes())
+                .await
+                .unwrap();
+            check_static_assets(&game_template, &static_prefix_directory).await
+        });
+        std::fs::remove_dir_all(&static_prefix_directory).unwrap();
+
+        assert_eq!(
+            warnings,
+            vec![CompilerWarning::MissingStaticAsset {
+                id_str: "absent".to_string(),
+                url: "absent.png".to_string(),
+                path: static_prefix_directory.join("absent.png"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_describe_grammar() {
+        let grammar = serde_json::to_value(describe_grammar()).unwrap();
+        let sections = grammar.as_array().unwrap();
+        assert_eq!(sections.len(), 12);
+
+        let monster = sections
+            .iter()
+            .find(|section| section["section"] == "MONSTER")
+            .unwrap();
+        let keys = monster["keys"].as_array().unwrap();
+        assert!(keys.contains(&json!({"key": "hitpoints", "aliases": [], "value": "expression"})));
+        assert!(keys.contains(&json!({"key": "name", "aliases": [], "value": "string"})));
+        assert!(keys.contains(&json!({"key": "activation", "aliases": [], "value": "trigger"})));
+        assert!(!keys.iter().any(|key| key["key"] == "duration"));
+
+        let action = sections
+            .iter()
+            .find(|section| section["section"] == "ACTION")
+            .unwrap();
+        let keys = action["keys"].as_array().unwrap();
+        assert!(keys.contains(&json!({"key": "duration", "aliases": [], "value": "time"})));
+        assert!(keys.contains(&json!({"key": "currency", "aliases": [], "value": "integer"})));
+        assert!(keys.contains(&json!({"key": "strength", "aliases": ["str"], "value": "float"})));
+        assert!(keys
+            .iter()
+            .all(|key| matches!(&key["value"], Value::String(_))));
+    }
+
+    fn quest_template(completions: &[&str]) -> String {
+        let mut template = "
+QUEST validation_test
+title Validation test
+activation none
+failure never
+BEGIN
+"
+        .to_string();
+        for (index, completion) in completions.iter().enumerate() {
+            template += &format!(
+                "
+    QUEST_STAGE stage_{index}
+    task Stage {index}.
+    completion {completion}
+"
+            );
+        }
+        template + "END\n"
+    }
+
+    fn validate(completions: &[&str]) -> Result<(), CompilerError> {
+        let mut game_template = GameTemplate::default();
+        async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            quest_template(completions).as_bytes(),
+        ))
+        .unwrap();
+        validate_quests(&game_template)
+    }
+
+    #[test]
+    fn test_validate_completable_quest() {
+        assert!(validate(&["monster_killed_count(1, rat)", "none"]).is_ok());
+    }
+
+    #[test]
+    fn test_validate_uncompletable_quest() {
+        assert!(matches!(
+            validate(&["monster_killed_count(1, rat)", "never"]),
+            Err(CompilerError::UncompletableQuest { quest_id_str }) if quest_id_str == "validation_test"
+        ));
+        assert!(matches!(
+            validate(&["never", "none"]),
+            Err(CompilerError::UnreachableQuestStage { quest_id_str, stage_id_str })
+                if quest_id_str == "validation_test" && stage_id_str == "stage_1"
+        ));
+    }
+
+    const BUILTIN_TEMPLATE: &str = include_str!("../../../data/builtin.tpl");
+
+    fn parse(template: &str) -> GameTemplate {
+        let mut game_template = GameTemplate::default();
+        async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            template.as_bytes(),
+        ))
+        .unwrap();
+        game_template
+    }
+
+    #[test]
+    fn test_validate_builtin_actions() {
+        assert!(validate_builtin_actions(&parse(BUILTIN_TEMPLATE)).is_ok());
+    }
+
+    const SHARED_ID_TEMPLATE: &str = "
+MONSTER shared
+name Shared monster
+hitpoints 10.0
+activation none
+deactivation never
+
+LOCATION shared
+name Shared location
+events (1.0, shared)
+activation none
+deactivation never
+
+QUEST duplicate
+title First quest
+activation none
+failure never
+BEGIN
+    QUEST_STAGE first_stage
+    task Do nothing.
+    completion none
+END
+";
+
+    const DUPLICATE_QUEST_TEMPLATE: &str = "
+QUEST duplicate
+title Second quest
+activation none
+failure never
+BEGIN
+    QUEST_STAGE second_stage
+    task Do nothing again.
+    completion none
+END
+";
+
+    #[test]
+    fn test_same_id_in_different_namespaces() {
+        assert!(validate_unique_ids(&parse(SHARED_ID_TEMPLATE)).is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_id_in_namespace() {
+        let template = format!("{SHARED_ID_TEMPLATE}{DUPLICATE_QUEST_TEMPLATE}");
+        let error = validate_unique_ids(&parse(&template)).unwrap_err();
+        if let CompilerError::DuplicateId {
+            kind,
+            id,
+            first,
+            second,
+        } = &error
+        {
+            assert_eq!(*kind, IdNamespace::Quest);
+            assert_eq!(id, "duplicate");
+            assert_eq!(
+                (first.start().line_number(), first.start().column_number()),
+                (14, 7)
+            );
+            assert_eq!(
+                (second.start().line_number(), second.start().column_number()),
+                (24, 7)
+            );
+        } else {
+            panic!("Unexpected error: {error:?}");
+        }
+        assert_eq!(error.diagnostic().kind, "DuplicateId");
+        assert_eq!(error.diagnostic().start_line, Some(24));
+    }
+
+    #[test]
+    fn test_missing_builtin_action() {
+        let template = BUILTIN_TEMPLATE.replace(
+            "BUILTIN_ACTION SLEEP
+name Sleep
+progressive sleeping
+simple_past slept
+activation none
+deactivation never
+",
+            "",
+        );
+        assert!(!template.contains("SLEEP"));
+        assert!(matches!(
+            validate_builtin_actions(&parse(&template)).map_err(|error| error.kind),
+            Err(ParserErrorKind::MissingActionSleep)
+        ));
+    }
+
+    #[test]
+    fn test_wrongly_typed_builtin_action() {
+        let mut game_template = parse(BUILTIN_TEMPLATE);
+        game_template
+            .actions
+            .iter_mut()
+            .find(|action| action.id_str == "TAVERN")
+            .unwrap()
+            .action_type = PlayerActionType::Work;
+        assert!(matches!(
+            validate_builtin_actions(&game_template).map_err(|error| error.kind),
+            Err(ParserErrorKind::WrongBuiltinActionType { id_str, action_type: PlayerActionType::Work })
+                if id_str == "TAVERN"
+        ));
+
+        let mut game_template = GameTemplate::default();
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            "ACTION inn
+name Inn
+progressive drinking
+simple_past drank
+type TAVERN
+duration 1h
+currency 0
+activation none
+deactivation never
+"
+            .as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            &error.kind,
+            ParserErrorKind::IllegalActionType(PlayerActionType::Tavern)
+        ));
+        assert_eq!(error.coordinates.unwrap().start().line_number(), 5);
+    }
+
+    const STATISTICS_TEMPLATE: &str = "
+ACTION statistics_action
+name Statistics action
+progressive counting
+simple_past counted
+type TRAIN
+duration 1h
+str 1.0
+currency 0
+activation none
+deactivation never
+
+QUEST statistics_quest
+title Statistics quest
+activation none
+failure never
+BEGIN
+    QUEST_STAGE first_statistics_stage
+    task Count.
+    completion none
+    QUEST_STAGE second_statistics_stage
+    task Count again.
+    completion action_count(2, statistics_action)
+END
+
+LOCATION statistics_location
+name Statistics location
+events (1.0, statistics_event)
+activation none
+deactivation never
+
+EXPLORATION_EVENT statistics_event
+currency 1
+monster statistics_monster
+activation none
+deactivation never
+
+MONSTER statistics_monster
+name Statistics monster
+hitpoints 10
+activation none
+deactivation never
+
+ITEM statistics_item
+name Statistics item
+description Counted.
+value 1
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_statistics() {
+        let mut game_template = GameTemplate::default();
+        async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            STATISTICS_TEMPLATE.as_bytes(),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            CompilerStatistics::new(&game_template),
+            CompilerStatistics {
+                actions: 1,
+                quests: 1,
+                quest_stages: 2,
+                locations: 1,
+                monsters: 1,
+                items: 1,
+                exploration_events: 1,
+                calendar_events: 0,
+                destinies: 0,
+                triggers: 14,
+                uncompressed_size: 0,
+                compressed_size: 0,
+            }
+        );
+    }
+
+    const BROKEN_TEMPLATE: &str = "ACTION broken_action
+name Broken action
+progressive breaking
+simple_past broke
+type TRAIN
+duration 1h
+currency 0
+activation none
+deactivation never
+colour blue
+";
+
+    #[test]
+    fn test_json_diagnostic() {
+        let error: CompilerError = async_std::task::block_on(parse_game_template_file(
+            &mut GameTemplate::default(),
+            BROKEN_TEMPLATE.as_bytes(),
+        ))
+        .unwrap_err()
+        .into();
+
+        let diagnostic = error.diagnostic();
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(
+            serde_json::to_value(&diagnostic).unwrap(),
+            json!({
+                "message": "IllegalKeyword(\"colour\")",
+                "kind": "IllegalKeyword",
+                "severity": "error",
+                "start_line": 10,
+                "start_column": 1,
+                "end_line": 10,
+                "end_column": 7,
+            })
+        );
+
+        let warning = CompilerWarning::MissingStaticAsset {
+            id_str: "absent".to_string(),
+            url: "absent.png".to_string(),
+            path: PathBuf::from("static/absent.png"),
+        }
+        .diagnostic();
+        assert_eq!(warning.kind, "MissingStaticAsset");
+        assert_eq!(warning.severity, Severity::Warning);
+        assert_eq!(warning.start_line, None);
+    }
+
+    const CONTRADICTION_TEMPLATE: &str = "
+ACTION contradictory_action
+name Contradictory action
+progressive contradicting
+simple_past contradicted
+type TRAIN
+duration 1h
+currency 0
+activation and(quest_completed(init), seq(level_geq(2), quest_failed(init)))
+deactivation never
+
+ACTION satisfiable_action
+name Satisfiable action
+progressive satisfying
+simple_past satisfied
+type TRAIN
+duration 1h
+currency 0
+activation or(quest_completed(init), quest_failed(init))
+deactivation and(quest_completed(init), quest_failed(other))
+";
+
+    #[test]
+    fn test_contradictory_condition() {
+        let mut game_template = GameTemplate::default();
+        async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            CONTRADICTION_TEMPLATE.as_bytes(),
+        ))
+        .unwrap();
+
+        let warnings = check_contradictions(&game_template);
+        assert_eq!(
+            warnings,
+            vec![CompilerWarning::ContradictoryCondition {
+                trigger_id_str: "action_contradictory_action_activation".to_string(),
+                quest_id_str: "init".to_string(),
+            }]
+        );
+        assert_eq!(warnings[0].diagnostic().kind, "ContradictoryCondition");
+    }
+
+    #[test]
+    fn test_verify_game_data() {
+        let game_template = create_test_game_template(&[]);
+        let compressed = compress(
+            &pot::to_vec(&game_template).unwrap(),
+            DEFAULT_COMPRESSION_LEVEL,
+        )
+        .unwrap();
+        verify_game_data(&compressed).unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("iced-rs-test-verify-{}.bin.gz", std::process::id()));
+        std::fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+        let result = async_std::task::block_on(verify(&VerifyConfiguration {
+            compiled_game_data: path.clone().into(),
+        }));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            result,
+            Err(CompilerError::InvalidGameData(LoadError::IoError(_)))
+        ));
+    }
+}
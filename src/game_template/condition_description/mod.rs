@@ -0,0 +1,429 @@
+use crate::game_state::currency::Currency;
+use crate::game_state::triggers::{GameAction, GameEvent};
+use crate::game_state::world::events::ExplorationEventKind;
+use crate::game_template::GameTemplate;
+use event_trigger_action_system::TriggerCondition;
+
+impl GameTemplate {
+    /// Describes what unlocks content with the given activation condition, e.g. "Reach level 10 and complete 'Wake up!'".
+    /// Returns `None` if the condition is fulfilled from the start or can never be fulfilled.
+    pub fn describe_activation_condition(&self, trigger_id_str: &str) -> Option<String> {
+        let trigger = self
+            .triggers
+            .iter()
+            .rev()
+            .find(|trigger| trigger.id_str == trigger_id_str)?;
+        match &trigger.condition {
+            TriggerCondition::None | TriggerCondition::Never => None,
+            condition => Some(capitalise(&self.describe_condition(condition))),
+        }
+    }
+
+    /// Renders the condition as lowercase text for the player, resolving identifiers to their names.
+    pub fn describe_condition(&self, condition: &TriggerCondition<GameEvent>) -> String {
+        match condition {
+            TriggerCondition::None => "nothing".to_string(),
+            TriggerCondition::Never => "the impossible".to_string(),
+            TriggerCondition::EventCount { event, required } => {
+                self.describe_event_count(event, *required)
+            }
+            TriggerCondition::Geq { event } => self.describe_geq(event),
+            TriggerCondition::And { conditions } => self.describe_conditions(conditions, " and "),
+            TriggerCondition::Sequence { conditions } => {
+                self.describe_conditions(conditions, ", then ")
+            }
+            TriggerCondition::Or { conditions } => self.describe_conditions(conditions, " or "),
+            TriggerCondition::AnyN { conditions, n } => {
+                format!("{n} of ({})", self.describe_conditions(conditions, ", "))
+            }
+        }
+    }
+
+    /// Joins the descriptions of the conditions, putting nested compound conditions in parentheses.
+    fn describe_conditions(
+        &self,
+        conditions: &[TriggerCondition<GameEvent>],
+        separator: &str,
+    ) -> String {
+        conditions
+            .iter()
+            .map(|condition| match condition {
+                TriggerCondition::And { conditions }
+                | TriggerCondition::Sequence { conditions }
+                | TriggerCondition::Or { conditions }
+                    if conditions.len() > 1 =>
+                {
+                    format!("({})", self.describe_condition(condition))
+                }
+                condition => self.describe_condition(condition),
+            })
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    fn describe_event_count(&self, event: &GameEvent, required: usize) -> String {
+        let times = times(required);
+        match event {
+            GameEvent::Action(action) => self.describe_game_action(action),
+            GameEvent::QuestStageActivated { quest_id, .. } => {
+                format!("progress in '{}'", self.quest_title(quest_id))
+            }
+            GameEvent::QuestStageFailed { quest_id, .. } => {
+                format!("fail a stage of '{}'", self.quest_title(quest_id))
+            }
+            GameEvent::QuestCompleted { id } => {
+                format!("complete '{}'{times}", self.quest_title(id))
+            }
+            GameEvent::ActionStarted { id } => format!("start {}{times}", self.action_name(id)),
+            GameEvent::ActionCompleted { id } => {
+                format!("complete {}{times}", self.action_name(id))
+            }
+            GameEvent::ExplorationStarted { id } => {
+                format!("start exploring {}{times}", self.location_name(id))
+            }
+            GameEvent::ExplorationCompleted { id } => {
+                format!("explore {}{times}", self.location_name(id))
+            }
+            GameEvent::MonsterKilled { id } => format!("defeat {}{times}", self.monster_name(id)),
+            GameEvent::MonsterFailed { id } => {
+                format!("lose against {}{times}", self.monster_name(id))
+            }
+            GameEvent::ExplorationEventCompleted { id } => {
+                format!("experience {}{times}", self.exploration_event_name(id))
+            }
+            GameEvent::CalendarEventOccurred { id } => {
+                format!("celebrate {}{times}", self.calendar_event_name(id))
+            }
+            event => self.describe_geq(event),
+        }
+    }
+
+    fn describe_geq(&self, event: &GameEvent) -> String {
+        match event {
+            GameEvent::CurrencyChanged { value } => format!("own {}", currency_string(*value)),
+            GameEvent::CurrencyLeq { value } => {
+                format!("own at most {}", currency_string(*value))
+            }
+            GameEvent::PlayerLevelChanged { value } => format!("reach level {value}"),
+            GameEvent::PlayerLevelLeq { value } => format!("be at most level {value}"),
+            GameEvent::PlayerStrengthChanged { value } => format!("reach {value} strength"),
+            GameEvent::PlayerStaminaChanged { value } => format!("reach {value} stamina"),
+            GameEvent::PlayerDexterityChanged { value } => format!("reach {value} dexterity"),
+            GameEvent::PlayerIntelligenceChanged { value } => {
+                format!("reach {value} intelligence")
+            }
+            GameEvent::PlayerWisdomChanged { value } => format!("reach {value} wisdom"),
+            GameEvent::PlayerCharismaChanged { value } => format!("reach {value} charisma"),
+            GameEvent::ItemCountChanged { id, count } => {
+                format!("own {count} {}", self.item_name(id))
+            }
+            GameEvent::PlayerIdle { duration } => {
+                format!("wait idly for {} hours", duration.hours())
+            }
+            event => self.describe_event_count(event, 1),
+        }
+    }
+
+    fn describe_game_action(&self, action: &GameAction) -> String {
+        match action {
+            GameAction::ActivateQuest { id } => format!("start '{}'", self.quest_title(id)),
+            GameAction::CompleteQuestStage { quest_id, .. } => {
+                format!("progress in '{}'", self.quest_title(quest_id))
+            }
+            GameAction::FailQuest { id } => format!("fail '{}'", self.quest_title(id)),
+            GameAction::OfferQuest { id } => format!("be offered '{}'", self.quest_title(id)),
+            GameAction::ActivateAction { id } => format!("unlock {}", self.action_name(id)),
+            GameAction::DeactivateAction { id } => format!("lose {}", self.action_name(id)),
+            GameAction::ActivateLocation { id } => format!("discover {}", self.location_name(id)),
+            GameAction::DeactivateLocation { id } => format!("lose {}", self.location_name(id)),
+            GameAction::ActivateExplorationEvent { id } => {
+                format!("unlock {}", self.exploration_event_name(id))
+            }
+            GameAction::DeactivateExplorationEvent { id } => {
+                format!("lose {}", self.exploration_event_name(id))
+            }
+            GameAction::ActivateMonster { id } => format!("encounter {}", self.monster_name(id)),
+            GameAction::DeactivateMonster { id } => format!("lose {}", self.monster_name(id)),
+            GameAction::ActivateItem { id } => format!("unlock {}", self.item_name(id)),
+            GameAction::DeactivateItem { id } => format!("lose {}", self.item_name(id)),
+        }
+    }
+
+    fn quest_title<'a>(&'a self, id_str: &'a str) -> &'a str {
+        self.quests
+            .iter()
+            .find(|quest| quest.id_str == id_str)
+            .map(|quest| quest.title.as_str())
+            .unwrap_or(id_str)
+    }
+
+    fn action_name<'a>(&'a self, id_str: &'a str) -> &'a str {
+        self.actions
+            .iter()
+            .find(|action| action.id_str == id_str)
+            .map(|action| action.name.as_str())
+            .unwrap_or(id_str)
+    }
+
+    fn location_name<'a>(&'a self, id_str: &'a str) -> &'a str {
+        self.locations
+            .iter()
+            .find(|location| location.id_str == id_str)
+            .map(|location| location.name.as_str())
+            .unwrap_or(id_str)
+    }
+
+    fn exploration_event_name<'a>(&'a self, id_str: &'a str) -> &'a str {
+        match self
+            .exploration_events
+            .iter()
+            .find(|exploration_event| exploration_event.id_str == id_str)
+            .map(|exploration_event| &exploration_event.kind)
+        {
+            Some(ExplorationEventKind::Normal { name, .. }) => name,
+            Some(ExplorationEventKind::Monster { monster }) => self.monster_name(monster),
+            // a pack is named after its first monster
+            Some(ExplorationEventKind::Pack { monsters }) => monsters
+                .first()
+                .map(|monster| self.monster_name(monster))
+                .unwrap_or(id_str),
+            None => id_str,
+        }
+    }
+
+    fn monster_name<'a>(&'a self, id_str: &'a str) -> &'a str {
+        self.monsters
+            .iter()
+            .find(|monster| monster.id_str == id_str)
+            .map(|monster| monster.name.as_str())
+            .unwrap_or(id_str)
+    }
+
+    fn item_name<'a>(&'a self, id_str: &'a str) -> &'a str {
+        self.items
+            .iter()
+            .find(|item| item.id_str == id_str)
+            .map(|item| item.name.as_str())
+            .unwrap_or(id_str)
+    }
+
+    fn calendar_event_name<'a>(&'a self, id_str: &'a str) -> &'a str {
+        self.calendar_events
+            .iter()
+            .find(|calendar_event| calendar_event.id_str == id_str)
+            .map(|calendar_event| calendar_event.name.as_str())
+            .unwrap_or(id_str)
+    }
+}
+
+fn times(count: usize) -> String {
+    match count {
+        0 | 1 => String::new(),
+        2 => " twice".to_string(),
+        count => format!(" {count} times"),
+    }
+}
+
+fn currency_string(currency: Currency) -> String {
+    if currency.gold() > 0 {
+        format!(
+            "{}g {}s {}c",
+            currency.gold(),
+            currency.silver_of_gold(),
+            currency.copper_of_silver()
+        )
+    } else if currency.silver() > 0 {
+        format!("{}s {}c", currency.silver(), currency.copper_of_silver())
+    } else {
+        format!("{}c", currency.copper())
+    }
+}
+
+fn capitalise(text: &str) -> String {
+    let mut characters = text.chars();
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::currency::Currency;
+    use crate::game_state::tests::create_test_game_state_with_templates;
+    use crate::game_state::triggers::GameEvent;
+    use crate::game_template::parser::parse_game_template_file;
+    use crate::game_template::GameTemplate;
+    use event_trigger_action_system::{and, event_count, geq, or, sequence, TriggerCondition};
+
+    const UNLOCK_TEMPLATE: &str = "
+ACTION unlock_study
+name Study
+progressive studying
+simple_past studied
+type TRAIN
+int 1.0
+duration 1h
+currency 0
+activation none
+deactivation never
+
+QUEST unlock_wake_up
+title Wake up!
+activation none
+failure never
+BEGIN
+    QUEST_STAGE unlock_stage
+    task Open your eyes.
+    completion never
+END
+
+LOCATION unlock_tower
+name Tower
+events (1, unlock_vision)
+activation and(level_geq(10), quest_completed(unlock_wake_up))
+deactivation never
+
+EXPLORATION_EVENT unlock_vision
+name Vision
+progressive having a vision
+simple_past had a vision
+activation none
+deactivation never
+";
+
+    fn parse_unlock_template() -> GameTemplate {
+        let mut game_template = GameTemplate::default();
+        async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            UNLOCK_TEMPLATE.as_bytes(),
+        ))
+        .unwrap();
+        game_template
+    }
+
+    #[test]
+    fn test_describe_leaves() {
+        let game_template = parse_unlock_template();
+        assert_eq!(
+            game_template.describe_condition(&geq(GameEvent::PlayerLevelChanged { value: 10 })),
+            "reach level 10"
+        );
+        assert_eq!(
+            game_template.describe_condition(&geq(GameEvent::CurrencyChanged {
+                value: Currency::from_silver(3),
+            })),
+            "own 3s 0c"
+        );
+        assert_eq!(
+            game_template.describe_condition(&event_count(
+                GameEvent::ActionCompleted {
+                    id: "unlock_study".to_string(),
+                },
+                5,
+            )),
+            "complete Study 5 times"
+        );
+        assert_eq!(
+            game_template.describe_condition(&event_count(
+                GameEvent::QuestCompleted {
+                    id: "unlock_wake_up".to_string(),
+                },
+                1,
+            )),
+            "complete 'Wake up!'"
+        );
+        assert_eq!(
+            game_template.describe_condition(&event_count(
+                GameEvent::ExplorationEventCompleted {
+                    id: "unknown_event".to_string(),
+                },
+                2,
+            )),
+            "experience unknown_event twice"
+        );
+    }
+
+    fn level() -> TriggerCondition<GameEvent> {
+        geq(GameEvent::PlayerLevelChanged { value: 10 })
+    }
+
+    fn quest() -> TriggerCondition<GameEvent> {
+        event_count(
+            GameEvent::QuestCompleted {
+                id: "unlock_wake_up".to_string(),
+            },
+            1,
+        )
+    }
+
+    fn study() -> TriggerCondition<GameEvent> {
+        event_count(
+            GameEvent::ActionCompleted {
+                id: "unlock_study".to_string(),
+            },
+            3,
+        )
+    }
+
+    #[test]
+    fn test_describe_compound_conditions() {
+        let game_template = parse_unlock_template();
+        assert_eq!(
+            game_template.describe_condition(&and(vec![level(), quest()])),
+            "reach level 10 and complete 'Wake up!'"
+        );
+        assert_eq!(
+            game_template.describe_condition(&or(vec![and(vec![level(), quest()]), study()])),
+            "(reach level 10 and complete 'Wake up!') or complete Study 3 times"
+        );
+        assert_eq!(
+            game_template.describe_condition(&sequence(vec![quest(), or(vec![study()]), level()])),
+            "complete 'Wake up!', then complete Study 3 times, then reach level 10"
+        );
+    }
+
+    #[test]
+    fn test_describe_activation_condition() {
+        let game_template = parse_unlock_template();
+        let tower = game_template
+            .locations
+            .iter()
+            .find(|location| location.id_str == "unlock_tower")
+            .unwrap();
+        assert_eq!(
+            game_template
+                .describe_activation_condition(&tower.activation_condition)
+                .as_deref(),
+            Some("Reach level 10 and complete 'Wake up!'")
+        );
+
+        let study = game_template
+            .actions
+            .iter()
+            .find(|action| action.id_str == "unlock_study")
+            .unwrap();
+        assert_eq!(
+            game_template.describe_activation_condition(&study.activation_condition),
+            None
+        );
+    }
+
+    #[test]
+    fn test_compiled_locations_carry_unlock_description() {
+        let game_state = create_test_game_state_with_templates(&[UNLOCK_TEMPLATE]);
+        let tower = game_state
+            .world
+            .locked_locations()
+            .find(|location| location.id_str == "unlock_tower")
+            .unwrap();
+        assert_eq!(
+            tower.unlock_description.as_deref(),
+            Some("Reach level 10 and complete 'Wake up!'")
+        );
+        assert!(game_state
+            .world
+            .active_locations()
+            .all(|location| location.id_str != "unlock_tower"));
+    }
+}
@@ -1,25 +1,145 @@
+use crate::game_state::story::quests::QuestId;
 use crate::game_state::time::GameTime;
 use crate::game_state::world::locations::LocationId;
+use crate::game_template::parser::error::{ParserError, ParserErrorKind};
 use crate::game_template::IdMaps;
 use serde::{Deserialize, Serialize};
 
+/// The starting time if the template does not specify one: six o'clock on the first day of the first era.
+pub fn default_starting_time() -> GameTime {
+    GameTime::from_eras(0).unwrap() + GameTime::from_hours(6)
+}
+
 #[derive(Debug)]
 pub struct GameInitialisation {
     pub starting_location: String,
     pub starting_time: GameTime,
+    /// The quest that makes prestige available once completed.
+    pub prestige_quest: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompiledGameInitialisation {
     pub starting_location: LocationId,
     pub starting_time: GameTime,
+    #[serde(default)]
+    pub prestige_quest: Option<QuestId>,
 }
 
 impl GameInitialisation {
-    pub fn compile(self, id_maps: &IdMaps) -> CompiledGameInitialisation {
-        CompiledGameInitialisation {
-            starting_location: *id_maps.locations.get(&self.starting_location).unwrap(),
+    pub fn compile(self, id_maps: &IdMaps) -> Result<CompiledGameInitialisation, ParserError> {
+        Ok(CompiledGameInitialisation {
+            starting_location: *id_maps.locations.get(&self.starting_location).ok_or_else(
+                || {
+                    ParserError::without_coordinates(ParserErrorKind::UnknownStartingLocation(
+                        self.starting_location.clone(),
+                    ))
+                },
+            )?,
             starting_time: self.starting_time,
+            prestige_quest: self
+                .prestige_quest
+                .map(|prestige_quest| {
+                    id_maps.quests.get(&prestige_quest).copied().ok_or_else(|| {
+                        ParserError::without_coordinates(ParserErrorKind::UnknownPrestigeQuest(
+                            prestige_quest,
+                        ))
+                    })
+                })
+                .transpose()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::default_starting_time;
+    use crate::game_template::parser::error::{ParserError, ParserErrorKind};
+    use crate::game_template::parser::parse_game_template_file;
+    use crate::game_template::{CompiledGameTemplate, GameTemplate};
+
+    /// Compiles the test templates with the given initialisation section instead of the builtin one.
+    fn compile_with_initialisation(
+        initialisation: &str,
+        additional_template: &str,
+    ) -> Result<CompiledGameTemplate, ParserError> {
+        let builtin = include_str!("../../../data/builtin.tpl");
+        let builtin = &builtin[builtin.find("BUILTIN_ACTION").unwrap()..];
+        let mut game_template = GameTemplate::default();
+        for template_file in [
+            initialisation,
+            builtin,
+            include_str!("../../../data/game.tpl"),
+            include_str!("../../../data/village.tpl"),
+            additional_template,
+        ] {
+            async_std::task::block_on(parse_game_template_file(
+                &mut game_template,
+                template_file.as_bytes(),
+            ))?;
         }
+        game_template.compile()
+    }
+
+    #[test]
+    fn test_default_starting_time() {
+        let game_template =
+            compile_with_initialisation("INITIALISATION\nstarting_location village\n", "").unwrap();
+        assert_eq!(
+            game_template.initialisation.starting_time,
+            default_starting_time()
+        );
+        assert_eq!(default_starting_time().hour_of_day(), 6);
+        assert_eq!(game_template.initialisation.prestige_quest, None);
+    }
+
+    #[test]
+    fn test_prestige_quest() {
+        let game_template = compile_with_initialisation(
+            "INITIALISATION\nstarting_location village\nprestige_quest look_around\n",
+            "",
+        )
+        .unwrap();
+        let prestige_quest = game_template.initialisation.prestige_quest.unwrap();
+        assert_eq!(
+            game_template.story.quest(prestige_quest).id_str,
+            "look_around"
+        );
+
+        let error = compile_with_initialisation(
+            "INITIALISATION\nstarting_location village\nprestige_quest nothing\n",
+            "",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::UnknownPrestigeQuest(quest) if quest == "nothing"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_starting_location() {
+        let error = compile_with_initialisation("INITIALISATION\nstarting_location nowhere\n", "")
+            .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::UnknownStartingLocation(location) if location == "nowhere"
+        ));
+
+        let error = compile_with_initialisation(
+            "INITIALISATION\nstarting_location hidden_valley\n",
+            "
+LOCATION hidden_valley
+name Hidden valley
+events (1.0, rat)
+activation level_geq(5)
+deactivation never
+",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::InactiveStartingLocation(location) if location == "hidden_valley"
+        ));
     }
 }
@@ -1,25 +1,30 @@
+use crate::game_state::calendar::{Calendar, CalendarEvent, CalendarEventId};
+use crate::game_state::destiny::{CompiledDestiny, Destiny};
 use crate::game_state::inventory::item::{Item, ItemId};
 use crate::game_state::inventory::Inventory;
 use crate::game_state::player_actions::{
-    PlayerAction, PlayerActionId, PlayerActionType, PlayerActions,
+    CompiledPlayerAction, PlayerAction, PlayerActionId, PlayerActionType, PlayerActions,
 };
 use crate::game_state::story::quests::quest_stages::QuestStageId;
 use crate::game_state::story::quests::{Quest, QuestId};
 use crate::game_state::story::Story;
+use crate::game_state::translations::Translations;
 use crate::game_state::triggers::{CompiledGameEvent, GameAction, GameEvent};
 use crate::game_state::world::events::{ExplorationEvent, ExplorationEventId};
-use crate::game_state::world::locations::{Location, LocationId};
+use crate::game_state::world::locations::{CompiledLocation, Location, LocationId};
 use crate::game_state::world::monsters::{Monster, MonsterId};
 use crate::game_state::world::World;
 use crate::game_template::game_initialisation::{CompiledGameInitialisation, GameInitialisation};
+use crate::game_template::parser::character_iterator::CharacterCoordinateRange;
 use crate::game_template::parser::error::{ParserError, ParserErrorKind};
-use event_trigger_action_system::{CompiledTriggers, Trigger, TriggerHandle};
+use event_trigger_action_system::{CompiledTriggers, Trigger, TriggerCondition, TriggerHandle};
 use log::debug;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod compiler;
+pub mod condition_description;
 pub mod game_initialisation;
 pub mod parser;
 
@@ -32,7 +37,23 @@ pub struct GameTemplate {
     exploration_events: Vec<ExplorationEvent>,
     monsters: Vec<Monster>,
     items: Vec<Item>,
+    calendar_events: Vec<CalendarEvent>,
+    destinies: Vec<Destiny>,
     triggers: Vec<Trigger<GameEvent, GameAction>>,
+    translations: Translations,
+    /// The identifiers of the parsed sections with their positions, in the order they were parsed.
+    id_ranges: Vec<(IdNamespace, String, CharacterCoordinateRange)>,
+}
+
+/// The kinds of sections whose identifiers must be unique among each other.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum IdNamespace {
+    Action,
+    Quest,
+    Location,
+    ExplorationEvent,
+    Monster,
+    Item,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,7 +63,13 @@ pub struct CompiledGameTemplate {
     pub story: Story,
     pub world: World,
     pub inventory: Inventory,
+    #[serde(default)]
+    pub calendar: Calendar,
+    #[serde(default)]
+    pub destinies: Vec<CompiledDestiny>,
     pub triggers: CompiledTriggers<CompiledGameEvent>,
+    #[serde(default)]
+    pub translations: Translations,
 }
 
 #[derive(Debug)]
@@ -54,6 +81,7 @@ pub struct IdMaps {
     pub exploration_events: HashMap<String, ExplorationEventId>,
     pub monsters: HashMap<String, MonsterId>,
     pub items: HashMap<String, ItemId>,
+    pub calendar_events: HashMap<String, CalendarEventId>,
     pub triggers: HashMap<String, TriggerHandle>,
 }
 
@@ -136,6 +164,15 @@ impl IdMaps {
                     ))
                 },
             )?,
+            calendar_events: build_id_map(
+                &game_template.calendar_events,
+                |calendar_event| calendar_event.id_str.clone(),
+                |identifier| {
+                    ParserError::without_coordinates(
+                        ParserErrorKind::DuplicateCalendarEventIdentifier(identifier),
+                    )
+                },
+            )?,
             triggers: build_id_map(
                 &game_template.triggers,
                 |trigger| trigger.id_str.clone(),
@@ -150,6 +187,34 @@ impl IdMaps {
 }
 
 impl GameTemplate {
+    /// Ensures that the starting location exists and is active from the start of the game.
+    fn validate_starting_location(&self, starting_location: &str) -> Result<(), ParserError> {
+        let location = self
+            .locations
+            .iter()
+            .find(|location| location.id_str == starting_location)
+            .ok_or_else(|| {
+                ParserError::without_coordinates(ParserErrorKind::UnknownStartingLocation(
+                    starting_location.to_string(),
+                ))
+            })?;
+        let is_initially_active = self
+            .triggers
+            .iter()
+            .rev()
+            .find(|trigger| trigger.id_str == location.activation_condition)
+            .map(|trigger| matches!(trigger.condition, TriggerCondition::None))
+            .unwrap_or(false);
+
+        if is_initially_active {
+            Ok(())
+        } else {
+            Err(ParserError::without_coordinates(
+                ParserErrorKind::InactiveStartingLocation(starting_location.to_string()),
+            ))
+        }
+    }
+
     pub fn compile(mut self) -> Result<CompiledGameTemplate, ParserError> {
         debug!("Compiling game template");
 
@@ -164,18 +229,38 @@ impl GameTemplate {
 
         let id_maps = IdMaps::from_game_template(&self)?;
 
-        let initialisation = self
-            .initialisation
-            .ok_or_else(|| {
-                ParserError::without_coordinates(ParserErrorKind::MissingSectionInitialisation)
-            })?
-            .compile(&id_maps);
+        let initialisation = self.initialisation.take().ok_or_else(|| {
+            ParserError::without_coordinates(ParserErrorKind::MissingSectionInitialisation)
+        })?;
+        self.validate_starting_location(&initialisation.starting_location)?;
+        let initialisation = initialisation.compile(&id_maps)?;
+        if !self.destinies.is_empty() && self.destinies.iter().all(|destiny| destiny.weight == 0.0)
+        {
+            return Err(ParserError::without_coordinates(
+                ParserErrorKind::AllWeightsZero,
+            ));
+        }
+
+        let action_unlock_descriptions: Vec<_> = self
+            .actions
+            .iter()
+            .map(|action| self.describe_activation_condition(&action.activation_condition))
+            .collect();
+        let location_unlock_descriptions: Vec<_> = self
+            .locations
+            .iter()
+            .map(|location| self.describe_activation_condition(&location.activation_condition))
+            .collect();
 
         Ok(CompiledGameTemplate {
             actions: PlayerActions::new(
                 self.actions
                     .into_iter()
-                    .map(|action| action.compile(&id_maps))
+                    .zip(action_unlock_descriptions)
+                    .map(|(action, unlock_description)| CompiledPlayerAction {
+                        unlock_description,
+                        ..action.compile(&id_maps)
+                    })
                     .collect(),
             )?,
             story: Story::new(
@@ -188,7 +273,11 @@ impl GameTemplate {
                 initialisation.starting_location,
                 self.locations
                     .into_iter()
-                    .map(|location| location.compile(&id_maps))
+                    .zip(location_unlock_descriptions)
+                    .map(|(location, unlock_description)| CompiledLocation {
+                        unlock_description,
+                        ..location.compile(&id_maps)
+                    })
                     .collect(),
                 self.exploration_events
                     .into_iter()
@@ -205,6 +294,17 @@ impl GameTemplate {
                     .map(|item| item.compile(&id_maps))
                     .collect(),
             ),
+            calendar: Calendar::new(
+                self.calendar_events
+                    .into_iter()
+                    .map(|calendar_event| calendar_event.compile(&id_maps))
+                    .collect(),
+            ),
+            destinies: self
+                .destinies
+                .into_iter()
+                .map(|destiny| destiny.compile(&id_maps))
+                .collect(),
             triggers: CompiledTriggers::new(
                 self.triggers
                     .into_iter()
@@ -215,6 +315,7 @@ impl GameTemplate {
                     })
                     .collect(),
             ),
+            translations: self.translations,
             initialisation,
         })
     }
@@ -12,6 +12,8 @@ pub struct CharacterIterator<Input> {
 
 pub struct CharacterIteratorWithCoordinates<Input> {
     characters: CharacterIterator<Input>,
+    /// A character that was read ahead while checking for a `\r\n` line ending.
+    read_ahead: Option<char>,
     current_line_number: usize,
     current_column_number: usize,
 }
@@ -114,6 +116,7 @@ impl<Input> CharacterIteratorWithCoordinates<Input> {
     pub fn new(characters: CharacterIterator<Input>) -> Self {
         Self {
             characters,
+            read_ahead: None,
             current_line_number: 1,
             current_column_number: 1,
         }
@@ -122,7 +125,7 @@ impl<Input> CharacterIteratorWithCoordinates<Input> {
 
 impl<Input: Read + Unpin> CharacterIteratorWithCoordinates<Input> {
     pub async fn next(&mut self) -> Result<Option<CharacterWithCoordinates>, ParserError> {
-        match self.characters.next().await {
+        match self.next_normalised_character().await {
             Ok(Some(character)) => {
                 let result = CharacterWithCoordinates::new(
                     character,
@@ -144,6 +147,24 @@ impl<Input: Read + Unpin> CharacterIteratorWithCoordinates<Input> {
             )),
         }
     }
+
+    /// Returns the next character, replacing `\r\n` line endings with a single `\n`.
+    async fn next_normalised_character(&mut self) -> Result<Option<char>, ParserErrorKind> {
+        if let Some(character) = self.read_ahead.take() {
+            return Ok(Some(character));
+        }
+
+        match self.characters.next().await? {
+            Some('\r') => match self.characters.next().await? {
+                Some('\n') => Ok(Some('\n')),
+                next => {
+                    self.read_ahead = next;
+                    Ok(Some('\r'))
+                }
+            },
+            character => Ok(character),
+        }
+    }
 }
 
 impl<Input> PeekableCharacterIteratorWithCoordinates<Input> {
@@ -222,12 +243,10 @@ impl CharacterCoordinates {
         }
     }
 
-    #[allow(dead_code)]
     pub fn line_number(&self) -> usize {
         self.line_number
     }
 
-    #[allow(dead_code)]
     pub fn column_number(&self) -> usize {
         self.column_number
     }
@@ -245,6 +264,15 @@ impl CharacterCoordinateRange {
         self.from = self.from.min(other.from);
         self.to = self.to.max(other.to);
     }
+
+    pub fn start(&self) -> CharacterCoordinates {
+        self.from
+    }
+
+    /// The coordinates of the character after the range.
+    pub fn end(&self) -> CharacterCoordinates {
+        self.to
+    }
 }
 
 impl From<CharacterCoordinates> for CharacterCoordinateRange {
@@ -16,6 +16,8 @@ pub struct ParserError {
 pub enum ParserErrorKind {
     Io(Arc<std::io::Error>),
     MalformedTimeString(String),
+    MalformedExpression(String),
+    UnknownExpressionVariable(String),
     IllegalKeyword(String),
     ExpectedSection(TokenKind),
     ExpectedSectionOrEnd(TokenKind),
@@ -25,8 +27,12 @@ pub enum ParserErrorKind {
     UnknownBuiltinAction(String),
     ExpectedNonemptyString,
     ExpectedActionType(String),
+    ExpectedTieBreak(String),
+    ExpectedBuffKind(String),
+    ExpectedCombatStyle(String),
     ExpectedInteger(TokenKindOrString),
     ExpectedFloat(TokenKindOrString),
+    ExpectedBoolean(TokenKindOrString),
     ExpectedTime(TokenKindOrString),
     UnexpectedValue(ValueTokenKind),
     UnexpectedTriggerCondition(String),
@@ -43,25 +49,52 @@ pub enum ParserErrorKind {
     DuplicateExplorationEventIdentifier(String),
     DuplicateMonsterIdentifier(String),
     DuplicateItemIdentifier(String),
+    DuplicateCalendarEventIdentifier(String),
     DuplicateTriggerIdentifier(String),
     ReservedActionId(String),
+    CombatStyleWithAttributes(String),
     IllegalWeight(f64),
+    IllegalCombatRatio(f64),
+    IllegalEnrage(f64),
+    IllegalCalendarDate {
+        month: usize,
+        day: usize,
+    },
     IllegalMean(f64),
     IllegalVariance(f64),
     AllWeightsZero,
     IllegalActionType(PlayerActionType),
     BeginWithoutEnd,
+    NestingTooDeep {
+        max_nesting_depth: usize,
+    },
     QuestHasNoStages,
+    UnknownStartingLocation(String),
+    InactiveStartingLocation(String),
+    UnknownPrestigeQuest(String),
 
     MissingSectionInitialisation,
     MissingActionWait,
     MissingActionSleep,
     MissingActionTavern,
     MissingActionExplore,
+    WrongBuiltinActionType {
+        id_str: String,
+        action_type: PlayerActionType,
+    },
 
-    MissingField { id_str: String, field: String },
-    DuplicateField { id_str: String, field: String },
-    UnexpectedField { id_str: String, field: String },
+    MissingField {
+        id_str: String,
+        field: String,
+    },
+    DuplicateField {
+        id_str: String,
+        field: String,
+    },
+    UnexpectedField {
+        id_str: String,
+        field: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -74,6 +107,18 @@ pub fn unexpected_eof() -> ParserError {
     ParserError::without_coordinates(ParserErrorKind::UnexpectedEof)
 }
 
+impl ParserErrorKind {
+    /// False if the input cannot be parsed any further after this error.
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(
+            self,
+            ParserErrorKind::Io(_)
+                | ParserErrorKind::UnexpectedEof
+                | ParserErrorKind::BeginWithoutEnd
+        )
+    }
+}
+
 impl ParserError {
     pub fn with_coordinates(kind: ParserErrorKind, coordinates: CharacterCoordinateRange) -> Self {
         Self {
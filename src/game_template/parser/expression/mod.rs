@@ -0,0 +1,232 @@
+use crate::game_state::expression::{BinaryOperator, Expression, ExpressionVariable};
+use crate::game_template::parser::character_iterator::CharacterCoordinateRange;
+use crate::game_template::parser::error::{ParserError, ParserErrorKind};
+use crate::game_template::parser::tokenizer::RangedElement;
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExpressionToken {
+    Number(f64),
+    Variable(String),
+    Operator(BinaryOperator),
+    OpenParenthesis,
+    CloseParenthesis,
+}
+
+/// Parses an arithmetic expression such as `10 + 2 * level` from a string value.
+/// The whole value is used as the error range, since string values do not track character coordinates.
+pub fn parse_expression(input: RangedElement<String>) -> Result<Expression, ParserError> {
+    let RangedElement {
+        element: input,
+        range,
+    } = input;
+    let error = || {
+        ParserError::with_coordinates(ParserErrorKind::MalformedExpression(input.clone()), range)
+    };
+
+    let mut tokens = tokenize_expression(&input)
+        .ok_or_else(error)?
+        .into_iter()
+        .peekable();
+    let expression = parse_sum(&mut tokens, &input, range)?;
+    if tokens.next().is_some() {
+        Err(error())
+    } else {
+        Ok(expression)
+    }
+}
+
+fn tokenize_expression(input: &str) -> Option<Vec<ExpressionToken>> {
+    let mut tokens = Vec::new();
+    let mut characters = input.char_indices().peekable();
+
+    while let Some((index, character)) = characters.next() {
+        let token = match character {
+            character if character.is_whitespace() => continue,
+            '+' => ExpressionToken::Operator(BinaryOperator::Add),
+            '-' => ExpressionToken::Operator(BinaryOperator::Subtract),
+            '*' => ExpressionToken::Operator(BinaryOperator::Multiply),
+            '/' => ExpressionToken::Operator(BinaryOperator::Divide),
+            '(' => ExpressionToken::OpenParenthesis,
+            ')' => ExpressionToken::CloseParenthesis,
+            character if character.is_ascii_digit() || character == '.' => {
+                let mut end = index + character.len_utf8();
+                while let Some((index, character)) = characters
+                    .next_if(|(_, character)| character.is_ascii_digit() || *character == '.')
+                {
+                    end = index + character.len_utf8();
+                }
+                ExpressionToken::Number(input[index..end].parse().ok()?)
+            }
+            character if character.is_ascii_alphabetic() || character == '_' => {
+                let mut end = index + character.len_utf8();
+                while let Some((index, character)) = characters.next_if(|(_, character)| {
+                    character.is_ascii_alphanumeric() || *character == '_'
+                }) {
+                    end = index + character.len_utf8();
+                }
+                ExpressionToken::Variable(input[index..end].to_string())
+            }
+            _ => return None,
+        };
+        tokens.push(token);
+    }
+
+    Some(tokens)
+}
+
+fn parse_sum(
+    tokens: &mut Peekable<IntoIter<ExpressionToken>>,
+    input: &str,
+    range: CharacterCoordinateRange,
+) -> Result<Expression, ParserError> {
+    let mut result = parse_product(tokens, input, range)?;
+    while let Some(ExpressionToken::Operator(
+        operator @ (BinaryOperator::Add | BinaryOperator::Subtract),
+    )) = tokens.peek().cloned()
+    {
+        tokens.next();
+        result = Expression::binary(operator, result, parse_product(tokens, input, range)?);
+    }
+    Ok(result)
+}
+
+fn parse_product(
+    tokens: &mut Peekable<IntoIter<ExpressionToken>>,
+    input: &str,
+    range: CharacterCoordinateRange,
+) -> Result<Expression, ParserError> {
+    let mut result = parse_factor(tokens, input, range)?;
+    while let Some(ExpressionToken::Operator(
+        operator @ (BinaryOperator::Multiply | BinaryOperator::Divide),
+    )) = tokens.peek().cloned()
+    {
+        tokens.next();
+        result = Expression::binary(operator, result, parse_factor(tokens, input, range)?);
+    }
+    Ok(result)
+}
+
+fn parse_factor(
+    tokens: &mut Peekable<IntoIter<ExpressionToken>>,
+    input: &str,
+    range: CharacterCoordinateRange,
+) -> Result<Expression, ParserError> {
+    match tokens.next() {
+        Some(ExpressionToken::Number(number)) => Ok(Expression::Constant(number)),
+        Some(ExpressionToken::Variable(variable)) => ExpressionVariable::from_identifier(&variable)
+            .map(Expression::Variable)
+            .ok_or_else(|| {
+                ParserError::with_coordinates(
+                    ParserErrorKind::UnknownExpressionVariable(variable),
+                    range,
+                )
+            }),
+        Some(ExpressionToken::Operator(BinaryOperator::Subtract)) => {
+            Ok(Expression::negation(parse_factor(tokens, input, range)?))
+        }
+        Some(ExpressionToken::OpenParenthesis) => {
+            let result = parse_sum(tokens, input, range)?;
+            if tokens.next() == Some(ExpressionToken::CloseParenthesis) {
+                Ok(result)
+            } else {
+                Err(ParserError::with_coordinates(
+                    ParserErrorKind::MalformedExpression(input.to_string()),
+                    range,
+                ))
+            }
+        }
+        _ => Err(ParserError::with_coordinates(
+            ParserErrorKind::MalformedExpression(input.to_string()),
+            range,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::expression::Expression;
+    use crate::game_state::tests::create_test_game_state_with_templates;
+    use crate::game_state::world::monsters::MonsterId;
+    use crate::game_template::parser::error::ParserErrorKind;
+    use crate::game_template::parser::parse_game_template_file;
+    use crate::game_template::GameTemplate;
+
+    fn monster_template(hitpoints: &str) -> String {
+        format!(
+            "
+MONSTER expression_test
+name Expression test
+hitpoints {hitpoints}
+activation none
+deactivation never
+"
+        )
+    }
+
+    fn parse(hitpoints: &str) -> Result<Expression, ParserErrorKind> {
+        let mut game_template = GameTemplate::default();
+        async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            monster_template(hitpoints).as_bytes(),
+        ))
+        .map_err(|error| error.kind)?;
+        Ok(game_template.monsters.pop().unwrap().hitpoints)
+    }
+
+    #[test]
+    fn test_evaluate_level_expression() {
+        let mut game_state =
+            create_test_game_state_with_templates(&[&monster_template("10 + 2*level")]);
+        let monster = (0..)
+            .map(MonsterId)
+            .find(|&monster| game_state.world.monster(monster).id_str == "expression_test")
+            .unwrap();
+
+        for (level, expected) in [(1, 12.0), (2, 14.0), (5, 20.0), (100, 210.0)] {
+            game_state.character.level = level;
+            assert_eq!(
+                game_state
+                    .world
+                    .monster(monster)
+                    .hitpoints
+                    .evaluate(&game_state.character),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let game_state = create_test_game_state_with_templates(&[]);
+        for (input, expected) in [
+            ("42", 42.0),
+            ("1.5 * 4", 6.0),
+            ("2 + 3 * 4 - 6 / 2", 11.0),
+            ("(2 + 3) * 4", 20.0),
+            ("-3 + 5", 2.0),
+            ("10 - -2", 12.0),
+        ] {
+            assert_eq!(
+                parse(input).unwrap().evaluate(&game_state.character),
+                expected,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_expressions() {
+        for input in ["1 +", "(1 + 2", "1 2", "3 % 2", "1..2"] {
+            assert!(
+                matches!(parse(input), Err(ParserErrorKind::MalformedExpression(_))),
+                "{input}"
+            );
+        }
+        assert!(matches!(
+            parse("2 * mana"),
+            Err(ParserErrorKind::UnknownExpressionVariable(variable)) if variable == "mana"
+        ));
+    }
+}
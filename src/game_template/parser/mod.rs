@@ -1,12 +1,13 @@
 use crate::game_state::currency::Currency;
-use crate::game_state::triggers::{GameAction, GameEvent};
+use crate::game_state::time::GameTime;
+use crate::game_state::triggers::{leq, GameAction, GameEvent};
 use crate::game_template::parser::character_iterator::CharacterCoordinateRange;
 use crate::game_template::parser::error::{unexpected_eof, ParserError, ParserErrorKind};
-use crate::game_template::parser::section::parse_section;
+use crate::game_template::parser::section::{parse_section, GameTemplateSection};
 use crate::game_template::parser::tokenizer::{
     RangedElement, SectionTokenKind, Token, TokenIterator, TokenKind, ValueTokenKind,
 };
-use crate::game_template::GameTemplate;
+use crate::game_template::{GameTemplate, IdNamespace};
 use async_recursion::async_recursion;
 use async_std::io::Read;
 use event_trigger_action_system::{
@@ -14,10 +15,11 @@ use event_trigger_action_system::{
 };
 use log::{debug, trace};
 
-mod character_iterator;
+pub mod character_iterator;
 pub mod error;
+mod expression;
 mod section;
-mod tokenizer;
+pub mod tokenizer;
 
 #[derive(Debug)]
 pub struct WeightedIdentifier {
@@ -40,6 +42,26 @@ pub async fn parse_game_template_file(
     parse(game_template, &mut TokenIterator::new(input)).await
 }
 
+/// Parses the file like [`parse_game_template_file`], but after a recoverable error it skips to the next top-level section and continues.
+/// Returns all errors in order, ending with the first non-recoverable one if parsing was aborted.
+/// Sections nested deeper than `max_nesting_depth` are rejected, see [`TokenIterator::with_max_nesting_depth`].
+/// Sections with unknown keywords are skipped if `lenient_sections` is set, see [`TokenIterator::with_lenient_sections`].
+pub async fn parse_game_template_file_recovering(
+    game_template: &mut GameTemplate,
+    input: impl Read + Unpin + Send,
+    max_nesting_depth: usize,
+    lenient_sections: bool,
+) -> Result<(), Vec<ParserError>> {
+    debug!("Parsing game template file with error recovery");
+    parse_recovering(
+        game_template,
+        &mut TokenIterator::new(input)
+            .with_max_nesting_depth(max_nesting_depth)
+            .with_lenient_sections(lenient_sections),
+    )
+    .await
+}
+
 async fn parse(
     game_template: &mut GameTemplate,
     tokens: &mut TokenIterator<impl Read + Unpin + Send>,
@@ -50,58 +72,8 @@ async fn parse(
         next_token = match token.kind() {
             TokenKind::Section(section) => {
                 let (section_template, next_token) =
-                    parse_section(game_template, tokens, section, None).await?;
-                match section {
-                    SectionTokenKind::Initialisation => {
-                        if game_template
-                            .initialisation
-                            .replace(section_template.into_initialisation()?)
-                            .is_some()
-                        {
-                            return Err(ParserError::with_coordinates(
-                                ParserErrorKind::DuplicateInitialisation,
-                                token.range(),
-                            ));
-                        };
-                    }
-                    SectionTokenKind::BuiltinAction => {
-                        let builtin_action = section_template.into_builtin_action(game_template)?;
-                        game_template.actions.push(builtin_action);
-                    }
-                    SectionTokenKind::Action => {
-                        let action = section_template.into_action(game_template)?;
-                        game_template.actions.push(action);
-                    }
-                    SectionTokenKind::QuestStageAction => {
-                        let quest_action =
-                            section_template.into_quest_stage_action(game_template)?;
-                        game_template.actions.push(quest_action);
-                    }
-                    SectionTokenKind::Quest => {
-                        let quest = section_template.into_quest(game_template)?;
-                        game_template.quests.push(quest);
-                    }
-                    SectionTokenKind::QuestStage => {
-                        return Err(token.error(|_| ParserErrorKind::UnexpectedQuestStage));
-                    }
-                    SectionTokenKind::Location => {
-                        let location = section_template.into_location(game_template)?;
-                        game_template.locations.push(location);
-                    }
-                    SectionTokenKind::ExplorationEvent => {
-                        let exploration_event =
-                            section_template.into_exploration_event(game_template)?;
-                        game_template.exploration_events.push(exploration_event);
-                    }
-                    SectionTokenKind::Monster => {
-                        let monster = section_template.into_monster(game_template)?;
-                        game_template.monsters.push(monster);
-                    }
-                    SectionTokenKind::Item => {
-                        let item = section_template.into_item(game_template)?;
-                        game_template.items.push(item);
-                    }
-                }
+                    parse_section(game_template, tokens, section, None, 0).await?;
+                add_section(game_template, section, section_template, token.range())?;
                 next_token
             }
             _ => return Err(token.error(ParserErrorKind::ExpectedSection)),
@@ -111,6 +83,164 @@ async fn parse(
     Ok(())
 }
 
+async fn parse_recovering(
+    game_template: &mut GameTemplate,
+    tokens: &mut TokenIterator<impl Read + Unpin + Send>,
+) -> Result<(), Vec<ParserError>> {
+    let mut errors = Vec::new();
+    let mut next_token = tokens.next().await;
+    loop {
+        let error = match next_token {
+            Ok(None) => break,
+            Ok(Some(token)) => match token.kind() {
+                TokenKind::Section(section) => {
+                    match parse_section(game_template, tokens, section, None, 0).await {
+                        Ok((section_template, following_token)) => {
+                            if let Err(error) =
+                                add_section(game_template, section, section_template, token.range())
+                            {
+                                // The section was parsed completely, so there is nothing to skip.
+                                debug!("Recovering from {error:?}");
+                                errors.push(error);
+                            }
+                            next_token = Ok(following_token);
+                            continue;
+                        }
+                        Err(error) => error,
+                    }
+                }
+                _ => token.error(ParserErrorKind::ExpectedSection),
+            },
+            Err(error) => error,
+        };
+
+        if !error.kind.is_recoverable() {
+            errors.push(error);
+            break;
+        }
+        debug!("Recovering from {error:?}");
+        errors.push(error);
+        next_token = skip_to_top_level_section(tokens).await;
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Skips tokens until the next section that may appear outside of a `BEGIN`/`END` block.
+/// Recoverable errors while skipping are ignored, since they belong to the skipped section.
+async fn skip_to_top_level_section(
+    tokens: &mut TokenIterator<impl Read + Unpin + Send>,
+) -> Result<Option<Token>, ParserError> {
+    loop {
+        match tokens.next().await {
+            Ok(Some(token)) => {
+                if matches!(token.kind(), TokenKind::Section(section) if *section != SectionTokenKind::QuestStage)
+                {
+                    return Ok(Some(token));
+                }
+            }
+            Ok(None) => return Ok(None),
+            Err(error) if error.kind.is_recoverable() => {}
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+fn add_section(
+    game_template: &mut GameTemplate,
+    section: &SectionTokenKind,
+    section_template: GameTemplateSection,
+    token_range: CharacterCoordinateRange,
+) -> Result<(), ParserError> {
+    let (id_str, id_range) = section_template.id();
+    let id_namespace = match section {
+        SectionTokenKind::BuiltinAction
+        | SectionTokenKind::Action
+        | SectionTokenKind::QuestStageAction => Some(IdNamespace::Action),
+        SectionTokenKind::Quest => Some(IdNamespace::Quest),
+        SectionTokenKind::Location => Some(IdNamespace::Location),
+        SectionTokenKind::ExplorationEvent => Some(IdNamespace::ExplorationEvent),
+        SectionTokenKind::Monster => Some(IdNamespace::Monster),
+        SectionTokenKind::Item => Some(IdNamespace::Item),
+        SectionTokenKind::Initialisation
+        | SectionTokenKind::QuestStage
+        | SectionTokenKind::CalendarEvent
+        | SectionTokenKind::Destiny => None,
+    };
+
+    match section {
+        SectionTokenKind::Initialisation => {
+            if game_template
+                .initialisation
+                .replace(section_template.into_initialisation()?)
+                .is_some()
+            {
+                return Err(ParserError::with_coordinates(
+                    ParserErrorKind::DuplicateInitialisation,
+                    token_range,
+                ));
+            };
+        }
+        SectionTokenKind::BuiltinAction => {
+            let builtin_action = section_template.into_builtin_action(game_template)?;
+            game_template.actions.push(builtin_action);
+        }
+        SectionTokenKind::Action => {
+            let action = section_template.into_action(game_template)?;
+            game_template.actions.push(action);
+        }
+        SectionTokenKind::QuestStageAction => {
+            let quest_action = section_template.into_quest_stage_action(game_template)?;
+            game_template.actions.push(quest_action);
+        }
+        SectionTokenKind::Quest => {
+            let quest = section_template.into_quest(game_template)?;
+            game_template.quests.push(quest);
+        }
+        SectionTokenKind::QuestStage => {
+            return Err(ParserError::with_coordinates(
+                ParserErrorKind::UnexpectedQuestStage,
+                token_range,
+            ));
+        }
+        SectionTokenKind::Location => {
+            let location = section_template.into_location(game_template)?;
+            game_template.locations.push(location);
+        }
+        SectionTokenKind::ExplorationEvent => {
+            let exploration_event = section_template.into_exploration_event(game_template)?;
+            game_template.exploration_events.push(exploration_event);
+        }
+        SectionTokenKind::Monster => {
+            let monster = section_template.into_monster(game_template)?;
+            game_template.monsters.push(monster);
+        }
+        SectionTokenKind::Item => {
+            let item = section_template.into_item(game_template)?;
+            game_template.items.push(item);
+        }
+        SectionTokenKind::CalendarEvent => {
+            let calendar_event = section_template.into_calendar_event()?;
+            game_template.calendar_events.push(calendar_event);
+        }
+        SectionTokenKind::Destiny => {
+            let destiny = section_template.into_destiny()?;
+            game_template.destinies.push(destiny);
+        }
+    }
+
+    if let Some(id_namespace) = id_namespace {
+        game_template
+            .id_ranges
+            .push((id_namespace, id_str, id_range));
+    }
+    Ok(())
+}
+
 async fn parse_trigger<'trigger>(
     game_template: &'trigger mut GameTemplate,
     tokens: &mut TokenIterator<impl Read + Unpin + Send>,
@@ -175,6 +305,14 @@ async fn parse_trigger_condition(
             expect_close_parenthesis(tokens).await?;
             event_count(GameEvent::ActionCompleted { id: action }, count as usize)
         }
+        "action_started_count" => {
+            expect_open_parenthesis(tokens).await?;
+            let count = expect_integer(tokens).await?.element;
+            expect_comma(tokens).await?;
+            let action = expect_identifier(tokens).await?.element;
+            expect_close_parenthesis(tokens).await?;
+            event_count(GameEvent::ActionStarted { id: action }, count as usize)
+        }
         "monster_killed_count" => {
             expect_open_parenthesis(tokens).await?;
             let count = expect_integer(tokens).await?.element;
@@ -189,6 +327,37 @@ async fn parse_trigger_condition(
             expect_close_parenthesis(tokens).await?;
             geq(GameEvent::PlayerLevelChanged { value: level })
         }
+        "level_leq" => {
+            expect_open_parenthesis(tokens).await?;
+            let level = expect_integer(tokens).await?.element;
+            expect_close_parenthesis(tokens).await?;
+            leq(GameEvent::PlayerLevelChanged { value: level })
+        }
+        "idle_for" => {
+            expect_open_parenthesis(tokens).await?;
+            let duration = expect_time(tokens).await?.element;
+            expect_close_parenthesis(tokens).await?;
+            geq(GameEvent::PlayerIdle { duration })
+        }
+        "currency_leq" => {
+            expect_open_parenthesis(tokens).await?;
+            let copper = expect_integer(tokens).await?.element;
+            expect_close_parenthesis(tokens).await?;
+            leq(GameEvent::CurrencyChanged {
+                value: Currency::from_copper(copper.into()),
+            })
+        }
+        "calendar_event_count" => {
+            expect_open_parenthesis(tokens).await?;
+            let count = expect_integer(tokens).await?.element;
+            expect_comma(tokens).await?;
+            let calendar_event = expect_identifier(tokens).await?.element;
+            expect_close_parenthesis(tokens).await?;
+            event_count(
+                GameEvent::CalendarEventOccurred { id: calendar_event },
+                count as usize,
+            )
+        }
         "explore_count" => {
             expect_open_parenthesis(tokens).await?;
             let count = expect_integer(tokens).await?.element;
@@ -221,19 +390,16 @@ async fn parse_trigger_condition(
             expect_close_parenthesis(tokens).await?;
             event_count(GameEvent::Action(GameAction::FailQuest { id: quest }), 1)
         }
-        "item_count" => {
+        "item_count" | "item_count_geq" => {
             expect_open_parenthesis(tokens).await?;
             let count = expect_integer(tokens).await?.element;
             expect_comma(tokens).await?;
             let item = expect_identifier(tokens).await?.element;
             expect_close_parenthesis(tokens).await?;
-            event_count(
-                GameEvent::ItemCountChanged {
-                    id: item,
-                    count: count as usize,
-                },
-                1,
-            )
+            geq(GameEvent::ItemCountChanged {
+                id: item,
+                count: count as usize,
+            })
         }
         _ => {
             return Err(ParserError::with_coordinates(
@@ -342,6 +508,12 @@ async fn parse_game_event(
             })
             .await?
         }
+        "calendar_event_occurred" => {
+            parse_f_identifier(tokens, |identifier| GameEvent::CalendarEventOccurred {
+                id: identifier,
+            })
+            .await?
+        }
         _ => {
             return Err(ParserError::with_coordinates(
                 ParserErrorKind::UnexpectedGameEvent(identifier),
@@ -523,6 +695,19 @@ async fn expect_float(
     }
 }
 
+async fn expect_time(
+    tokens: &mut TokenIterator<impl Read + Unpin + Send>,
+) -> Result<RangedElement<GameTime>, ParserError> {
+    let (kind, range) = expect_any(tokens).await?.decompose();
+    match kind {
+        TokenKind::Value(ValueTokenKind::Time(time)) => Ok(RangedElement::new(time, range)),
+        other => Err(ParserError::with_coordinates(
+            ParserErrorKind::ExpectedTime(other.into()),
+            range,
+        )),
+    }
+}
+
 async fn expect_open_parenthesis(
     tokens: &mut TokenIterator<impl Read + Unpin + Send>,
 ) -> Result<CharacterCoordinateRange, ParserError> {
@@ -586,3 +771,513 @@ impl ExpectedIdentifierCount {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::inventory::item::ItemId;
+    use crate::game_state::player_actions::{
+        PlayerActionId, PlayerActionInProgressKind, ACTION_EXPLORE,
+    };
+    use crate::game_state::tests::{create_test_game_state_with_templates, execute_test_event};
+    use crate::game_state::time::GameTime;
+    use crate::game_state::triggers::CompiledGameEvent;
+    use crate::game_state::{GameState, GameStateMessage};
+    use crate::game_template::parser::error::ParserErrorKind;
+    use crate::game_template::parser::tokenizer::DEFAULT_MAX_NESTING_DEPTH;
+    use crate::game_template::parser::{
+        parse_game_template_file, parse_game_template_file_recovering,
+    };
+    use crate::game_template::GameTemplate;
+
+    const ITEM_COUNT_TEMPLATE: &str = "
+ITEM pelt
+name Pelt
+description The pelt of a rat.
+value 1
+activation none
+deactivation never
+
+QUEST collect_pelts
+title Collect pelts
+activation none
+failure never
+BEGIN
+    QUEST_STAGE collect_pelt_stage
+    task Collect four pelts.
+    completion item_count(4, pelt)
+END
+";
+
+    fn collect_stage_progress(game_state: &GameState) -> Option<(f64, f64)> {
+        let quest = game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.id_str == "collect_pelts")
+            .unwrap();
+        quest.active_stage().map(|stage| {
+            game_state
+                .triggers
+                .progress(stage.completion_condition)
+                .unwrap()
+        })
+    }
+
+    #[test]
+    fn test_item_count_condition() {
+        let mut game_state = create_test_game_state_with_templates(&[ITEM_COUNT_TEMPLATE]);
+        let pelt = (0..)
+            .map(ItemId)
+            .find(|&item| game_state.inventory.item(item).id_str == "pelt")
+            .unwrap();
+
+        for count in 1..4 {
+            execute_test_event(
+                &mut game_state,
+                CompiledGameEvent::ItemCountChanged { id: pelt, count },
+            );
+            let (progress, goal) = collect_stage_progress(&game_state).unwrap();
+            assert_eq!(goal, 2.0);
+            assert_eq!(progress, 1.0 + count as f64 / 4.0);
+        }
+
+        execute_test_event(
+            &mut game_state,
+            CompiledGameEvent::ItemCountChanged { id: pelt, count: 4 },
+        );
+        assert_eq!(collect_stage_progress(&game_state), None);
+    }
+
+    #[test]
+    fn test_item_count_geq_condition() {
+        let geq_template = ITEM_COUNT_TEMPLATE.replace("item_count(", "item_count_geq(");
+        for template in [ITEM_COUNT_TEMPLATE, geq_template.as_str()] {
+            let mut game_state = create_test_game_state_with_templates(&[template]);
+            let pelt = (0..)
+                .map(ItemId)
+                .find(|&item| game_state.inventory.item(item).id_str == "pelt")
+                .unwrap();
+
+            execute_test_event(
+                &mut game_state,
+                CompiledGameEvent::ItemCountChanged { id: pelt, count: 2 },
+            );
+            assert_eq!(collect_stage_progress(&game_state), Some((1.5, 2.0)));
+
+            // Exceeding the count without reaching it exactly still completes the stage.
+            execute_test_event(
+                &mut game_state,
+                CompiledGameEvent::ItemCountChanged { id: pelt, count: 6 },
+            );
+            assert_eq!(collect_stage_progress(&game_state), None);
+        }
+    }
+
+    const ACTION_STARTED_TEMPLATE: &str = "
+ACTION attempt_study
+name Study
+progressive studying
+simple_past studied
+type TRAIN
+duration 1h
+int 1.0
+currency 0
+activation none
+deactivation never
+
+QUEST attempt_started
+title Attempt to study
+activation none
+failure never
+BEGIN
+    QUEST_STAGE attempt_started_stage
+    task Start studying twice.
+    completion action_started_count(2, attempt_study)
+END
+
+QUEST attempt_completed
+title Finish studying
+activation none
+failure never
+BEGIN
+    QUEST_STAGE attempt_completed_stage
+    task Study once.
+    completion action_count(1, attempt_study)
+END
+";
+
+    fn is_quest_completed(game_state: &GameState, id_str: &str) -> bool {
+        game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.id_str == id_str)
+            .unwrap()
+            .state()
+            .is_completed()
+    }
+
+    fn start_and_cancel(game_state: &mut GameState, action: PlayerActionId) {
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(action)));
+        while game_state.actions.in_progress().source.action_id() != action {
+            game_state.update_offline(1_000);
+        }
+        game_state.current_time += GameTime::from_minutes(10);
+        game_state.cancel_current_action();
+    }
+
+    #[test]
+    fn test_action_started_count_condition() {
+        let mut game_state = create_test_game_state_with_templates(&[ACTION_STARTED_TEMPLATE]);
+        let study = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "attempt_study")
+            .unwrap()
+            .id;
+
+        start_and_cancel(&mut game_state, study);
+        assert!(!is_quest_completed(&game_state, "attempt_started"));
+
+        // canceled actions count as started, but not as completed
+        start_and_cancel(&mut game_state, study);
+        assert!(is_quest_completed(&game_state, "attempt_started"));
+        assert!(!is_quest_completed(&game_state, "attempt_completed"));
+    }
+
+    const UNKNOWN_SECTION_TEMPLATE: &str = "
+ACTION lenient_known
+name Known
+progressive knowing
+simple_past knew
+type TRAIN
+str 1.0
+duration 1h
+currency 0
+activation none
+deactivation never
+
+FUTURE_SECTION lenient_future
+name Future
+colour red
+BEGIN
+    ACTION lenient_nested
+    name Nested
+END
+
+ITEM lenient_item
+name Item
+description An item after the unknown section.
+value 1
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_unknown_section_in_lenient_mode() {
+        let mut game_template = GameTemplate::default();
+        async_std::task::block_on(parse_game_template_file_recovering(
+            &mut game_template,
+            UNKNOWN_SECTION_TEMPLATE.as_bytes(),
+            DEFAULT_MAX_NESTING_DEPTH,
+            true,
+        ))
+        .unwrap();
+        let actions: Vec<_> = game_template
+            .actions
+            .iter()
+            .map(|action| action.id_str.as_str())
+            .collect();
+        assert_eq!(actions, ["lenient_known"]);
+        assert_eq!(game_template.items.len(), 1);
+        assert_eq!(game_template.items[0].id_str, "lenient_item");
+    }
+
+    #[test]
+    fn test_unknown_section_in_strict_mode() {
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut GameTemplate::default(),
+            UNKNOWN_SECTION_TEMPLATE.as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(
+            matches!(&error.kind, ParserErrorKind::IllegalKeyword(keyword) if keyword == "FUTURE_SECTION"),
+            "{error:?}"
+        );
+    }
+
+    const MONSTER_POOL_TEMPLATE: &str = "
+LOCATION arena
+name Arena
+monster_pool (1, arena_rat)
+combat_ratio 0.9
+activation none
+deactivation never
+
+MONSTER arena_rat
+name Arena rat
+hitpoints 10.0
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_monster_pool_location() {
+        let mut game_state = create_test_game_state_with_templates(&[MONSTER_POOL_TEMPLATE]);
+        let arena = game_state
+            .world
+            .active_locations()
+            .find(|location| location.id_str == "arena")
+            .unwrap();
+        assert!(arena.events.is_empty());
+        assert_eq!(arena.combat_ratio, 0.9);
+        let arena = arena.id;
+
+        let arena_rat = game_state.world.location(arena).monsters[0].id;
+        assert_eq!(game_state.world.monster(arena_rat).id_str, "arena_rat");
+        assert!(game_state.world.select_location(arena));
+        for _ in 0..10 {
+            let action = game_state
+                .world
+                .explore(
+                    &mut game_state.rng,
+                    game_state.current_time,
+                    GameTime::from_hours(1),
+                    &game_state.character,
+                    game_state.difficulty,
+                    1.0,
+                )
+                .unwrap();
+            assert_eq!(action.source.action_id(), ACTION_EXPLORE);
+            assert!(matches!(
+                action.kind,
+                PlayerActionInProgressKind::Combat(monster) if monster == arena_rat
+            ));
+        }
+    }
+
+    #[test]
+    fn test_illegal_combat_ratio() {
+        let template = MONSTER_POOL_TEMPLATE.replace("combat_ratio 0.9", "combat_ratio 1.5");
+        let mut game_template = GameTemplate::default();
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            template.as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::IllegalCombatRatio(combat_ratio) if combat_ratio == 1.5
+        ));
+    }
+
+    #[test]
+    fn test_illegal_enrage() {
+        let template = "
+MONSTER frenzied_rat
+name Frenzied rat
+hitpoints 10
+enrage -0.5
+activation none
+deactivation never
+";
+        let mut game_template = GameTemplate::default();
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            template.as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::IllegalEnrage(enrage) if enrage == -0.5
+        ));
+    }
+
+    #[test]
+    fn test_illegal_calendar_date() {
+        let template = "
+CALENDAR_EVENT leap_day
+name Leap day
+month 2
+day 29
+";
+        let mut game_template = GameTemplate::default();
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            template.as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::IllegalCalendarDate { month: 2, day: 29 }
+        ));
+    }
+
+    const TWO_BROKEN_SECTIONS_TEMPLATE: &str = "
+ACTION first_broken
+name First broken
+progressive breaking
+simple_past broke
+type TRAIN
+duration 1x
+currency 0
+activation none
+deactivation never
+
+ITEM intact_item
+name Intact item
+description Not broken.
+value 1
+activation none
+deactivation never
+
+QUEST second_broken
+title Second broken
+activation none
+failure never
+BEGIN
+    QUEST_STAGE second_broken_stage
+    task Break.
+    colour blue
+    completion never
+END
+
+ITEM trailing_item
+name Trailing item
+description Also not broken.
+value 1
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_recover_from_independent_errors() {
+        let mut game_template = GameTemplate::default();
+        let errors = async_std::task::block_on(parse_game_template_file_recovering(
+            &mut game_template,
+            TWO_BROKEN_SECTIONS_TEMPLATE.as_bytes(),
+            DEFAULT_MAX_NESTING_DEPTH,
+            false,
+        ))
+        .unwrap_err();
+
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(matches!(
+            &errors[0].kind,
+            ParserErrorKind::MalformedTimeString(time) if time == "1x"
+        ));
+        assert!(matches!(
+            &errors[1].kind,
+            ParserErrorKind::IllegalKeyword(keyword) if keyword == "colour"
+        ));
+        assert_eq!(
+            errors[1]
+                .coordinates
+                .as_ref()
+                .unwrap()
+                .start()
+                .line_number(),
+            26
+        );
+        assert_eq!(
+            game_template
+                .items
+                .iter()
+                .map(|item| item.id_str.as_str())
+                .collect::<Vec<_>>(),
+            vec!["intact_item", "trailing_item"]
+        );
+
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut GameTemplate::default(),
+            TWO_BROKEN_SECTIONS_TEMPLATE.as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::MalformedTimeString(_)
+        ));
+    }
+
+    #[test]
+    fn test_recovery_stops_at_eof() {
+        let template = TWO_BROKEN_SECTIONS_TEMPLATE.to_string() + "\nITEM truncated_item\nname";
+        let errors = async_std::task::block_on(parse_game_template_file_recovering(
+            &mut GameTemplate::default(),
+            template.as_bytes(),
+            DEFAULT_MAX_NESTING_DEPTH,
+            false,
+        ))
+        .unwrap_err();
+
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(matches!(errors[2].kind, ParserErrorKind::UnexpectedEof));
+    }
+
+    /// A quest whose first stage contains the given number of nested `BEGIN` blocks.
+    fn nested_template(nesting_depth: usize) -> String {
+        let mut template =
+            "QUEST nested_quest\ntitle Nested\nactivation none\nfailure never\n".to_string();
+        for depth in 0..nesting_depth {
+            template +=
+                &format!("BEGIN\nQUEST_STAGE nested_stage_{depth}\ntask Nest.\ncompletion never\n");
+        }
+        for _ in 0..nesting_depth {
+            template += "END\n";
+        }
+        template
+    }
+
+    #[test]
+    fn test_nesting_depth_limit() {
+        let errors = async_std::task::block_on(parse_game_template_file_recovering(
+            &mut GameTemplate::default(),
+            nested_template(4).as_bytes(),
+            3,
+            false,
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            errors[0].kind,
+            ParserErrorKind::NestingTooDeep {
+                max_nesting_depth: 3
+            }
+        ));
+        // the fourth BEGIN is on line 17
+        assert_eq!(
+            errors[0]
+                .coordinates
+                .as_ref()
+                .unwrap()
+                .start()
+                .line_number(),
+            17
+        );
+
+        // nesting exactly as deep as the limit is allowed
+        let result = async_std::task::block_on(parse_game_template_file_recovering(
+            &mut GameTemplate::default(),
+            nested_template(3).as_bytes(),
+            3,
+            false,
+        ));
+        if let Err(errors) = result {
+            assert!(errors
+                .iter()
+                .all(|error| !matches!(error.kind, ParserErrorKind::NestingTooDeep { .. })));
+        }
+    }
+
+    #[test]
+    fn test_deep_nesting_does_not_overflow() {
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut GameTemplate::default(),
+            nested_template(100_000).as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::NestingTooDeep {
+                max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH
+            }
+        ));
+    }
+}
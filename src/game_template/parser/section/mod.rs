@@ -1,28 +1,35 @@
-use crate::game_state::character::{CharacterAttributeProgress, CharacterAttributeProgressFactor};
+use crate::game_state::buffs::Buff;
+use crate::game_state::calendar::CalendarEvent;
+use crate::game_state::character::{
+    CharacterAttributeProgress, CharacterAttributeProgressFactor, CharacterAttributes, CombatStyle,
+};
 use crate::game_state::currency::Currency;
+use crate::game_state::destiny::Destiny;
+use crate::game_state::expression::Expression;
 use crate::game_state::inventory::item::Item;
 use crate::game_state::player_actions::{PlayerAction, PlayerActionType};
 use crate::game_state::story::quests::quest_stages::QuestStage;
 use crate::game_state::story::quests::Quest;
-use crate::game_state::time::GameTime;
+use crate::game_state::time::{GameTime, DAYS_PER_MONTH};
 use crate::game_state::triggers::{GameAction, GameEvent};
 use crate::game_state::world::events::{ExplorationEvent, ExplorationEventKind};
-use crate::game_state::world::locations::Location;
+use crate::game_state::world::locations::{Location, DEFAULT_COMBAT_RATIO};
 use crate::game_state::world::monsters::Monster;
-use crate::game_template::game_initialisation::GameInitialisation;
+use crate::game_template::game_initialisation::{default_starting_time, GameInitialisation};
 use crate::game_template::parser::character_iterator::CharacterCoordinateRange;
 use crate::game_template::parser::error::{unexpected_eof, ParserError, ParserErrorKind};
+use crate::game_template::parser::expression::parse_expression;
 use crate::game_template::parser::tokenizer::{
     KeyTokenKind, RangedElement, SectionTokenKind, Token, TokenIterator, TokenKind, ValueTokenKind,
 };
 use crate::game_template::parser::{
-    expect_identifier, parse_expected_identifier_counts, parse_trigger, parse_weighted_identifiers,
-    ExpectedIdentifierCount, WeightedIdentifier,
+    expect_identifier, expect_integer, parse_expected_identifier_counts, parse_trigger,
+    parse_weighted_identifiers, ExpectedIdentifierCount, WeightedIdentifier,
 };
 use crate::game_template::GameTemplate;
 use async_recursion::async_recursion;
 use async_std::io::Read;
-use event_trigger_action_system::{event_count, or, Trigger, TriggerCondition};
+use event_trigger_action_system::{event_count, geq, or, Trigger, TriggerCondition};
 use log::trace;
 use section_parser_derive::SectionParser;
 use std::mem;
@@ -33,6 +40,8 @@ pub struct GameTemplateSection {
     id_range: CharacterCoordinateRange,
     name: Option<RangedElement<String>>,
     url: Option<RangedElement<String>>,
+    sound: Option<RangedElement<String>>,
+    category: Option<RangedElement<String>>,
     progressive: Option<RangedElement<String>>,
     simple_past: Option<RangedElement<String>>,
     title: Option<RangedElement<String>>,
@@ -48,23 +57,44 @@ pub struct GameTemplateSection {
     intelligence: Option<RangedElement<f64>>,
     wisdom: Option<RangedElement<f64>>,
     charisma: Option<RangedElement<f64>>,
+    normalize: Option<RangedElement<bool>>,
+    combat_style: Option<RangedElement<String>>,
     currency: Option<RangedElement<Currency>>,
+    allow_debt: Option<RangedElement<bool>>,
     value: Option<RangedElement<Currency>>,
     items: Option<RangedElement<Vec<ExpectedIdentifierCount>>>,
+    buff: Option<RangedElement<String>>,
+    buff_duration: Option<RangedElement<GameTime>>,
 
     type_name: Option<RangedElement<String>>,
     duration: Option<RangedElement<GameTime>>,
+    time_limit: Option<RangedElement<GameTime>>,
+    then: Option<RangedElement<String>>,
     events: Option<RangedElement<Vec<WeightedIdentifier>>>,
+    monster_pool: Option<RangedElement<Vec<WeightedIdentifier>>>,
+    combat_ratio: Option<RangedElement<f64>>,
     monster: Option<RangedElement<String>>,
-    hitpoints: Option<RangedElement<f64>>,
+    hitpoints: Option<RangedElement<Expression>>,
+    enrage: Option<RangedElement<f64>>,
+    weight: Option<RangedElement<f64>>,
+    tie_break: Option<RangedElement<String>>,
+
+    min_level: Option<RangedElement<u64>>,
+    max_level: Option<RangedElement<u64>>,
+    max_count: Option<RangedElement<u64>>,
+
+    month: Option<RangedElement<u64>>,
+    day: Option<RangedElement<u64>>,
 
     activation: Option<RangedElement<String>>,
+    manual_activation: Option<RangedElement<bool>>,
     deactivation: Option<RangedElement<String>>,
     completion: Option<RangedElement<String>>,
     failure: Option<RangedElement<String>>,
 
     starting_location: Option<RangedElement<String>>,
     starting_time: Option<RangedElement<GameTime>>,
+    prestige_quest: Option<RangedElement<String>>,
 
     subsections: Option<RangedElement<Vec<GameTemplateSection>>>,
 }
@@ -89,6 +119,7 @@ pub async fn parse_section<'parent_id: 'async_recursion>(
     tokens: &mut TokenIterator<impl Read + Unpin + Send>,
     section_kind: &SectionTokenKind,
     parent_id: Option<&'parent_id str>,
+    nesting_depth: usize,
 ) -> Result<(GameTemplateSection, Option<Token>), ParserError> {
     trace!("Parsing section {section_kind:?}");
     let (id_str, id_range) = if section_kind == &SectionTokenKind::Initialisation {
@@ -97,6 +128,7 @@ pub async fn parse_section<'parent_id: 'async_recursion>(
         expect_identifier(tokens).await?.decompose()
     };
     let mut section = GameTemplateSection::new(id_str.clone(), id_range);
+    let mut translations = Vec::new();
     let mut next_token = None;
 
     while let Some(token) = tokens.next().await? {
@@ -105,321 +137,40 @@ pub async fn parse_section<'parent_id: 'async_recursion>(
             TokenKind::Section(section) => {
                 next_token = Some(Token::new(TokenKind::Section(section), range));
             }
-            TokenKind::Key(key) => match key {
-                KeyTokenKind::Name => {
-                    section.set_name(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Url => {
-                    section.set_url(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Progressive => {
-                    section.set_progressive(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::SimplePast => {
-                    section.set_simple_past(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Title => {
-                    section.set_title(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Description => {
-                    section.set_description(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Task => {
-                    section.set_task(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-
-                KeyTokenKind::Quest => {
-                    section.set_quest(RangedElement::new(
-                        expect_identifier(tokens).await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::QuestStage => {
-                    section.set_quest_stage(RangedElement::new(
-                        expect_identifier(tokens).await?.element,
-                        range,
-                    ))?;
-                }
-
-                KeyTokenKind::Strength => {
-                    let strength = tokens.expect_string_value().await?;
-                    let parsed = strength.element.parse();
-                    section.set_strength(RangedElement::new(
-                        parsed.map_err(move |_| {
-                            ParserError::with_coordinates(
-                                ParserErrorKind::ExpectedFloat(strength.element.into()),
-                                strength.range,
-                            )
-                        })?,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Stamina => {
-                    let stamina = tokens.expect_string_value().await?;
-                    let parsed = stamina.element.parse();
-                    section.set_stamina(RangedElement::new(
-                        parsed.map_err(move |_| {
-                            ParserError::with_coordinates(
-                                ParserErrorKind::ExpectedFloat(stamina.element.into()),
-                                stamina.range,
-                            )
-                        })?,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Dexterity => {
-                    let dexterity = tokens.expect_string_value().await?;
-                    let parsed = dexterity.element.parse();
-                    section.set_dexterity(RangedElement::new(
-                        parsed.map_err(move |_| {
-                            ParserError::with_coordinates(
-                                ParserErrorKind::ExpectedFloat(dexterity.element.into()),
-                                dexterity.range,
-                            )
-                        })?,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Intelligence => {
-                    let intelligence = tokens.expect_string_value().await?;
-                    let parsed = intelligence.element.parse();
-                    section.set_intelligence(RangedElement::new(
-                        parsed.map_err(move |_| {
-                            ParserError::with_coordinates(
-                                ParserErrorKind::ExpectedFloat(intelligence.element.into()),
-                                intelligence.range,
-                            )
-                        })?,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Wisdom => {
-                    let wisdom = tokens.expect_string_value().await?;
-                    let parsed = wisdom.element.parse();
-                    section.set_wisdom(RangedElement::new(
-                        parsed.map_err(move |_| {
-                            ParserError::with_coordinates(
-                                ParserErrorKind::ExpectedFloat(wisdom.element.into()),
-                                wisdom.range,
-                            )
-                        })?,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Charisma => {
-                    let charisma = tokens.expect_string_value().await?;
-                    let parsed = charisma.element.parse();
-                    section.set_charisma(RangedElement::new(
-                        parsed.map_err(move |_| {
-                            ParserError::with_coordinates(
-                                ParserErrorKind::ExpectedFloat(charisma.element.into()),
-                                charisma.range,
-                            )
-                        })?,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Currency | KeyTokenKind::Value => {
-                    if let Some(token) = tokens.next().await? {
-                        let (kind, range) = token.decompose();
-                        match kind {
-                            TokenKind::Value(ValueTokenKind::Integer(integer)) => match key {
-                                KeyTokenKind::Currency => {
-                                    section.set_currency(RangedElement::new(
-                                        Currency::from_copper(integer.into()),
-                                        range,
-                                    ))?
-                                }
-                                KeyTokenKind::Value => section.set_value(RangedElement::new(
-                                    Currency::from_copper(integer.into()),
-                                    range,
-                                ))?,
-                                _ => unreachable!(),
-                            },
-                            kind => {
-                                return Err(ParserError::with_coordinates(
-                                    ParserErrorKind::ExpectedInteger(kind.into()),
-                                    range,
-                                ));
-                            }
-                        }
-                    } else {
-                        return Err(unexpected_eof());
-                    }
-                }
-                KeyTokenKind::Items => {
-                    section.set_items(parse_expected_identifier_counts(tokens).await?)?;
-                }
-
-                KeyTokenKind::Type => {
-                    section.set_type_name(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Duration => {
-                    if let Some(token) = tokens.next().await? {
-                        let (kind, range) = token.decompose();
-                        match kind {
-                            TokenKind::Value(ValueTokenKind::Time(time)) => {
-                                section.set_duration(RangedElement::new(time, range))?;
-                            }
-                            kind => {
-                                return Err(ParserError::with_coordinates(
-                                    ParserErrorKind::ExpectedTime(kind.into()),
-                                    range,
-                                ));
-                            }
-                        }
-                    } else {
-                        return Err(unexpected_eof());
-                    }
-                }
-                KeyTokenKind::Events => {
-                    section.set_events(parse_weighted_identifiers(tokens).await?)?;
-                }
-                KeyTokenKind::Monsters => {
-                    section.set_monster(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Hitpoints => {
-                    let hitpoints = tokens.expect_string_value().await?;
-                    let parsed = hitpoints.element.parse();
-                    section.set_hitpoints(RangedElement::new(
-                        parsed.map_err(move |_| {
-                            ParserError::with_coordinates(
-                                ParserErrorKind::ExpectedFloat(hitpoints.element.into()),
-                                hitpoints.range,
-                            )
-                        })?,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::Activation => {
-                    let id_str = format!(
-                        "{}_{}_activation",
-                        section_kind.to_snake_case_string(),
-                        section.id_str
-                    );
-                    parse_trigger(
-                        game_template,
-                        tokens,
-                        id_str.clone(),
-                        vec![section_kind
-                            .activation_action(section.id_str.clone(), section.id_range)?],
-                    )
-                    .await?;
-                    section.set_activation(RangedElement::new(id_str, range))?;
-                }
-                KeyTokenKind::Deactivation => {
-                    let id_str = format!(
-                        "{}_{}_deactivation",
-                        section_kind.to_snake_case_string(),
-                        section.id_str
-                    );
-                    parse_trigger(
-                        game_template,
-                        tokens,
-                        id_str.clone(),
-                        vec![section_kind
-                            .deactivation_action(section.id_str.clone(), section.id_range)?],
-                    )
-                    .await?;
-                    section.set_activation(RangedElement::new(id_str, range))?;
-                }
-                KeyTokenKind::Completion => {
-                    let id_str = format!(
-                        "{}_{}_completion",
-                        section_kind.to_snake_case_string(),
-                        section.id_str
-                    );
-                    parse_trigger(
-                        game_template,
-                        tokens,
-                        id_str.clone(),
-                        vec![GameAction::CompleteQuestStage {
-                            quest_id: parent_id
-                                .ok_or_else(|| {
-                                    ParserError::with_coordinates(
-                                        ParserErrorKind::UnexpectedField {
-                                            id_str: section.id_str.clone(),
-                                            field: "completion".to_string(),
-                                        },
-                                        range,
-                                    )
-                                })?
-                                .to_string(),
-                            stage_id: section.id_str.clone(),
-                        }],
-                    )
-                    .await?;
-                    section.set_completion(RangedElement::new(id_str, range))?;
-                }
-                KeyTokenKind::Failure => {
-                    let id_str = format!(
-                        "{}_{}_fail",
-                        section_kind.to_snake_case_string(),
-                        section.id_str
-                    );
-                    parse_trigger(
-                        game_template,
-                        tokens,
-                        id_str.clone(),
-                        vec![GameAction::FailQuest {
-                            id: section.id_str.clone(),
-                        }],
-                    )
-                    .await?;
-                    section.set_failure(RangedElement::new(id_str, range))?;
-                }
-                KeyTokenKind::StartingLocation => {
-                    section.set_starting_location(RangedElement::new(
-                        tokens.expect_string_value().await?.element,
-                        range,
-                    ))?;
-                }
-                KeyTokenKind::StartingTime => {
-                    if let Some(token) = tokens.next().await? {
-                        let (kind, range) = token.decompose();
-                        match kind {
-                            TokenKind::Value(ValueTokenKind::Time(time)) => {
-                                section.set_starting_time(RangedElement::new(time, range))?;
-                            }
-                            kind => {
-                                return Err(ParserError::with_coordinates(
-                                    ParserErrorKind::ExpectedTime(kind.into()),
-                                    range,
-                                ));
-                            }
-                        }
-                    } else {
-                        return Err(unexpected_eof());
-                    }
-                }
-            },
+            TokenKind::Key(key) if !section_kind.allowed_keys().contains(&key) => {
+                return Err(ParserError::with_coordinates(
+                    ParserErrorKind::UnexpectedField {
+                        id_str: section.id_str,
+                        field: key.keyword().to_string(),
+                    },
+                    range,
+                ));
+            }
+            TokenKind::TranslatedKey { key, .. } if !section_kind.allowed_keys().contains(&key) => {
+                return Err(ParserError::with_coordinates(
+                    ParserErrorKind::UnexpectedField {
+                        id_str: section.id_str,
+                        field: key.keyword().to_string(),
+                    },
+                    range,
+                ));
+            }
+            TokenKind::TranslatedKey { key, language } => {
+                let translation = tokens.expect_string_value().await?.element;
+                translations.push((key, language, RangedElement::new(translation, range)));
+            }
+            TokenKind::Key(key) => {
+                parse_key_value(
+                    game_template,
+                    tokens,
+                    &mut section,
+                    section_kind,
+                    parent_id,
+                    key,
+                    range,
+                )
+                .await?
+            }
             TokenKind::Value(value) => {
                 return Err(ParserError::with_coordinates(
                     ParserErrorKind::UnexpectedValue(value),
@@ -427,6 +178,14 @@ pub async fn parse_section<'parent_id: 'async_recursion>(
                 ));
             }
             TokenKind::Begin => {
+                if nesting_depth >= tokens.max_nesting_depth() {
+                    return Err(ParserError::with_coordinates(
+                        ParserErrorKind::NestingTooDeep {
+                            max_nesting_depth: tokens.max_nesting_depth(),
+                        },
+                        range,
+                    ));
+                }
                 let mut subsections = Vec::new();
                 if let Some(token) = tokens.next().await? {
                     let mut current_section_token = Some(token);
@@ -439,6 +198,7 @@ pub async fn parse_section<'parent_id: 'async_recursion>(
                                     tokens,
                                     &section_token,
                                     Some(&id_str),
+                                    nesting_depth + 1,
                                 )
                                 .await?;
                                 subsections.push(subsection);
@@ -469,9 +229,560 @@ pub async fn parse_section<'parent_id: 'async_recursion>(
         }
     }
 
+    for (key, language, translation) in translations {
+        let default = match key {
+            KeyTokenKind::Name => section.name.as_ref(),
+            KeyTokenKind::Title => section.title.as_ref(),
+            KeyTokenKind::Description => section.description.as_ref(),
+            KeyTokenKind::Task => section.task.as_ref(),
+            _ => unreachable!("{key:?} is not translatable"),
+        };
+        let default = default.ok_or_else(|| {
+            ParserError::with_coordinates(
+                ParserErrorKind::MissingField {
+                    id_str: id_str.clone(),
+                    field: key.keyword().to_string(),
+                },
+                translation.range,
+            )
+        })?;
+        game_template
+            .translations
+            .add(&default.element, &language, translation.element);
+    }
+
     Ok((section, next_token))
 }
 
+/// Parses the value of a key that is allowed in the section and sets it in the section.
+/// This is not part of [`parse_section`], because nested sections would repeat its large stack frame once per level.
+async fn parse_key_value(
+    game_template: &mut GameTemplate,
+    tokens: &mut TokenIterator<impl Read + Unpin + Send>,
+    section: &mut GameTemplateSection,
+    section_kind: &SectionTokenKind,
+    parent_id: Option<&str>,
+    key: KeyTokenKind,
+    range: CharacterCoordinateRange,
+) -> Result<(), ParserError> {
+    match key {
+        KeyTokenKind::Name => {
+            section.set_name(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Url => {
+            section.set_url(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Sound => {
+            section.set_sound(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Category => {
+            section.set_category(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Progressive => {
+            section.set_progressive(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::SimplePast => {
+            section.set_simple_past(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Title => {
+            section.set_title(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Description => {
+            section.set_description(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Task => {
+            section.set_task(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+
+        KeyTokenKind::Quest => {
+            section.set_quest(RangedElement::new(
+                expect_identifier(tokens).await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::QuestStage => {
+            section.set_quest_stage(RangedElement::new(
+                expect_identifier(tokens).await?.element,
+                range,
+            ))?;
+        }
+
+        KeyTokenKind::Strength => {
+            let strength = tokens.expect_string_value().await?;
+            let parsed = strength.element.parse();
+            section.set_strength(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(strength.element.into()),
+                        strength.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Stamina => {
+            let stamina = tokens.expect_string_value().await?;
+            let parsed = stamina.element.parse();
+            section.set_stamina(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(stamina.element.into()),
+                        stamina.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Dexterity => {
+            let dexterity = tokens.expect_string_value().await?;
+            let parsed = dexterity.element.parse();
+            section.set_dexterity(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(dexterity.element.into()),
+                        dexterity.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Intelligence => {
+            let intelligence = tokens.expect_string_value().await?;
+            let parsed = intelligence.element.parse();
+            section.set_intelligence(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(intelligence.element.into()),
+                        intelligence.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Wisdom => {
+            let wisdom = tokens.expect_string_value().await?;
+            let parsed = wisdom.element.parse();
+            section.set_wisdom(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(wisdom.element.into()),
+                        wisdom.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Charisma => {
+            let charisma = tokens.expect_string_value().await?;
+            let parsed = charisma.element.parse();
+            section.set_charisma(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(charisma.element.into()),
+                        charisma.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::CombatStyle => {
+            section.set_combat_style(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Normalize => {
+            let normalize = tokens.expect_string_value().await?;
+            let parsed = normalize.element.parse();
+            section.set_normalize(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedBoolean(normalize.element.into()),
+                        normalize.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::AllowDebt => {
+            let allow_debt = tokens.expect_string_value().await?;
+            let parsed = allow_debt.element.parse();
+            section.set_allow_debt(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedBoolean(allow_debt.element.into()),
+                        allow_debt.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::ManualActivation => {
+            let manual_activation = tokens.expect_string_value().await?;
+            let parsed = manual_activation.element.parse();
+            section.set_manual_activation(RangedElement::new(
+                parsed.map_err(move |_| {
+                    ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedBoolean(manual_activation.element.into()),
+                        manual_activation.range,
+                    )
+                })?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Currency | KeyTokenKind::Value => {
+            if let Some(token) = tokens.next().await? {
+                let (kind, range) = token.decompose();
+                match kind {
+                    TokenKind::Value(ValueTokenKind::Integer(integer)) => match key {
+                        KeyTokenKind::Currency => section.set_currency(RangedElement::new(
+                            Currency::from_copper(integer.into()),
+                            range,
+                        ))?,
+                        KeyTokenKind::Value => section.set_value(RangedElement::new(
+                            Currency::from_copper(integer.into()),
+                            range,
+                        ))?,
+                        _ => unreachable!(),
+                    },
+                    kind => {
+                        return Err(ParserError::with_coordinates(
+                            ParserErrorKind::ExpectedInteger(kind.into()),
+                            range,
+                        ));
+                    }
+                }
+            } else {
+                return Err(unexpected_eof());
+            }
+        }
+        KeyTokenKind::Items => {
+            section.set_items(parse_expected_identifier_counts(tokens).await?)?;
+        }
+        KeyTokenKind::Buff => {
+            section.set_buff(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::BuffDuration => {
+            if let Some(token) = tokens.next().await? {
+                let (kind, range) = token.decompose();
+                match kind {
+                    TokenKind::Value(ValueTokenKind::Time(time)) => {
+                        section.set_buff_duration(RangedElement::new(time, range))?;
+                    }
+                    kind => {
+                        return Err(ParserError::with_coordinates(
+                            ParserErrorKind::ExpectedTime(kind.into()),
+                            range,
+                        ));
+                    }
+                }
+            } else {
+                return Err(unexpected_eof());
+            }
+        }
+
+        KeyTokenKind::Type => {
+            section.set_type_name(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Duration => {
+            if let Some(token) = tokens.next().await? {
+                let (kind, range) = token.decompose();
+                match kind {
+                    TokenKind::Value(ValueTokenKind::Time(time)) => {
+                        section.set_duration(RangedElement::new(time, range))?;
+                    }
+                    kind => {
+                        return Err(ParserError::with_coordinates(
+                            ParserErrorKind::ExpectedTime(kind.into()),
+                            range,
+                        ));
+                    }
+                }
+            } else {
+                return Err(unexpected_eof());
+            }
+        }
+        KeyTokenKind::TimeLimit => {
+            if let Some(token) = tokens.next().await? {
+                let (kind, range) = token.decompose();
+                match kind {
+                    TokenKind::Value(ValueTokenKind::Time(time)) => {
+                        section.set_time_limit(RangedElement::new(time, range))?;
+                    }
+                    kind => {
+                        return Err(ParserError::with_coordinates(
+                            ParserErrorKind::ExpectedTime(kind.into()),
+                            range,
+                        ));
+                    }
+                }
+            } else {
+                return Err(unexpected_eof());
+            }
+        }
+        KeyTokenKind::Events => {
+            section.set_events(parse_weighted_identifiers(tokens).await?)?;
+        }
+        KeyTokenKind::MonsterPool => {
+            section.set_monster_pool(parse_weighted_identifiers(tokens).await?)?;
+        }
+        KeyTokenKind::CombatRatio => {
+            let combat_ratio = tokens.expect_string_value().await?;
+            let parsed: Result<f64, _> = combat_ratio.element.parse();
+            let parsed = match parsed {
+                Ok(parsed) if (0.0..=1.0).contains(&parsed) => parsed,
+                Ok(parsed) => {
+                    return Err(ParserError::with_coordinates(
+                        ParserErrorKind::IllegalCombatRatio(parsed),
+                        combat_ratio.range,
+                    ))
+                }
+                Err(_) => {
+                    return Err(ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(combat_ratio.element.into()),
+                        combat_ratio.range,
+                    ))
+                }
+            };
+            section.set_combat_ratio(RangedElement::new(parsed, range))?;
+        }
+        KeyTokenKind::Monsters => {
+            section.set_monster(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Hitpoints => {
+            section.set_hitpoints(RangedElement::new(
+                parse_expression(tokens.expect_string_value().await?)?,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Enrage => {
+            let enrage = tokens.expect_string_value().await?;
+            let parsed: Result<f64, _> = enrage.element.parse();
+            let parsed = match parsed {
+                Ok(parsed) if parsed.is_finite() && parsed >= 0.0 => parsed,
+                Ok(parsed) => {
+                    return Err(ParserError::with_coordinates(
+                        ParserErrorKind::IllegalEnrage(parsed),
+                        enrage.range,
+                    ))
+                }
+                Err(_) => {
+                    return Err(ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(enrage.element.into()),
+                        enrage.range,
+                    ))
+                }
+            };
+            section.set_enrage(RangedElement::new(parsed, range))?;
+        }
+        KeyTokenKind::Weight => {
+            let weight = tokens.expect_string_value().await?;
+            let parsed: Result<f64, _> = weight.element.parse();
+            let parsed = match parsed {
+                Ok(parsed) if parsed.is_finite() && parsed >= 0.0 => parsed,
+                Ok(parsed) => {
+                    return Err(ParserError::with_coordinates(
+                        ParserErrorKind::IllegalWeight(parsed),
+                        weight.range,
+                    ))
+                }
+                Err(_) => {
+                    return Err(ParserError::with_coordinates(
+                        ParserErrorKind::ExpectedFloat(weight.element.into()),
+                        weight.range,
+                    ))
+                }
+            };
+            section.set_weight(RangedElement::new(parsed, range))?;
+        }
+        KeyTokenKind::TieBreak => {
+            section.set_tie_break(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::MinLevel => {
+            section.set_min_level(RangedElement::new(
+                expect_integer(tokens).await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::MaxLevel => {
+            section.set_max_level(RangedElement::new(
+                expect_integer(tokens).await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::MaxCount => {
+            section.set_max_count(RangedElement::new(
+                expect_integer(tokens).await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Month => {
+            section.set_month(RangedElement::new(
+                expect_integer(tokens).await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Day => {
+            section.set_day(RangedElement::new(
+                expect_integer(tokens).await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Activation => {
+            let id_str = format!(
+                "{}_{}_activation",
+                section_kind.to_snake_case_string(),
+                section.id_str
+            );
+            parse_trigger(
+                game_template,
+                tokens,
+                id_str.clone(),
+                vec![section_kind.activation_action(section.id_str.clone(), section.id_range)?],
+            )
+            .await?;
+            section.set_activation(RangedElement::new(id_str, range))?;
+        }
+        KeyTokenKind::Deactivation => {
+            let id_str = format!(
+                "{}_{}_deactivation",
+                section_kind.to_snake_case_string(),
+                section.id_str
+            );
+            parse_trigger(
+                game_template,
+                tokens,
+                id_str.clone(),
+                vec![section_kind.deactivation_action(section.id_str.clone(), section.id_range)?],
+            )
+            .await?;
+            section.set_deactivation(RangedElement::new(id_str, range))?;
+        }
+        KeyTokenKind::Completion => {
+            let id_str = format!(
+                "{}_{}_completion",
+                section_kind.to_snake_case_string(),
+                section.id_str
+            );
+            parse_trigger(
+                game_template,
+                tokens,
+                id_str.clone(),
+                vec![GameAction::CompleteQuestStage {
+                    quest_id: parent_id
+                        .ok_or_else(|| {
+                            ParserError::with_coordinates(
+                                ParserErrorKind::UnexpectedField {
+                                    id_str: section.id_str.clone(),
+                                    field: "completion".to_string(),
+                                },
+                                range,
+                            )
+                        })?
+                        .to_string(),
+                    stage_id: section.id_str.clone(),
+                }],
+            )
+            .await?;
+            section.set_completion(RangedElement::new(id_str, range))?;
+        }
+        KeyTokenKind::Failure => {
+            let id_str = format!(
+                "{}_{}_fail",
+                section_kind.to_snake_case_string(),
+                section.id_str
+            );
+            parse_trigger(
+                game_template,
+                tokens,
+                id_str.clone(),
+                vec![GameAction::FailQuest {
+                    id: section.id_str.clone(),
+                }],
+            )
+            .await?;
+            section.set_failure(RangedElement::new(id_str, range))?;
+        }
+        KeyTokenKind::StartingLocation => {
+            section.set_starting_location(RangedElement::new(
+                tokens.expect_string_value().await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::Then => {
+            section.set_then(RangedElement::new(
+                expect_identifier(tokens).await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::PrestigeQuest => {
+            section.set_prestige_quest(RangedElement::new(
+                expect_identifier(tokens).await?.element,
+                range,
+            ))?;
+        }
+        KeyTokenKind::StartingTime => {
+            if let Some(token) = tokens.next().await? {
+                let (kind, range) = token.decompose();
+                match kind {
+                    TokenKind::Value(ValueTokenKind::Time(time)) => {
+                        section.set_starting_time(RangedElement::new(time, range))?;
+                    }
+                    kind => {
+                        return Err(ParserError::with_coordinates(
+                            ParserErrorKind::ExpectedTime(kind.into()),
+                            range,
+                        ));
+                    }
+                }
+            } else {
+                return Err(unexpected_eof());
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl GameTemplateSection {
     fn new(id_str: String, id_range: CharacterCoordinateRange) -> Self {
         Self {
@@ -479,6 +790,8 @@ impl GameTemplateSection {
             id_range,
             name: None,
             url: None,
+            sound: None,
+            category: None,
             progressive: None,
             simple_past: None,
             title: None,
@@ -492,36 +805,58 @@ impl GameTemplateSection {
             intelligence: None,
             wisdom: None,
             charisma: None,
+            normalize: None,
+            combat_style: None,
             currency: None,
+            allow_debt: None,
             value: None,
             items: None,
+            buff: None,
+            buff_duration: None,
             type_name: None,
             duration: None,
+            time_limit: None,
+            then: None,
             events: None,
+            monster_pool: None,
+            combat_ratio: None,
             monster: None,
             hitpoints: None,
+            enrage: None,
+            weight: None,
+            tie_break: None,
+            min_level: None,
+            max_level: None,
+            max_count: None,
+            month: None,
+            day: None,
             activation: None,
+            manual_activation: None,
             deactivation: None,
             completion: None,
             failure: None,
             starting_location: None,
             starting_time: None,
+            prestige_quest: None,
             subsections: None,
         }
     }
 
+    /// The identifier of the section together with its position in the template file.
+    pub fn id(&self) -> (String, CharacterCoordinateRange) {
+        (self.id_str.clone(), self.id_range)
+    }
+
     pub fn into_builtin_action(
         mut self,
         game_template: &mut GameTemplate,
     ) -> Result<PlayerAction, ParserError> {
         self.create_default_activation_trigger(game_template, SectionTokenKind::BuiltinAction)?;
         self.create_default_deactivation_trigger(game_template, SectionTokenKind::BuiltinAction)?;
+        self.add_level_window(game_template);
 
-        let action_type = match self.id_str.as_str() {
-            "EXPLORE" => PlayerActionType::Explore,
-            "SLEEP" => PlayerActionType::Sleep,
-            "TAVERN" => PlayerActionType::Tavern,
-            "WAIT" => PlayerActionType::Wait,
+        let action_type = match self.id_str.parse::<PlayerActionType>() {
+            Ok(action_type) if action_type.is_builtin() => action_type,
             _ => {
                 return Err(ParserError::with_coordinates(
                     ParserErrorKind::UnknownBuiltinAction(self.id_str),
@@ -559,12 +894,16 @@ impl GameTemplateSection {
         let result = Ok(PlayerAction {
             id_str: self.id_str.clone(),
             name: self.name()?.element,
+            sound: self.sound.take().map(|sound| sound.element),
+            category: self.category.take().map(|category| category.element),
             verb_progressive: self.progressive()?.element,
             verb_simple_past: self.simple_past()?.element,
             action_type,
             duration,
+            then: None,
             attribute_progress_factor: Default::default(),
             currency_reward: Default::default(),
+            allow_debt: false,
             items: Default::default(),
             activation_condition: self.activation()?.element,
             deactivation_condition,
@@ -579,6 +918,7 @@ impl GameTemplateSection {
     ) -> Result<PlayerAction, ParserError> {
         self.create_default_activation_trigger(game_template, SectionTokenKind::Action)?;
         self.create_default_deactivation_trigger(game_template, SectionTokenKind::Action)?;
+        self.add_level_window(game_template);
 
         match self.id_str.as_str() {
             "EXPLORE" | "SLEEP" | "TAVERN" | "WAIT" => {
@@ -591,13 +931,20 @@ impl GameTemplateSection {
         }
 
         let action_type = self.type_name()?;
-        let parsed_action_type = action_type.element.parse();
+        let action_type_range = action_type.range;
+        let parsed_action_type = action_type.element.parse::<PlayerActionType>();
         let action_type = parsed_action_type.map_err(move |_| {
             ParserError::with_coordinates(
                 ParserErrorKind::ExpectedActionType(action_type.element),
                 action_type.range,
             )
         })?;
+        if action_type.is_builtin() {
+            return Err(ParserError::with_coordinates(
+                ParserErrorKind::IllegalActionType(action_type),
+                action_type_range,
+            ));
+        }
 
         let deactivation_condition = self.deactivation()?.element;
         let deactivation_trigger = game_template
@@ -623,12 +970,20 @@ impl GameTemplateSection {
         let result = Ok(PlayerAction {
             id_str: self.id_str.clone(),
             name: self.name()?.element,
+            sound: self.sound.take().map(|sound| sound.element),
+            category: self.category.take().map(|category| category.element),
             verb_progressive: self.progressive()?.element,
             verb_simple_past: self.simple_past()?.element,
             action_type,
             duration: self.duration()?.element,
-            attribute_progress_factor: self.take_character_attribute_progress_factor(),
+            then: self.then.take().map(|then| then.element),
+            attribute_progress_factor: self.take_character_attribute_progress_factor()?,
             currency_reward: self.currency()?.element,
+            allow_debt: self
+                .allow_debt
+                .take()
+                .map(|allow_debt| allow_debt.element)
+                .unwrap_or(false),
             items: self
                 .items
                 .take()
@@ -645,12 +1000,6 @@ impl GameTemplateSection {
         mut self,
         game_template: &mut GameTemplate,
     ) -> Result<PlayerAction, ParserError> {
-        self.create_default_activation_trigger(game_template, SectionTokenKind::QuestStageAction)?;
-        self.create_default_deactivation_trigger(
-            game_template,
-            SectionTokenKind::QuestStageAction,
-        )?;
-
         match self.id_str.as_str() {
             "EXPLORE" | "SLEEP" | "TAVERN" | "WAIT" => {
                 unreachable!("Trying to parse a builtin action as normal action")
@@ -660,20 +1009,14 @@ impl GameTemplateSection {
 
         let action_type = self.type_name()?;
         let action_type_range = action_type.range;
-        let parsed_action_type = action_type.element.parse();
+        let parsed_action_type = action_type.element.parse::<PlayerActionType>();
         let action_type = parsed_action_type.map_err(move |_| {
             ParserError::with_coordinates(
                 ParserErrorKind::ExpectedActionType(action_type.element),
                 action_type.range,
             )
         })?;
-        if matches!(
-            action_type,
-            PlayerActionType::Explore
-                | PlayerActionType::Sleep
-                | PlayerActionType::Tavern
-                | PlayerActionType::Wait
-        ) {
+        if action_type.is_builtin() {
             return Err(ParserError::with_coordinates(
                 ParserErrorKind::IllegalActionType(action_type),
                 action_type_range,
@@ -726,12 +1069,20 @@ impl GameTemplateSection {
         let result = Ok(PlayerAction {
             id_str: self.id_str.clone(),
             name: self.name()?.element,
+            sound: self.sound.take().map(|sound| sound.element),
+            category: self.category.take().map(|category| category.element),
             verb_progressive: self.progressive()?.element,
             verb_simple_past: self.simple_past()?.element,
             action_type,
             duration: self.duration()?.element,
-            attribute_progress_factor: self.take_character_attribute_progress_factor(),
+            then: None,
+            attribute_progress_factor: self.take_character_attribute_progress_factor()?,
             currency_reward: self.currency()?.element,
+            allow_debt: self
+                .allow_debt
+                .take()
+                .map(|allow_debt| allow_debt.element)
+                .unwrap_or(false),
             items: self
                 .items
                 .take()
@@ -745,6 +1096,29 @@ impl GameTemplateSection {
     }
 
     pub fn into_quest(mut self, game_template: &mut GameTemplate) -> Result<Quest, ParserError> {
+        let activation_condition = self.activation()?.element;
+        let manual_activation = self
+            .manual_activation
+            .take()
+            .map(|manual_activation| manual_activation.element)
+            .unwrap_or(false);
+        if manual_activation {
+            // the quest is only offered to the player, who activates it by accepting it
+            let activation_trigger = game_template
+                .triggers
+                .iter_mut()
+                .rev()
+                .find(|trigger| trigger.id_str == activation_condition)
+                .unwrap();
+            *activation_trigger = Trigger::new(
+                activation_trigger.id_str.clone(),
+                mem::replace(&mut activation_trigger.condition, TriggerCondition::Never),
+                vec![GameAction::OfferQuest {
+                    id: self.id_str.clone(),
+                }],
+            );
+        }
+
         let result = Quest {
             id_str: self.id_str.clone(),
             title: self.title()?.element,
@@ -762,8 +1136,10 @@ impl GameTemplateSection {
                 .take()
                 .map(|items| items.element.into_iter().map(Into::into).collect())
                 .unwrap_or_default(),
-            activation_condition: self.activation()?.element,
+            activation_condition,
+            manual_activation,
             failure_condition: self.failure()?.element,
+            time_limit: self.time_limit.take().map(|time_limit| time_limit.element),
             stages: self
                 .subsections()?
                 .element
@@ -837,6 +1213,7 @@ impl GameTemplateSection {
         mut self,
         game_template: &mut GameTemplate,
     ) -> Result<Location, ParserError> {
+        self.add_level_window(game_template);
         let deactivation_condition = self.deactivation()?.element;
         let deactivation_trigger = game_template
             .triggers
@@ -858,11 +1235,42 @@ impl GameTemplateSection {
             ],
         };
 
+        let tie_break = if let Ok(tie_break) = self.tie_break() {
+            let parsed_tie_break = tie_break.element.parse();
+            parsed_tie_break.map_err(move |_| {
+                ParserError::with_coordinates(
+                    ParserErrorKind::ExpectedTieBreak(tie_break.element),
+                    tie_break.range,
+                )
+            })?
+        } else {
+            Default::default()
+        };
+
+        // a location with a monster pool does not need exploration events
+        let monsters: Vec<_> = self
+            .monster_pool
+            .take()
+            .map(|monsters| monsters.element.into_iter().map(Into::into).collect())
+            .unwrap_or_default();
+        let events = if monsters.is_empty() || self.events.is_some() {
+            self.events()?.element.into_iter().map(Into::into).collect()
+        } else {
+            Vec::new()
+        };
+
         let result = Ok(Location {
             id_str: self.id_str.clone(),
             name: self.name()?.element,
             url: self.url.take().map(|url| url.element),
-            events: self.events()?.element.into_iter().map(Into::into).collect(),
+            events,
+            monsters,
+            combat_ratio: self
+                .combat_ratio
+                .take()
+                .map(|combat_ratio| combat_ratio.element)
+                .unwrap_or(DEFAULT_COMBAT_RATIO),
+            tie_break,
             activation_condition: self.activation()?.element,
             deactivation_condition,
         });
@@ -882,20 +1290,30 @@ impl GameTemplateSection {
 
         let activation_condition = self.activation()?.element;
         let deactivation_condition = self.deactivation()?.element;
-        if let Some(monster) = &self.monster {
-            let monster = &monster.element;
+        // a comma separated list of monsters makes a pack that is fought in a single combat
+        let monsters: Vec<_> = self
+            .monster
+            .iter()
+            .flat_map(|monster| monster.element.split(','))
+            .map(str::trim)
+            .filter(|monster| !monster.is_empty())
+            .map(str::to_string)
+            .collect();
+        if !monsters.is_empty() {
             let activation_trigger = game_template
                 .triggers
                 .iter_mut()
                 .rev()
                 .find(|trigger| trigger.id_str == activation_condition)
                 .unwrap();
-            activation_trigger.condition &= TriggerCondition::EventCount {
-                required: 1,
-                event: GameEvent::Action(GameAction::ActivateMonster {
-                    id: monster.clone(),
-                }),
-            };
+            for monster in &monsters {
+                activation_trigger.condition &= TriggerCondition::EventCount {
+                    required: 1,
+                    event: GameEvent::Action(GameAction::ActivateMonster {
+                        id: monster.clone(),
+                    }),
+                };
+            }
             let deactivation_trigger = game_template
                 .triggers
                 .iter_mut()
@@ -904,27 +1322,36 @@ impl GameTemplateSection {
                 .unwrap();
             let deactivation_trigger_condition =
                 mem::replace(&mut deactivation_trigger.condition, TriggerCondition::Never);
-            deactivation_trigger.condition = TriggerCondition::EventCount {
-                required: 1,
-                event: GameEvent::Action(GameAction::DeactivateMonster {
-                    id: monster.clone(),
-                }),
-            } | TriggerCondition::Sequence {
-                conditions: vec![
-                    TriggerCondition::EventCount {
-                        required: 1,
-                        event: GameEvent::Action(GameAction::ActivateExplorationEvent {
-                            id: self.id_str.clone(),
-                        }),
-                    },
-                    deactivation_trigger_condition,
-                ],
-            };
+            let monster_deactivation_condition = monsters
+                .iter()
+                .map(|monster| TriggerCondition::EventCount {
+                    required: 1,
+                    event: GameEvent::Action(GameAction::DeactivateMonster {
+                        id: monster.clone(),
+                    }),
+                })
+                .reduce(|condition, other| condition | other)
+                .unwrap();
+            deactivation_trigger.condition = monster_deactivation_condition
+                | TriggerCondition::Sequence {
+                    conditions: vec![
+                        TriggerCondition::EventCount {
+                            required: 1,
+                            event: GameEvent::Action(GameAction::ActivateExplorationEvent {
+                                id: self.id_str.clone(),
+                            }),
+                        },
+                        deactivation_trigger_condition,
+                    ],
+                };
         }
 
         let kind = if self.monster.is_some() {
-            ExplorationEventKind::Monster {
-                monster: self.monster()?.element,
+            let monster = self.monster()?.element;
+            if monsters.len() > 1 {
+                ExplorationEventKind::Pack { monsters }
+            } else {
+                ExplorationEventKind::Monster { monster }
             }
         } else {
             ExplorationEventKind::Normal {
@@ -948,6 +1375,8 @@ impl GameTemplateSection {
                 .take()
                 .map(|items| items.element.into_iter().map(Into::into).collect())
                 .unwrap_or_default(),
+            buff: self.take_buff()?,
+            then: self.then.take().map(|then| then.element),
             activation_condition,
             deactivation_condition,
         });
@@ -987,6 +1416,7 @@ impl GameTemplateSection {
             id_str: self.id_str.clone(),
             name: self.name()?.element,
             hitpoints: self.hitpoints()?.element,
+            enrage: self.enrage.take().map(|enrage| enrage.element),
             activation_condition: self.activation()?.element,
             deactivation_condition,
         });
@@ -1024,6 +1454,11 @@ impl GameTemplateSection {
             name: self.name()?.element,
             description: self.description()?.element,
             value: self.value()?.element,
+            max_count: self
+                .max_count
+                .take()
+                .map(|max_count| max_count.element as usize),
+            buff: self.take_buff()?,
             activation_condition: self.activation()?.element,
             deactivation_condition,
         });
@@ -1031,24 +1466,134 @@ impl GameTemplateSection {
         result
     }
 
+    pub fn into_calendar_event(mut self) -> Result<CalendarEvent, ParserError> {
+        let month = self.month()?;
+        let day = self.day()?;
+        let is_valid_date = (1..=DAYS_PER_MONTH.len() as u64).contains(&month.element)
+            && (1..=DAYS_PER_MONTH[month.element as usize - 1] as u64).contains(&day.element);
+        if !is_valid_date {
+            return Err(ParserError::with_coordinates(
+                ParserErrorKind::IllegalCalendarDate {
+                    month: month.element as usize,
+                    day: day.element as usize,
+                },
+                day.range,
+            ));
+        }
+
+        let result = Ok(CalendarEvent {
+            id_str: self.id_str.clone(),
+            name: self.name()?.element,
+            month: month.element as usize,
+            day: day.element as usize,
+        });
+        self.ensure_empty()?;
+        result
+    }
+
+    pub fn into_destiny(mut self) -> Result<Destiny, ParserError> {
+        let result = Ok(Destiny {
+            id_str: self.id_str.clone(),
+            name: self.name()?.element,
+            weight: self
+                .weight
+                .take()
+                .map(|weight| weight.element)
+                .unwrap_or(1.0),
+            currency: self
+                .currency
+                .take()
+                .map(|currency| currency.element)
+                .unwrap_or_default(),
+            attributes: self.take_character_attribute_levels(),
+            items: self
+                .items
+                .take()
+                .map(|items| items.element.into_iter().map(Into::into).collect())
+                .unwrap_or_default(),
+        });
+        self.ensure_empty()?;
+        result
+    }
+
     pub fn into_initialisation(mut self) -> Result<GameInitialisation, ParserError> {
         let result = Ok(GameInitialisation {
             starting_location: self.starting_location()?.element,
-            starting_time: self.starting_time()?.element,
+            starting_time: self
+                .starting_time()
+                .map(|e| e.element)
+                .unwrap_or_else(|_| default_starting_time()),
+            prestige_quest: self.prestige_quest().ok().map(|e| e.element),
         });
         self.ensure_empty()?;
         result
     }
 
-    fn take_character_attribute_progress_factor(&mut self) -> CharacterAttributeProgressFactor {
-        CharacterAttributeProgressFactor::new(
+    /// A buff requires a duration, and a duration requires a buff.
+    fn take_buff(&mut self) -> Result<Option<Buff>, ParserError> {
+        if let Some(buff) = self.buff.take() {
+            let parsed_kind = buff.element.parse();
+            let kind = parsed_kind.map_err(move |_| {
+                ParserError::with_coordinates(
+                    ParserErrorKind::ExpectedBuffKind(buff.element),
+                    buff.range,
+                )
+            })?;
+            Ok(Some(Buff {
+                kind,
+                duration: self.buff_duration()?.element,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Takes the attribute factors, or the split of the `combat_style` if given instead.
+    fn take_character_attribute_progress_factor(
+        &mut self,
+    ) -> Result<CharacterAttributeProgressFactor, ParserError> {
+        if let Some(combat_style) = self.combat_style.take() {
+            let has_attributes = [
+                &self.strength,
+                &self.stamina,
+                &self.dexterity,
+                &self.intelligence,
+                &self.wisdom,
+                &self.charisma,
+            ]
+            .iter()
+            .any(|attribute| attribute.is_some())
+                || self.normalize.is_some();
+            if has_attributes {
+                return Err(ParserError::with_coordinates(
+                    ParserErrorKind::CombatStyleWithAttributes(self.id_str.clone()),
+                    combat_style.range,
+                ));
+            }
+
+            let parsed_combat_style: Result<CombatStyle, _> = combat_style.element.parse();
+            let combat_style = parsed_combat_style.map_err(move |_| {
+                ParserError::with_coordinates(
+                    ParserErrorKind::ExpectedCombatStyle(combat_style.element),
+                    combat_style.range,
+                )
+            })?;
+            return Ok(combat_style.attribute_progress_factor());
+        }
+
+        let factor = CharacterAttributeProgressFactor::new(
             self.strength().map(|e| e.element).unwrap_or(0.0),
             self.stamina().map(|e| e.element).unwrap_or(0.0),
             self.dexterity().map(|e| e.element).unwrap_or(0.0),
             self.intelligence().map(|e| e.element).unwrap_or(0.0),
             self.wisdom().map(|e| e.element).unwrap_or(0.0),
             self.charisma().map(|e| e.element).unwrap_or(0.0),
-        )
+        );
+        Ok(if self.normalize().map(|e| e.element).unwrap_or(false) {
+            factor.normalized()
+        } else {
+            factor
+        })
     }
 
     fn take_character_attribute_progress(&mut self) -> CharacterAttributeProgress {
@@ -1072,6 +1617,18 @@ impl GameTemplateSection {
         )
     }
 
+    fn take_character_attribute_levels(&mut self) -> CharacterAttributes {
+        let progress = self.take_character_attribute_progress();
+        CharacterAttributes::new(
+            progress.strength,
+            progress.stamina,
+            progress.dexterity,
+            progress.intelligence,
+            progress.wisdom,
+            progress.charisma,
+        )
+    }
+
     fn missing_field_error(&self, field: &str) -> GameTemplateSectionError {
         GameTemplateSectionError {
             id_str: self.id_str.clone(),
@@ -1107,6 +1664,37 @@ impl GameTemplateSection {
         }
     }
 
+    /// Adds the `min_level` and `max_level` of the section to its activation and deactivation triggers.
+    fn add_level_window(&mut self, game_template: &mut GameTemplate) {
+        if let (Ok(min_level), Some(activation_condition)) = (self.min_level(), &self.activation) {
+            let activation_condition = &activation_condition.element;
+            let activation_trigger = game_template
+                .triggers
+                .iter_mut()
+                .rev()
+                .find(|trigger| &trigger.id_str == activation_condition)
+                .unwrap();
+            activation_trigger.condition &= geq(GameEvent::PlayerLevelChanged {
+                value: min_level.element,
+            });
+        }
+
+        if let (Ok(max_level), Some(deactivation_condition)) =
+            (self.max_level(), &self.deactivation)
+        {
+            let deactivation_condition = &deactivation_condition.element;
+            let deactivation_trigger = game_template
+                .triggers
+                .iter_mut()
+                .rev()
+                .find(|trigger| &trigger.id_str == deactivation_condition)
+                .unwrap();
+            deactivation_trigger.condition |= geq(GameEvent::PlayerLevelChanged {
+                value: max_level.element + 1,
+            });
+        }
+    }
+
     fn create_default_activation_trigger(
         &mut self,
         game_template: &mut GameTemplate,
@@ -1151,3 +1739,216 @@ impl GameTemplateSection {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::character::CombatStyle;
+    use crate::game_state::inventory::item::ItemId;
+    use crate::game_state::tests::{create_test_game_state_with_templates, execute_test_event};
+    use crate::game_state::time::GameTime;
+    use crate::game_state::triggers::CompiledGameEvent;
+    use crate::game_state::GameState;
+    use crate::game_template::parser::error::ParserErrorKind;
+    use crate::game_template::parser::parse_game_template_file;
+    use crate::game_template::GameTemplate;
+
+    const LEVEL_WINDOW_TEMPLATE: &str = "
+ACTION level_window
+name Level window
+progressive training in a window
+simple_past trained in a window
+type TRAIN
+duration 1h
+strength 1.0
+currency 0
+min_level 5
+max_level 10
+activation none
+deactivation never
+";
+
+    fn is_level_window_choosable(game_state: &GameState) -> bool {
+        game_state
+            .actions
+            .list_choosable()
+            .any(|action| action.id_str == "level_window")
+    }
+
+    #[test]
+    fn test_action_level_window() {
+        let mut game_state = create_test_game_state_with_templates(&[LEVEL_WINDOW_TEMPLATE]);
+        assert!(!is_level_window_choosable(&game_state));
+
+        for level in 2..=4 {
+            execute_test_event(
+                &mut game_state,
+                CompiledGameEvent::PlayerLevelChanged { value: level },
+            );
+            assert!(!is_level_window_choosable(&game_state));
+        }
+
+        for level in 5..=10 {
+            execute_test_event(
+                &mut game_state,
+                CompiledGameEvent::PlayerLevelChanged { value: level },
+            );
+            assert!(is_level_window_choosable(&game_state));
+        }
+
+        execute_test_event(
+            &mut game_state,
+            CompiledGameEvent::PlayerLevelChanged { value: 11 },
+        );
+        assert!(!is_level_window_choosable(&game_state));
+    }
+
+    const MIN_LEVEL_LOCATION_TEMPLATE: &str = "
+LOCATION high_ground
+name High ground
+events (1.0, rat)
+min_level 10
+activation none
+deactivation never
+";
+
+    fn is_high_ground_active(game_state: &GameState) -> bool {
+        game_state
+            .world
+            .active_locations()
+            .any(|location| location.id_str == "high_ground")
+    }
+
+    #[test]
+    fn test_location_min_level() {
+        let mut game_state = create_test_game_state_with_templates(&[MIN_LEVEL_LOCATION_TEMPLATE]);
+        assert!(!is_high_ground_active(&game_state));
+
+        execute_test_event(
+            &mut game_state,
+            CompiledGameEvent::PlayerLevelChanged { value: 9 },
+        );
+        assert!(!is_high_ground_active(&game_state));
+
+        execute_test_event(
+            &mut game_state,
+            CompiledGameEvent::PlayerLevelChanged { value: 10 },
+        );
+        assert!(is_high_ground_active(&game_state));
+    }
+
+    const MAX_COUNT_TEMPLATE: &str = "
+ITEM feather
+name Feather
+description A light feather.
+value 1
+max_count 3
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_item_max_count() {
+        let mut game_state = create_test_game_state_with_templates(&[MAX_COUNT_TEMPLATE]);
+        let feather = (0..)
+            .map(ItemId)
+            .find(|&item| game_state.inventory.item(item).id_str == "feather")
+            .unwrap();
+        assert_eq!(game_state.inventory.item(feather).max_count, Some(3));
+
+        let events: Vec<_> = game_state.inventory.add(feather, 5).collect();
+        assert!(matches!(
+            events[..],
+            [CompiledGameEvent::ItemCountChanged { count: 3, .. }]
+        ));
+        assert_eq!(game_state.inventory.take_wasted_items()[0].count, 2);
+    }
+
+    #[test]
+    fn test_normalize_action_factors() {
+        let template = "
+ACTION normalized
+name Normalized
+progressive training normalized
+simple_past trained normalized
+type TRAIN
+duration 1h
+str 3.0
+dex 1.0
+normalize true
+currency 0
+activation none
+deactivation never
+";
+        let mut game_template = GameTemplate::default();
+        async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            template.as_bytes(),
+        ))
+        .unwrap();
+        let factor = game_template
+            .actions
+            .pop()
+            .unwrap()
+            .attribute_progress_factor;
+        assert_eq!(factor.strength, 0.75);
+        assert_eq!(factor.dexterity, 0.25);
+    }
+
+    const COMBAT_STYLE_TEMPLATE: &str = "
+ACTION spellcraft
+name Spellcraft
+progressive practicing spells
+simple_past practiced spells
+type TRAIN
+duration 1h
+combat_style MAGIC
+currency 0
+activation none
+deactivation never
+";
+
+    #[test]
+    fn test_combat_style_action_factors() {
+        let game_state = create_test_game_state_with_templates(&[COMBAT_STYLE_TEMPLATE]);
+        let spellcraft = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "spellcraft")
+            .unwrap();
+        assert_eq!(
+            spellcraft.attribute_progress_factor,
+            CombatStyle::Magic.attribute_progress_factor()
+        );
+
+        let progress = spellcraft
+            .attribute_progress_factor
+            .into_progress(GameTime::from_hours(10));
+        assert_eq!(
+            (
+                progress.strength,
+                progress.stamina,
+                progress.dexterity,
+                progress.charisma
+            ),
+            (0, 0, 0, 0)
+        );
+        assert!(progress.intelligence > 0);
+        assert_eq!(progress.intelligence * 3, progress.wisdom * 2);
+    }
+
+    #[test]
+    fn test_combat_style_with_attributes() {
+        let template =
+            COMBAT_STYLE_TEMPLATE.replace("combat_style MAGIC", "combat_style MAGIC\nint 1.0");
+        let mut game_template = GameTemplate::default();
+        let error = async_std::task::block_on(parse_game_template_file(
+            &mut game_template,
+            template.as_bytes(),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::CombatStyleWithAttributes(id_str) if id_str == "spellcraft"
+        ));
+    }
+}
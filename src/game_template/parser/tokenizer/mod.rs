@@ -4,9 +4,11 @@ use crate::game_template::parser::character_iterator::{
     CharacterCoordinateRange, CharacterIterator, CharacterIteratorWithCoordinates,
     PeekableCharacterIteratorWithCoordinates,
 };
-use crate::game_template::parser::error::{ParserError, ParserErrorKind};
+use crate::game_template::parser::error::{unexpected_eof, ParserError, ParserErrorKind};
 use async_std::io::Read;
-use log::trace;
+use enum_iterator::{all, Sequence};
+use log::{trace, warn};
+use serde::Serialize;
 
 #[derive(Debug, Clone)]
 pub struct Token {
@@ -18,12 +20,17 @@ pub struct Token {
 pub enum TokenKind {
     Section(SectionTokenKind),
     Key(KeyTokenKind),
+    /// A display string key tagged with a language, e.g. `name.de`.
+    TranslatedKey {
+        key: KeyTokenKind,
+        language: String,
+    },
     Value(ValueTokenKind),
     Begin,
     End,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Sequence)]
 pub enum SectionTokenKind {
     Initialisation,
 
@@ -36,12 +43,16 @@ pub enum SectionTokenKind {
     ExplorationEvent,
     Monster,
     Item,
+    CalendarEvent,
+    Destiny,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq, Sequence)]
 pub enum KeyTokenKind {
     Name,
     Url,
+    Sound,
+    Category,
     Progressive,
     SimplePast,
     Title,
@@ -57,23 +68,61 @@ pub enum KeyTokenKind {
     Intelligence,
     Wisdom,
     Charisma,
+    Normalize,
+    CombatStyle,
     Currency,
+    AllowDebt,
     Value,
     Items,
+    Buff,
+    BuffDuration,
 
     Type,
     Duration,
+    TimeLimit,
+    Then,
     Events,
+    MonsterPool,
+    CombatRatio,
     Monsters,
     Hitpoints,
+    Enrage,
+    Weight,
+    TieBreak,
+
+    MinLevel,
+    MaxLevel,
+    MaxCount,
+
+    Month,
+    Day,
 
     Activation,
+    ManualActivation,
     Deactivation,
     Completion,
     Failure,
 
     StartingLocation,
     StartingTime,
+    PrestigeQuest,
+}
+
+/// The kind of value expected after a key.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyValueKind {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    /// An arithmetic expression over `level` and the attributes.
+    Expression,
+    Time,
+    Identifier,
+    IdentifierCounts,
+    WeightedIdentifiers,
+    Trigger,
 }
 
 #[derive(Debug, Clone)]
@@ -89,9 +138,14 @@ pub enum ValueTokenKind {
     String(String),
 }
 
+/// `BEGIN` blocks may be nested at most this deep by default, see [`TokenIterator::with_max_nesting_depth`].
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 16;
+
 pub struct TokenIterator<Input> {
     input: PeekableCharacterIteratorWithCoordinates<Input>,
     is_first_of_line: bool,
+    max_nesting_depth: usize,
+    lenient_sections: bool,
 }
 
 impl<Input> TokenIterator<Input> {
@@ -104,12 +158,74 @@ impl<Input> TokenIterator<Input> {
                 )),
             ),
             is_first_of_line: true,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+            lenient_sections: false,
         }
     }
+
+    /// Sections nested in more than this many `BEGIN` blocks are rejected by the parser,
+    /// such that pathological input cannot overflow the stack of the recursive descent.
+    pub fn with_max_nesting_depth(mut self, max_nesting_depth: usize) -> Self {
+        self.max_nesting_depth = max_nesting_depth;
+        self
+    }
+
+    pub fn max_nesting_depth(&self) -> usize {
+        self.max_nesting_depth
+    }
+
+    /// If set, sections with an unknown keyword are skipped with a warning instead of being rejected,
+    /// such that templates written for newer versions can be partially loaded.
+    pub fn with_lenient_sections(mut self, lenient_sections: bool) -> Self {
+        self.lenient_sections = lenient_sections;
+        self
+    }
+}
+
+/// True if the keyword has the shape of a section keyword, i.e. it consists of uppercase letters, digits and underscores.
+fn is_section_like_keyword(keyword: &str) -> bool {
+    keyword.starts_with(|character: char| character.is_ascii_uppercase())
+        && keyword.chars().all(|character| {
+            character.is_ascii_uppercase() || character.is_ascii_digit() || character == '_'
+        })
 }
 
 impl<Input: Read + Unpin> TokenIterator<Input> {
     pub async fn next(&mut self) -> Result<Option<Token>, ParserError> {
+        match self.next_strict().await {
+            Err(ParserError {
+                kind: ParserErrorKind::IllegalKeyword(keyword),
+                coordinates,
+            }) if self.lenient_sections && is_section_like_keyword(&keyword) => {
+                warn!("Skipping section with unknown keyword {keyword:?} at {coordinates:?}");
+                self.skip_unknown_section().await
+            }
+            result => result,
+        }
+    }
+
+    /// Skips the tokens of a section with an unknown keyword, including its `BEGIN`/`END` block.
+    /// Returns the next sibling section, the `END` of the parent block or `None` at the end of the input.
+    /// Recoverable errors while skipping are ignored, since they belong to the skipped section.
+    async fn skip_unknown_section(&mut self) -> Result<Option<Token>, ParserError> {
+        let mut depth = 0usize;
+        loop {
+            match self.next_strict().await {
+                Ok(Some(token)) => match token.kind() {
+                    TokenKind::Section(_) if depth == 0 => return Ok(Some(token)),
+                    TokenKind::Begin => depth += 1,
+                    TokenKind::End if depth == 0 => return Ok(Some(token)),
+                    TokenKind::End => depth -= 1,
+                    _ => {}
+                },
+                Ok(None) => return Ok(None),
+                Err(error) if error.kind.is_recoverable() => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    async fn next_strict(&mut self) -> Result<Option<Token>, ParserError> {
         self.skip_whitespace().await?;
         let is_first_of_line = self.is_first_of_line;
         self.is_first_of_line = false;
@@ -127,130 +243,20 @@ impl<Input: Read + Unpin> TokenIterator<Input> {
                 );
                 trace!("Word at first of line: {word:?}");
 
-                match word.as_str() {
-                    "INITIALISATION" => Ok(Some(Token::new(
-                        SectionTokenKind::Initialisation.into(),
-                        range,
-                    ))),
-                    "BUILTIN_ACTION" => Ok(Some(Token::new(
-                        SectionTokenKind::BuiltinAction.into(),
-                        range,
-                    ))),
-                    "ACTION" => Ok(Some(Token::new(SectionTokenKind::Action.into(), range))),
-                    "QUEST_STAGE_ACTION" => Ok(Some(Token::new(
-                        SectionTokenKind::QuestStageAction.into(),
-                        range,
-                    ))),
-                    "QUEST" => Ok(Some(Token::new(SectionTokenKind::Quest.into(), range))),
-                    "QUEST_STAGE" => {
-                        Ok(Some(Token::new(SectionTokenKind::QuestStage.into(), range)))
-                    }
-                    "LOCATION" => Ok(Some(Token::new(SectionTokenKind::Location.into(), range))),
-                    "EXPLORATION_EVENT" => Ok(Some(Token::new(
-                        SectionTokenKind::ExplorationEvent.into(),
-                        range,
-                    ))),
-                    "MONSTER" => Ok(Some(Token::new(SectionTokenKind::Monster.into(), range))),
-                    "ITEM" => Ok(Some(Token::new(SectionTokenKind::Item.into(), range))),
-
-                    "name" => Ok(Some(Token::new(TokenKind::Key(KeyTokenKind::Name), range))),
-                    "url" => Ok(Some(Token::new(TokenKind::Key(KeyTokenKind::Url), range))),
-                    "progressive" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Progressive),
-                        range,
-                    ))),
-                    "simple_past" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::SimplePast),
-                        range,
-                    ))),
-                    "title" => Ok(Some(Token::new(TokenKind::Key(KeyTokenKind::Title), range))),
-                    "description" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Description),
-                        range,
-                    ))),
-                    "task" => Ok(Some(Token::new(TokenKind::Key(KeyTokenKind::Task), range))),
-
-                    "quest" => Ok(Some(Token::new(TokenKind::Key(KeyTokenKind::Quest), range))),
-                    "quest_stage" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::QuestStage),
-                        range,
-                    ))),
-
-                    "str" | "strength" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Strength),
-                        range,
-                    ))),
-                    "sta" | "stamina" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Stamina),
-                        range,
-                    ))),
-                    "dex" | "dexterity" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Dexterity),
-                        range,
-                    ))),
-                    "int" | "intelligence" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Intelligence),
-                        range,
-                    ))),
-                    "wis" | "wisdom" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Wisdom),
-                        range,
-                    ))),
-                    "chr" | "charisma" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Charisma),
-                        range,
-                    ))),
-                    "currency" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Currency),
-                        range,
-                    ))),
-                    "value" => Ok(Some(Token::new(TokenKind::Key(KeyTokenKind::Value), range))),
-                    "items" => Ok(Some(Token::new(TokenKind::Key(KeyTokenKind::Items), range))),
-
-                    "type" => Ok(Some(Token::new(TokenKind::Key(KeyTokenKind::Type), range))),
-                    "duration" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Duration),
-                        range,
-                    ))),
-                    "events" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Events),
-                        range,
-                    ))),
-                    "monster" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Monsters),
-                        range,
-                    ))),
-                    "hitpoints" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Hitpoints),
-                        range,
-                    ))),
-
-                    "activation" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Activation),
-                        range,
-                    ))),
-                    "deactivation" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Deactivation),
-                        range,
-                    ))),
-                    "completion" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Completion),
-                        range,
-                    ))),
-                    "failure" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::Failure),
-                        range,
-                    ))),
-
-                    "starting_location" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::StartingLocation),
-                        range,
-                    ))),
-                    "starting_time" => Ok(Some(Token::new(
-                        TokenKind::Key(KeyTokenKind::StartingTime),
+                if let Some(section) = SectionTokenKind::from_keyword(&word) {
+                    return Ok(Some(Token::new(section.into(), range)));
+                }
+                if let Some(key) = KeyTokenKind::from_keyword(&word) {
+                    return Ok(Some(Token::new(TokenKind::Key(key), range)));
+                }
+                if let Some((key, language)) = KeyTokenKind::from_translated_keyword(&word) {
+                    return Ok(Some(Token::new(
+                        TokenKind::TranslatedKey { key, language },
                         range,
-                    ))),
+                    )));
+                }
 
+                match word.as_str() {
                     "BEGIN" => Ok(Some(Token::new(TokenKind::Begin, range))),
                     "END" => Ok(Some(Token::new(TokenKind::End, range))),
 
@@ -378,11 +384,17 @@ impl<Input: Read + Unpin> TokenIterator<Input> {
 
     pub async fn expect_string_value(&mut self) -> Result<RangedElement<String>, ParserError> {
         let skipped = self.skip_whitespace().await?;
+        // Without any skipped whitespace, the input ended right after the key.
+        let expected_nonempty_string = || {
+            skipped
+                .map(|skipped| {
+                    ParserError::with_coordinates(ParserErrorKind::ExpectedNonemptyString, skipped)
+                })
+                .unwrap_or_else(unexpected_eof)
+        };
+
         if self.is_first_of_line {
-            Err(ParserError::with_coordinates(
-                ParserErrorKind::ExpectedNonemptyString,
-                skipped.unwrap(),
-            ))
+            Err(expected_nonempty_string())
         } else {
             let mut result = String::new();
             let range = self
@@ -390,7 +402,10 @@ impl<Input: Read + Unpin> TokenIterator<Input> {
                     character == '\n' || character == '\r'
                 })
                 .await?;
-            Ok(RangedElement::new(result, range.unwrap()))
+            Ok(RangedElement::new(
+                result,
+                range.ok_or_else(expected_nonempty_string)?,
+            ))
         }
     }
 
@@ -468,6 +483,160 @@ impl Token {
 }
 
 impl SectionTokenKind {
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        all::<Self>().find(|section| section.keyword() == keyword)
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            SectionTokenKind::Initialisation => "INITIALISATION",
+            SectionTokenKind::BuiltinAction => "BUILTIN_ACTION",
+            SectionTokenKind::Action => "ACTION",
+            SectionTokenKind::QuestStageAction => "QUEST_STAGE_ACTION",
+            SectionTokenKind::Quest => "QUEST",
+            SectionTokenKind::QuestStage => "QUEST_STAGE",
+            SectionTokenKind::Location => "LOCATION",
+            SectionTokenKind::ExplorationEvent => "EXPLORATION_EVENT",
+            SectionTokenKind::Monster => "MONSTER",
+            SectionTokenKind::Item => "ITEM",
+            SectionTokenKind::CalendarEvent => "CALENDAR_EVENT",
+            SectionTokenKind::Destiny => "DESTINY",
+        }
+    }
+
+    /// The keys that may appear in a section of this kind.
+    pub fn allowed_keys(&self) -> &'static [KeyTokenKind] {
+        use KeyTokenKind::*;
+
+        match self {
+            SectionTokenKind::Initialisation => &[StartingLocation, StartingTime, PrestigeQuest],
+            SectionTokenKind::BuiltinAction => &[
+                Name,
+                Sound,
+                Category,
+                Progressive,
+                SimplePast,
+                Duration,
+                MinLevel,
+                MaxLevel,
+                Activation,
+                Deactivation,
+            ],
+            SectionTokenKind::Action => &[
+                Name,
+                Sound,
+                Category,
+                Progressive,
+                SimplePast,
+                Strength,
+                Stamina,
+                Dexterity,
+                Intelligence,
+                Wisdom,
+                Charisma,
+                Normalize,
+                CombatStyle,
+                Currency,
+                AllowDebt,
+                Items,
+                Type,
+                Duration,
+                Then,
+                MinLevel,
+                MaxLevel,
+                Activation,
+                Deactivation,
+            ],
+            SectionTokenKind::QuestStageAction => &[
+                Name,
+                Sound,
+                Category,
+                Progressive,
+                SimplePast,
+                Quest,
+                QuestStage,
+                Strength,
+                Stamina,
+                Dexterity,
+                Intelligence,
+                Wisdom,
+                Charisma,
+                Normalize,
+                CombatStyle,
+                Currency,
+                AllowDebt,
+                Items,
+                Type,
+                Duration,
+            ],
+            SectionTokenKind::Quest => &[
+                Title,
+                Description,
+                Currency,
+                Items,
+                Activation,
+                ManualActivation,
+                Failure,
+                TimeLimit,
+            ],
+            SectionTokenKind::QuestStage => &[Description, Task, Currency, Items, Completion],
+            SectionTokenKind::Location => &[
+                Name,
+                Url,
+                Events,
+                MonsterPool,
+                CombatRatio,
+                TieBreak,
+                MinLevel,
+                Activation,
+                Deactivation,
+            ],
+            SectionTokenKind::ExplorationEvent => &[
+                Name,
+                Progressive,
+                SimplePast,
+                Strength,
+                Stamina,
+                Dexterity,
+                Intelligence,
+                Wisdom,
+                Charisma,
+                Currency,
+                Items,
+                Buff,
+                BuffDuration,
+                Monsters,
+                Then,
+                Activation,
+                Deactivation,
+            ],
+            SectionTokenKind::Monster => &[Name, Hitpoints, Enrage, Activation, Deactivation],
+            SectionTokenKind::Item => &[
+                Name,
+                Description,
+                Value,
+                MaxCount,
+                Buff,
+                BuffDuration,
+                Activation,
+                Deactivation,
+            ],
+            SectionTokenKind::CalendarEvent => &[Name, Month, Day],
+            SectionTokenKind::Destiny => &[
+                Name,
+                Weight,
+                Currency,
+                Strength,
+                Stamina,
+                Dexterity,
+                Intelligence,
+                Wisdom,
+                Charisma,
+                Items,
+            ],
+        }
+    }
+
     pub fn to_snake_case_string(&self) -> &'static str {
         match self {
             SectionTokenKind::Initialisation => "initialisation",
@@ -480,6 +649,8 @@ impl SectionTokenKind {
             SectionTokenKind::ExplorationEvent => "exploration_event",
             SectionTokenKind::Monster => "monster",
             SectionTokenKind::Item => "item",
+            SectionTokenKind::CalendarEvent => "calendar_event",
+            SectionTokenKind::Destiny => "destiny",
         }
     }
 
@@ -499,7 +670,10 @@ impl SectionTokenKind {
             }
             SectionTokenKind::Monster => GameAction::ActivateMonster { id: id_str },
             SectionTokenKind::Item => GameAction::ActivateItem { id: id_str },
-            SectionTokenKind::Initialisation | SectionTokenKind::QuestStage => {
+            SectionTokenKind::Initialisation
+            | SectionTokenKind::QuestStage
+            | SectionTokenKind::CalendarEvent
+            | SectionTokenKind::Destiny => {
                 return Err(ParserError::with_coordinates(
                     ParserErrorKind::UnexpectedField {
                         id_str,
@@ -528,7 +702,9 @@ impl SectionTokenKind {
             SectionTokenKind::Item => GameAction::DeactivateItem { id: id_str },
             SectionTokenKind::Initialisation
             | SectionTokenKind::QuestStage
-            | SectionTokenKind::Quest => {
+            | SectionTokenKind::Quest
+            | SectionTokenKind::CalendarEvent
+            | SectionTokenKind::Destiny => {
                 return Err(ParserError::with_coordinates(
                     ParserErrorKind::UnexpectedField {
                         id_str,
@@ -541,6 +717,148 @@ impl SectionTokenKind {
     }
 }
 
+impl KeyTokenKind {
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        all::<Self>().find(|key| key.keywords().contains(&keyword))
+    }
+
+    /// The keyword of this key, followed by its abbreviations if any.
+    pub fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            KeyTokenKind::Name => &["name"],
+            KeyTokenKind::Url => &["url"],
+            KeyTokenKind::Sound => &["sound"],
+            KeyTokenKind::Category => &["category"],
+            KeyTokenKind::Progressive => &["progressive"],
+            KeyTokenKind::SimplePast => &["simple_past"],
+            KeyTokenKind::Title => &["title"],
+            KeyTokenKind::Description => &["description"],
+            KeyTokenKind::Task => &["task"],
+            KeyTokenKind::Quest => &["quest"],
+            KeyTokenKind::QuestStage => &["quest_stage"],
+            KeyTokenKind::Strength => &["strength", "str"],
+            KeyTokenKind::Stamina => &["stamina", "sta"],
+            KeyTokenKind::Dexterity => &["dexterity", "dex"],
+            KeyTokenKind::Intelligence => &["intelligence", "int"],
+            KeyTokenKind::Wisdom => &["wisdom", "wis"],
+            KeyTokenKind::Charisma => &["charisma", "chr"],
+            KeyTokenKind::Normalize => &["normalize"],
+            KeyTokenKind::CombatStyle => &["combat_style"],
+            KeyTokenKind::Currency => &["currency"],
+            KeyTokenKind::AllowDebt => &["allow_debt"],
+            KeyTokenKind::Value => &["value"],
+            KeyTokenKind::Items => &["items"],
+            KeyTokenKind::Buff => &["buff"],
+            KeyTokenKind::BuffDuration => &["buff_duration"],
+            KeyTokenKind::Type => &["type"],
+            KeyTokenKind::Duration => &["duration"],
+            KeyTokenKind::TimeLimit => &["time_limit"],
+            KeyTokenKind::Then => &["then"],
+            KeyTokenKind::Events => &["events"],
+            KeyTokenKind::MonsterPool => &["monster_pool"],
+            KeyTokenKind::CombatRatio => &["combat_ratio"],
+            KeyTokenKind::Monsters => &["monster"],
+            KeyTokenKind::Hitpoints => &["hitpoints"],
+            KeyTokenKind::Enrage => &["enrage"],
+            KeyTokenKind::Weight => &["weight"],
+            KeyTokenKind::TieBreak => &["tie_break"],
+            KeyTokenKind::MinLevel => &["min_level"],
+            KeyTokenKind::MaxLevel => &["max_level"],
+            KeyTokenKind::MaxCount => &["max_count"],
+            KeyTokenKind::Month => &["month"],
+            KeyTokenKind::Day => &["day"],
+            KeyTokenKind::Activation => &["activation"],
+            KeyTokenKind::ManualActivation => &["manual_activation"],
+            KeyTokenKind::Deactivation => &["deactivation"],
+            KeyTokenKind::Completion => &["completion"],
+            KeyTokenKind::Failure => &["failure"],
+            KeyTokenKind::StartingLocation => &["starting_location"],
+            KeyTokenKind::StartingTime => &["starting_time"],
+            KeyTokenKind::PrestigeQuest => &["prestige_quest"],
+        }
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        self.keywords()[0]
+    }
+
+    /// Parses a translatable key tagged with a language, e.g. `name.de`.
+    pub fn from_translated_keyword(keyword: &str) -> Option<(Self, String)> {
+        let (key, language) = keyword.split_once('.')?;
+        let key = Self::from_keyword(key).filter(Self::is_translatable)?;
+        let is_language = !language.is_empty()
+            && language.chars().all(|character| {
+                character.is_ascii_alphanumeric() || character == '_' || character == '-'
+            });
+        is_language.then(|| (key, language.to_string()))
+    }
+
+    /// True if the value of this key is a display string that can be given in multiple languages.
+    pub fn is_translatable(&self) -> bool {
+        matches!(
+            self,
+            KeyTokenKind::Name
+                | KeyTokenKind::Title
+                | KeyTokenKind::Description
+                | KeyTokenKind::Task
+        )
+    }
+
+    pub fn value_kind(&self) -> KeyValueKind {
+        match self {
+            KeyTokenKind::Name
+            | KeyTokenKind::Url
+            | KeyTokenKind::Sound
+            | KeyTokenKind::Category
+            | KeyTokenKind::Progressive
+            | KeyTokenKind::SimplePast
+            | KeyTokenKind::Title
+            | KeyTokenKind::Description
+            | KeyTokenKind::Task
+            | KeyTokenKind::Type
+            | KeyTokenKind::Monsters
+            | KeyTokenKind::TieBreak
+            | KeyTokenKind::Buff
+            | KeyTokenKind::CombatStyle
+            | KeyTokenKind::StartingLocation => KeyValueKind::String,
+            KeyTokenKind::Currency
+            | KeyTokenKind::Value
+            | KeyTokenKind::MinLevel
+            | KeyTokenKind::MaxLevel
+            | KeyTokenKind::MaxCount
+            | KeyTokenKind::Month
+            | KeyTokenKind::Day => KeyValueKind::Integer,
+            KeyTokenKind::Strength
+            | KeyTokenKind::Stamina
+            | KeyTokenKind::Dexterity
+            | KeyTokenKind::Intelligence
+            | KeyTokenKind::Wisdom
+            | KeyTokenKind::Charisma
+            | KeyTokenKind::CombatRatio
+            | KeyTokenKind::Enrage
+            | KeyTokenKind::Weight => KeyValueKind::Float,
+            KeyTokenKind::Normalize | KeyTokenKind::AllowDebt | KeyTokenKind::ManualActivation => {
+                KeyValueKind::Boolean
+            }
+            KeyTokenKind::Hitpoints => KeyValueKind::Expression,
+            KeyTokenKind::Duration
+            | KeyTokenKind::TimeLimit
+            | KeyTokenKind::BuffDuration
+            | KeyTokenKind::StartingTime => KeyValueKind::Time,
+            KeyTokenKind::Quest
+            | KeyTokenKind::QuestStage
+            | KeyTokenKind::Then
+            | KeyTokenKind::PrestigeQuest => KeyValueKind::Identifier,
+            KeyTokenKind::Items => KeyValueKind::IdentifierCounts,
+            KeyTokenKind::Events | KeyTokenKind::MonsterPool => KeyValueKind::WeightedIdentifiers,
+            KeyTokenKind::Activation
+            | KeyTokenKind::Deactivation
+            | KeyTokenKind::Completion
+            | KeyTokenKind::Failure => KeyValueKind::Trigger,
+        }
+    }
+}
+
 impl From<SectionTokenKind> for TokenKind {
     fn from(kind: SectionTokenKind) -> Self {
         TokenKind::Section(kind)
@@ -568,3 +886,124 @@ impl<T> RangedElement<T> {
         (self.element, self.range)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyTokenKind, TokenIterator, TokenKind};
+    use crate::game_template::parser::error::ParserErrorKind;
+    use crate::game_template::parser::parse_game_template_file;
+    use crate::game_template::GameTemplate;
+
+    const TEMPLATE: &str = "INITIALISATION
+starting_location village
+starting_time 5000y+120d
+
+ACTION
+name Train strength
+progressive training strength
+str 1.5
+duration 1h + 30m
+activation and(level_geq(2), action_count(3, wait))
+
+QUEST
+name first_quest
+title The first quest
+
+QUEST_STAGE
+task Do something
+completion none
+BEGIN
+END
+";
+
+    fn tokenize(input: &str) -> Vec<String> {
+        async_std::task::block_on(async {
+            let mut tokens = TokenIterator::new(input.as_bytes());
+            let mut result = Vec::new();
+            while let Some(token) = tokens.next().await.unwrap() {
+                let expects_string = matches!(
+                    token.kind(),
+                    TokenKind::Key(
+                        KeyTokenKind::Name
+                            | KeyTokenKind::Progressive
+                            | KeyTokenKind::SimplePast
+                            | KeyTokenKind::Title
+                            | KeyTokenKind::Description
+                            | KeyTokenKind::Task
+                    ) | TokenKind::TranslatedKey { .. }
+                );
+                result.push(format!("{token:?}"));
+                if expects_string {
+                    result.push(format!("{:?}", tokens.expect_string_value().await.unwrap()));
+                }
+            }
+            result
+        })
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let lf = tokenize(TEMPLATE);
+        let crlf = tokenize(&TEMPLATE.replace('\n', "\r\n"));
+        assert_eq!(lf, crlf);
+        assert!(crlf.iter().all(|token| !token.contains("\\r")));
+    }
+
+    #[test]
+    fn test_mixed_line_endings() {
+        let lf = tokenize(TEMPLATE);
+        let mixed: String = TEMPLATE
+            .split_inclusive('\n')
+            .enumerate()
+            .map(|(index, line)| {
+                if index % 2 == 0 {
+                    line.replace('\n', "\r\n")
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        assert_eq!(lf, tokenize(&mixed));
+    }
+
+    #[test]
+    fn test_translated_keys() {
+        assert_eq!(
+            KeyTokenKind::from_translated_keyword("name.de"),
+            Some((KeyTokenKind::Name, "de".to_string()))
+        );
+        assert_eq!(
+            KeyTokenKind::from_translated_keyword("task.pt-BR"),
+            Some((KeyTokenKind::Task, "pt-BR".to_string()))
+        );
+        assert_eq!(KeyTokenKind::from_translated_keyword("name."), None);
+        assert_eq!(KeyTokenKind::from_translated_keyword("name"), None);
+        assert_eq!(KeyTokenKind::from_translated_keyword("duration.de"), None);
+        assert_eq!(KeyTokenKind::from_translated_keyword("name.d e"), None);
+
+        let tokens = tokenize("ACTION work\nname Work\nname.de Arbeiten\n");
+        assert!(tokens[4].contains(r#"TranslatedKey { key: Name, language: "de" }"#));
+        assert!(tokens[5].contains(r#""Arbeiten""#));
+    }
+
+    #[test]
+    fn test_string_value_at_eof() {
+        let parse = |input: &str| {
+            async_std::task::block_on(parse_game_template_file(
+                &mut GameTemplate::default(),
+                input.as_bytes(),
+            ))
+            .unwrap_err()
+        };
+
+        let error = parse("ACTION train\nname");
+        assert!(matches!(error.kind, ParserErrorKind::UnexpectedEof));
+
+        let error = parse("QUEST first_quest\ntitle   ");
+        assert!(matches!(
+            error.kind,
+            ParserErrorKind::ExpectedNonemptyString
+        ));
+        assert!(error.coordinates.is_some());
+    }
+}
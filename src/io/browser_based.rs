@@ -1,33 +1,143 @@
 use crate::game_template::CompiledGameTemplate;
-use crate::io::{LoadError, SaveError};
+use crate::io::{
+    decode_compressed_eager, decode_compressed_streaming, load_game_from_storage,
+    save_game_to_storage, KeyValueStorage, LoadError, SaveBackend, SaveError, SaveSlot,
+    STREAMING_LOAD_THRESHOLD,
+};
+use crate::preferences::Preferences;
 use crate::{GameState, RunConfiguration};
 use async_std::path::Path;
 use async_std::sync::Arc;
-use flate2::bufread::GzDecoder;
 use log::info;
 use reqwest::Url;
-use web_sys::window;
+use wasm_bindgen::JsValue;
+use web_sys::{window, Storage};
+
+impl KeyValueStorage for Storage {
+    type Error = JsValue;
+
+    fn get_item(&self, key: &str) -> Result<Option<String>, Self::Error> {
+        Storage::get_item(self, key)
+    }
+
+    fn set_item(&self, key: &str, value: &str) -> Result<(), Self::Error> {
+        Storage::set_item(self, key, value)
+    }
+}
 
 pub async fn load_game(path: impl AsRef<Path>) -> Result<GameState, LoadError> {
     let storage = window()
         .ok_or(LoadError::JsWindowNotFound)?
         .local_storage()?
         .ok_or(LoadError::LocalStorageNotFound)?;
-    let savegame = storage
+    load_game_from_storage(&storage, &path.as_ref().to_string_lossy())
+}
+
+/// Stores each slot as an item of the browser's local storage.
+/// The slots are the keys with the given extension.
+#[derive(Debug, Clone)]
+pub struct LocalStorageSaveBackend {
+    extension: String,
+    compression_level: Option<u32>,
+}
+
+impl LocalStorageSaveBackend {
+    pub fn new(extension: impl Into<String>) -> Self {
+        Self {
+            extension: extension.into(),
+            compression_level: None,
+        }
+    }
+
+    /// Compress savegames with the given gzip level, or not at all if it is `None`.
+    pub fn with_compression_level(mut self, compression_level: Option<u32>) -> Self {
+        self.compression_level = compression_level;
+        self
+    }
+}
+
+impl SaveBackend for LocalStorageSaveBackend {
+    fn load(&self, slot: &str) -> Result<GameState, LoadError> {
+        let storage = window()
+            .ok_or(LoadError::JsWindowNotFound)?
+            .local_storage()?
+            .ok_or(LoadError::LocalStorageNotFound)?;
+        load_game_from_storage(&storage, slot)
+    }
+
+    fn save(&self, slot: &str, game_state: &GameState) -> Result<(), SaveError> {
+        let storage = window()
+            .ok_or(SaveError::JsWindowNotFound)?
+            .local_storage()?
+            .ok_or(SaveError::LocalStorageNotFound)?;
+        save_game_to_storage(&storage, slot, game_state, self.compression_level)
+    }
+
+    fn list_slots(&self) -> Result<Vec<String>, LoadError> {
+        let storage = window()
+            .ok_or(LoadError::JsWindowNotFound)?
+            .local_storage()?
+            .ok_or(LoadError::LocalStorageNotFound)?;
+        let suffix = format!(".{}", self.extension);
+        let mut slots = Vec::new();
+        for index in 0..storage.length()? {
+            if let Some(key) = storage.key(index)? {
+                if key.ends_with(&suffix) {
+                    slots.push(key);
+                }
+            }
+        }
+        slots.sort();
+        Ok(slots)
+    }
+
+    fn delete_slot(&self, slot: &str) -> Result<(), SaveError> {
+        let storage = window()
+            .ok_or(SaveError::JsWindowNotFound)?
+            .local_storage()?
+            .ok_or(SaveError::LocalStorageNotFound)?;
+        storage.remove_item(slot)?;
+        Ok(())
+    }
+}
+
+/// Saves to the local storage key named like the given savegame file.
+pub fn platform_save_slot(
+    savegame_file: &std::path::Path,
+    compression_level: Option<u32>,
+) -> SaveSlot {
+    let backend = LocalStorageSaveBackend::new(
+        savegame_file
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy(),
+    )
+    .with_compression_level(compression_level);
+    SaveSlot::new(Arc::new(backend), savegame_file.to_string_lossy())
+}
+
+pub async fn load_preferences(path: impl AsRef<Path>) -> Result<Preferences, LoadError> {
+    let storage = window()
+        .ok_or(LoadError::JsWindowNotFound)?
+        .local_storage()?
+        .ok_or(LoadError::LocalStorageNotFound)?;
+    let preferences = storage
         .get_item(&path.as_ref().to_string_lossy())?
-        .ok_or(LoadError::SavegameNotFound)?;
-    Ok(pot::from_slice(&base64::decode(&savegame)?)?)
+        .ok_or(LoadError::PreferencesNotFound)?;
+    Ok(serde_json::from_str(&preferences)?)
 }
 
-pub async fn save_game(game_state: &GameState) -> Result<(), SaveError> {
+pub async fn save_preferences(
+    path: impl AsRef<Path>,
+    preferences: Preferences,
+) -> Result<(), SaveError> {
     let storage = window()
         .ok_or(SaveError::JsWindowNotFound)?
         .local_storage()?
         .ok_or(SaveError::LocalStorageNotFound)?;
-    let savegame = base64::encode(pot::to_vec(game_state)?);
     storage.set_item(
-        &game_state.savegame_file.as_ref().to_string_lossy(),
-        &savegame,
+        &path.as_ref().to_string_lossy(),
+        &serde_json::to_string(&preferences)?,
     )?;
     Ok(())
 }
@@ -45,8 +155,18 @@ pub async fn load_game_template(
     let url = base_url.join(&configuration.compiled_game_data_url)?;
     info!("Loading {:?}", url);
     let body = reqwest::get(url).await?.bytes().await?;
-    let decoder = GzDecoder::new(&body[..]);
-    Ok(pot::from_reader(decoder)?)
+    if body.len() as u64 > STREAMING_LOAD_THRESHOLD {
+        decode_compressed_streaming(&body[..])
+    } else {
+        decode_compressed_eager(&body)
+    }
+}
+
+/// The browser cannot list the compiled game data on the server, so no files are discovered.
+pub async fn discover_game_data_files(
+    _directory: impl AsRef<std::path::Path>,
+) -> Result<Vec<std::path::PathBuf>, LoadError> {
+    Ok(Vec::new())
 }
 
 pub async fn load_bytes(
@@ -1,12 +1,17 @@
 use crate::game_template::CompiledGameTemplate;
-use crate::io::{LoadError, SaveError};
+use crate::io::{
+    decode_compressed_eager, decode_compressed_streaming, decode_savegame, encode_savegame,
+    LoadError, SaveBackend, SaveError, SaveSlot, GAME_DATA_SUFFIX, STREAMING_LOAD_THRESHOLD,
+};
+use crate::preferences::Preferences;
 use crate::{GameState, RunConfiguration};
 use async_std::fs::File;
-use async_std::io::{BufReader, BufWriter, ReadExt, WriteExt};
+use async_std::io::{BufReader, ReadExt, WriteExt};
 use async_std::path::Path;
 use async_std::sync::Arc;
-use flate2::bufread::GzDecoder;
 use log::{debug, info};
+use std::ffi::OsString;
+use std::path::PathBuf;
 
 pub async fn load_game(path: impl AsRef<Path>) -> Result<GameState, LoadError> {
     let path = path.as_ref();
@@ -15,16 +20,118 @@ pub async fn load_game(path: impl AsRef<Path>) -> Result<GameState, LoadError> {
     BufReader::new(savegame_file)
         .read_to_end(&mut savegame)
         .await?;
-    Ok(pot::from_slice(&savegame)?)
+    decode_savegame(&savegame)
 }
 
-pub async fn save_game(game_state: &GameState) -> Result<(), SaveError> {
-    let path = &game_state.savegame_file.as_ref();
-    let savegame_file = File::create(path).await?;
-    let savegame = pot::to_vec(game_state)?;
-    let mut writer = BufWriter::new(savegame_file);
-    writer.write_all(&savegame).await?;
-    writer.flush().await?;
+/// Stores each slot as a file in a directory.
+/// The slots are the files in the directory with the given extension.
+#[derive(Debug, Clone)]
+pub struct FileSaveBackend {
+    directory: PathBuf,
+    extension: OsString,
+    compression_level: Option<u32>,
+}
+
+impl FileSaveBackend {
+    pub fn new(directory: impl Into<PathBuf>, extension: impl Into<OsString>) -> Self {
+        Self {
+            directory: directory.into(),
+            extension: extension.into(),
+            compression_level: None,
+        }
+    }
+
+    /// Compress savegames with the given gzip level, or not at all if it is `None`.
+    /// Savegames are loaded independently of how they were compressed.
+    pub fn with_compression_level(mut self, compression_level: Option<u32>) -> Self {
+        self.compression_level = compression_level;
+        self
+    }
+
+    fn slot_path(&self, slot: &str) -> PathBuf {
+        self.directory.join(slot)
+    }
+}
+
+impl SaveBackend for FileSaveBackend {
+    fn load(&self, slot: &str) -> Result<GameState, LoadError> {
+        let savegame = std::fs::read(self.slot_path(slot)).map_err(|error| {
+            if error.kind() == std::io::ErrorKind::NotFound {
+                LoadError::SavegameNotFound
+            } else {
+                error.into()
+            }
+        })?;
+        decode_savegame(&savegame)
+    }
+
+    fn save(&self, slot: &str, game_state: &GameState) -> Result<(), SaveError> {
+        std::fs::write(
+            self.slot_path(slot),
+            encode_savegame(game_state, self.compression_level)?,
+        )?;
+        Ok(())
+    }
+
+    fn list_slots(&self) -> Result<Vec<String>, LoadError> {
+        let mut slots = Vec::new();
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().unwrap_or_default() == self.extension {
+                if let Some(file_name) = path.file_name() {
+                    slots.push(file_name.to_string_lossy().to_string());
+                }
+            }
+        }
+        slots.sort();
+        Ok(slots)
+    }
+
+    fn delete_slot(&self, slot: &str) -> Result<(), SaveError> {
+        match std::fs::remove_file(self.slot_path(slot)) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Saves to the given savegame file through a [`FileSaveBackend`] for its directory.
+pub fn platform_save_slot(
+    savegame_file: &std::path::Path,
+    compression_level: Option<u32>,
+) -> SaveSlot {
+    let directory = savegame_file
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let backend = FileSaveBackend::new(directory, savegame_file.extension().unwrap_or_default())
+        .with_compression_level(compression_level);
+    let slot = savegame_file
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    SaveSlot::new(Arc::new(backend), slot)
+}
+
+pub async fn load_preferences(path: impl AsRef<Path>) -> Result<Preferences, LoadError> {
+    let mut preferences = String::new();
+    File::open(path.as_ref())
+        .await?
+        .read_to_string(&mut preferences)
+        .await?;
+    Ok(serde_json::from_str(&preferences)?)
+}
+
+pub async fn save_preferences(
+    path: impl AsRef<Path>,
+    preferences: Preferences,
+) -> Result<(), SaveError> {
+    let mut preferences_file = File::create(path.as_ref()).await?;
+    preferences_file
+        .write_all(serde_json::to_string_pretty(&preferences)?.as_bytes())
+        .await?;
+    preferences_file.flush().await?;
     Ok(())
 }
 
@@ -33,12 +140,38 @@ pub async fn load_game_template(
 ) -> Result<CompiledGameTemplate, LoadError> {
     info!("Loading {:?}", &configuration.compiled_game_data_file);
     let savegame_file = File::open(&configuration.compiled_game_data_file).await?;
+    if savegame_file.metadata().await?.len() > STREAMING_LOAD_THRESHOLD {
+        debug!("Streaming large game data");
+        let savegame_file = std::fs::File::open(&configuration.compiled_game_data_file)?;
+        return decode_compressed_streaming(std::io::BufReader::new(savegame_file));
+    }
+
     let mut compressed_savegame = Vec::new();
     BufReader::new(savegame_file)
         .read_to_end(&mut compressed_savegame)
         .await?;
-    let decoder = GzDecoder::new(compressed_savegame.as_slice());
-    Ok(pot::from_reader(decoder)?)
+    decode_compressed_eager(&compressed_savegame)
+}
+
+/// The compiled game data files in the given directory, sorted by path.
+pub async fn discover_game_data_files(
+    directory: impl AsRef<std::path::Path>,
+) -> Result<Vec<PathBuf>, LoadError> {
+    let mut game_data_files = Vec::new();
+    for entry in std::fs::read_dir(directory.as_ref())? {
+        let path = entry?.path();
+        if path.is_file()
+            && path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .ends_with(GAME_DATA_SUFFIX)
+        {
+            game_data_files.push(path);
+        }
+    }
+    game_data_files.sort();
+    Ok(game_data_files)
 }
 
 pub async fn load_bytes(
@@ -1,24 +1,53 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{Read, Write};
 use std::sync::Arc;
 use wasm_bindgen::JsValue;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod file_based;
 #[cfg(not(target_arch = "wasm32"))]
-pub use file_based::{load_bytes, load_game, load_game_template, save_game};
+pub use file_based::{
+    discover_game_data_files, load_bytes, load_game, load_game_template, load_preferences,
+    platform_save_slot, save_preferences, FileSaveBackend,
+};
 
 #[cfg(target_arch = "wasm32")]
 mod browser_based;
+use crate::game_state::invariants::InvariantViolation;
 use crate::GameState;
 #[cfg(target_arch = "wasm32")]
-pub use browser_based::{load_bytes, load_game, load_game_template, save_game};
+pub use browser_based::{
+    discover_game_data_files, load_bytes, load_game, load_game_template, load_preferences,
+    platform_save_slot, save_preferences, LocalStorageSaveBackend,
+};
 
 pub mod pathbuf_serde;
 
+/// Compressed game data larger than this many bytes is decompressed and deserialised incrementally.
+pub const STREAMING_LOAD_THRESHOLD: u64 = 1 << 20;
+
+/// The gzip compression level of the compiled game data if none is given.
+pub const DEFAULT_COMPRESSION_LEVEL: u32 = 9;
+
+/// The highest gzip compression level, giving the smallest output at the lowest speed.
+pub const MAX_COMPRESSION_LEVEL: u32 = 9;
+
+/// The file name suffix of compiled game data files.
+pub const GAME_DATA_SUFFIX: &str = ".bin.gz";
+
+/// The first bytes of gzip compressed data, which serialised game states never start with.
+const GZIP_MAGIC_BYTES: [u8; 2] = [0x1f, 0x8b];
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum LoadError {
     IoError(Arc<std::io::Error>),
     PotError(Arc<pot::Error>),
+    JsonError(Arc<serde_json::Error>),
     Base64Error(Arc<base64::DecodeError>),
     ReqwestError(Arc<reqwest::Error>),
     UrlParseError(Arc<url::ParseError>),
@@ -26,7 +55,10 @@ pub enum LoadError {
     JsWindowNotFound,
     LocalStorageNotFound,
     SavegameNotFound,
+    PreferencesNotFound,
     LocationNotFound,
+    RecordingHasNoStart,
+    InvalidGameState(InvariantViolation),
 }
 
 impl From<std::io::Error> for LoadError {
@@ -35,12 +67,24 @@ impl From<std::io::Error> for LoadError {
     }
 }
 
+impl From<InvariantViolation> for LoadError {
+    fn from(violation: InvariantViolation) -> Self {
+        Self::InvalidGameState(violation)
+    }
+}
+
 impl From<pot::Error> for LoadError {
     fn from(error: pot::Error) -> Self {
         Self::PotError(Arc::new(error))
     }
 }
 
+impl From<serde_json::Error> for LoadError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::JsonError(Arc::new(error))
+    }
+}
+
 impl From<base64::DecodeError> for LoadError {
     fn from(error: base64::DecodeError) -> Self {
         Self::Base64Error(Arc::new(error))
@@ -65,25 +109,30 @@ impl From<JsValue> for LoadError {
     }
 }
 
-impl ToString for LoadError {
-    fn to_string(&self) -> String {
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            LoadError::IoError(error) => format!("IO error: {error}"),
-            LoadError::PotError(error) => format!("Parsing error: {error}"),
-            LoadError::Base64Error(error) => format!("Parsing error: {error}"),
-            LoadError::ReqwestError(error) => format!("HTTP request error: {error}"),
-            LoadError::UrlParseError(error) => format!("URL parse error: {error}"),
-            LoadError::JsError(error) => format!("Javascript error: {error:?}"),
+            LoadError::IoError(error) => write!(f, "IO error: {error}"),
+            LoadError::PotError(error) => write!(f, "Parsing error: {error}"),
+            LoadError::JsonError(error) => write!(f, "Parsing error: {error}"),
+            LoadError::Base64Error(error) => write!(f, "Parsing error: {error}"),
+            LoadError::ReqwestError(error) => write!(f, "HTTP request error: {error}"),
+            LoadError::UrlParseError(error) => write!(f, "URL parse error: {error}"),
+            LoadError::JsError(error) => write!(f, "Javascript error: {error:?}"),
             LoadError::JsWindowNotFound => {
-                "The browser does not provide a window object".to_string()
+                write!(f, "The browser does not provide a window object")
             }
             LoadError::LocalStorageNotFound => {
-                "The browser does not provide local storage".to_string()
-            }
-            LoadError::SavegameNotFound => "Could not find savegame".to_string(),
-            LoadError::LocationNotFound => {
-                "The browser does not support the window.location interface".to_string()
+                write!(f, "The browser does not provide local storage")
             }
+            LoadError::SavegameNotFound => write!(f, "Could not find savegame"),
+            LoadError::PreferencesNotFound => write!(f, "Could not find preferences"),
+            LoadError::LocationNotFound => write!(
+                f,
+                "The browser does not support the window.location interface"
+            ),
+            LoadError::RecordingHasNoStart => write!(f, "The recording has no start state"),
+            LoadError::InvalidGameState(violation) => write!(f, "Invalid savegame: {violation}"),
         }
     }
 }
@@ -93,6 +142,7 @@ impl ToString for LoadError {
 pub enum SaveError {
     IoError(Arc<std::io::Error>),
     PotError(Arc<pot::Error>),
+    JsonError(Arc<serde_json::Error>),
     JsError(String),
     JsWindowNotFound,
     LocalStorageNotFound,
@@ -110,6 +160,12 @@ impl From<pot::Error> for SaveError {
     }
 }
 
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::JsonError(Arc::new(error))
+    }
+}
+
 impl From<JsValue> for SaveError {
     fn from(error: JsValue) -> Self {
         Self::JsError(format!("{error:?}"))
@@ -121,6 +177,7 @@ impl ToString for SaveError {
         match self {
             SaveError::IoError(error) => format!("IO error: {}", error),
             SaveError::PotError(error) => format!("Serialization error: {}", error),
+            SaveError::JsonError(error) => format!("Serialization error: {}", error),
             SaveError::JsError(error) => format!("Javascript error: {error:?}"),
             SaveError::JsWindowNotFound => {
                 "The browser does not provide a window object".to_string()
@@ -132,6 +189,419 @@ impl ToString for SaveError {
     }
 }
 
-pub async fn save_game_owned(game_state: GameState) -> Result<(), SaveError> {
-    save_game(&game_state).await
+/// Stores savegames in named slots.
+pub trait SaveBackend: Debug + Send + Sync {
+    fn load(&self, slot: &str) -> Result<GameState, LoadError>;
+
+    fn save(&self, slot: &str, game_state: &GameState) -> Result<(), SaveError>;
+
+    /// The names of all slots that contain a savegame, in ascending order.
+    fn list_slots(&self) -> Result<Vec<String>, LoadError>;
+
+    /// Deleting an empty slot is not an error.
+    fn delete_slot(&self, slot: &str) -> Result<(), SaveError>;
+}
+
+/// A save backend together with the slot that a running game is saved to.
+#[derive(Debug, Clone)]
+pub struct SaveSlot {
+    backend: Arc<dyn SaveBackend>,
+    slot: String,
+}
+
+impl SaveSlot {
+    pub fn new(backend: Arc<dyn SaveBackend>, slot: impl Into<String>) -> Self {
+        Self {
+            backend,
+            slot: slot.into(),
+        }
+    }
+
+    pub async fn save(self, game_state: GameState) -> Result<(), SaveError> {
+        self.backend.save(&self.slot, &game_state)
+    }
+}
+
+/// A string key-value store like the browser's local storage.
+pub trait KeyValueStorage {
+    type Error;
+
+    fn get_item(&self, key: &str) -> Result<Option<String>, Self::Error>;
+
+    fn set_item(&self, key: &str, value: &str) -> Result<(), Self::Error>;
+}
+
+/// Loads the savegame stored under the given key by [`save_game_to_storage`].
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+pub fn load_game_from_storage<Storage: KeyValueStorage>(
+    storage: &Storage,
+    key: &str,
+) -> Result<GameState, LoadError>
+where
+    LoadError: From<Storage::Error>,
+{
+    let savegame = storage.get_item(key)?.ok_or(LoadError::SavegameNotFound)?;
+    decode_savegame(&base64::decode(savegame)?)
+}
+
+/// Stores the savegame base64-encoded under the given key, see [`encode_savegame`] for the compression level.
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+pub fn save_game_to_storage<Storage: KeyValueStorage>(
+    storage: &Storage,
+    key: &str,
+    game_state: &GameState,
+    compression_level: Option<u32>,
+) -> Result<(), SaveError>
+where
+    SaveError: From<Storage::Error>,
+{
+    let savegame = base64::encode(encode_savegame(game_state, compression_level)?);
+    storage.set_item(key, &savegame)?;
+    Ok(())
+}
+
+/// Serialises the savegame, gzip compressed with the given level or uncompressed if it is `None`.
+pub fn encode_savegame(
+    game_state: &GameState,
+    compression_level: Option<u32>,
+) -> Result<Vec<u8>, SaveError> {
+    let savegame = pot::to_vec(game_state)?;
+    Ok(match compression_level {
+        Some(compression_level) => compress(&savegame, compression_level)?,
+        None => savegame,
+    })
+}
+
+/// Deserialises a savegame written by [`encode_savegame`], independently of its compression level.
+pub fn decode_savegame(savegame: &[u8]) -> Result<GameState, LoadError> {
+    let mut game_state: GameState = if savegame.starts_with(&GZIP_MAGIC_BYTES) {
+        decode_compressed_eager(savegame)?
+    } else {
+        pot::from_slice(savegame)?
+    };
+    game_state.complete_loaded_savegame();
+    game_state.validate_invariants()?;
+    Ok(game_state)
+}
+
+/// The name of the game content in the compiled game data file, which is its file name without [`GAME_DATA_SUFFIX`].
+pub fn game_data_name(game_data_file: impl AsRef<std::path::Path>) -> String {
+    let file_name = game_data_file
+        .as_ref()
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    file_name
+        .strip_suffix(GAME_DATA_SUFFIX)
+        .unwrap_or(&file_name)
+        .to_string()
+}
+
+/// The savegame file of the game content in the compiled game data file,
+/// which is the given savegame file with the name of the content inserted before its extension.
+/// For example, the savegame of `data/expansion.bin.gz` next to `savegame.json` is `savegame.expansion.json`.
+pub fn game_data_savegame_file(
+    savegame_file: impl AsRef<std::path::Path>,
+    game_data_file: impl AsRef<std::path::Path>,
+) -> std::path::PathBuf {
+    let savegame_file = savegame_file.as_ref();
+    let mut file_name = savegame_file.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(game_data_name(game_data_file));
+    if let Some(extension) = savegame_file.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    savegame_file.with_file_name(file_name)
+}
+
+/// Compresses the data with gzip, where levels above [`MAX_COMPRESSION_LEVEL`] are treated as the maximum.
+pub fn compress(data: &[u8], compression_level: u32) -> Result<Vec<u8>, std::io::Error> {
+    let mut encoder = GzEncoder::new(
+        Vec::new(),
+        Compression::new(compression_level.min(MAX_COMPRESSION_LEVEL)),
+    );
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Decompresses the whole compiled game data before deserialising it.
+pub fn decode_compressed_eager<T: DeserializeOwned>(compressed: &[u8]) -> Result<T, LoadError> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(compressed).read_to_end(&mut decompressed)?;
+    Ok(pot::from_slice(&decompressed)?)
+}
+
+/// Deserialises the compiled game data while decompressing it,
+/// such that neither the compressed nor the decompressed data is held in memory as a whole.
+pub fn decode_compressed_streaming<T: DeserializeOwned>(
+    compressed: impl Read,
+) -> Result<T, LoadError> {
+    Ok(pot::from_reader(GzDecoder::new(compressed))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        compress, decode_compressed_eager, decode_compressed_streaming, decode_savegame,
+        discover_game_data_files, encode_savegame, game_data_name, game_data_savegame_file,
+        load_game_from_storage, save_game_to_storage, FileSaveBackend, KeyValueStorage, LoadError,
+        SaveBackend, SaveError, SaveSlot, MAX_COMPRESSION_LEVEL,
+    };
+    use crate::game_state::tests::{
+        canonical_pot, create_test_game_state, create_test_game_template,
+    };
+    use crate::game_template::CompiledGameTemplate;
+    use crate::GameState;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::cell::RefCell;
+    use std::collections::{BTreeMap, HashMap};
+    use std::io::{BufReader, Write};
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use wasm_bindgen::JsValue;
+
+    /// Stands in for the browser's local storage.
+    #[derive(Default)]
+    struct MemoryStorage {
+        items: RefCell<HashMap<String, String>>,
+    }
+
+    impl KeyValueStorage for MemoryStorage {
+        type Error = JsValue;
+
+        fn get_item(&self, key: &str) -> Result<Option<String>, Self::Error> {
+            Ok(self.items.borrow().get(key).cloned())
+        }
+
+        fn set_item(&self, key: &str, value: &str) -> Result<(), Self::Error> {
+            self.items
+                .borrow_mut()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    /// Keeps the serialised savegames in memory.
+    #[derive(Debug, Default)]
+    struct MemorySaveBackend {
+        slots: Mutex<BTreeMap<String, Vec<u8>>>,
+    }
+
+    impl SaveBackend for MemorySaveBackend {
+        fn load(&self, slot: &str) -> Result<GameState, LoadError> {
+            let slots = self.slots.lock().unwrap();
+            let savegame = slots.get(slot).ok_or(LoadError::SavegameNotFound)?;
+            let game_state: GameState = pot::from_slice(savegame)?;
+            game_state.validate_invariants()?;
+            Ok(game_state)
+        }
+
+        fn save(&self, slot: &str, game_state: &GameState) -> Result<(), SaveError> {
+            let savegame = pot::to_vec(game_state)?;
+            self.slots
+                .lock()
+                .unwrap()
+                .insert(slot.to_string(), savegame);
+            Ok(())
+        }
+
+        fn list_slots(&self) -> Result<Vec<String>, LoadError> {
+            Ok(self.slots.lock().unwrap().keys().cloned().collect())
+        }
+
+        fn delete_slot(&self, slot: &str) -> Result<(), SaveError> {
+            self.slots.lock().unwrap().remove(slot);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_streaming_and_eager_load_are_identical() {
+        let game_template = create_test_game_template(&[]);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&pot::to_vec(&game_template).unwrap())
+            .unwrap();
+        let path =
+            std::env::temp_dir().join(format!("iced-rs-test-data-{}.bin.gz", std::process::id()));
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let eager: CompiledGameTemplate =
+            decode_compressed_eager(&std::fs::read(&path).unwrap()).unwrap();
+        let streamed: CompiledGameTemplate =
+            decode_compressed_streaming(BufReader::new(std::fs::File::open(&path).unwrap()))
+                .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let expected = canonical_pot(&game_template);
+        assert_eq!(canonical_pot(&eager), expected);
+        assert_eq!(canonical_pot(&streamed), expected);
+    }
+
+    #[test]
+    fn test_compression_levels() {
+        let game_template = create_test_game_template(&[]);
+        let uncompressed = pot::to_vec(&game_template).unwrap();
+        let expected = canonical_pot(&game_template);
+        let compressed: Vec<_> = (0..=MAX_COMPRESSION_LEVEL)
+            .map(|level| compress(&uncompressed, level).unwrap())
+            .collect();
+        for (level, compressed) in compressed.iter().enumerate() {
+            let decompressed: CompiledGameTemplate = decode_compressed_eager(compressed).unwrap();
+            assert_eq!(canonical_pot(&decompressed), expected, "{level}");
+        }
+
+        // level 0 only stores the data
+        assert!(compressed[0].len() > uncompressed.len());
+        assert!(compressed[0].len() > compressed[MAX_COMPRESSION_LEVEL as usize].len());
+    }
+
+    #[test]
+    fn test_savegame_compression_levels() {
+        let mut game_state = create_test_game_state();
+        game_state.update(1_000_000);
+        let expected = canonical_pot(&game_state);
+        for compression_level in [None, Some(0), Some(1), Some(6), Some(MAX_COMPRESSION_LEVEL)] {
+            let savegame = encode_savegame(&game_state, compression_level).unwrap();
+            assert_eq!(
+                canonical_pot(&decode_savegame(&savegame).unwrap()),
+                expected,
+                "{compression_level:?}"
+            );
+        }
+
+        let storage = MemoryStorage::default();
+        save_game_to_storage(&storage, "savegame.json", &game_state, Some(3)).unwrap();
+        let loaded = load_game_from_storage(&storage, "savegame.json").unwrap();
+        assert_eq!(canonical_pot(&loaded), expected);
+    }
+
+    #[test]
+    fn test_storage_round_trip() {
+        let mut game_state = create_test_game_state();
+        game_state.update(1_000_000);
+        let storage = MemoryStorage::default();
+        save_game_to_storage(&storage, "savegame.json", &game_state, None).unwrap();
+
+        assert_eq!(storage.items.borrow().len(), 1);
+        let loaded = load_game_from_storage(&storage, "savegame.json").unwrap();
+        assert_eq!(canonical_pot(&loaded), canonical_pot(&game_state));
+
+        assert!(matches!(
+            load_game_from_storage(&storage, "other_slot"),
+            Err(LoadError::SavegameNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_save_backend_slots() {
+        let mut game_state = create_test_game_state();
+        let backend = Arc::new(MemorySaveBackend::default());
+        assert!(backend.list_slots().unwrap().is_empty());
+        assert!(matches!(
+            backend.load("first"),
+            Err(LoadError::SavegameNotFound)
+        ));
+
+        let initial_game_state = game_state.clone();
+        backend.save("second", &initial_game_state).unwrap();
+        game_state.update(1_000_000);
+        async_std::task::block_on(SaveSlot::new(backend.clone(), "first").save(game_state.clone()))
+            .unwrap();
+        assert_eq!(backend.list_slots().unwrap(), vec!["first", "second"]);
+        assert_eq!(
+            canonical_pot(&backend.load("first").unwrap()),
+            canonical_pot(&game_state)
+        );
+        assert_eq!(
+            canonical_pot(&backend.load("second").unwrap()),
+            canonical_pot(&initial_game_state)
+        );
+
+        backend.delete_slot("second").unwrap();
+        backend.delete_slot("third").unwrap();
+        assert_eq!(backend.list_slots().unwrap(), vec!["first"]);
+        assert!(matches!(
+            backend.load("second"),
+            Err(LoadError::SavegameNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_file_save_backend() {
+        let directory =
+            std::env::temp_dir().join(format!("iced-rs-test-saves-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("notes.txt"), "not a savegame").unwrap();
+        let backend = FileSaveBackend::new(&directory, "json").with_compression_level(Some(6));
+
+        let game_state = create_test_game_state();
+        backend.save("savegame.json", &game_state).unwrap();
+        assert_eq!(backend.list_slots().unwrap(), vec!["savegame.json"]);
+        assert_eq!(
+            canonical_pot(&backend.load("savegame.json").unwrap()),
+            canonical_pot(&game_state)
+        );
+
+        backend.delete_slot("savegame.json").unwrap();
+        backend.delete_slot("savegame.json").unwrap();
+        assert!(backend.list_slots().unwrap().is_empty());
+        assert!(matches!(
+            backend.load("savegame.json"),
+            Err(LoadError::SavegameNotFound)
+        ));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+    #[test]
+    fn test_discover_game_data_files() {
+        let directory =
+            std::env::temp_dir().join(format!("iced-rs-test-game-data-{}", std::process::id()));
+        std::fs::create_dir_all(directory.join("nested.bin.gz")).unwrap();
+        for file_name in ["expansion.bin.gz", "base.bin.gz", "notes.txt", "data.bin"] {
+            std::fs::write(directory.join(file_name), "").unwrap();
+        }
+
+        let game_data_files =
+            async_std::task::block_on(discover_game_data_files(&directory)).unwrap();
+        assert_eq!(
+            game_data_files,
+            vec![
+                directory.join("base.bin.gz"),
+                directory.join("expansion.bin.gz")
+            ]
+        );
+        assert_eq!(game_data_name(&game_data_files[1]), "expansion");
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(matches!(
+            async_std::task::block_on(discover_game_data_files(&directory)),
+            Err(LoadError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_game_data_savegame_file() {
+        for (savegame_file, game_data_file, expected) in [
+            (
+                "savegame.json",
+                "data/expansion.bin.gz",
+                "savegame.expansion.json",
+            ),
+            ("saves/game.json", "base.bin.gz", "saves/game.base.json"),
+            ("savegame", "data/expansion.bin.gz", "savegame.expansion"),
+            (
+                "savegame.json",
+                "data/custom.dat",
+                "savegame.custom.dat.json",
+            ),
+        ] {
+            assert_eq!(
+                game_data_savegame_file(savegame_file, game_data_file),
+                PathBuf::from(expected),
+                "{savegame_file} {game_data_file}"
+            );
+        }
+    }
 }
@@ -0,0 +1,488 @@
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
+extern crate core;
+
+use crate::game_state::GameState;
+use crate::ui::ApplicationState;
+use async_std::path::PathBuf;
+use clap::{Args, Parser, Subcommand};
+#[cfg(not(target_arch = "wasm32"))]
+use clap::{CommandFactory, FromArgMatches, ValueSource};
+use iced::{Application, Settings};
+use log::{info, LevelFilter};
+#[cfg(not(target_arch = "wasm32"))]
+use simplelog::{ColorChoice, CombinedLogger, ConfigBuilder, TermLogger, TerminalMode};
+#[cfg(not(target_arch = "wasm32"))]
+use std::ffi::OsString;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod config_file;
+mod game_state;
+mod game_template;
+mod io;
+mod notifications;
+mod preferences;
+mod recording;
+mod sound;
+mod ui;
+mod utils;
+
+pub const TITLE: &str = "Hero Quest";
+
+pub use crate::game_state::observers::GameEventObserver;
+pub use crate::game_state::triggers::CompiledGameEvent;
+
+/// The parts of the game template language measured by the benchmarks in `benches/`.
+#[cfg(not(target_arch = "wasm32"))]
+#[doc(hidden)]
+pub mod benchmarks {
+    pub use crate::game_state::time::GameTime;
+    pub use crate::game_template::compiler::CompilerStatistics;
+    pub use crate::game_template::parser::parse_game_template_file;
+    pub use crate::game_template::parser::tokenizer::{TokenIterator, TokenKind, ValueTokenKind};
+    pub use crate::game_template::GameTemplate;
+}
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+#[clap(propagate_version = true)]
+pub struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+
+    #[clap(long, default_value = "Info")]
+    log_level: LevelFilter,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    Run(Box<RunConfiguration>),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    Compile(crate::game_template::compiler::CompileConfiguration),
+
+    /// Check that the compiled game data can be loaded and starts a valid game.
+    #[cfg(not(target_arch = "wasm32"))]
+    Verify(crate::game_template::compiler::VerifyConfiguration),
+
+    /// Print the sections and keys of the game template language as JSON.
+    #[cfg(not(target_arch = "wasm32"))]
+    DescribeGrammar,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct RunConfiguration {
+    /// Read the other options from this JSON file, except for those given on the command line.
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    #[clap(long, default_value = "savegame.json")]
+    savegame_file: PathBuf,
+
+    /// Compress savegames with this gzip level from 0 to 9 instead of storing them uncompressed.
+    /// Savegames are loaded independently of how they were compressed.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(0..=9))]
+    savegame_compression_level: Option<u32>,
+
+    #[clap(long, default_value = "preferences.json")]
+    preferences_file: PathBuf,
+
+    #[clap(long, default_value = "data.bin.gz")]
+    compiled_game_data_file: PathBuf,
+
+    #[clap(long, default_value = "data.bin.gz")]
+    compiled_game_data_url: String,
+
+    /// Offer the compiled game data files in this directory to choose from in the main menu.
+    /// Each of them is played with its own savegame next to the savegame file.
+    #[clap(long)]
+    compiled_game_data_directory: Option<PathBuf>,
+
+    #[clap(long, default_value = "static")]
+    static_prefix_directory: PathBuf,
+
+    #[clap(long, default_value = "static")]
+    static_prefix_url: String,
+
+    #[clap(long, default_value = "60.0")]
+    target_fps: f32,
+
+    /// The initial width of the window, enlarged to the minimum width of the layout if necessary.
+    #[clap(long, default_value = "1500")]
+    window_width: u32,
+
+    /// The initial height of the window, enlarged to the minimum height of the layout if necessary.
+    #[clap(long, default_value = "800")]
+    window_height: u32,
+
+    /// Allow resizing the window down to the minimum size of the layout.
+    #[clap(long)]
+    resizable: bool,
+
+    /// Show the display strings of the game data in this language where they are translated.
+    #[clap(long)]
+    language: Option<String>,
+
+    #[clap(long)]
+    profile: bool,
+
+    /// Updates covering more real seconds than this are logged as a warning.
+    #[clap(long, default_value = "5.0")]
+    long_update_seconds: f64,
+
+    /// Updates covering more real seconds than this are made in bulk with a progress screen.
+    #[clap(long, default_value = "3600.0")]
+    bulk_update_seconds: f64,
+
+    /// Append all messages that change the game state to this file.
+    #[clap(long)]
+    record: Option<PathBuf>,
+
+    /// Start from the game state reproduced by replaying this recording instead of loading the savegame.
+    #[clap(long)]
+    replay: Option<PathBuf>,
+
+    /// The program used to play action completion sounds, called with the path of the sound file.
+    #[clap(long, default_value = crate::sound::DEFAULT_SOUND_COMMAND)]
+    sound_command: String,
+
+    #[clap(skip)]
+    observers: crate::game_state::observers::SharedGameEventObservers,
+}
+
+fn initialize_logging(log_level: LevelFilter) {
+    #[cfg(not(target_arch = "wasm32"))]
+    CombinedLogger::init(vec![TermLogger::new(
+        log_level,
+        ConfigBuilder::default()
+            .add_filter_allow_str("iced_rs_test")
+            .build(),
+        TerminalMode::Mixed,
+        ColorChoice::Auto,
+    )])
+    .unwrap();
+
+    #[cfg(all(target_arch = "wasm32", debug_assertions))]
+    let log_level = log_level.max(LevelFilter::Debug);
+    #[cfg(target_arch = "wasm32")]
+    wasm_logger::init(
+        wasm_logger::Config::new(log_level.to_level().unwrap()).module_prefix("iced_rs_test"),
+    );
+
+    info!("Logging initialised successfully");
+}
+
+/// Parses the command line and fills the options of the `run` command that were not given on it
+/// from the configuration file given with `--config`.
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_cli(
+    args: impl IntoIterator<Item = impl Into<OsString> + Clone>,
+) -> Result<Cli, clap::Error> {
+    let matches = Cli::command().try_get_matches_from(args)?;
+    let mut cli = Cli::from_arg_matches(&matches)?;
+    if let (Command::Run(configuration), Some(("run", run_matches))) =
+        (&mut cli.command, matches.subcommand())
+    {
+        if let Some(config) = configuration.config.clone() {
+            crate::config_file::ConfigFile::load(&config)
+                .map_err(|error| clap::Error::raw(clap::ErrorKind::Io, format!("{error}\n")))?
+                .apply(configuration, |id| {
+                    run_matches.value_source(id) == Some(ValueSource::CommandLine)
+                });
+        }
+    }
+    Ok(cli)
+}
+
+/// Runs the command given on the command line, or the game in the browser.
+pub fn run() -> Result<(), Error> {
+    run_with_observers(Vec::new())
+}
+
+/// Like [`run`], but the given observers are called with each event dispatched by the game while it is played.
+pub fn run_with_observers(observers: Vec<GameEventObserver>) -> Result<(), Error> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let cli = parse_cli(std::env::args_os()).unwrap_or_else(|error| error.exit());
+    #[cfg(target_arch = "wasm32")]
+    let cli = Cli {
+        #[cfg(debug_assertions)]
+        log_level: LevelFilter::Debug,
+        #[cfg(not(debug_assertions))]
+        log_level: LevelFilter::Info,
+
+        command: Command::Run(Box::new(RunConfiguration::wasm_default())),
+    };
+    initialize_logging(cli.log_level);
+
+    match cli.command {
+        Command::Run(mut configuration) => {
+            configuration.observers = observers.into();
+            let window_size = configuration.window_size();
+            let resizable = configuration.resizable;
+            let mut settings = Settings::with_flags(*configuration);
+            settings.exit_on_close_request = false;
+            settings.window.resizable = resizable;
+            settings.window.size = window_size;
+            settings.window.min_size = Some(crate::ui::min_window_size());
+            ApplicationState::run(settings)?;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Command::Compile(configuration) => {
+            async_std::task::Builder::new()
+                .name("Game data compiler".to_string())
+                .blocking(crate::game_template::compiler::compile(&configuration))?;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Command::Verify(configuration) => {
+            async_std::task::Builder::new()
+                .name("Game data verifier".to_string())
+                .blocking(crate::game_template::compiler::verify(&configuration))?;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Command::DescribeGrammar => crate::game_template::compiler::print_grammar()?,
+    }
+
+    Ok(())
+}
+
+/// How an update covering some amount of real time is made.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UpdateKind {
+    Normal,
+    /// A normal update that is long enough to be worth a warning.
+    Long,
+    Bulk,
+}
+
+impl RunConfiguration {
+    /// The initial size of the window, enlarged to the minimum size of the layout if necessary.
+    pub fn window_size(&self) -> (u32, u32) {
+        let (min_width, min_height) = crate::ui::min_window_size();
+        (
+            self.window_width.max(min_width),
+            self.window_height.max(min_height),
+        )
+    }
+
+    pub fn update_kind(&self, passed_real_milliseconds: i64) -> UpdateKind {
+        let passed_real_seconds = passed_real_milliseconds as f64 / 1000.0;
+        if passed_real_seconds > self.bulk_update_seconds {
+            UpdateKind::Bulk
+        } else if passed_real_seconds > self.long_update_seconds {
+            UpdateKind::Long
+        } else {
+            UpdateKind::Normal
+        }
+    }
+
+    /// This configuration with the given compiled game data file instead,
+    /// which is saved to its own savegame file derived from the configured one.
+    pub fn with_compiled_game_data_file(&self, compiled_game_data_file: PathBuf) -> Self {
+        let mut configuration = self.clone();
+        configuration.savegame_file =
+            crate::io::game_data_savegame_file(&self.savegame_file, &compiled_game_data_file)
+                .into();
+        configuration.compiled_game_data_file = compiled_game_data_file;
+        configuration
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    fn wasm_default() -> Self {
+        Self {
+            config: None,
+            savegame_file: "savegame.json".into(),
+            savegame_compression_level: None,
+            preferences_file: "preferences.json".into(),
+            compiled_game_data_file: "".into(),
+            compiled_game_data_url: "data.bin.gz".into(),
+            compiled_game_data_directory: None,
+            static_prefix_directory: "".into(),
+            static_prefix_url: "static".into(),
+            target_fps: 60.0,
+            window_width: 1500,
+            window_height: 800,
+            resizable: false,
+            language: None,
+            profile: false,
+            long_update_seconds: 5.0,
+            bulk_update_seconds: 3600.0,
+            record: None,
+            replay: None,
+            sound_command: crate::sound::DEFAULT_SOUND_COMMAND.into(),
+            observers: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    IcedError(iced::Error),
+    #[cfg(not(target_arch = "wasm32"))]
+    CompilerError(Box<crate::game_template::compiler::CompilerError>),
+}
+
+impl From<iced::Error> for Error {
+    fn from(error: iced::Error) -> Self {
+        Self::IcedError(error)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<crate::game_template::compiler::CompilerError> for Error {
+    fn from(error: crate::game_template::compiler::CompilerError) -> Self {
+        Self::CompilerError(Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::min_window_size;
+    use crate::{parse_cli, Command, RunConfiguration, UpdateKind};
+    use async_std::path::PathBuf;
+
+    #[test]
+    fn test_default_update_kinds() {
+        let configuration = RunConfiguration::wasm_default();
+        for (passed_real_milliseconds, expected) in [
+            (0, UpdateKind::Normal),
+            (5_000, UpdateKind::Normal),
+            (5_001, UpdateKind::Long),
+            (3_600_000, UpdateKind::Long),
+            (3_600_001, UpdateKind::Bulk),
+        ] {
+            assert_eq!(
+                configuration.update_kind(passed_real_milliseconds),
+                expected,
+                "{passed_real_milliseconds}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_configured_update_kinds() {
+        let configuration = RunConfiguration {
+            long_update_seconds: 0.5,
+            bulk_update_seconds: 60.0,
+            ..RunConfiguration::wasm_default()
+        };
+        for (passed_real_milliseconds, expected) in [
+            (500, UpdateKind::Normal),
+            (501, UpdateKind::Long),
+            (5_001, UpdateKind::Long),
+            (60_000, UpdateKind::Long),
+            (60_001, UpdateKind::Bulk),
+        ] {
+            assert_eq!(
+                configuration.update_kind(passed_real_milliseconds),
+                expected,
+                "{passed_real_milliseconds}"
+            );
+        }
+    }
+
+    fn parse_run_configuration(args: &[&str]) -> RunConfiguration {
+        let cli = parse_cli(["iced-rs-test", "run"].iter().chain(args)).unwrap();
+        match cli.command {
+            Command::Run(configuration) => *configuration,
+            command => panic!("Expected the run command, but got {command:?}"),
+        }
+    }
+
+    fn write_config_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "iced-rs-test-config-{name}-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_config_file_sets_defaults() {
+        let path = write_config_file(
+            "defaults",
+            r#"{"savegame_file": "file_savegame.json", "target_fps": 30.0, "record": "file.rec"}"#,
+        );
+        let configuration = parse_run_configuration(&["--config", path.to_str().unwrap()]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            configuration.savegame_file,
+            PathBuf::from("file_savegame.json")
+        );
+        assert_eq!(configuration.target_fps, 30.0);
+        assert_eq!(configuration.record, Some(PathBuf::from("file.rec")));
+        assert_eq!(
+            configuration.preferences_file,
+            PathBuf::from("preferences.json")
+        );
+    }
+
+    #[test]
+    fn test_command_line_overrides_config_file() {
+        let path = write_config_file(
+            "overrides",
+            r#"{"savegame_file": "file_savegame.json", "target_fps": 30.0}"#,
+        );
+        let configuration =
+            parse_run_configuration(&["--target-fps", "90", "--config", path.to_str().unwrap()]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(configuration.target_fps, 90.0);
+        assert_eq!(
+            configuration.savegame_file,
+            PathBuf::from("file_savegame.json")
+        );
+    }
+
+    #[test]
+    fn test_window_size() {
+        let configuration = parse_run_configuration(&[]);
+        assert_eq!(configuration.window_size(), (1500, 800));
+        assert!(!configuration.resizable);
+
+        let (min_width, min_height) = min_window_size();
+        let configuration = parse_run_configuration(&[
+            "--window-width",
+            "640",
+            "--window-height",
+            "2000",
+            "--resizable",
+        ]);
+        assert_eq!(configuration.window_size(), (min_width, 2000));
+        assert!(configuration.resizable);
+
+        let path = write_config_file(
+            "window",
+            r#"{"window_width": 1920, "window_height": 100, "resizable": true}"#,
+        );
+        let configuration = parse_run_configuration(&["--config", path.to_str().unwrap()]);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(configuration.window_size(), (1920, min_height));
+        assert!(configuration.resizable);
+    }
+
+    #[test]
+    fn test_invalid_config_file() {
+        let path = write_config_file("invalid", r#"{"target_fbs": 30.0}"#);
+        let result = parse_cli(["iced-rs-test", "run", "--config", path.to_str().unwrap()]);
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+
+        assert!(parse_cli(["iced-rs-test", "run", "--config", "does-not-exist.json"]).is_err());
+    }
+
+    #[test]
+    fn test_compression_levels() {
+        assert_eq!(
+            parse_run_configuration(&[]).savegame_compression_level,
+            None
+        );
+        let configuration = parse_run_configuration(&["--savegame-compression-level", "0"]);
+        assert_eq!(configuration.savegame_compression_level, Some(0));
+        assert!(parse_cli(["iced-rs-test", "run", "--savegame-compression-level", "10"]).is_err());
+
+        assert!(parse_cli(["iced-rs-test", "compile", "--compression-level", "0"]).is_ok());
+        assert!(parse_cli(["iced-rs-test", "compile", "--compression-level", "10"]).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::game_state::triggers::{CompiledGameAction, CompiledGameEvent};
+use crate::game_state::GameState;
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use std::collections::VecDeque;
+
+lazy_static! {
+    pub static ref TOAST_LIFETIME: Duration = Duration::seconds(5);
+}
+
+/// At most this many toasts are shown at once, older ones are dropped first.
+pub const MAX_TOASTS: usize = 5;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Toast {
+    pub message: String,
+    pub expires: DateTime<Utc>,
+}
+
+/// Short-lived messages about quest state changes.
+#[derive(Debug, Clone, Default)]
+pub struct ToastQueue {
+    toasts: VecDeque<Toast>,
+}
+
+impl ToastQueue {
+    /// Enqueues a toast for each quest activation, offer, completion and failure among the given events.
+    pub fn enqueue_events<'events>(
+        &mut self,
+        game_state: &GameState,
+        events: impl IntoIterator<Item = &'events CompiledGameEvent>,
+        now: DateTime<Utc>,
+    ) {
+        for event in events {
+            let message = match event {
+                CompiledGameEvent::Action(CompiledGameAction::ActivateQuest { id }) => {
+                    format!("New quest: {}", game_state.story.quest(*id).title)
+                }
+                CompiledGameEvent::Action(CompiledGameAction::OfferQuest { id }) => {
+                    format!("Quest available: {}", game_state.story.quest(*id).title)
+                }
+                CompiledGameEvent::QuestCompleted { id } => {
+                    format!("Quest completed: {}", game_state.story.quest(*id).title)
+                }
+                CompiledGameEvent::Action(CompiledGameAction::FailQuest { id }) => {
+                    format!("Quest failed: {}", game_state.story.quest(*id).title)
+                }
+                _ => continue,
+            };
+            self.enqueue(message, now);
+        }
+    }
+
+    pub fn enqueue(&mut self, message: String, now: DateTime<Utc>) {
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            message,
+            expires: now + *TOAST_LIFETIME,
+        });
+    }
+
+    /// Removes all toasts that expired before the given time.
+    pub fn expire(&mut self, now: DateTime<Utc>) {
+        self.toasts.retain(|toast| toast.expires > now);
+    }
+
+    pub fn iter(&self) -> impl '_ + Iterator<Item = &'_ Toast> {
+        self.toasts.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ToastQueue, TOAST_LIFETIME};
+    use crate::game_state::tests::{create_test_game_state_with_templates, execute_test_event};
+    use crate::game_state::triggers::CompiledGameEvent;
+    use crate::game_state::world::monsters::MonsterId;
+    use chrono::{Duration, Utc};
+
+    const TOAST_TEMPLATE: &str = "
+QUEST toast_test
+title Catch a rat
+activation none
+failure never
+BEGIN
+    QUEST_STAGE catch_rat
+    task Catch a rat.
+    completion monster_killed_count(1, rat)
+END
+";
+
+    #[test]
+    fn test_quest_completion_toast() {
+        let mut game_state = create_test_game_state_with_templates(&[TOAST_TEMPLATE]);
+        game_state.take_quest_events();
+        let rat = (0..)
+            .map(MonsterId)
+            .find(|&monster| game_state.world.monster(monster).id_str == "rat")
+            .unwrap();
+        let now = Utc::now();
+
+        execute_test_event(
+            &mut game_state,
+            CompiledGameEvent::MonsterKilled { id: rat },
+        );
+        let events = game_state.take_quest_events();
+        let mut toasts = ToastQueue::default();
+        toasts.enqueue_events(&game_state, events.iter(), now);
+
+        let messages: Vec<_> = toasts.iter().map(|toast| toast.message.clone()).collect();
+        assert_eq!(messages, vec!["Quest completed: Catch a rat".to_string()]);
+
+        toasts.expire(now + *TOAST_LIFETIME - Duration::milliseconds(1));
+        assert_eq!(toasts.iter().count(), 1);
+        toasts.expire(now + *TOAST_LIFETIME);
+        assert_eq!(toasts.iter().count(), 0);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::utils::number::{NumberFormat, ProgressFormat};
+use serde::{Deserialize, Serialize};
+
+pub const MIN_UI_SCALE: f32 = 0.5;
+pub const MAX_UI_SCALE: f32 = 3.0;
+pub const UI_SCALE_STEP: f32 = 0.25;
+pub const MAX_DECIMAL_PRECISION: usize = 3;
+
+/// User preferences that are independent of the savegame.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub ui_scale: f32,
+    pub high_contrast: bool,
+    pub mute_sounds: bool,
+    pub number_format: NumberFormat,
+    /// How the progress of quests is labelled.
+    pub progress_format: ProgressFormat,
+    /// Show the savegame path and the frame rate below the sidebar.
+    pub show_debug_overlay: bool,
+    /// The number of decimals of the frame rate and of progress rates.
+    pub decimal_precision: usize,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            high_contrast: false,
+            mute_sounds: false,
+            number_format: NumberFormat::Grouped,
+            progress_format: ProgressFormat::Steps,
+            show_debug_overlay: cfg!(debug_assertions),
+            decimal_precision: 1,
+        }
+    }
+}
+
+impl Preferences {
+    /// Scales an element size by the UI scale factor.
+    pub fn scale(&self, size: u16) -> u16 {
+        (f32::from(size) * self.ui_scale).round().max(1.0) as u16
+    }
+
+    pub fn increase_ui_scale(&mut self) {
+        self.ui_scale = (self.ui_scale + UI_SCALE_STEP).min(MAX_UI_SCALE);
+    }
+
+    pub fn decrease_ui_scale(&mut self) {
+        self.ui_scale = (self.ui_scale - UI_SCALE_STEP).max(MIN_UI_SCALE);
+    }
+
+    pub fn increase_decimal_precision(&mut self) {
+        self.decimal_precision = (self.decimal_precision + 1).min(MAX_DECIMAL_PRECISION);
+    }
+
+    pub fn decrease_decimal_precision(&mut self) {
+        self.decimal_precision = self.decimal_precision.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Preferences, MAX_UI_SCALE, MIN_UI_SCALE};
+
+    #[test]
+    fn test_scale() {
+        let mut preferences = Preferences::default();
+        assert_eq!(preferences.scale(24), 24);
+
+        preferences.increase_ui_scale();
+        preferences.increase_ui_scale();
+        assert_eq!(preferences.ui_scale, 1.5);
+        assert_eq!(preferences.scale(24), 36);
+        assert_eq!(preferences.scale(100), 150);
+        assert_eq!(preferences.scale(15), 23);
+
+        preferences.ui_scale = MIN_UI_SCALE;
+        preferences.decrease_ui_scale();
+        assert_eq!(preferences.ui_scale, MIN_UI_SCALE);
+        assert_eq!(preferences.scale(12), 6);
+        assert_eq!(preferences.scale(1), 1);
+
+        preferences.ui_scale = MAX_UI_SCALE;
+        preferences.increase_ui_scale();
+        assert_eq!(preferences.ui_scale, MAX_UI_SCALE);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::game_state::{GameState, GameStateMessage};
+use crate::io::{LoadError, SaveError};
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// A message that changed the game state, see [`RunConfiguration::record`](crate::RunConfiguration).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RecordedMessage {
+    /// The game state at the start of the recording. Contains the state of the random number generator.
+    Start(Box<GameState>),
+    Update {
+        passed_real_milliseconds: i64,
+    },
+    GameState(GameStateMessage),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedEntry {
+    pub timestamp: DateTime<Utc>,
+    pub message: RecordedMessage,
+}
+
+/// Appends recorded messages to a file.
+/// Each entry is stored in pot format, prefixed by its length as little endian `u64`.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    file: Arc<Mutex<File>>,
+}
+
+impl Recorder {
+    /// Opens the recording file for appending and records the given game state as start of the recording.
+    pub fn create(path: impl AsRef<Path>, game_state: &GameState) -> Result<Self, SaveError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut result = Self {
+            file: Arc::new(Mutex::new(file)),
+        };
+        result.record(RecordedMessage::Start(Box::new(game_state.clone())))?;
+        Ok(result)
+    }
+
+    pub fn record(&mut self, message: RecordedMessage) -> Result<(), SaveError> {
+        let entry = pot::to_vec(&RecordedEntry {
+            timestamp: Utc::now(),
+            message,
+        })?;
+        let mut buffer = (entry.len() as u64).to_le_bytes().to_vec();
+        buffer.extend(entry);
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&buffer)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+pub fn load_recording(path: impl AsRef<Path>) -> Result<Vec<RecordedEntry>, LoadError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    loop {
+        let mut length = [0; 8];
+        match reader.read_exact(&mut length) {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error.into()),
+        }
+        let mut entry = vec![0; u64::from_le_bytes(length) as usize];
+        reader.read_exact(&mut entry)?;
+        entries.push(pot::from_slice(&entry)?);
+    }
+    Ok(entries)
+}
+
+/// Replays the recording from its last start.
+/// Returns `None` if the recording has no start.
+pub fn replay(entries: impl IntoIterator<Item = RecordedEntry>) -> Option<GameState> {
+    let mut game_state = None;
+    for entry in entries {
+        match entry.message {
+            RecordedMessage::Start(start) => game_state = Some(*start),
+            RecordedMessage::Update {
+                passed_real_milliseconds,
+            } => {
+                if let Some(game_state) = &mut game_state {
+                    game_state.update(passed_real_milliseconds);
+                }
+            }
+            RecordedMessage::GameState(message) => {
+                if let Some(game_state) = &mut game_state {
+                    game_state.handle_message(&message);
+                }
+            }
+        }
+    }
+    game_state
+}
+
+/// Loads a recording and replays it.
+pub async fn replay_file(path: impl AsRef<Path>) -> Result<GameState, LoadError> {
+    let game_state = replay(load_recording(path)?).ok_or(LoadError::RecordingHasNoStart)?;
+    debug!("Replayed game state:\n{}", game_state.dump());
+    Ok(game_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_recording, replay, RecordedMessage, Recorder};
+    use crate::game_state::character::CombatStyle;
+    use crate::game_state::tests::{canonical_pot, create_test_game_state};
+    use crate::game_state::GameStateMessage;
+
+    #[test]
+    fn test_replay_reproduces_session() {
+        let path =
+            std::env::temp_dir().join(format!("iced-rs-test-recording-{}.bin", std::process::id()));
+        let mut game_state = create_test_game_state();
+        let train_str = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "train_str")
+            .unwrap()
+            .id;
+        let mut recorder = Recorder::create(&path, &game_state).unwrap();
+
+        let messages = [
+            RecordedMessage::Update {
+                passed_real_milliseconds: 60_000,
+            },
+            RecordedMessage::GameState(GameStateMessage::ActionChanged(train_str)),
+            RecordedMessage::Update {
+                passed_real_milliseconds: 120_000,
+            },
+            RecordedMessage::GameState(GameStateMessage::CombatStyleChanged(CombatStyle::Magic)),
+            RecordedMessage::GameState(GameStateMessage::CancelCurrentAction),
+            RecordedMessage::GameState(GameStateMessage::RenameCharacter("Replayer".to_string())),
+            RecordedMessage::Update {
+                passed_real_milliseconds: 1_000_000,
+            },
+        ];
+        for message in messages {
+            match &message {
+                RecordedMessage::Start(_) => unreachable!(),
+                RecordedMessage::Update {
+                    passed_real_milliseconds,
+                } => game_state.update(*passed_real_milliseconds),
+                RecordedMessage::GameState(message) => {
+                    game_state.handle_message(message);
+                }
+            }
+            recorder.record(message).unwrap();
+        }
+
+        let entries = load_recording(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(entries.len(), 8);
+        let replayed = replay(entries).unwrap();
+
+        assert_eq!(replayed.character.name, "Replayer");
+        assert_eq!(replayed.diff(&game_state), Vec::new());
+        assert_eq!(canonical_pot(&replayed), canonical_pot(&game_state));
+    }
+}
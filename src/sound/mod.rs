@@ -0,0 +1,179 @@
+use crate::game_state::player_actions::PlayerActionId;
+use crate::game_state::GameState;
+use crate::RunConfiguration;
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use log::{debug, warn};
+
+lazy_static! {
+    pub static ref SOUND_COOLDOWN: Duration = Duration::seconds(2);
+}
+
+#[cfg(target_os = "macos")]
+pub const DEFAULT_SOUND_COMMAND: &str = "afplay";
+#[cfg(not(target_os = "macos"))]
+pub const DEFAULT_SOUND_COMMAND: &str = "aplay";
+
+/// Chooses the sounds played when actions are completed.
+/// At most one sound is played per [`SOUND_COOLDOWN`].
+#[derive(Debug, Clone, Default)]
+pub struct CompletionSounds {
+    last_played: Option<DateTime<Utc>>,
+}
+
+impl CompletionSounds {
+    /// Returns the sound of the most recently completed action that has one,
+    /// unless sounds are muted or a sound was played less than [`SOUND_COOLDOWN`] ago.
+    pub fn select(
+        &mut self,
+        game_state: &GameState,
+        completed_actions: &[PlayerActionId],
+        muted: bool,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        if muted {
+            return None;
+        }
+        if let Some(last_played) = self.last_played {
+            if now - last_played < *SOUND_COOLDOWN {
+                return None;
+            }
+        }
+
+        let sound = completion_sounds(game_state, completed_actions).last()?;
+        self.last_played = Some(now);
+        Some(sound.to_string())
+    }
+}
+
+/// The sounds of the given completed actions, skipping actions without a sound.
+pub fn completion_sounds<'result>(
+    game_state: &'result GameState,
+    completed_actions: &'result [PlayerActionId],
+) -> impl 'result + Iterator<Item = &'result str> {
+    completed_actions
+        .iter()
+        .filter_map(|action| game_state.actions.action(*action).sound.as_deref())
+}
+
+/// Plays the sound at the given url relative to the static directory with the configured sound command.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn play_sound(configuration: &RunConfiguration, url: &str) {
+    let path = configuration.static_prefix_directory.join(url);
+    debug!("Playing {path:?}");
+    if let Err(error) = std::process::Command::new(&configuration.sound_command)
+        .arg(path.as_os_str())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+    {
+        warn!(
+            "Could not run sound command {:?}: {error}",
+            configuration.sound_command
+        );
+    }
+}
+
+/// Sounds are not supported in the browser.
+#[cfg(target_arch = "wasm32")]
+pub fn play_sound(_configuration: &RunConfiguration, url: &str) {
+    debug!("Not playing {url:?} in the browser");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{completion_sounds, CompletionSounds, SOUND_COOLDOWN};
+    use crate::game_state::player_actions::{ACTION_SLEEP, ACTION_WAIT};
+    use crate::game_state::tests::create_test_game_state_with_templates;
+    use crate::game_state::GameState;
+    use chrono::{Duration, Utc};
+
+    const SOUND_TEMPLATE: &str = "
+ACTION sound_test
+name Play a sound
+progressive playing a sound
+simple_past played a sound
+type TRAIN
+duration 1h
+currency 0
+sound sounds/chime.wav
+activation none
+deactivation never
+";
+
+    fn sound_test_game_state() -> GameState {
+        create_test_game_state_with_templates(&[SOUND_TEMPLATE])
+    }
+
+    #[test]
+    fn test_completion_sounds() {
+        let game_state = sound_test_game_state();
+        let sound_test = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "sound_test")
+            .unwrap()
+            .id;
+
+        assert_eq!(
+            completion_sounds(&game_state, &[ACTION_WAIT, sound_test, ACTION_SLEEP])
+                .collect::<Vec<_>>(),
+            vec!["sounds/chime.wav"]
+        );
+        assert_eq!(completion_sounds(&game_state, &[ACTION_WAIT]).count(), 0);
+
+        let mut sounds = CompletionSounds::default();
+        let now = Utc::now();
+        assert_eq!(sounds.select(&game_state, &[ACTION_WAIT], false, now), None);
+        assert_eq!(
+            sounds.select(&game_state, &[sound_test, ACTION_WAIT], false, now),
+            Some("sounds/chime.wav".to_string())
+        );
+        assert_eq!(
+            sounds.select(
+                &game_state,
+                &[sound_test],
+                false,
+                now + Duration::seconds(1)
+            ),
+            None
+        );
+        assert_eq!(
+            sounds.select(&game_state, &[sound_test], false, now + *SOUND_COOLDOWN),
+            Some("sounds/chime.wav".to_string())
+        );
+    }
+
+    #[test]
+    fn test_muted_sounds() {
+        let mut game_state = sound_test_game_state();
+        let sound_test = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "sound_test")
+            .unwrap()
+            .id;
+        game_state.actions.selected_action = sound_test;
+        game_state.update(100_000);
+        let completed_actions = game_state.take_completed_actions();
+        assert!(completed_actions.contains(&sound_test));
+
+        let mut sounds = CompletionSounds::default();
+        let now = Utc::now();
+        assert_eq!(
+            sounds.select(&game_state, &completed_actions, true, now),
+            None
+        );
+        assert_eq!(
+            sounds.select(&game_state, &completed_actions, false, now),
+            Some("sounds/chime.wav".to_string())
+        );
+    }
+
+    #[test]
+    fn test_offline_updates_report_no_completed_actions() {
+        let mut game_state = sound_test_game_state();
+        game_state.update_offline(100_000);
+        assert!(game_state.take_completed_actions().is_empty());
+    }
+}
@@ -1,4 +1,6 @@
+use crate::game_state::offline_progress::ProgressSnapshot;
 use crate::ui::running_state::RunningState;
+use crate::ui::style::{scaled, TITLE_SIZE};
 use crate::ui::{do_nothing, ApplicationUiState, Message};
 use crate::{GameState, RunConfiguration};
 use async_std::sync::Arc;
@@ -17,6 +19,7 @@ lazy_static! {
 pub struct BulkUpdateState {
     game_state: Option<GameState>,
     initial_time: DateTime<Utc>,
+    initial_progress: ProgressSnapshot,
     update_count: u64,
 }
 
@@ -24,6 +27,7 @@ impl BulkUpdateState {
     pub fn new(game_state: GameState) -> Self {
         Self {
             initial_time: game_state.last_update,
+            initial_progress: ProgressSnapshot::new(&game_state),
             game_state: game_state.into(),
             update_count: 0,
         }
@@ -31,7 +35,7 @@ impl BulkUpdateState {
 
     pub fn update(
         &mut self,
-        _configuration: Arc<RunConfiguration>,
+        configuration: Arc<RunConfiguration>,
         message: BulkUpdateMessage,
     ) -> Command<Message> {
         match message {
@@ -66,9 +70,11 @@ impl BulkUpdateState {
             }
             BulkUpdateMessage::Finished(game_state) => {
                 info!("Finished bulk update");
-                Command::perform(do_nothing(game_state), |game_state| {
+                let offline_summary = self.initial_progress.summary(&game_state);
+                Command::perform(do_nothing(game_state), move |game_state| {
                     Message::ChangeState(Box::new(ApplicationUiState::Running(Box::new(
-                        RunningState::new(*game_state),
+                        RunningState::new(*game_state, &configuration)
+                            .with_offline_summary(offline_summary),
                     ))))
                 })
             }
@@ -85,7 +91,7 @@ impl BulkUpdateState {
             "Evaluating offline progress... ({}/{total_steps})",
             self.update_count
         ))
-        .size(100)
+        .size(scaled(TITLE_SIZE))
         .horizontal_alignment(Horizontal::Center)
         .vertical_alignment(Vertical::Center)
         .width(Length::Fill)
@@ -95,7 +101,7 @@ impl BulkUpdateState {
 }
 
 async fn update(mut game_state: Box<GameState>, delta_milliseconds: i64) -> Box<GameState> {
-    game_state.update(delta_milliseconds);
+    game_state.update_offline(delta_milliseconds);
     game_state
 }
 
@@ -1,18 +1,20 @@
 use crate::game_state::character::CharacterRace;
+use crate::game_state::difficulty::Difficulty;
 use crate::game_state::GameStateInitialisation;
 use crate::game_template::CompiledGameTemplate;
 use crate::ui::elements::{labelled_element, title};
 use crate::ui::running_state::RunningState;
+use crate::ui::style::{error_color, scaled, HEADING_SIZE};
 use crate::ui::{do_nothing, ApplicationUiState, Message};
 use crate::{GameState, RunConfiguration};
 use async_std::path::PathBuf;
 use enum_iterator::all;
 use iced::alignment::{Horizontal, Vertical};
 use iced::{
-    Alignment,  Color, Command,Element,
+    Alignment, Command,Element,
     Length,
 };
-use iced::widget::{Button, Column,PickList, Space, Text, TextInput, Container, };
+use iced::widget::{Button, Checkbox, Column,PickList, Space, Text, TextInput, Container, };
 use std::borrow::Borrow;
 
 #[derive(Debug, Clone)]
@@ -29,6 +31,8 @@ pub enum CreateNewGameMessage {
     PronounChanged(String),
     SavegameFileChanged(PathBuf),
     RaceChanged(CharacterRace),
+    DifficultyChanged(Difficulty),
+    DestinyRollToggled(bool),
     CreateGame,
 }
 
@@ -39,6 +43,8 @@ impl CreateNewGameState {
             name: "Hugo".to_string(),
             pronoun: "he".to_string(),
             race: Default::default(),
+            difficulty: Default::default(),
+            destiny_roll: false,
         };
         Self {
             message: Default::default(),
@@ -49,7 +55,7 @@ impl CreateNewGameState {
 
     pub fn update(
         &mut self,
-        _configuration: &RunConfiguration,
+        configuration: &RunConfiguration,
         message: CreateNewGameMessage,
     ) -> Command<Message> {
         match message {
@@ -68,10 +74,13 @@ impl CreateNewGameState {
                     self.message = Some("Error: name is empty".to_string());
                 } else {
                     return Command::perform(
-                        do_nothing(Box::new(RunningState::new(GameState::new(
-                            self.game_template.take().unwrap(),
-                            self.game_initialisation.clone(),
-                        )))),
+                        do_nothing(Box::new(RunningState::new(
+                            GameState::new(
+                                self.game_template.take().unwrap(),
+                                self.game_initialisation.clone(),
+                            ),
+                            configuration,
+                        ))),
                         |running_state| {
                             Message::ChangeState(Box::new(ApplicationUiState::Running(
                                 running_state,
@@ -83,6 +92,12 @@ impl CreateNewGameState {
             CreateNewGameMessage::RaceChanged(race) => {
                 self.game_initialisation.race = race;
             }
+            CreateNewGameMessage::DifficultyChanged(difficulty) => {
+                self.game_initialisation.difficulty = difficulty;
+            }
+            CreateNewGameMessage::DestinyRollToggled(destiny_roll) => {
+                self.game_initialisation.destiny_roll = destiny_roll;
+            }
         }
 
         Command::none()
@@ -125,13 +140,26 @@ impl CreateNewGameState {
         )
         .padding(5);
 
+        let difficulty_field_input = PickList::new(
+            all::<Difficulty>().collect::<Vec<_>>(),
+            Some(self.game_initialisation.difficulty),
+            |difficulty| CreateNewGameMessage::DifficultyChanged(difficulty).into(),
+        )
+        .padding(5);
+
+        let destiny_roll_field_input = Checkbox::new(
+            self.game_initialisation.destiny_roll,
+            "Roll a random starting bonus",
+            |destiny_roll| CreateNewGameMessage::DestinyRollToggled(destiny_roll).into(),
+        );
+
         let column = Column::new()
             .padding(15)
             .spacing(5)
             .align_items(Alignment::Center)
             .width(Length::Fill)
             .push(title())
-            .push(Text::new("Create New Game").size(40))
+            .push(Text::new("Create New Game").size(scaled(HEADING_SIZE)))
             .push(Space::new(Length::Shrink, Length::Units(10)))
             .push(
                 Container::new(
@@ -157,6 +185,16 @@ impl CreateNewGameState {
                             "Race:",
                             label_column_width,
                             race_field_input,
+                        ))
+                        .push(labelled_element(
+                            "Difficulty:",
+                            label_column_width,
+                            difficulty_field_input,
+                        ))
+                        .push(labelled_element(
+                            "Destiny:",
+                            label_column_width,
+                            destiny_roll_field_input,
                         )),
                 )
                 .width(Length::Units(500))
@@ -174,7 +212,7 @@ impl CreateNewGameState {
         let column = if let Some(message) = &self.message {
             column
                 .push(Space::new(Length::Shrink, Length::Units(100)))
-                .push(Text::new(message).color(Color::from_rgb(0.9, 0.1, 0.1)))
+                .push(Text::new(message).style(error_color()))
         } else {
             column
         };
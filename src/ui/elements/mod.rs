@@ -1,18 +1,30 @@
 use crate::game_state::character::CharacterAttributes;
+use crate::game_state::combat_log::{CombatHit, CombatSide, HitOutcome};
 use crate::game_state::currency::Currency;
 use crate::game_state::event_log::{GameEvent, GameEventKind};
 use crate::game_state::player_actions::{PlayerActionInProgress, PlayerActionInProgressKind};
+use crate::game_state::story::quests::QuestId;
 use crate::game_state::story::Story;
 use crate::game_state::time::GameTime;
 use crate::game_state::triggers::CompiledGameEvent;
+use crate::game_state::GameStateMessage;
+use crate::ui::running_state::main_view::{
+    EventCategoryFilter, QuestCategory, QuestProgress, QuestStateFilter,
+};
+use crate::ui::style::{
+    copper_color, error_color, formatted_decimal, formatted_number, formatted_progress, gold_color, scaled, silver_color, SMALL_TEXT_SIZE, SUBHEADING_SIZE,
+    TINY_TEXT_SIZE, TITLE_SIZE,
+};
+use crate::ui::Message;
 use crate::utils::text::ordinal_suffix;
 use crate::{GameState, TITLE};
 use event_trigger_action_system::CompiledTriggers;
 use iced::alignment::{Horizontal, Vertical};
 use iced::{
-    Alignment, Color, Element, Length,
+    Alignment, Element, Length,
 };
-use iced::widget::{Column, Container, Row, Scrollable, Space, Text, ProgressBar};
+use iced::widget::tooltip::Position;
+use iced::widget::{scrollable, Button, Column, Container, Row, Scrollable, Space, Text, ProgressBar, Tooltip};
 use std::cmp::Ordering;
 use std::collections::VecDeque;
 
@@ -22,7 +34,7 @@ pub fn title<'a, T: 'a>() -> Container<'a, T> {
             .push(Space::new(Length::Shrink, Length::Units(20)))
             .push(
                 Text::new(TITLE)
-                    .size(100)
+                    .size(scaled(TITLE_SIZE))
                     .horizontal_alignment(Horizontal::Center)
                     .width(Length::Fill),
             )
@@ -48,6 +60,18 @@ pub fn labelled_element<'a, T: 'a, E: Into<Element<'a, T>>>(
         .push(element)
 }
 
+/// A progress bar of the progress of a trigger condition, labelled with the progress towards its goal.
+/// The progress of a trigger starts at one, so the bar and the label start there as well.
+pub fn trigger_progress_bar<'a, T: 'a>(progress: f64, goal: f64, height: u16) -> Row<'a, T> {
+    Row::new()
+        .spacing(5)
+        .align_items(Alignment::Center)
+        .push(ProgressBar::new(1.0..=goal as f32, progress as f32).height(Length::Units(height)))
+        .push(
+            Text::new(formatted_progress(progress - 1.0, goal - 1.0)).size(scaled(TINY_TEXT_SIZE)),
+        )
+}
+
 pub fn labelled_label<'a, T: 'a>(
     label: impl ToString,
     label_column_width: u16,
@@ -73,6 +97,7 @@ pub fn attribute<'a, T: 'a>(
     name: impl AsRef<str>,
     attribute: u64,
     attribute_progress: u64,
+    attribute_progress_per_hour: u64,
 ) -> Row<'a, T> {
     let attribute_progress_bar_width = 50;
     let attribute_progress_bar_height = 10;
@@ -80,10 +105,18 @@ pub fn attribute<'a, T: 'a>(
     Row::new()
         .spacing(5)
         .push(
-            Text::new(&format!("{} {}", name.as_ref(), attribute))
+            Text::new(&format!("{} {}", name.as_ref(), formatted_number(attribute)))
                 .horizontal_alignment(Horizontal::Left)
                 .width(Length::Fill),
         )
+        .push(
+            Text::new(progress_rate(
+                attribute_progress_per_hour,
+                CharacterAttributes::required_attribute_progress(attribute),
+            ))
+            .size(scaled(SMALL_TEXT_SIZE))
+            .vertical_alignment(Vertical::Center),
+        )
         .push(
             Column::new()
                 .align_items(Alignment::Start)
@@ -99,14 +132,32 @@ pub fn attribute<'a, T: 'a>(
         )
 }
 
-pub fn currency<'a, T: 'a>(currency: Currency, align_center: bool) -> Row<'a, T> {
-    let copper_color = Color::from_rgb8(184, 115, 51);
-    let silver_color = Color::from_rgb8(171, 175, 183);
-    let gold_color = Color::from_rgb8(212, 175, 55);
+/// The progress per game hour as percentage of the required progress, or nothing if there is no progress.
+pub fn progress_rate(progress_per_hour: u64, required_progress: u64) -> String {
+    if progress_per_hour == 0 || required_progress == 0 {
+        String::new()
+    } else {
+        format!(
+            "+{}%/h",
+            formatted_decimal(progress_per_hour as f64 / required_progress as f64 * 100.0)
+        )
+    }
+}
 
-    let gold = Text::new(format!("{}g", currency.gold())).color(gold_color);
-    let silver = Text::new(format!("{}s", currency.silver_of_gold())).color(silver_color);
-    let copper = Text::new(format!("{}c", currency.copper_of_silver())).color(copper_color);
+/// An entry for locked content that explains what unlocks it when hovered.
+pub fn locked_entry<'a, T: 'a>(name: &str, unlock_description: &str) -> Tooltip<'a, T> {
+    Tooltip::new(
+        Text::new(format!("{name} (locked)")).size(scaled(SMALL_TEXT_SIZE)),
+        unlock_description,
+        Position::FollowCursor,
+    )
+    .gap(5)
+}
+
+pub fn currency<'a, T: 'a>(currency: Currency, align_center: bool) -> Row<'a, T> {
+    let gold = Text::new(format!("{}g", formatted_number(currency.gold()))).style(gold_color());
+    let silver = Text::new(format!("{}s", currency.silver_of_gold())).style(silver_color());
+    let copper = Text::new(format!("{}c", currency.copper_of_silver())).style(copper_color());
 
     let elements = if currency.gold() > 0 {
         VecDeque::from([gold, silver, copper])
@@ -136,42 +187,209 @@ pub fn currency<'a, T: 'a>(currency: Currency, align_center: bool) -> Row<'a, T>
     result
 }
 
-pub fn scrollable_quest_column<'a, T: 'a>(
-    story: &Story,
-    triggers: &CompiledTriggers<CompiledGameEvent>,
-) -> Scrollable<'a, T> {
-    let mut quest_column = Column::new()
+/// An entry of the [`QuestColumn`].
+#[derive(Debug)]
+enum QuestColumnEntry {
+    Heading(&'static str),
+    Available {
+        id: QuestId,
+        title: String,
+    },
+    Active {
+        title: String,
+        task: String,
+        progress: QuestProgress,
+        deadline: Option<GameTime>,
+    },
+    Completed {
+        title: String,
+        description: Option<String>,
+    },
+    Failed {
+        title: String,
+        failed_task: Option<String>,
+    },
+}
+
+/// The contents of the quest column, which only need to be rebuilt when the story or the filter changed.
+/// The remaining time of quests with a deadline is computed when drawing.
+#[derive(Debug)]
+pub struct QuestColumn {
+    entries: Vec<QuestColumnEntry>,
+}
+
+impl QuestColumn {
+    pub fn new(
+        story: &Story,
+        triggers: &CompiledTriggers<CompiledGameEvent>,
+        quest_state_filter: &QuestStateFilter,
+    ) -> Self {
+        let mut entries = Vec::new();
+
+        if quest_state_filter.is_shown(QuestCategory::Available) {
+            entries.push(QuestColumnEntry::Heading("Available quests:"));
+            entries.extend(
+                story
+                    .iter_available_quests_by_offer_time()
+                    .rev()
+                    .map(|quest| QuestColumnEntry::Available {
+                        id: quest.id,
+                        title: quest.title.clone(),
+                    }),
+            );
+        }
+
+        if quest_state_filter.is_shown(QuestCategory::Active) {
+            entries.push(QuestColumnEntry::Heading("Active quests:"));
+        }
+        entries.extend(
+            story
+                .iter_active_quests_by_activation_time()
+                .rev()
+                .filter(|quest| quest_state_filter.matches(quest.state()))
+                .map(|quest| QuestColumnEntry::Active {
+                    title: quest.title.clone(),
+                    task: quest.active_stage().unwrap().task.clone(),
+                    progress: QuestProgress::new(quest, triggers).unwrap(),
+                    deadline: quest.deadline(),
+                }),
+        );
+
+        if quest_state_filter.is_shown(QuestCategory::Completed) {
+            entries.push(QuestColumnEntry::Heading("Completed quests:"));
+        }
+        entries.extend(
+            story
+                .iter_completed_quests_by_completion_time()
+                .rev()
+                .filter(|quest| quest_state_filter.matches(quest.state()))
+                .map(|quest| QuestColumnEntry::Completed {
+                    title: quest.title.clone(),
+                    description: quest.description.clone(),
+                }),
+        );
+
+        if quest_state_filter.is_shown(QuestCategory::Failed) {
+            entries.push(QuestColumnEntry::Heading("Failed quests:"));
+        }
+        entries.extend(
+            story
+                .iter_failed_quests_by_failure_time()
+                .rev()
+                .filter(|quest| quest_state_filter.matches(quest.state()))
+                .map(|quest| QuestColumnEntry::Failed {
+                    title: quest.title.clone(),
+                    failed_task: quest.failed_stage().map(|stage| stage.task.clone()),
+                }),
+        );
+
+        Self { entries }
+    }
+}
+
+pub fn scrollable_quest_column<'a>(
+    quest_column: &QuestColumn,
+    current_time: GameTime,
+) -> Scrollable<'a, Message> {
+    let mut column = Column::new()
         .width(Length::Shrink)
         .height(Length::Shrink)
         .spacing(5)
-        .padding(5)
-        .push(Text::new("Active quests:").size(24));
-    for quest in story.iter_active_quests_by_activation_time().rev() {
-        let (progress, goal) = triggers
-            .progress(quest.active_stage().unwrap().completion_condition)
-            .unwrap();
-        quest_column = quest_column
-            .push(Text::new(&quest.title))
-            .push(Text::new(&quest.active_stage().unwrap().task).size(16))
-            .push(ProgressBar::new(1.0..=goal as f32, progress as f32).height(Length::Units(10)));
-    }
+        .padding(5);
 
-    quest_column = quest_column.push(Text::new("Completed quests:").size(24));
-    for quest in story.iter_completed_quests_by_completion_time().rev() {
-        quest_column = quest_column.push(Text::new(&quest.title));
-        quest_column = if let Some(description) = &quest.description {
-            quest_column.push(Text::new(description).size(16))
-        } else {
-            quest_column
+    for entry in &quest_column.entries {
+        column = match entry {
+            QuestColumnEntry::Heading(heading) => {
+                column.push(Text::new(*heading).size(scaled(SUBHEADING_SIZE)))
+            }
+            QuestColumnEntry::Available { id, title } => column.push(
+                Row::new()
+                    .spacing(5)
+                    .align_items(Alignment::Center)
+                    .push(Text::new(title.clone()))
+                    .push(
+                        Button::new(Text::new("Accept"))
+                            .on_press(GameStateMessage::AcceptQuest(*id).into()),
+                    ),
+            ),
+            QuestColumnEntry::Active {
+                title,
+                task,
+                progress,
+                deadline,
+            } => {
+                column = column.push(Text::new(title.clone()));
+                // multistage quests show the overall progress above the smaller progress of the active stage
+                let stage_progress_height = if progress.is_multistage() {
+                    let (completed_stages, stage_count) = progress.stages;
+                    // the progress of a trigger starts at one, so the stages are offset by one as well
+                    column = column.push(trigger_progress_bar(
+                        completed_stages as f64 + 1.0,
+                        stage_count as f64 + 1.0,
+                        10,
+                    ));
+                    6
+                } else {
+                    10
+                };
+                let (stage_progress, stage_goal) = progress.active_stage;
+                column = column
+                    .push(Text::new(task.clone()).size(scaled(SMALL_TEXT_SIZE)))
+                    .push(trigger_progress_bar(
+                        stage_progress,
+                        stage_goal,
+                        stage_progress_height,
+                    ));
+                if let Some(deadline) = deadline {
+                    let remaining_time = (*deadline - current_time).max(GameTime::zero());
+                    column = column.push(
+                        Text::new(format!("Time left: {}", time_span(remaining_time)))
+                            .size(scaled(SMALL_TEXT_SIZE))
+                            .style(error_color()),
+                    );
+                }
+                column
+            }
+            QuestColumnEntry::Completed { title, description } => {
+                column = column.push(Text::new(title.clone()));
+                if let Some(description) = description {
+                    column.push(Text::new(description.clone()).size(scaled(SMALL_TEXT_SIZE)))
+                } else {
+                    column
+                }
+            }
+            QuestColumnEntry::Failed { title, failed_task } => {
+                column = column.push(Text::new(title.clone()).style(error_color()));
+                if let Some(failed_task) = failed_task {
+                    column.push(Text::new(failed_task.clone()).size(scaled(SMALL_TEXT_SIZE)))
+                } else {
+                    column
+                }
+            }
         };
     }
 
-    Scrollable::new(quest_column)
-        .scrollbar_width(20)
+    Scrollable::new(column).scrollbar_width(20)
 }
 
+/// The events of the log matching the given search query and category filter, newest first.
+pub fn filtered_events<'a>(
+    game_state: &'a GameState,
+    query: &'a str,
+    event_category_filter: &'a EventCategoryFilter,
+) -> impl 'a + Iterator<Item = &'a GameEvent> {
+    game_state
+        .log
+        .iter_rev_matching(query)
+        .filter(move |event| event_category_filter.matches(event, &game_state.actions))
+}
+
+/// The given events of the log, grouped by date.
+/// They are the [`filtered_events`] that are built only when the log or the filter changed.
 pub fn event_log<'a, T: 'a>(
+    events: &[GameEvent],
     game_state: &GameState,
+    id: scrollable::Id,
 ) -> Scrollable<'a, T> {
     let mut event_column = Column::new()
         .width(Length::Shrink)
@@ -179,9 +397,9 @@ pub fn event_log<'a, T: 'a>(
         .spacing(5)
         .padding(5);
 
-    if let Some(event) = game_state.log.iter_rev().next() {
+    if let Some(event) = events.first() {
         let mut last_date = event.time.floor_day();
-        for event in game_state.log.iter_rev() {
+        for event in events {
             if last_date.days() != event.time.days() {
                 event_column = event_column.push(date_without_era(last_date));
                 last_date = event.time.floor_day();
@@ -193,6 +411,37 @@ pub fn event_log<'a, T: 'a>(
 
     Scrollable::new(event_column)
         .scrollbar_width(20)
+        .id(id)
+}
+
+/// The relative scroll offset of the first event on or after the given time among the given events of the log.
+pub fn event_log_offset(
+    events: &[GameEvent],
+    game_state: &GameState,
+    time: GameTime,
+) -> Option<f32> {
+    let target = game_state.log.first_event_at_or_after(time)?;
+    let event_count = events.len();
+    let newer_event_count = events
+        .iter()
+        .filter(|event| event.time > target.time)
+        .count();
+    Some(if event_count > 1 {
+        newer_event_count as f32 / (event_count - 1) as f32
+    } else {
+        0.0
+    })
+}
+
+/// The distinct days of the logged events, newest first.
+pub fn event_log_days(game_state: &GameState) -> Vec<GameTime> {
+    let mut days: Vec<_> = game_state
+        .log
+        .iter_rev()
+        .map(|event| event.time.floor_day())
+        .collect();
+    days.dedup();
+    days
 }
 
 pub fn event_string<'a, T: 'a>(event: &GameEvent, game_state: &GameState) -> Row<'a, T> {
@@ -214,8 +463,8 @@ pub fn active_action_description<'a, T: 'a>(game_state: &GameState) -> Row<'a, T
                         "{} is {}",
                         game_state.character.name, current_action.verb_progressive,
                     )));
-            if !current_action.success {
-                action_descriptor_row.push(Text::new(" (failure)").color(*ERROR_COLOR))
+            let action_descriptor_row = if !current_action.success {
+                action_descriptor_row.push(Text::new(" (failure)").style(error_color()))
             } else if current_action_currency_reward != Currency::zero() {
                 action_descriptor_row
                     .push(Text::new(
@@ -229,6 +478,23 @@ pub fn active_action_description<'a, T: 'a>(game_state: &GameState) -> Row<'a, T
                     .push(Text::new(")"))
             } else {
                 action_descriptor_row
+            };
+            if let Some((_, fight_start, combat_hitpoints)) =
+                current_action.current_fight(game_state.current_time)
+            {
+                let elapsed = game_state.current_time - fight_start;
+                action_descriptor_row.push(Text::new(format!(
+                    " [enemy ~{} HP, {} ~{} HP]",
+                    formatted_number(
+                        combat_hitpoints.remaining_monster_hitpoints(elapsed).ceil() as i128
+                    ),
+                    game_state.character.name,
+                    formatted_number(
+                        combat_hitpoints.remaining_character_hitpoints(elapsed).ceil() as i128
+                    ),
+                )))
+            } else {
+                action_descriptor_row
             }
         }
         PlayerActionInProgressKind::None => {
@@ -255,7 +521,7 @@ pub fn active_action_description<'a, T: 'a>(game_state: &GameState) -> Row<'a, T
                 action_descriptor_row
             };
             if !current_action.success {
-                action_descriptor_row.push(Text::new(" (failure)").color(*ERROR_COLOR))
+                action_descriptor_row.push(Text::new(" (failure)").style(error_color()))
             } else {
                 action_descriptor_row
             }
@@ -263,6 +529,48 @@ pub fn active_action_description<'a, T: 'a>(game_state: &GameState) -> Row<'a, T
     }
 }
 
+/// The most recent hits of the fight in progress, newest first, or nothing if the character is not fighting.
+pub fn combat_log<'a, T: 'a>(game_state: &GameState) -> Column<'a, T> {
+    let monster_name = match game_state
+        .actions
+        .in_progress()
+        .current_fight(game_state.current_time)
+    {
+        Some((monster, _, _)) => &game_state.world.monster(monster).name,
+        None => return Column::new(),
+    };
+    game_state
+        .combat_log
+        .iter_rev()
+        .fold(Column::new(), |column, hit| {
+            column.push(
+                Text::new(combat_hit_string(
+                    hit,
+                    &game_state.character.name,
+                    monster_name,
+                ))
+                .size(scaled(SMALL_TEXT_SIZE)),
+            )
+        })
+}
+
+fn combat_hit_string(hit: &CombatHit, character_name: &str, monster_name: &str) -> String {
+    let monster_name = monster_name.to_lowercase();
+    let (attacker, target) = match hit.attacker {
+        CombatSide::Character => (character_name.to_string(), format!("the {monster_name}")),
+        CombatSide::Monster => (format!("The {monster_name}"), character_name.to_string()),
+    };
+    let damage = formatted_number(hit.damage.round() as i128);
+    let minute = hit.elapsed.minutes();
+    match hit.outcome {
+        HitOutcome::Hit => format!("{minute}m: {attacker} hits {target} for {damage} damage"),
+        HitOutcome::Miss => format!("{minute}m: {attacker} misses {target}"),
+        HitOutcome::Critical => {
+            format!("{minute}m: {attacker} critically hits {target} for {damage} damage")
+        }
+    }
+}
+
 pub fn completed_action_description<'a, T: 'a>(
     action: &PlayerActionInProgress,
     game_state: &GameState,
@@ -278,7 +586,7 @@ pub fn completed_action_description<'a, T: 'a>(
                         game_state.character.name, action.verb_simple_past,
                     )));
             if !action.success {
-                action_descriptor_row.push(Text::new(" (failure)").color(*ERROR_COLOR))
+                action_descriptor_row.push(Text::new(" (failure)").style(error_color()))
             } else if action_currency_reward != Currency::zero() {
                 action_descriptor_row
                     .push(Text::new(if action_currency_reward > Currency::zero() {
@@ -316,7 +624,7 @@ pub fn completed_action_description<'a, T: 'a>(
                 action_descriptor_row
             };
             if !action.success {
-                action_descriptor_row.push(Text::new(" (failure)").color(*ERROR_COLOR))
+                action_descriptor_row.push(Text::new(" (failure)").style(error_color()))
             } else {
                 action_descriptor_row
             }
@@ -332,6 +640,19 @@ pub fn clock_time(time: GameTime) -> Text<'static> {
     ))
 }
 
+pub fn time_span(time: GameTime) -> String {
+    if time.days() > 0 {
+        format!(
+            "{}d {}h {}m",
+            time.days(),
+            time.hour_of_day(),
+            time.minute_of_hour()
+        )
+    } else {
+        format!("{}h {}m", time.hour_of_day(), time.minute_of_hour())
+    }
+}
+
 pub fn date(time: GameTime) -> Text<'static> {
     let year = time.year_of_era() + 1;
     Text::new(format!(
@@ -346,14 +667,18 @@ pub fn date(time: GameTime) -> Text<'static> {
 }
 
 pub fn date_without_era(time: GameTime) -> Text<'static> {
+    Text::new(date_without_era_string(time))
+}
+
+pub fn date_without_era_string(time: GameTime) -> String {
     let year = time.year_of_era() + 1;
-    Text::new(format!(
+    format!(
         "{}, {} of {}, {}",
         time.day_of_week_str_common(),
         time.day_of_month_str_ord(),
         time.month_of_year_str_common(),
         year,
-    ))
+    )
 }
 
 pub fn year_of_era(year: i128) -> Text<'static> {
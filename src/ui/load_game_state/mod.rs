@@ -1,6 +1,8 @@
 use crate::io::{load_game, LoadError};
+use crate::recording::replay_file;
 use crate::ui::bulk_update_state::BulkUpdateState;
 use crate::ui::main_menu_state::MainMenuState;
+use crate::ui::style::{scaled, TITLE_SIZE};
 use crate::ui::{do_nothing, ApplicationUiState, Message};
 use crate::{GameState, RunConfiguration};
 use async_std::path::PathBuf;
@@ -27,6 +29,12 @@ impl LoadGameState {
     ) -> Command<Message> {
         match message {
             LoadGameMessage::Init => {
+                if let Some(replay) = configuration.replay.clone() {
+                    info!("Replaying {replay:?}");
+                    return Command::perform(replay_file(replay), |loaded| {
+                        LoadGameMessage::Loaded(Box::new(loaded)).into()
+                    });
+                }
                 info!("Loading {:?}", self.path);
                 Command::perform(load_game(self.path.clone()), |loaded| {
                     LoadGameMessage::Loaded(Box::new(loaded)).into()
@@ -64,7 +72,7 @@ impl LoadGameState {
 
     pub fn view(&mut self) -> Element<Message> {
         Text::new("Loading...")
-            .size(100)
+            .size(scaled(TITLE_SIZE))
             .horizontal_alignment(Horizontal::Center)
             .vertical_alignment(Vertical::Center)
             .width(Length::Fill)
@@ -2,6 +2,7 @@ use crate::game_template::CompiledGameTemplate;
 use crate::io::{load_game_template, LoadError};
 use crate::ui::create_new_game_state::CreateNewGameState;
 use crate::ui::main_menu_state::MainMenuState;
+use crate::ui::style::{scaled, TITLE_SIZE};
 use crate::ui::{do_nothing, ApplicationUiState, Message};
 use crate::RunConfiguration;
 use async_std::sync::Arc;
@@ -70,7 +71,7 @@ impl LoadGameTemplateState {
 
     pub fn view(&mut self) -> Element<Message> {
         Text::new("Loading game template...")
-            .size(100)
+            .size(scaled(TITLE_SIZE))
             .horizontal_alignment(Horizontal::Center)
             .vertical_alignment(Vertical::Center)
             .width(Length::Fill)
@@ -1,22 +1,30 @@
+use crate::io::{discover_game_data_files, game_data_name, save_preferences, LoadError, SaveError};
+use crate::preferences::Preferences;
 use crate::ui::elements::title;
 use crate::ui::load_game_state::LoadGameState;
 use crate::ui::load_game_template_state::LoadGameTemplateState;
 use crate::ui::{do_nothing, ApplicationUiState, Message};
+use crate::utils::number::{NumberFormat, ProgressFormat};
+use crate::utils::ui::PickListContainer;
 use crate::RunConfiguration;
 use async_std::path::PathBuf;
 use iced::alignment::Horizontal;
 use iced::{
     Alignment, Command, Element, Length,
 };
-use iced::widget::{Button,Column,Space, Text,
+use iced::widget::{Button, Checkbox, Column, PickList, Row, Space, Text,
                    TextInput,};
+use log::warn;
 use std::borrow::Borrow;
-use crate::ui::style::RedText;
+use crate::ui::style::{preferences, set_preferences, RedText};
 
 #[derive(Debug, Clone)]
 pub struct MainMenuState {
     savegame_file: PathBuf,
     message: Option<String>,
+    /// The compiled game data files to choose from, empty if there is no choice.
+    game_data_files: Vec<PathBuf>,
+    selected_game_data_file: Option<PathBuf>,
 }
 
 impl MainMenuState {
@@ -24,12 +32,14 @@ impl MainMenuState {
         Self {
             savegame_file: default_savegame_file,
             message,
+            game_data_files: Vec::new(),
+            selected_game_data_file: None,
         }
     }
 
     pub fn update(
         &mut self,
-        _configuration: &RunConfiguration,
+        configuration: &RunConfiguration,
         message: MainMenuMessage,
     ) -> Command<Message> {
         match message {
@@ -48,7 +58,78 @@ impl MainMenuState {
                 })
             }
             MainMenuMessage::SavegameFileInputChanged(input) => self.savegame_file = input,
-            MainMenuMessage::Init => {}
+            MainMenuMessage::GameDataDiscovered(result) => match result {
+                Ok(game_data_files) => {
+                    self.game_data_files = game_data_files.into_iter().map(PathBuf::from).collect();
+                    self.selected_game_data_file = self
+                        .game_data_files
+                        .iter()
+                        .find(|file| **file == configuration.compiled_game_data_file)
+                        .cloned();
+                }
+                Err(error) => warn!("Could not discover game data: {}", error.to_string()),
+            },
+            MainMenuMessage::GameDataSelected(compiled_game_data_file) => {
+                self.selected_game_data_file = Some(compiled_game_data_file);
+                self.savegame_file = configuration.savegame_file.clone();
+            }
+            MainMenuMessage::DecreaseUiScale => {
+                return update_preferences(configuration, Preferences::decrease_ui_scale);
+            }
+            MainMenuMessage::IncreaseUiScale => {
+                return update_preferences(configuration, Preferences::increase_ui_scale);
+            }
+            MainMenuMessage::DecreaseDecimalPrecision => {
+                return update_preferences(configuration, Preferences::decrease_decimal_precision);
+            }
+            MainMenuMessage::IncreaseDecimalPrecision => {
+                return update_preferences(configuration, Preferences::increase_decimal_precision);
+            }
+            MainMenuMessage::HighContrastToggled(high_contrast) => {
+                return update_preferences(configuration, |preferences| {
+                    preferences.high_contrast = high_contrast
+                });
+            }
+            MainMenuMessage::MuteSoundsToggled(mute_sounds) => {
+                return update_preferences(configuration, |preferences| {
+                    preferences.mute_sounds = mute_sounds
+                });
+            }
+            MainMenuMessage::ShortNumbersToggled(short_numbers) => {
+                return update_preferences(configuration, |preferences| {
+                    preferences.number_format = if short_numbers {
+                        NumberFormat::Short
+                    } else {
+                        NumberFormat::Grouped
+                    }
+                });
+            }
+            MainMenuMessage::ProgressPercentToggled(progress_percent) => {
+                return update_preferences(configuration, |preferences| {
+                    preferences.progress_format = if progress_percent {
+                        ProgressFormat::Percent
+                    } else {
+                        ProgressFormat::Steps
+                    }
+                });
+            }
+            MainMenuMessage::ShowDebugOverlayToggled(show_debug_overlay) => {
+                return update_preferences(configuration, |preferences| {
+                    preferences.show_debug_overlay = show_debug_overlay
+                });
+            }
+            MainMenuMessage::PreferencesSaved(result) => {
+                if let Err(error) = result {
+                    warn!("Could not save preferences: {}", error.to_string());
+                }
+            }
+            MainMenuMessage::Init => {
+                if let Some(directory) = configuration.compiled_game_data_directory.clone() {
+                    return Command::perform(discover_game_data_files(directory), |result| {
+                        MainMenuMessage::GameDataDiscovered(result).into()
+                    });
+                }
+            }
         }
 
         Command::none()
@@ -75,15 +156,106 @@ impl MainMenuState {
         .padding(5)
         .width(Length::Units(100));
 
+        let preferences = preferences();
+        let ui_scale_row = Row::new()
+            .spacing(5)
+            .align_items(Alignment::Center)
+            .push(
+                Button::new(Text::new("-").horizontal_alignment(Horizontal::Center))
+                    .on_press(MainMenuMessage::DecreaseUiScale.into())
+                    .width(Length::Units(30)),
+            )
+            .push(Text::new(format!(
+                "UI scale {:.0}%",
+                preferences.ui_scale * 100.0
+            )))
+            .push(
+                Button::new(Text::new("+").horizontal_alignment(Horizontal::Center))
+                    .on_press(MainMenuMessage::IncreaseUiScale.into())
+                    .width(Length::Units(30)),
+            );
+        let decimal_precision_row = Row::new()
+            .spacing(5)
+            .align_items(Alignment::Center)
+            .push(
+                Button::new(Text::new("-").horizontal_alignment(Horizontal::Center))
+                    .on_press(MainMenuMessage::DecreaseDecimalPrecision.into())
+                    .width(Length::Units(30)),
+            )
+            .push(Text::new(format!(
+                "{} decimals in rates",
+                preferences.decimal_precision
+            )))
+            .push(
+                Button::new(Text::new("+").horizontal_alignment(Horizontal::Center))
+                    .on_press(MainMenuMessage::IncreaseDecimalPrecision.into())
+                    .width(Length::Units(30)),
+            );
+        let high_contrast_checkbox =
+            Checkbox::new(preferences.high_contrast, "High contrast", |high_contrast| {
+                MainMenuMessage::HighContrastToggled(high_contrast).into()
+            });
+        let mute_sounds_checkbox =
+            Checkbox::new(preferences.mute_sounds, "Mute sounds", |mute_sounds| {
+                MainMenuMessage::MuteSoundsToggled(mute_sounds).into()
+            });
+        let short_numbers_checkbox = Checkbox::new(
+            preferences.number_format == NumberFormat::Short,
+            "Short numbers",
+            |short_numbers| MainMenuMessage::ShortNumbersToggled(short_numbers).into(),
+        );
+        let progress_percent_checkbox = Checkbox::new(
+            preferences.progress_format == ProgressFormat::Percent,
+            "Progress in percent",
+            |progress_percent| MainMenuMessage::ProgressPercentToggled(progress_percent).into(),
+        );
+        let show_debug_overlay_checkbox = Checkbox::new(
+            preferences.show_debug_overlay,
+            "Show FPS and savegame path",
+            |show_debug_overlay| {
+                MainMenuMessage::ShowDebugOverlayToggled(show_debug_overlay).into()
+            },
+        );
+
+        let game_data_pick_list = (!self.game_data_files.is_empty()).then(|| {
+            let game_data_file =
+                |file: &PathBuf| PickListContainer::new(game_data_name(file), file.clone());
+            PickList::new(
+                self.game_data_files
+                    .iter()
+                    .map(game_data_file)
+                    .collect::<Vec<_>>(),
+                self.selected_game_data_file.as_ref().map(game_data_file),
+                |file| MainMenuMessage::GameDataSelected(file.data).into(),
+            )
+            .placeholder("Game data")
+            .padding(5)
+            .width(Length::Units(200))
+        });
+
         let column = Column::new()
             .padding(15)
             .spacing(5)
             .align_items(Alignment::Center)
             .width(Length::Fill)
-            .push(title())
+            .push(title());
+        let column = if let Some(game_data_pick_list) = game_data_pick_list {
+            column.push(game_data_pick_list)
+        } else {
+            column
+        };
+        let column = column
             .push(savegame_file_input)
             .push(load_game_button)
-            .push(new_game_button);
+            .push(new_game_button)
+            .push(Space::new(Length::Shrink, Length::Units(20)))
+            .push(ui_scale_row)
+            .push(decimal_precision_row)
+            .push(high_contrast_checkbox)
+            .push(mute_sounds_checkbox)
+            .push(short_numbers_checkbox)
+            .push(progress_percent_checkbox)
+            .push(show_debug_overlay_checkbox);
 
         let column = if let Some(message) = &self.message {
             column
@@ -103,4 +275,30 @@ pub enum MainMenuMessage {
     LoadGame,
     NewGame,
     SavegameFileInputChanged(PathBuf),
+    GameDataDiscovered(Result<Vec<std::path::PathBuf>, LoadError>),
+    /// Play the given compiled game data, handled by the application to change the configuration.
+    GameDataSelected(PathBuf),
+    DecreaseUiScale,
+    IncreaseUiScale,
+    DecreaseDecimalPrecision,
+    IncreaseDecimalPrecision,
+    HighContrastToggled(bool),
+    MuteSoundsToggled(bool),
+    ShortNumbersToggled(bool),
+    ProgressPercentToggled(bool),
+    ShowDebugOverlayToggled(bool),
+    PreferencesSaved(Result<(), SaveError>),
+}
+
+fn update_preferences(
+    configuration: &RunConfiguration,
+    update: impl FnOnce(&mut Preferences),
+) -> Command<Message> {
+    let mut preferences = preferences();
+    update(&mut preferences);
+    set_preferences(preferences);
+    Command::perform(
+        save_preferences(configuration.preferences_file.clone(), preferences),
+        |result| MainMenuMessage::PreferencesSaved(result).into(),
+    )
 }
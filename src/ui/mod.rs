@@ -3,7 +3,11 @@ use crate::ui::create_new_game_state::{CreateNewGameMessage, CreateNewGameState}
 use crate::ui::load_game_state::{LoadGameMessage, LoadGameState};
 use crate::ui::load_game_template_state::{LoadGameTemplateMessage, LoadGameTemplateState};
 use crate::ui::main_menu_state::{MainMenuMessage, MainMenuState};
+use crate::io::{load_preferences, LoadError};
+use crate::preferences::Preferences;
+use crate::sound::play_sound;
 use crate::ui::running_state::{RunningMessage, RunningState};
+use crate::ui::style::set_preferences;
 use crate::{GameState, RunConfiguration, TITLE};
 use async_std::sync::Arc;
 use iced::{Application, Command, Element, Subscription};
@@ -19,9 +23,27 @@ mod main_menu_state;
 mod running_state;
 mod style;
 
+/// The width of the sidebar with the character sheet next to the main view of the running game.
+pub const SIDEBAR_WIDTH: u16 = 220;
+/// The narrowest main view that shows the columns of the overview without clipping.
+pub const MIN_MAIN_VIEW_WIDTH: u16 = 880;
+/// The lowest window that shows the title and the sidebar down to the attribute bars without clipping.
+pub const MIN_WINDOW_HEIGHT: u16 = 760;
+
+/// The smallest window size in which the layout of the running game is not clipped.
+pub fn min_window_size() -> (u32, u32) {
+    (
+        u32::from(SIDEBAR_WIDTH) + u32::from(MIN_MAIN_VIEW_WIDTH),
+        u32::from(MIN_WINDOW_HEIGHT),
+    )
+}
+
 #[derive(Debug)]
 pub struct ApplicationState {
     configuration: Arc<RunConfiguration>,
+    /// The configuration as given on startup, from which the configuration for the compiled game data
+    /// selected in the main menu is derived.
+    base_configuration: Arc<RunConfiguration>,
     ui_state: ApplicationUiState,
     should_exit: bool,
 }
@@ -42,12 +64,14 @@ pub enum Message {
     NativeEvent(iced_native::Event),
     ChangeState(Box<ApplicationUiState>),
     ChangeFromRunningToBulkUpdate,
+    PreferencesLoaded(Result<Preferences, LoadError>),
     MainMenu(MainMenuMessage),
     LoadGame(LoadGameMessage),
     LoadGameTemplate(LoadGameTemplateMessage),
     BulkUpdate(BulkUpdateMessage),
     CreateNewGame(CreateNewGameMessage),
     Running(RunningMessage),
+    PlaySound(String),
     Quit,
 }
 
@@ -58,16 +82,26 @@ impl Application for ApplicationState {
     type Flags = RunConfiguration;
 
     fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        let load_preferences = Command::perform(
+            load_preferences(flags.preferences_file.clone()),
+            Message::PreferencesLoaded,
+        );
+        let ui_state = ApplicationUiState::MainMenu(Box::new(MainMenuState::new(
+            flags.savegame_file.clone(),
+            None,
+        )));
+        let init = Command::perform(do_nothing(ui_state.init_message()), |init_message| {
+            init_message
+        });
+        let configuration: Arc<RunConfiguration> = flags.into();
         (
             Self {
-                ui_state: ApplicationUiState::MainMenu(Box::new(MainMenuState::new(
-                    flags.savegame_file.clone(),
-                    None,
-                ))),
-                configuration: flags.into(),
+                ui_state,
+                base_configuration: configuration.clone(),
+                configuration,
                 should_exit: false,
             },
-            Command::none(),
+            Command::batch([load_preferences, init]),
         )
     }
 
@@ -109,11 +143,36 @@ impl Application for ApplicationState {
                     init_message
                 })
             }
+            (Message::PreferencesLoaded(result), _) => {
+                match result {
+                    Ok(preferences) => set_preferences(preferences),
+                    Err(error) => debug!("Using default preferences: {}", error.to_string()),
+                }
+                Command::none()
+            }
+            (Message::PlaySound(url), _) => {
+                play_sound(&self.configuration, &url);
+                Command::none()
+            }
             (Message::Quit, _) => {
                 info!("Exiting...");
                 self.should_exit = true;
                 Command::none()
             }
+            (
+                Message::MainMenu(MainMenuMessage::GameDataSelected(compiled_game_data_file)),
+                ApplicationUiState::MainMenu(main_menu_state),
+            ) => {
+                info!("Selected game data {compiled_game_data_file:?}");
+                self.configuration = Arc::new(
+                    self.base_configuration
+                        .with_compiled_game_data_file(compiled_game_data_file.clone()),
+                );
+                main_menu_state.update(
+                    &self.configuration,
+                    MainMenuMessage::GameDataSelected(compiled_game_data_file),
+                )
+            }
             (
                 Message::MainMenu(main_menu_message),
                 ApplicationUiState::MainMenu(main_menu_state),
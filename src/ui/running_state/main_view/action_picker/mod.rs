@@ -1,19 +1,49 @@
 use crate::game_state::character::CombatStyle;
-use crate::game_state::player_actions::ACTION_EXPLORE;
-use crate::ui::running_state::GameStateMessage;
-use crate::ui::style::{FramedContainer, RadioStyleSheet};
+use crate::game_state::player_actions::{
+    group_actions_by_category, TrainingPolicy, ACTION_EXPLORE, COMBAT_POLICY_CHOICES,
+};
+use crate::ui::elements::locked_entry;
+use crate::ui::running_state::main_view::MainViewMessage;
+use crate::ui::running_state::{GameStateMessage, RunningMessage};
+use crate::ui::style::{
+    scaled, ButtonStyleSheet, FramedContainer, RadioStyleSheet, SelectedButtonStyleSheet,
+    SUBHEADING_SIZE,
+};
 use crate::ui::Message;
 use crate::GameState;
 use enum_iterator::all;
-use iced::{ Element, Length,};
-use iced::widget::{ Column, Container,Radio, Row, Space, Text};
+use iced::{Alignment, Command, Element, Length};
+use iced::widget::{Button, Column, Container, Radio, Row, Space, Text};
+use std::collections::HashSet;
 
 #[derive(Debug, Clone)]
-pub struct ActionPickerState {}
+pub struct ActionPickerState {
+    /// The categories whose actions are hidden, with `None` standing for the uncategorized actions.
+    collapsed_categories: HashSet<Option<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ActionPickerMessage {
+    ToggleCategory(Option<String>),
+}
 
 impl ActionPickerState {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            collapsed_categories: Default::default(),
+        }
+    }
+
+    pub fn update(&mut self, message: ActionPickerMessage) -> Command<Message> {
+        match message {
+            ActionPickerMessage::ToggleCategory(category) => {
+                if !self.collapsed_categories.remove(&category) {
+                    self.collapsed_categories.insert(category);
+                }
+            }
+        }
+
+        Command::none()
     }
 
     pub fn view(&self, game_state: &GameState) -> Element<Message> {
@@ -21,7 +51,7 @@ impl ActionPickerState {
         let mut action_picker_column = Column::new()
             .spacing(5)
             .padding(5)
-            .push(Text::new("Action").size(24));
+            .push(Text::new("Action").size(scaled(SUBHEADING_SIZE)));
         let selected_action = if game_state.actions.selected_action == ACTION_EXPLORE {
             None
         } else {
@@ -34,19 +64,109 @@ impl ActionPickerState {
             .filter(|action| action.id != ACTION_EXPLORE)
             .collect();
         choosable_actions.sort_by_key(|action| &action.name);
-        for action in choosable_actions {
-            action_picker_column = action_picker_column.push(
-                Radio::new(action.id, action.name.clone(), selected_action, |id| {
-                    GameStateMessage::ActionChanged(id).into()
-                })
-                .style(RadioStyleSheet),
+        game_state.sort_favorite_actions_first(&mut choosable_actions);
+        let groups = group_actions_by_category(choosable_actions);
+        // without categories, the actions are listed without a header
+        let has_categories = groups.iter().any(|(category, _)| category.is_some());
+        for (category, actions) in groups {
+            let category = category.map(str::to_string);
+            let is_collapsed = self.collapsed_categories.contains(&category);
+            if has_categories {
+                let header = format!(
+                    "{} {}",
+                    if is_collapsed { "+" } else { "-" },
+                    category.as_deref().unwrap_or("Other")
+                );
+                action_picker_column = action_picker_column.push(
+                    Button::new(Text::new(header))
+                        .on_press(ActionPickerMessage::ToggleCategory(category).into())
+                        .style(ButtonStyleSheet::style_sheet()),
+                );
+                if is_collapsed {
+                    continue;
+                }
+            }
+
+            for action in actions {
+                let is_favorite = game_state.is_favorite_action(action.id);
+                let label = if game_state.can_afford_action(action.id) {
+                    action.name.clone()
+                } else {
+                    format!("{} (cannot afford)", action.name)
+                };
+                action_picker_column = action_picker_column.push(
+                    Row::new()
+                        .spacing(5)
+                        .align_items(Alignment::Center)
+                        .push(
+                            Button::new(Text::new(if is_favorite { "Unpin" } else { "Pin" }))
+                                .on_press(GameStateMessage::ToggleFavorite(action.id).into())
+                                .style(if is_favorite {
+                                    SelectedButtonStyleSheet::style_sheet()
+                                } else {
+                                    ButtonStyleSheet::style_sheet()
+                                }),
+                        )
+                        .push(
+                            Button::new(Text::new("Queue"))
+                                .on_press(GameStateMessage::EnqueueAction(action.id).into())
+                                .style(ButtonStyleSheet::style_sheet()),
+                        )
+                        .push(
+                            Radio::new(action.id, label, selected_action, |id| {
+                                GameStateMessage::ActionChanged(id).into()
+                            })
+                            .style(RadioStyleSheet),
+                        ),
+                );
+            }
+        }
+
+        let mut locked_actions: Vec<_> = game_state.actions.list_locked().collect();
+        locked_actions.sort_by_key(|action| &action.name);
+        for action in locked_actions {
+            if let Some(unlock_description) = &action.unlock_description {
+                action_picker_column =
+                    action_picker_column.push(locked_entry(&action.name, unlock_description));
+            }
+        }
+
+        let mut action_queue_column = Column::new()
+            .spacing(5)
+            .padding(5)
+            .push(Text::new("Queue").size(scaled(SUBHEADING_SIZE)));
+        for (index, &action) in game_state.action_queue().iter().enumerate() {
+            let mut move_up_button =
+                Button::new(Text::new("Up")).style(ButtonStyleSheet::style_sheet());
+            if index > 0 {
+                move_up_button =
+                    move_up_button.on_press(GameStateMessage::MoveQueuedActionUp(index).into());
+            }
+            action_queue_column = action_queue_column.push(
+                Row::new()
+                    .spacing(5)
+                    .align_items(Alignment::Center)
+                    .push(move_up_button)
+                    .push(
+                        Button::new(Text::new("Remove"))
+                            .on_press(GameStateMessage::RemoveQueuedAction(index).into())
+                            .style(ButtonStyleSheet::style_sheet()),
+                    )
+                    .push(Text::new(&game_state.actions.action(action).name)),
+            );
+        }
+        if !game_state.action_queue().is_empty() {
+            action_queue_column = action_queue_column.push(
+                Button::new(Text::new("Clear"))
+                    .on_press(GameStateMessage::ClearActionQueue.into())
+                    .style(ButtonStyleSheet::style_sheet()),
             );
         }
 
         let mut location_picker_column = Column::new()
             .spacing(5)
             .padding(5)
-            .push(Text::new("Explore").size(24));
+            .push(Text::new("Explore").size(scaled(SUBHEADING_SIZE)));
         let selected_location = if game_state.actions.selected_action == ACTION_EXPLORE {
             Some(game_state.world.selected_location)
         } else {
@@ -70,7 +190,7 @@ impl ActionPickerState {
         let mut combat_style_picker_column = Column::new()
             .spacing(5)
             .padding(5)
-            .push(Text::new("Combat style").size(24));
+            .push(Text::new("Combat style").size(scaled(SUBHEADING_SIZE)));
         let selected_combat_style = Some(game_state.character.selected_combat_style);
 
         for combat_style in all::<CombatStyle>() {
@@ -85,10 +205,51 @@ impl ActionPickerState {
             );
         }
 
+        let mut combat_policy_picker_column = Column::new()
+            .spacing(5)
+            .padding(5)
+            .push(Text::new("Combat policy").size(scaled(SUBHEADING_SIZE)));
+        let selected_combat_policy = Some(game_state.combat_policy());
+
+        for combat_policy in COMBAT_POLICY_CHOICES {
+            combat_policy_picker_column = combat_policy_picker_column.push(
+                Radio::new(
+                    combat_policy,
+                    combat_policy.to_string(),
+                    selected_combat_policy,
+                    |combat_policy| GameStateMessage::CombatPolicyChanged(combat_policy).into(),
+                )
+                .style(RadioStyleSheet),
+            );
+        }
+
+        let mut training_policy_picker_column = Column::new()
+            .spacing(5)
+            .padding(5)
+            .push(Text::new("Training policy").size(scaled(SUBHEADING_SIZE)));
+        let selected_training_policy = Some(game_state.training_policy());
+
+        for training_policy in all::<TrainingPolicy>() {
+            training_policy_picker_column = training_policy_picker_column.push(
+                Radio::new(
+                    training_policy,
+                    training_policy.to_string(),
+                    selected_training_policy,
+                    |training_policy| {
+                        GameStateMessage::TrainingPolicyChanged(training_policy).into()
+                    },
+                )
+                .style(RadioStyleSheet),
+            );
+        }
+
         rows = rows
             .push(Container::new(action_picker_column).style(FramedContainer))
+            .push(Container::new(action_queue_column).style(FramedContainer))
             .push(Container::new(location_picker_column).style(FramedContainer))
             .push(Container::new(combat_style_picker_column).style(FramedContainer))
+            .push(Container::new(combat_policy_picker_column).style(FramedContainer))
+            .push(Container::new(training_policy_picker_column).style(FramedContainer))
             .push(Space::new(Length::Fill, Length::Shrink));
 
         Container::new(rows)
@@ -98,3 +259,11 @@ impl ActionPickerState {
             .into()
     }
 }
+
+impl From<ActionPickerMessage> for Message {
+    fn from(message: ActionPickerMessage) -> Self {
+        Message::Running(RunningMessage::MainView(MainViewMessage::ActionPicker(
+            message,
+        )))
+    }
+}
@@ -2,7 +2,7 @@ use crate::game_state::time::{GameTime, DAYS_PER_MONTH, FIRST_DAY_OF_MONTH};
 use crate::ui::elements::{date, year_of_era};
 use crate::ui::running_state::main_view::MainViewMessage;
 use crate::ui::running_state::RunningMessage;
-use crate::ui::style::{ColoredFramedContainer, FramedContainer};
+use crate::ui::style::{scaled, ColoredFramedContainer, FramedContainer, BUTTON_SYMBOL_SIZE};
 use crate::ui::Message;
 use crate::GameState;
 use iced::alignment::{Horizontal, Vertical};
@@ -91,7 +91,7 @@ impl CalendarState {
             Container::new(column).style(FramedContainer)
         });
 
-        let plus_minus_size = 20;
+        let plus_minus_size = scaled(BUTTON_SYMBOL_SIZE);
         let year_selector = Row::new()
             .spacing(5)
             .padding(5)
@@ -0,0 +1,180 @@
+use crate::game_state::time::GameTime;
+use crate::game_state::world::locations::LocationId;
+use crate::game_state::world::World;
+use crate::ui::elements::{date_without_era_string, locked_entry};
+use crate::ui::style::{scaled, ColoredFramedContainer, FramedContainer, RED, SUBHEADING_SIZE};
+use crate::ui::Message;
+use crate::GameState;
+use iced::widget::{Column, Container, Scrollable, Text};
+use iced::{Element, Length};
+
+#[derive(Debug, Clone)]
+pub struct LocationMapState {}
+
+/// The active locations of the world in the order they were unlocked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocationMap {
+    pub nodes: Vec<LocationMapNode>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocationMapNode {
+    pub id: LocationId,
+    pub name: String,
+    pub activation_time: GameTime,
+    pub selected: bool,
+}
+
+impl LocationMapState {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn view(&self, game_state: &GameState) -> Element<Message> {
+        let location_map = LocationMap::new(&game_state.world);
+        let mut column = Column::new()
+            .width(Length::Fill)
+            .spacing(5)
+            .padding(5)
+            .push(Text::new("Locations").size(scaled(SUBHEADING_SIZE)));
+
+        for node in location_map.nodes {
+            let label = Column::new()
+                .padding(5)
+                .push(Text::new(if node.selected {
+                    format!("{} (selected)", node.name)
+                } else {
+                    node.name
+                }))
+                .push(Text::new(format!(
+                    "Unlocked {}",
+                    date_without_era_string(node.activation_time)
+                )));
+            column = column.push(if node.selected {
+                Container::new(label).style(ColoredFramedContainer::new(RED))
+            } else {
+                Container::new(label).style(FramedContainer)
+            });
+        }
+
+        let mut locked_locations: Vec<_> = game_state.world.locked_locations().collect();
+        locked_locations.sort_by_key(|location| location.id);
+        for location in locked_locations {
+            if let Some(unlock_description) = &location.unlock_description {
+                column = column.push(locked_entry(&location.name, unlock_description));
+            }
+        }
+
+        Container::new(Scrollable::new(column))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(FramedContainer)
+            .into()
+    }
+}
+
+impl LocationMap {
+    pub fn new(world: &World) -> Self {
+        let mut nodes: Vec<_> = world
+            .active_locations()
+            .map(|location| LocationMapNode {
+                id: location.id,
+                name: location.name.clone(),
+                activation_time: location.state.activation_time().unwrap(),
+                selected: location.id == world.selected_location,
+            })
+            .collect();
+        nodes.sort_by_key(|node| (node.activation_time, node.id));
+        Self { nodes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocationMap;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::world::locations::{
+        CompiledLocation, LocationId, LocationState, DEFAULT_COMBAT_RATIO,
+    };
+    use crate::game_state::world::World;
+    use event_trigger_action_system::TriggerHandle;
+
+    fn create_world(location_count: usize) -> World {
+        World::new(
+            LocationId(0),
+            (0..location_count)
+                .map(|index| CompiledLocation {
+                    id: LocationId(index),
+                    id_str: format!("location_{index}"),
+                    state: LocationState::Inactive,
+                    name: format!("Location {index}"),
+                    url: None,
+                    events: Vec::new(),
+                    monsters: Vec::new(),
+                    combat_ratio: DEFAULT_COMBAT_RATIO,
+                    tie_break: Default::default(),
+                    event_choice_counts: Vec::new(),
+                    activation_condition: TriggerHandle::from(0),
+                    deactivation_condition: TriggerHandle::from(0),
+                    unlock_description: None,
+                })
+                .collect(),
+            Vec::new(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_location_map_contains_only_active_locations() {
+        let mut world = create_world(4);
+        world
+            .activate_location(LocationId(3), GameTime::from_seconds(1))
+            .for_each(drop);
+        world
+            .activate_location(LocationId(0), GameTime::from_seconds(2))
+            .for_each(drop);
+        world
+            .activate_location(LocationId(1), GameTime::from_seconds(3))
+            .for_each(drop);
+        world
+            .deactivate_location(LocationId(1), GameTime::from_seconds(4))
+            .for_each(drop);
+
+        let location_map = LocationMap::new(&world);
+        assert_eq!(
+            location_map
+                .nodes
+                .iter()
+                .map(|node| node.id)
+                .collect::<Vec<_>>(),
+            vec![LocationId(3), LocationId(0)]
+        );
+        assert_eq!(location_map.nodes[0].name, "Location 3");
+        assert_eq!(
+            location_map.nodes[0].activation_time,
+            GameTime::from_seconds(1)
+        );
+    }
+
+    #[test]
+    fn test_location_map_marks_selected_location() {
+        let mut world = create_world(3);
+        world
+            .activate_location(LocationId(0), GameTime::zero())
+            .for_each(drop);
+        world
+            .activate_location(LocationId(2), GameTime::zero())
+            .for_each(drop);
+        assert!(world.select_location(LocationId(2)));
+
+        let location_map = LocationMap::new(&world);
+        let selected: Vec<_> = location_map
+            .nodes
+            .iter()
+            .filter(|node| node.selected)
+            .map(|node| node.id)
+            .collect();
+        assert_eq!(selected, vec![LocationId(2)]);
+        assert_eq!(location_map.nodes.len(), 2);
+    }
+}
@@ -1,20 +1,29 @@
-use crate::ui::elements::active_action_description;
-use crate::ui::running_state::main_view::action_picker::ActionPickerState;
+use crate::game_state::event_log::{GameEvent, GameEventCategory};
+use crate::game_state::player_actions::PlayerActions;
+use crate::game_state::story::quests::{CompiledQuest, QuestState};
+use crate::game_state::triggers::CompiledGameEvent;
+use crate::ui::elements::{active_action_description, combat_log};
+use crate::ui::running_state::main_view::action_picker::{ActionPickerMessage, ActionPickerState};
 use crate::ui::running_state::main_view::calendar::{CalendarMessage, CalendarState};
 use crate::ui::running_state::main_view::location::{LocationMessage, LocationState};
-use crate::ui::running_state::main_view::overview::OverviewState;
+use crate::ui::running_state::main_view::location_map::LocationMapState;
+use crate::ui::running_state::main_view::overview::{OverviewMessage, OverviewState};
 use crate::ui::running_state::main_view::story::{StoryMessage, StoryState};
 use crate::ui::running_state::{GameStateMessage, RunningMessage};
 use crate::ui::style::{ButtonStyleSheet, FramedContainer, SelectedButtonStyleSheet};
 use crate::ui::Message;
 use crate::{GameState, RunConfiguration};
 use async_std::sync::Arc;
+use enum_iterator::all;
+use event_trigger_action_system::CompiledTriggers;
 use iced::{Command,  Element, Length};
 use iced::widget::{Button, Column, Container, ProgressBar, Row, Text};
+use std::collections::HashSet;
 
 mod action_picker;
 mod calendar;
 mod location;
+mod location_map;
 mod overview;
 mod story;
 
@@ -23,24 +32,79 @@ pub struct MainViewState {
     selected_view: SelectedView,
     overview_state: OverviewState,
     location_state: LocationState,
+    location_map_state: LocationMapState,
     action_picker_state: ActionPickerState,
     story_state: StoryState,
     calendar_state: CalendarState,
+    quest_state_filter: QuestStateFilter,
+    event_category_filter: EventCategoryFilter,
+    event_log_scroll: EventLogScroll,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SelectedView {
     Overview,
     Location,
+    LocationMap,
     ActionPicker,
     Story,
     Calendar,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuestCategory {
+    Available,
+    Active,
+    Completed,
+    Failed,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuestStateFilter {
+    pub show_available: bool,
+    pub show_active: bool,
+    pub show_completed: bool,
+    pub show_failed: bool,
+}
+
+/// The progress of an active quest as shown in the quest list.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuestProgress {
+    /// The number of completed stages and the total number of stages.
+    pub stages: (usize, usize),
+    /// The trigger progress of the completion condition of the active stage, starting at one.
+    pub active_stage: (f64, f64),
+}
+
+/// The categories of events shown in the event log.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventCategoryFilter {
+    shown: HashSet<GameEventCategory>,
+}
+
+/// Where the player wants the event log to be scrolled to.
+/// The newest events are at the top, and while following, new events stay in view.
+/// Otherwise, the view stays at the events the player is reading.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventLogScroll {
+    auto_follow: bool,
+    /// The relative scroll offset, where 0 is the newest and 1 is the oldest event.
+    offset: f32,
+    event_count: usize,
+}
+
 #[derive(Clone, Debug)]
 pub enum MainViewMessage {
     Init,
     SelectView(SelectedView),
+    ToggleQuestCategory(QuestCategory),
+    ToggleEventCategory(GameEventCategory),
+    ToggleEventLogAutoFollow,
+    EventLogScrolled(f32),
+    JumpEventLogToTop,
+    JumpEventLogToBottom,
+    Overview(OverviewMessage),
+    ActionPicker(ActionPickerMessage),
     Calendar(CalendarMessage),
     Story(StoryMessage),
     Location(LocationMessage),
@@ -52,9 +116,13 @@ impl MainViewState {
             selected_view: SelectedView::Overview,
             overview_state: OverviewState::new(),
             location_state: LocationState::new(game_state),
+            location_map_state: LocationMapState::new(),
             action_picker_state: ActionPickerState::new(),
             story_state: StoryState::new(),
             calendar_state: CalendarState::new(game_state),
+            quest_state_filter: QuestStateFilter::new(),
+            event_category_filter: EventCategoryFilter::new(),
+            event_log_scroll: EventLogScroll::new(game_state.log.iter_rev().count()),
         }
     }
 
@@ -74,6 +142,43 @@ impl MainViewState {
                 self.selected_view = selected_view;
                 Command::none()
             }
+            MainViewMessage::ToggleQuestCategory(quest_category) => {
+                self.quest_state_filter.toggle(quest_category);
+                Command::none()
+            }
+            MainViewMessage::ToggleEventCategory(event_category) => {
+                self.event_category_filter.toggle(event_category);
+                self.event_log_scroll.reset_event_count(
+                    self.overview_state
+                        .log_event_count(game_state, &self.event_category_filter),
+                );
+                Command::none()
+            }
+            MainViewMessage::ToggleEventLogAutoFollow => {
+                match self.event_log_scroll.toggle_auto_follow() {
+                    Some(offset) => self.overview_state.snap_log_to(offset),
+                    None => Command::none(),
+                }
+            }
+            MainViewMessage::EventLogScrolled(offset) => {
+                self.event_log_scroll.scrolled(offset);
+                Command::none()
+            }
+            MainViewMessage::JumpEventLogToTop => self
+                .overview_state
+                .snap_log_to(self.event_log_scroll.jump(0.0)),
+            MainViewMessage::JumpEventLogToBottom => self
+                .overview_state
+                .snap_log_to(self.event_log_scroll.jump(1.0)),
+            MainViewMessage::Overview(overview_message) => self.overview_state.update(
+                overview_message,
+                game_state,
+                &self.event_category_filter,
+                &mut self.event_log_scroll,
+            ),
+            MainViewMessage::ActionPicker(action_picker_message) => {
+                self.action_picker_state.update(action_picker_message)
+            }
             MainViewMessage::Calendar(calendar_message) => {
                 self.calendar_state.update(calendar_message)
             }
@@ -86,6 +191,17 @@ impl MainViewState {
         }
     }
 
+    /// Keeps the event log at the position the player wants after new events were logged.
+    pub fn update_event_log(&mut self, game_state: &GameState) -> Command<Message> {
+        let event_count = self
+            .overview_state
+            .log_event_count(game_state, &self.event_category_filter);
+        match self.event_log_scroll.events_changed(event_count) {
+            Some(offset) => self.overview_state.snap_log_to(offset),
+            None => Command::none(),
+        }
+    }
+
     pub fn update_game_state(
         &mut self,
         configuration: Arc<RunConfiguration>,
@@ -130,6 +246,18 @@ impl MainViewState {
                                         ButtonStyleSheet::style_sheet()
                                     }),
                             )
+                            .push(
+                                Button::new(Text::new("Map"))
+                                    .on_press(
+                                        MainViewMessage::SelectView(SelectedView::LocationMap)
+                                            .into(),
+                                    )
+                                    .style(if self.selected_view == SelectedView::LocationMap {
+                                        SelectedButtonStyleSheet::style_sheet()
+                                    } else {
+                                        ButtonStyleSheet::style_sheet()
+                                    }),
+                            )
                             .push(
                                 Button::new( Text::new("Actions"))
                                     .on_press(
@@ -168,17 +296,40 @@ impl MainViewState {
                     .style(FramedContainer),
                 )
                 .push(match self.selected_view {
-                    SelectedView::Overview => self.overview_state.view(game_state),
+                    SelectedView::Overview => self.overview_state.view(
+                        game_state,
+                        &self.quest_state_filter,
+                        &self.event_category_filter,
+                        &self.event_log_scroll,
+                    ),
                     SelectedView::Location => self.location_state.view(),
+                    SelectedView::LocationMap => self.location_map_state.view(game_state),
                     SelectedView::ActionPicker => self.action_picker_state.view(game_state),
-                    SelectedView::Story => self.story_state.view(game_state),
+                    SelectedView::Story => {
+                        self.story_state.view(game_state, &self.quest_state_filter)
+                    }
                     SelectedView::Calendar => self.calendar_state.view(game_state),
                 })
+                .push(combat_log(game_state))
                 .push(active_action_description(game_state))
-                .push(ProgressBar::new(
-                    0.0..=1.0,
-                    game_state.current_action_progress(),
-                )),
+                .push(
+                    Row::new()
+                        .spacing(5)
+                        .push(ProgressBar::new(
+                            0.0..=1.0,
+                            game_state.current_action_progress(),
+                        ))
+                        .push(
+                            Button::new(Text::new("Skip"))
+                                .on_press(GameStateMessage::SkipToActionCompletion.into())
+                                .style(ButtonStyleSheet::style_sheet()),
+                        )
+                        .push(
+                            Button::new(Text::new("Cancel"))
+                                .on_press(GameStateMessage::CancelCurrentAction.into())
+                                .style(ButtonStyleSheet::style_sheet()),
+                        ),
+                ),
         )
         .padding(5)
         .style(FramedContainer)
@@ -186,8 +337,449 @@ impl MainViewState {
     }
 }
 
+impl QuestStateFilter {
+    pub fn new() -> Self {
+        Self {
+            show_available: true,
+            show_active: true,
+            show_completed: true,
+            show_failed: true,
+        }
+    }
+
+    pub fn is_shown(&self, quest_category: QuestCategory) -> bool {
+        match quest_category {
+            QuestCategory::Available => self.show_available,
+            QuestCategory::Active => self.show_active,
+            QuestCategory::Completed => self.show_completed,
+            QuestCategory::Failed => self.show_failed,
+        }
+    }
+
+    pub fn toggle(&mut self, quest_category: QuestCategory) {
+        let shown = match quest_category {
+            QuestCategory::Available => &mut self.show_available,
+            QuestCategory::Active => &mut self.show_active,
+            QuestCategory::Completed => &mut self.show_completed,
+            QuestCategory::Failed => &mut self.show_failed,
+        };
+        *shown = !*shown;
+    }
+
+    /// Returns true if a quest in the given state should be displayed.
+    /// Inactive quests are never displayed, available quests are listed separately.
+    pub fn matches(&self, quest_state: &QuestState) -> bool {
+        match quest_state {
+            QuestState::Inactive => false,
+            QuestState::Active { .. } => self.show_active,
+            QuestState::Completed { .. } => self.show_completed,
+            QuestState::FailedWhileInactive { .. } | QuestState::FailedWhileActive { .. } => {
+                self.show_failed
+            }
+        }
+    }
+}
+
+impl QuestProgress {
+    /// Returns `None` if the quest is not active.
+    pub fn new(
+        quest: &CompiledQuest,
+        triggers: &CompiledTriggers<CompiledGameEvent>,
+    ) -> Option<Self> {
+        let active_stage = triggers
+            .progress(quest.active_stage()?.completion_condition)
+            .unwrap();
+        Some(Self {
+            stages: (quest.completed_stages().count(), quest.stage_count()),
+            active_stage,
+        })
+    }
+
+    pub fn is_multistage(&self) -> bool {
+        self.stages.1 > 1
+    }
+}
+
+impl EventCategoryFilter {
+    pub fn new() -> Self {
+        Self {
+            shown: all::<GameEventCategory>().collect(),
+        }
+    }
+
+    pub fn is_shown(&self, event_category: GameEventCategory) -> bool {
+        self.shown.contains(&event_category)
+    }
+
+    pub fn toggle(&mut self, event_category: GameEventCategory) {
+        if !self.shown.remove(&event_category) {
+            self.shown.insert(event_category);
+        }
+    }
+
+    /// Returns true if the given event should be displayed in the event log.
+    pub fn matches(&self, event: &GameEvent, actions: &PlayerActions) -> bool {
+        self.is_shown(event.category(actions))
+    }
+}
+
+impl EventLogScroll {
+    pub fn new(event_count: usize) -> Self {
+        Self {
+            auto_follow: true,
+            offset: 0.0,
+            event_count,
+        }
+    }
+
+    pub fn auto_follow(&self) -> bool {
+        self.auto_follow
+    }
+
+    /// Returns the offset to scroll to if auto-follow was turned on.
+    pub fn toggle_auto_follow(&mut self) -> Option<f32> {
+        self.auto_follow = !self.auto_follow;
+        if self.auto_follow {
+            self.offset = 0.0;
+            Some(self.offset)
+        } else {
+            None
+        }
+    }
+
+    /// Scrolling away from the newest events stops following them.
+    pub fn scrolled(&mut self, offset: f32) {
+        self.offset = offset;
+        if offset > 0.0 {
+            self.auto_follow = false;
+        }
+    }
+
+    /// Returns the offset to scroll to.
+    pub fn jump(&mut self, offset: f32) -> f32 {
+        self.scrolled(offset);
+        self.offset
+    }
+
+    /// Sets the number of displayed events without treating additional ones as new, e.g. when the search changes.
+    pub fn reset_event_count(&mut self, event_count: usize) {
+        self.event_count = event_count;
+    }
+
+    /// Returns the offset to scroll to if new events would move the view while not following.
+    pub fn events_changed(&mut self, event_count: usize) -> Option<f32> {
+        let previous_event_count = self.event_count;
+        self.event_count = event_count;
+        if self.auto_follow || event_count <= previous_event_count {
+            return None;
+        }
+
+        let newer_event_count = self.offset * previous_event_count.saturating_sub(1) as f32
+            + (event_count - previous_event_count) as f32;
+        self.offset = if event_count > 1 {
+            (newer_event_count / (event_count - 1) as f32).min(1.0)
+        } else {
+            0.0
+        };
+        Some(self.offset)
+    }
+}
+
 impl From<MainViewMessage> for Message {
     fn from(message: MainViewMessage) -> Self {
         Message::Running(RunningMessage::MainView(message))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EventCategoryFilter, EventLogScroll, QuestCategory, QuestProgress, QuestStateFilter,
+    };
+    use crate::game_state::event_log::{GameEvent, GameEventCategory};
+    use crate::game_state::player_actions::{
+        PlayerActionInProgressKind, PlayerActionInProgressSource, PlayerActionType,
+    };
+    use crate::game_state::story::quests::QuestState;
+    use crate::game_state::tests::create_test_game_state_with_templates;
+    use crate::game_state::time::GameTime;
+    use crate::game_state::world::events::ExplorationEventId;
+    use crate::game_state::world::monsters::MonsterId;
+    use crate::game_state::{GameState, GAME_TIME_PER_MILLISECOND};
+    use enum_iterator::all;
+
+    const WORK_TEMPLATE: &str = "
+ACTION event_category_work
+name Work
+progressive working
+simple_past worked
+type WORK
+duration 1h
+currency 1
+activation none
+deactivation never
+";
+
+    const QUEST_PROGRESS_TEMPLATE: &str = "
+ACTION quest_progress_work
+name Work
+progressive working
+simple_past worked
+type WORK
+duration 1h
+currency 1
+activation none
+deactivation never
+
+QUEST quest_progress
+title Work a lot
+activation none
+failure never
+BEGIN
+    QUEST_STAGE quest_progress_once
+    task Work once.
+    completion action_count(1, quest_progress_work)
+
+    QUEST_STAGE quest_progress_thrice
+    task Work three more times.
+    completion action_count(3, quest_progress_work)
+END
+";
+
+    fn quest_progress(game_state: &GameState) -> QuestProgress {
+        let quest = game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.id_str == "quest_progress")
+            .unwrap();
+        let quest_progress = QuestProgress::new(quest, &game_state.triggers).unwrap();
+        assert_eq!(
+            Some(quest_progress.active_stage),
+            game_state
+                .triggers
+                .progress(quest.active_stage().unwrap().completion_condition)
+        );
+        quest_progress
+    }
+
+    fn all_quest_states() -> [QuestState; 5] {
+        [
+            QuestState::Inactive,
+            QuestState::Active {
+                activation_time: GameTime::from_seconds(1),
+                active_stage: 0,
+            },
+            QuestState::Completed {
+                activation_time: GameTime::from_seconds(1),
+                completion_time: GameTime::from_seconds(2),
+            },
+            QuestState::FailedWhileInactive {
+                failure_time: GameTime::from_seconds(1),
+            },
+            QuestState::FailedWhileActive {
+                activation_time: GameTime::from_seconds(1),
+                failure_time: GameTime::from_seconds(2),
+                failed_stage: 0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_quest_state_filter_matches() {
+        for show_active in [false, true] {
+            for show_completed in [false, true] {
+                for show_failed in [false, true] {
+                    let filter = QuestStateFilter {
+                        show_available: true,
+                        show_active,
+                        show_completed,
+                        show_failed,
+                    };
+                    let [inactive, active, completed, failed_while_inactive, failed_while_active] =
+                        all_quest_states();
+                    assert!(!filter.matches(&inactive));
+                    assert_eq!(filter.matches(&active), show_active);
+                    assert_eq!(filter.matches(&completed), show_completed);
+                    assert_eq!(filter.matches(&failed_while_inactive), show_failed);
+                    assert_eq!(filter.matches(&failed_while_active), show_failed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_quest_state_filter_toggle() {
+        let mut filter = QuestStateFilter::new();
+        for quest_category in [
+            QuestCategory::Available,
+            QuestCategory::Active,
+            QuestCategory::Completed,
+            QuestCategory::Failed,
+        ] {
+            assert!(filter.is_shown(quest_category));
+            filter.toggle(quest_category);
+            assert!(!filter.is_shown(quest_category));
+        }
+        assert!(all_quest_states()
+            .iter()
+            .all(|quest_state| !filter.matches(quest_state)));
+    }
+
+    #[test]
+    fn test_quest_progress_follows_active_stage() {
+        let mut game_state = create_test_game_state_with_templates(&[QUEST_PROGRESS_TEMPLATE]);
+        let initial_progress = quest_progress(&game_state);
+        assert_eq!(initial_progress.stages, (0, 2));
+        assert!(initial_progress.is_multistage());
+
+        let work = game_state
+            .actions
+            .list_choosable()
+            .find(|action| action.id_str == "quest_progress_work")
+            .unwrap()
+            .id;
+        game_state.actions.selected_action = work;
+        for _ in 0..48 {
+            if quest_progress(&game_state).stages.0 > 0 {
+                break;
+            }
+            game_state.update_offline(
+                (GameTime::from_hours(1).milliseconds() / GAME_TIME_PER_MILLISECOND.milliseconds())
+                    as i64,
+            );
+        }
+
+        let second_stage_progress = quest_progress(&game_state);
+        assert_eq!(second_stage_progress.stages, (1, 2));
+        assert!(second_stage_progress.active_stage.1 > initial_progress.active_stage.1);
+        assert!(second_stage_progress.active_stage.0 < second_stage_progress.active_stage.1);
+
+        let quest = game_state
+            .story
+            .iter_all_quests()
+            .find(|quest| quest.id_str == "multistage_test")
+            .unwrap();
+        assert_eq!(
+            QuestProgress::new(quest, &game_state.triggers)
+                .unwrap()
+                .stages,
+            (0, 3)
+        );
+    }
+
+    #[test]
+    fn test_event_category_filter() {
+        let game_state = create_test_game_state_with_templates(&[WORK_TEMPLATE]);
+        let event = |source, kind| {
+            let mut action = game_state.actions.in_progress().clone();
+            action.source = source;
+            action.kind = kind;
+            GameEvent::from(action)
+        };
+        let action_event = |action_type| {
+            let action = game_state
+                .actions
+                .list_choosable()
+                .find(|action| action.action_type == action_type)
+                .unwrap();
+            event(
+                PlayerActionInProgressSource::Action(action.id),
+                PlayerActionInProgressKind::None,
+            )
+        };
+        let events = [
+            (
+                action_event(PlayerActionType::Wait),
+                GameEventCategory::Rest,
+            ),
+            (
+                action_event(PlayerActionType::Train),
+                GameEventCategory::Training,
+            ),
+            (
+                action_event(PlayerActionType::Work),
+                GameEventCategory::Work,
+            ),
+            (
+                event(
+                    PlayerActionInProgressSource::Exploration(ExplorationEventId(0)),
+                    PlayerActionInProgressKind::None,
+                ),
+                GameEventCategory::Exploration,
+            ),
+            (
+                event(
+                    PlayerActionInProgressSource::Exploration(ExplorationEventId(0)),
+                    PlayerActionInProgressKind::Combat(MonsterId(0)),
+                ),
+                GameEventCategory::Combat,
+            ),
+        ];
+        for (event, category) in &events {
+            assert_eq!(event.category(&game_state.actions), *category);
+        }
+
+        let mut filter = EventCategoryFilter::new();
+        for hidden_category in all::<GameEventCategory>() {
+            assert!(events
+                .iter()
+                .all(|(event, _)| filter.matches(event, &game_state.actions)));
+            filter.toggle(hidden_category);
+            assert!(!filter.is_shown(hidden_category));
+            for (event, category) in &events {
+                assert_eq!(
+                    filter.matches(event, &game_state.actions),
+                    *category != hidden_category
+                );
+            }
+            filter.toggle(hidden_category);
+        }
+    }
+
+    #[test]
+    fn test_event_log_auto_follow_ignores_new_events() {
+        let mut scroll = EventLogScroll::new(10);
+        assert!(scroll.auto_follow());
+        assert_eq!(scroll.events_changed(15), None);
+        assert_eq!(scroll.events_changed(20), None);
+        assert!(scroll.auto_follow());
+
+        scroll.scrolled(0.0);
+        assert!(scroll.auto_follow());
+        assert_eq!(scroll.events_changed(21), None);
+    }
+
+    #[test]
+    fn test_event_log_scrolling_away_stops_following() {
+        let mut scroll = EventLogScroll::new(11);
+        scroll.scrolled(0.5);
+        assert!(!scroll.auto_follow());
+
+        // five events were newer than the view, now ten are
+        assert_eq!(scroll.events_changed(16), Some(10.0 / 15.0));
+        assert!(!scroll.auto_follow());
+        assert_eq!(scroll.events_changed(16), None);
+
+        assert_eq!(scroll.toggle_auto_follow(), Some(0.0));
+        assert!(scroll.auto_follow());
+        assert_eq!(scroll.events_changed(20), None);
+        assert_eq!(scroll.toggle_auto_follow(), None);
+        assert!(!scroll.auto_follow());
+        assert_eq!(scroll.events_changed(21), Some(1.0 / 20.0));
+    }
+
+    #[test]
+    fn test_event_log_jumps() {
+        let mut scroll = EventLogScroll::new(5);
+        assert_eq!(scroll.jump(1.0), 1.0);
+        assert!(!scroll.auto_follow());
+        assert_eq!(scroll.events_changed(9), Some(1.0));
+
+        assert_eq!(scroll.jump(0.0), 0.0);
+        assert!(!scroll.auto_follow());
+        assert_eq!(scroll.events_changed(10), Some(1.0 / 9.0));
+
+        scroll.reset_event_count(3);
+        assert_eq!(scroll.events_changed(3), None);
+    }
+}
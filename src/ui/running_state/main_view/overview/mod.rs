@@ -1,30 +1,135 @@
 use crate::game_state::character::CombatStyle;
-use crate::ui::elements::{event_log, labelled_element, labelled_label, scrollable_quest_column};
-use crate::ui::running_state::GameStateMessage;
+use crate::game_state::event_log::{GameEvent, GameEventCategory};
+use crate::game_state::time::GameTime;
+use crate::ui::elements::{
+    currency, date_without_era_string, event_log, event_log_days, event_log_offset,
+    filtered_events, labelled_element, labelled_label, scrollable_quest_column, time_span,
+    QuestColumn,
+};
+use crate::ui::running_state::main_view::{
+    EventCategoryFilter, EventLogScroll, MainViewMessage, QuestStateFilter,
+};
+use crate::ui::running_state::{GameStateMessage, RunningMessage};
 use crate::ui::Message;
 use crate::utils::ui::PickListContainer;
+use crate::utils::view_cache::ViewCache;
 use crate::GameState;
 use enum_iterator::all;
-use iced::{Element, Length};
-use iced::widget::{ Column, PickList, Row};
+use iced::widget::{scrollable, Button, Checkbox, Column, PickList, Row, Text, TextInput};
+use iced::{Command, Element, Length};
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct OverviewState {
+    log_search: String,
+    log_scrollable_id: scrollable::Id,
+    /// Rebuilt when the game state or the quest filter changed.
+    quest_column: ViewCache<(u64, QuestStateFilter), QuestColumn>,
+    /// Rebuilt when the game state, the search or the event category filter changed.
+    log_events: ViewCache<(u64, String, EventCategoryFilter), Vec<GameEvent>>,
+    log_days: ViewCache<u64, Vec<GameTime>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum OverviewMessage {
+    LogSearchChanged(String),
+    JumpToDate(GameTime),
 }
 
 impl OverviewState {
     pub fn new() -> Self {
         Self {
+            log_search: String::new(),
+            log_scrollable_id: scrollable::Id::unique(),
+            quest_column: Default::default(),
+            log_events: Default::default(),
+            log_days: Default::default(),
         }
     }
 
-    pub fn view(&self, game_state: &GameState) -> Element<Message> {
+    pub fn update(
+        &mut self,
+        message: OverviewMessage,
+        game_state: &GameState,
+        event_category_filter: &EventCategoryFilter,
+        event_log_scroll: &mut EventLogScroll,
+    ) -> Command<Message> {
+        match message {
+            OverviewMessage::LogSearchChanged(log_search) => {
+                self.log_search = log_search;
+                event_log_scroll
+                    .reset_event_count(self.log_event_count(game_state, event_category_filter));
+                Command::none()
+            }
+            OverviewMessage::JumpToDate(date) => {
+                if let Some(offset) = event_log_offset(
+                    &self.log_events(game_state, event_category_filter),
+                    game_state,
+                    date,
+                ) {
+                    self.snap_log_to(event_log_scroll.jump(offset))
+                } else {
+                    Command::none()
+                }
+            }
+        }
+    }
+
+    /// The number of events shown in the log with the current search and category filter.
+    pub fn log_event_count(
+        &self,
+        game_state: &GameState,
+        event_category_filter: &EventCategoryFilter,
+    ) -> usize {
+        self.log_events(game_state, event_category_filter).len()
+    }
+
+    /// The events shown in the log with the current search and category filter, newest first.
+    fn log_events(
+        &self,
+        game_state: &GameState,
+        event_category_filter: &EventCategoryFilter,
+    ) -> Arc<Vec<GameEvent>> {
+        self.log_events.get(
+            (
+                game_state.revision(),
+                self.log_search.clone(),
+                event_category_filter.clone(),
+            ),
+            || {
+                filtered_events(game_state, &self.log_search, event_category_filter)
+                    .cloned()
+                    .collect()
+            },
+        )
+    }
+
+    pub fn snap_log_to(&self, offset: f32) -> Command<Message> {
+        scrollable::snap_to(self.log_scrollable_id.clone(), offset)
+    }
+
+    pub fn view(
+        &self,
+        game_state: &GameState,
+        quest_state_filter: &QuestStateFilter,
+        event_category_filter: &EventCategoryFilter,
+        event_log_scroll: &EventLogScroll,
+    ) -> Element<Message> {
         let label_column_width = 160;
+        let quest_column = self
+            .quest_column
+            .get((game_state.revision(), quest_state_filter.clone()), || {
+                QuestColumn::new(&game_state.story, &game_state.triggers, quest_state_filter)
+            });
+        let log_days = self
+            .log_days
+            .get(game_state.revision(), || event_log_days(game_state));
 
         let mut active_locations: Vec<_> = game_state.world.active_locations().collect();
         active_locations.sort_by_key(|location| location.state.activation_time().unwrap());
         let mut choosable_actions: Vec<_> = game_state.actions.list_choosable().collect();
         choosable_actions.sort_by_key(|action| &action.name);
+        game_state.sort_favorite_actions_first(&mut choosable_actions);
 
         let action_column = Column::new()
             .width(Length::Shrink)
@@ -80,6 +185,36 @@ impl OverviewState {
                 format!("{:.0}", game_state.character.damage_output()),
             ));
 
+        let mut tavern_row = Row::new()
+            .spacing(5)
+            .push(Text::new(game_state.tavern_quality.to_string()));
+        if let Some(upgrade) = game_state.tavern_quality.upgrade() {
+            let mut upgrade_button = Button::new(Text::new(format!(
+                "Upgrade to {}",
+                upgrade.to_string()
+            )));
+            if game_state.inventory.currency >= upgrade.price() {
+                upgrade_button = upgrade_button
+                    .on_press(GameStateMessage::PurchaseTavernQuality(upgrade).into());
+            }
+            tavern_row = tavern_row
+                .push(upgrade_button)
+                .push(currency(upgrade.price(), false));
+        }
+        let mut action_column =
+            action_column.push(labelled_element("Tavern:", label_column_width, tavern_row));
+        for (buff_kind, expiry) in &game_state.buffs {
+            action_column = action_column.push(labelled_label(
+                "Buff:",
+                label_column_width,
+                format!(
+                    "{} ({} left)",
+                    buff_kind.to_string(),
+                    time_span(*expiry - game_state.current_time)
+                ),
+            ));
+        }
+
         Column::new()
             .width(Length::Fill)
             .height(Length::Fill)
@@ -93,19 +228,81 @@ impl OverviewState {
                     .padding(5)
                     .push(action_column)
                     .push(
-                        scrollable_quest_column(
-                            &game_state.story,
-                            &game_state.triggers,
-                        )
-                        .width(Length::Units(300))
-                        .height(Length::Fill),
+                        scrollable_quest_column(&quest_column, game_state.current_time)
+                            .width(Length::Units(300))
+                            .height(Length::Fill),
                     )
                     .push(
-                        event_log(game_state)
+                        Column::new()
                             .width(Length::Units(300))
-                            .height(Length::Fill),
+                            .height(Length::Fill)
+                            .spacing(5)
+                            .push(
+                                TextInput::new("Search events", &self.log_search, |query| {
+                                    OverviewMessage::LogSearchChanged(query).into()
+                                })
+                                .padding(5),
+                            )
+                            .push(PickList::new(
+                                log_days
+                                    .iter()
+                                    .copied()
+                                    .map(|day| {
+                                        PickListContainer::new(date_without_era_string(day), day)
+                                    })
+                                    .collect::<Vec<_>>(),
+                                None,
+                                |day| OverviewMessage::JumpToDate(day.data).into(),
+                            ))
+                            .push(
+                                Row::new()
+                                    .spacing(5)
+                                    .push(Checkbox::new(
+                                        event_log_scroll.auto_follow(),
+                                        "Follow",
+                                        |_| MainViewMessage::ToggleEventLogAutoFollow.into(),
+                                    ))
+                                    .push(
+                                        Button::new(Text::new("Top"))
+                                            .on_press(MainViewMessage::JumpEventLogToTop.into()),
+                                    )
+                                    .push(
+                                        Button::new(Text::new("Bottom"))
+                                            .on_press(MainViewMessage::JumpEventLogToBottom.into()),
+                                    ),
+                            )
+                            .push(all::<GameEventCategory>().fold(
+                                Row::new().spacing(5),
+                                |row, event_category| {
+                                    row.push(Checkbox::new(
+                                        event_category_filter.is_shown(event_category),
+                                        event_category.to_string(),
+                                        move |_| {
+                                            MainViewMessage::ToggleEventCategory(event_category)
+                                                .into()
+                                        },
+                                    ))
+                                },
+                            ))
+                            .push(
+                                event_log(
+                                    &self.log_events(game_state, event_category_filter),
+                                    game_state,
+                                    self.log_scrollable_id.clone(),
+                                )
+                                .on_scroll(|offset| {
+                                    MainViewMessage::EventLogScrolled(offset).into()
+                                })
+                                .height(Length::Fill),
+                            ),
                     ),
             )
             .into()
     }
 }
+
+impl From<OverviewMessage> for Message {
+    fn from(message: OverviewMessage) -> Self {
+        Message::Running(RunningMessage::MainView(MainViewMessage::Overview(message)))
+    }
+}
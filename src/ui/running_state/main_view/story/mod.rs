@@ -1,13 +1,18 @@
 use crate::game_state::story::quests::quest_stages::{CompiledQuestStage, QuestStageState};
 use crate::game_state::story::quests::{CompiledQuest, CurrentQuestStage, QuestId};
-use crate::ui::running_state::main_view::MainViewMessage;
-use crate::ui::style::{ButtonStyleSheet, FramedContainer, SelectedButtonStyleSheet};
+use crate::ui::elements::trigger_progress_bar;
+use crate::ui::running_state::main_view::{MainViewMessage, QuestCategory, QuestStateFilter};
+use crate::ui::style::{
+    error_color, scaled, ButtonStyleSheet, FramedContainer, SelectedButtonStyleSheet,
+    SUBHEADING_SIZE,
+};
+use crate::game_state::{GameStateMessage, MAX_PINNED_QUESTS};
 use crate::ui::Message;
 use crate::GameState;
 use iced::{
      Command,  Element, Length,
 };
-use iced::widget::{Column, Button, Container, ProgressBar, Row, Scrollable, Text};
+use iced::widget::{Column, Button, Container, Row, Scrollable, Text};
 
 #[derive(Debug, Clone)]
 pub struct StoryState {
@@ -56,12 +61,17 @@ impl StoryState {
         Command::none()
     }
 
-    pub fn view(&self, game_state: &GameState) -> Element<Message> {
+    pub fn view(
+        &self,
+        game_state: &GameState,
+        quest_state_filter: &QuestStateFilter,
+    ) -> Element<Message> {
         let mut columns = Row::new().spacing(5).padding(5);
         columns = columns
             .push(view_quest_picker(
                 self.selected_quest,
                 game_state,
+                quest_state_filter,
             ))
             .push(view_quest(self.selected_quest, game_state));
 
@@ -98,7 +108,22 @@ fn view_quest<'result>(
         }
 
         let mut rows = Column::new().spacing(5).width(Length::Fill);
-        rows = rows.push(Text::new(quest.title.clone()).size(24));
+        let mut title_row = Row::new().spacing(5).push(
+            Text::new(quest.title.clone())
+                .size(scaled(SUBHEADING_SIZE))
+                .width(Length::Fill),
+        );
+        if quest.state().is_active() {
+            let is_pinned = game_state.is_pinned_quest(quest_id);
+            let can_pin = is_pinned || game_state.pinned_quests().count() < MAX_PINNED_QUESTS;
+            let mut pin_button = Button::new(Text::new(if is_pinned { "Unpin" } else { "Pin" }));
+            if can_pin {
+                pin_button =
+                    pin_button.on_press(GameStateMessage::TogglePinnedQuest(quest_id).into());
+            }
+            title_row = title_row.push(pin_button);
+        }
+        rows = rows.push(title_row);
         if let Some(description) = quest.description.clone() {
             rows = rows.push(Text::new(description));
         }
@@ -130,12 +155,10 @@ fn view_quest_stage<'result>(
                 .triggers
                 .progress(stage.completion_condition)
                 .unwrap();
-            stage_rows = stage_rows.push(
-                ProgressBar::new(1.0..=goal as f32, progress as f32).height(Length::Units(10)),
-            );
+            stage_rows = stage_rows.push(trigger_progress_bar(progress, goal, 10));
         }
         QuestStageState::FailedWhileInactive { .. } | QuestStageState::FailedWhileActive { .. } => {
-            stage_rows = stage_rows.push(Text::new("failed").color(*ERROR_COLOR));
+            stage_rows = stage_rows.push(Text::new("failed").style(error_color()));
         }
         _ => {}
     }
@@ -150,29 +173,77 @@ fn view_quest_stage<'result>(
 fn view_quest_picker<'result, 'quest_buttons: 'result, 'quest_picker_state: 'result>(
     selected_quest: Option<QuestId>,
     game_state: &GameState,
+    quest_state_filter: &QuestStateFilter,
 ) -> Element<'result, Message> {
     let mut quest_picker = Column::new().spacing(5).padding(5);
 
-    quest_picker = quest_picker.push(Text::new("Active quests").size(24));
+    let mut filter_row = Row::new().spacing(5);
+    for (label, quest_category) in [
+        ("Available", QuestCategory::Available),
+        ("Active", QuestCategory::Active),
+        ("Completed", QuestCategory::Completed),
+        ("Failed", QuestCategory::Failed),
+    ] {
+        filter_row = filter_row.push(
+            Button::new(Text::new(label))
+                .on_press(MainViewMessage::ToggleQuestCategory(quest_category).into())
+                .style(if quest_state_filter.is_shown(quest_category) {
+                    SelectedButtonStyleSheet::style_sheet()
+                } else {
+                    ButtonStyleSheet::style_sheet()
+                }),
+        );
+    }
+    quest_picker = quest_picker.push(filter_row);
+
+    if quest_state_filter.is_shown(QuestCategory::Available) {
+        quest_picker = quest_picker.push(Text::new("Available quests").size(scaled(SUBHEADING_SIZE)));
+        for quest in game_state.story.iter_available_quests_by_offer_time().rev() {
+            quest_picker = quest_picker.push(
+                Row::new()
+                    .spacing(5)
+                    .push(Text::new(&quest.title))
+                    .push(
+                        Button::new(Text::new("Accept"))
+                            .on_press(GameStateMessage::AcceptQuest(quest.id).into()),
+                    ),
+            );
+        }
+    }
+
+    if quest_state_filter.is_shown(QuestCategory::Active) {
+        quest_picker = quest_picker.push(Text::new("Active quests").size(scaled(SUBHEADING_SIZE)));
+    }
     for quest in game_state
         .story
         .iter_active_quests_by_activation_time()
         .rev()
+        .filter(|quest| quest_state_filter.matches(quest.state()))
     {
         quest_picker = view_quest_button(quest_picker, selected_quest, quest);
     }
 
-    quest_picker = quest_picker.push(Text::new("Completed quests").size(24));
+    if quest_state_filter.is_shown(QuestCategory::Completed) {
+        quest_picker = quest_picker.push(Text::new("Completed quests").size(scaled(SUBHEADING_SIZE)));
+    }
     for quest in game_state
         .story
         .iter_completed_quests_by_completion_time()
         .rev()
+        .filter(|quest| quest_state_filter.matches(quest.state()))
     {
         quest_picker = view_quest_button(quest_picker, selected_quest, quest);
     }
 
-    quest_picker = quest_picker.push(Text::new("Failed quests").size(24));
-    for quest in game_state.story.iter_failed_quests_by_failure_time().rev() {
+    if quest_state_filter.is_shown(QuestCategory::Failed) {
+        quest_picker = quest_picker.push(Text::new("Failed quests").size(scaled(SUBHEADING_SIZE)));
+    }
+    for quest in game_state
+        .story
+        .iter_failed_quests_by_failure_time()
+        .rev()
+        .filter(|quest| quest_state_filter.matches(quest.state()))
+    {
         quest_picker = view_quest_button(quest_picker, selected_quest, quest);
     }
 
@@ -187,8 +258,14 @@ fn view_quest_button<'a>(
     quest: &CompiledQuest,
 ) -> Column<'a, Message> {
 
+    let title = if quest.failed_stage().is_some() {
+        Text::new(quest.title.clone()).style(error_color())
+    } else {
+        Text::new(quest.title.clone())
+    };
+
     quest_picker.push(
-        Button::new( Text::new(quest.title.clone()))
+        Button::new(title)
             .on_press(StoryMessage::SelectQuest(quest.id).into())
             .style(if selected_quest == Some(quest.id) {
                 SelectedButtonStyleSheet::style_sheet()
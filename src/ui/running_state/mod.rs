@@ -1,35 +1,62 @@
-use crate::game_state::character::CombatStyle;
-use crate::game_state::player_actions::{PlayerActionId, ACTION_EXPLORE};
-use crate::game_state::time::GameTime;
-use crate::game_state::world::locations::LocationId;
-use crate::io::{save_game_owned, SaveError};
-use crate::ui::elements::{attribute, clock_time, currency, date, title};
+use crate::game_state::offline_progress::OfflineProgressSummary;
+use crate::game_state::prestige::Prestige;
+use crate::game_template::CompiledGameTemplate;
+use crate::io::{load_game_template, platform_save_slot, LoadError, SaveError, SaveSlot};
+use crate::notifications::ToastQueue;
+use crate::preferences::Preferences;
+use crate::recording::{RecordedMessage, Recorder};
+use crate::sound::CompletionSounds;
+use crate::ui::elements::{
+    attribute, clock_time, currency, date, progress_rate, time_span, title, trigger_progress_bar,
+};
 use crate::ui::running_state::main_view::{MainViewMessage, MainViewState};
-use crate::ui::{do_nothing, Message};
-use crate::{GameState, RunConfiguration};
+use crate::ui::running_state::portrait::{PortraitMessage, PortraitState};
+use crate::ui::style::{
+    formatted_decimal, preferences, scaled, ButtonStyleSheet, FramedContainer, HEADING_SIZE, SECTION_TITLE_SIZE, SMALL_TEXT_SIZE, TINY_TEXT_SIZE,
+};
+use crate::ui::{do_nothing, Message, SIDEBAR_WIDTH};
+use crate::{GameState, RunConfiguration, UpdateKind};
 use async_std::sync::Arc;
 use chrono::{DateTime, Duration, Utc};
 use iced::alignment::Horizontal;
 use iced::{Alignment, Command, Element, Length,};
-use iced::widget::{Column, Row, Space, Text, ProgressBar};
+use iced::widget::{Button, Column, Container, Row, Space, Text, TextInput, ProgressBar};
 use lazy_static::lazy_static;
 use log::{error, info, trace, warn};
 use std::collections::VecDeque;
 
-mod main_view;
+pub mod main_view;
+pub mod portrait;
+
+pub use crate::game_state::GameStateMessage;
 
 lazy_static! {
     pub static ref AUTOSAVE_INTERVAL: Duration = Duration::seconds(10);
 }
 
+/// Schedules the periodic saves, such that at most one save is in flight at a time.
+#[derive(Debug, Clone)]
+pub struct Autosave {
+    last_save: DateTime<Utc>,
+    save_in_flight: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct RunningState {
     game_state: GameState,
     frame_times: VecDeque<DateTime<Utc>>,
     fps: Option<f32>,
-    last_save: DateTime<Utc>,
+    autosave: Autosave,
     main_view_state: MainViewState,
+    portrait_state: PortraitState,
     last_view_duration: Duration,
+    toasts: ToastQueue,
+    offline_summary: Option<OfflineProgressSummary>,
+    /// The new name while the character is being renamed.
+    character_name_input: Option<String>,
+    recorder: Option<Recorder>,
+    completion_sounds: CompletionSounds,
+    save_slot: SaveSlot,
 }
 
 #[derive(Clone, Debug)]
@@ -38,31 +65,57 @@ pub enum RunningMessage {
     Update,
     GameSaved(Result<(), SaveError>),
     SaveAndQuit,
+    DismissOfflineSummary,
+    CharacterNameInputChanged(Option<String>),
+    Prestige,
+    PrestigeGameTemplateLoaded(Box<Result<CompiledGameTemplate, LoadError>>),
 
     GameState(GameStateMessage),
     MainView(MainViewMessage),
+    Portrait(PortraitMessage),
 }
 
-#[derive(Clone, Debug)]
-pub enum GameStateMessage {
-    ActionChanged(PlayerActionId),
-    ActionChangedExplore(LocationId),
-    ExplorationLocationChanged(LocationId),
-    CombatStyleChanged(CombatStyle),
-}
 
 impl RunningState {
-    pub fn new(game_state: GameState) -> Self {
+    pub fn new(mut game_state: GameState, configuration: &RunConfiguration) -> Self {
+        game_state.select_language(configuration.language.as_deref());
+        game_state.register_shared_observers(&configuration.observers);
         Self {
             frame_times: Default::default(),
             fps: Default::default(),
-            last_save: Utc::now(),
+            autosave: Autosave::new(Utc::now()),
             main_view_state: MainViewState::new(&game_state),
+            portrait_state: PortraitState::new(game_state.character.race),
             last_view_duration: Duration::zero(),
+            toasts: Default::default(),
+            offline_summary: None,
+            character_name_input: None,
+            recorder: None,
+            completion_sounds: Default::default(),
+            save_slot: platform_save_slot(
+                game_state.savegame_file.as_ref(),
+                configuration.savegame_compression_level,
+            ),
             game_state,
         }
     }
 
+    fn record(&mut self, message: RecordedMessage) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(error) = recorder.record(message) {
+                warn!("Error recording message: {error:?}");
+            }
+        }
+    }
+
+    /// Shows a dialog summarising the given offline progress until it is dismissed.
+    pub fn with_offline_summary(mut self, offline_summary: OfflineProgressSummary) -> Self {
+        if !offline_summary.is_empty() {
+            self.offline_summary = Some(offline_summary);
+        }
+        self
+    }
+
     pub fn update(
         &mut self,
         configuration: Arc<RunConfiguration>,
@@ -70,43 +123,88 @@ impl RunningState {
     ) -> Command<Message> {
         match message {
             RunningMessage::Init => {
-                return Command::batch([self.main_view_state.update(
-                    configuration,
-                    MainViewMessage::Init,
-                    &self.game_state,
-                )])
+                if let Some(record) = &configuration.record {
+                    match Recorder::create(record, &self.game_state) {
+                        Ok(recorder) => {
+                            info!("Recording to {record:?}");
+                            self.recorder = Some(recorder);
+                        }
+                        Err(error) => error!("Error creating recording: {error:?}"),
+                    }
+                }
+                return Command::batch([
+                    self.main_view_state.update(
+                        configuration.clone(),
+                        MainViewMessage::Init,
+                        &self.game_state,
+                    ),
+                    self.portrait_state.update(configuration, PortraitMessage::Init),
+                ])
             }
             RunningMessage::Update => {
                 // measure time delta
                 let current_time = Utc::now();
                 let passed_real_milliseconds =
                     (current_time - self.game_state.last_update).num_milliseconds();
-                if i128::from(passed_real_milliseconds) > GameTime::from_hours(1).milliseconds() {
-                    return Command::perform(do_nothing(()), |()| {
-                        Message::ChangeFromRunningToBulkUpdate
-                    });
-                } else if passed_real_milliseconds > 5_000 {
-                    warn!(
+                let update_kind = configuration.update_kind(passed_real_milliseconds);
+                match update_kind {
+                    UpdateKind::Bulk => {
+                        return Command::perform(do_nothing(()), |()| {
+                            Message::ChangeFromRunningToBulkUpdate
+                        });
+                    }
+                    UpdateKind::Long => warn!(
                         "Making {:.0} seconds worth of updates",
                         passed_real_milliseconds as f64 / 1000.0
-                    );
-                } else {
-                    trace!(
+                    ),
+                    UpdateKind::Normal => trace!(
                         "Making {:.3} seconds worth of updates",
                         passed_real_milliseconds as f64 / 1000.0
-                    );
+                    ),
                 }
 
                 // update game state
+                self.game_state.profile = configuration.profile;
                 let pre_update = Utc::now();
                 self.game_state.update(passed_real_milliseconds);
+                self.record(RecordedMessage::Update {
+                    passed_real_milliseconds,
+                });
                 let post_update = Utc::now();
                 let update_duration = post_update - pre_update;
+
+                // show quest state changes
+                let quest_events = self.game_state.take_quest_events();
+                self.toasts
+                    .enqueue_events(&self.game_state, quest_events.iter(), current_time);
+                for wasted_items in self.game_state.inventory.take_wasted_items() {
+                    self.toasts.enqueue(
+                        format!(
+                            "Cannot carry {} more {}",
+                            wasted_items.count,
+                            self.game_state.inventory.item(wasted_items.id).name
+                        ),
+                        current_time,
+                    );
+                }
+                self.toasts.expire(current_time);
+                let completed_actions = self.game_state.take_completed_actions();
+                let play_sound = match self.completion_sounds.select(
+                    &self.game_state,
+                    &completed_actions,
+                    preferences().mute_sounds,
+                    current_time,
+                ) {
+                    Some(sound) => Command::perform(do_nothing(sound), Message::PlaySound),
+                    None => Command::none(),
+                };
+                let update_event_log = self.main_view_state.update_event_log(&self.game_state);
                 if configuration.profile {
                     info!(
-                        "Update/View times: {}ms/{}ms",
+                        "Update/View times: {}ms/{}ms; FPS: {}",
                         update_duration.num_milliseconds(),
-                        self.last_view_duration.num_milliseconds()
+                        self.last_view_duration.num_milliseconds(),
+                        format_fps(self.fps)
                     );
                 }
 
@@ -128,21 +226,35 @@ impl RunningState {
                     }
                 }
 
-                if current_time - self.last_save >= *AUTOSAVE_INTERVAL {
-                    // save game periodically
-                    self.last_save = current_time;
+                if self.autosave.start(current_time, update_kind) {
+                    let save = self.save_slot.clone().save(self.game_state.clone());
 
-                    return Command::perform(save_game_owned(self.game_state.clone()), |result| {
-                        RunningMessage::GameSaved(result).into()
-                    });
+                    return Command::batch([
+                        play_sound,
+                        update_event_log,
+                        Command::perform(save, |result| {
+                            RunningMessage::GameSaved(result).into()
+                        }),
+                    ]);
                 }
+                return Command::batch([play_sound, update_event_log]);
+            }
+            RunningMessage::GameSaved(result) => {
+                self.autosave.finish();
+                match result {
+                    Ok(()) => info!("Game saved successfully"),
+                    Err(error) => error!("Error saving game: {error:?}"),
+                }
+            }
+            RunningMessage::DismissOfflineSummary => {
+                self.offline_summary = None;
+            }
+            RunningMessage::CharacterNameInputChanged(character_name_input) => {
+                self.character_name_input = character_name_input;
             }
-            RunningMessage::GameSaved(result) => match result {
-                Ok(()) => info!("Game saved successfully"),
-                Err(error) => error!("Error saving game: {error:?}"),
-            },
             RunningMessage::SaveAndQuit => {
-                return Command::perform(save_game_owned(self.game_state.clone()), |result| {
+                let save = self.save_slot.clone().save(self.game_state.clone());
+                return Command::perform(save, |result| {
                     match result {
                         Ok(()) => {
                             info!("Game saved successfully!");
@@ -154,22 +266,41 @@ impl RunningState {
                     Message::Quit
                 });
             }
-            RunningMessage::GameState(game_state_message) => {
-                match &game_state_message {
-                    GameStateMessage::ActionChanged(action) => {
-                        self.game_state.actions.selected_action = *action;
-                    }
-                    GameStateMessage::ActionChangedExplore(location) => {
-                        self.game_state.actions.selected_action = ACTION_EXPLORE;
-                        self.game_state.world.selected_location = *location;
-                    }
-                    GameStateMessage::ExplorationLocationChanged(location) => {
-                        self.game_state.world.selected_location = *location;
-                    }
-                    GameStateMessage::CombatStyleChanged(combat_style) => {
-                        self.game_state.character.selected_combat_style = *combat_style;
+            RunningMessage::Prestige => {
+                return Command::perform(load_game_template(configuration), |loaded| {
+                    RunningMessage::PrestigeGameTemplateLoaded(Box::new(loaded)).into()
+                });
+            }
+            RunningMessage::PrestigeGameTemplateLoaded(loaded) => match *loaded {
+                Ok(game_template) => {
+                    if self.game_state.prestige(game_template) {
+                        info!("Prestiged {} times", self.game_state.prestige.count);
+                        self.game_state
+                            .select_language(configuration.language.as_deref());
+                        self.record(RecordedMessage::Start(Box::new(self.game_state.clone())));
+                        self.main_view_state = MainViewState::new(&self.game_state);
+                        self.offline_summary = None;
+                        self.character_name_input = None;
+                        self.toasts = Default::default();
+                        return self.main_view_state.update(
+                            configuration,
+                            MainViewMessage::Init,
+                            &self.game_state,
+                        );
+                    } else {
+                        warn!("Prestige is not available");
                     }
                 }
+                Err(error) => error!("Error loading game template for prestige: {error:?}"),
+            },
+            RunningMessage::GameState(game_state_message) => {
+                self.record(RecordedMessage::GameState(game_state_message.clone()));
+                if !self.game_state.handle_message(&game_state_message) {
+                    return Command::none();
+                }
+                if let GameStateMessage::RenameCharacter(_) = &game_state_message {
+                    self.character_name_input = None;
+                }
                 return self.main_view_state.update_game_state(
                     configuration,
                     &self.game_state,
@@ -183,6 +314,9 @@ impl RunningState {
                     &self.game_state,
                 )
             }
+            RunningMessage::Portrait(portrait_message) => {
+                return self.portrait_state.update(configuration, portrait_message)
+            }
         }
 
         Command::none()
@@ -190,25 +324,54 @@ impl RunningState {
 
     pub fn view(&mut self) -> Element<Message> {
         let pre_view = Utc::now();
-        let result = Column::new()
+        let attribute_progress_per_hour = self.game_state.attribute_progress_per_hour();
+        let mut result = Column::new()
             .width(Length::Fill)
             .height(Length::Fill)
-            .push(title())
+            .push(title());
+        if let Some(offline_summary) = &self.offline_summary {
+            result = result.push(view_offline_summary(offline_summary, &self.game_state));
+        }
+        let result = result
             .push(
                 Row::new()
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .push(
                         Column::new()
-                            .width(Length::Units(220))
+                            .width(Length::Units(SIDEBAR_WIDTH))
                             .align_items(Alignment::Fill)
                             .spacing(5)
                             .padding(5)
-                            .push(
-                                Text::new(&self.game_state.character.name)
-                                    .size(40)
-                                    .horizontal_alignment(Horizontal::Center),
-                            )
+                            .push(if let Some(character_name_input) = &self.character_name_input {
+                                Element::from(
+                                    TextInput::new("Name", character_name_input, |input| {
+                                        RunningMessage::CharacterNameInputChanged(Some(input))
+                                            .into()
+                                    })
+                                    .on_submit(
+                                        GameStateMessage::RenameCharacter(
+                                            character_name_input.clone(),
+                                        )
+                                        .into(),
+                                    )
+                                    .padding(5),
+                                )
+                            } else {
+                                Button::new(
+                                    Text::new(&self.game_state.character.name)
+                                        .size(scaled(HEADING_SIZE))
+                                        .horizontal_alignment(Horizontal::Center),
+                                )
+                                .on_press(
+                                    RunningMessage::CharacterNameInputChanged(Some(
+                                        self.game_state.character.name.clone(),
+                                    ))
+                                    .into(),
+                                )
+                                .width(Length::Fill)
+                                .into()
+                            })
                             .push(
                                 Text::new(&format!("Level {}", self.game_state.character.level))
                                     .horizontal_alignment(Horizontal::Center),
@@ -224,9 +387,14 @@ impl RunningState {
                                 ),
                             )
                             .push(
-                                Text::new(&self.game_state.character.race.to_string())
-                                    .horizontal_alignment(Horizontal::Center),
+                                Text::new(progress_rate(
+                                    attribute_progress_per_hour.sum(),
+                                    self.game_state.character.required_level_progress(),
+                                ))
+                                .size(scaled(SMALL_TEXT_SIZE))
+                                .horizontal_alignment(Horizontal::Center),
                             )
+                            .push(self.portrait_state.view())
                             .push(
                                 date(self.game_state.current_time)
                                     .horizontal_alignment(Horizontal::Center),
@@ -235,11 +403,37 @@ impl RunningState {
                                 clock_time(self.game_state.current_time)
                                     .horizontal_alignment(Horizontal::Center),
                             )
+                            .push(
+                                Text::new(&format!(
+                                    "Played {} ({} in-game)",
+                                    time_span(self.game_state.real_time_played),
+                                    time_span(self.game_state.game_time_played()),
+                                ))
+                                .size(scaled(SMALL_TEXT_SIZE))
+                                .horizontal_alignment(Horizontal::Center),
+                            )
                             .push(currency(self.game_state.inventory.currency, true))
+                            .push(if self.game_state.can_prestige() {
+                                Element::from(
+                                    Button::new(
+                                        Text::new(&format!(
+                                            "Prestige (+{:.0}% progress)",
+                                            Prestige::multiplier_gain(
+                                                self.game_state.character.level
+                                            ) * 100.0
+                                        ))
+                                        .horizontal_alignment(Horizontal::Center),
+                                    )
+                                    .on_press(RunningMessage::Prestige.into())
+                                    .width(Length::Fill),
+                                )
+                            } else {
+                                Space::new(Length::Shrink, Length::Shrink).into()
+                            })
                             .push(Space::new(Length::Shrink, Length::Units(20)))
                             .push(
                                 Text::new("Attributes")
-                                    .size(25)
+                                    .size(scaled(SECTION_TITLE_SIZE))
                                     .horizontal_alignment(Horizontal::Center),
                             )
                             .push(
@@ -251,43 +445,60 @@ impl RunningState {
                                         "STR",
                                         self.game_state.character.attributes().strength,
                                         self.game_state.character.attribute_progress().strength,
+                                        attribute_progress_per_hour.strength,
                                     ))
                                     .push(attribute(
                                         "STA",
                                         self.game_state.character.attributes().stamina,
                                         self.game_state.character.attribute_progress().stamina,
+                                        attribute_progress_per_hour.stamina,
                                     ))
                                     .push(attribute(
                                         "DEX",
                                         self.game_state.character.attributes().dexterity,
                                         self.game_state.character.attribute_progress().dexterity,
+                                        attribute_progress_per_hour.dexterity,
                                     ))
                                     .push(attribute(
                                         "INT",
                                         self.game_state.character.attributes().intelligence,
                                         self.game_state.character.attribute_progress().intelligence,
+                                        attribute_progress_per_hour.intelligence,
                                     ))
                                     .push(attribute(
                                         "WIS",
                                         self.game_state.character.attributes().wisdom,
                                         self.game_state.character.attribute_progress().wisdom,
+                                        attribute_progress_per_hour.wisdom,
                                     ))
                                     .push(attribute(
                                         "CHR",
                                         self.game_state.character.attributes().charisma,
                                         self.game_state.character.attribute_progress().charisma,
+                                        attribute_progress_per_hour.charisma,
                                     )),
                             )
+                            .push(view_goal_tracker(&self.game_state))
                             .push(Space::new(Length::Shrink, Length::Fill))
+                            .push(self.toasts.iter().fold(
+                                Column::new().spacing(5),
+                                |column, toast| {
+                                    column.push(
+                                        Container::new(
+                                            Text::new(&toast.message)
+                                                .size(scaled(SMALL_TEXT_SIZE)),
+                                        )
+                                        .padding(5)
+                                        .width(Length::Fill)
+                                        .style(FramedContainer),
+                                    )
+                                },
+                            ))
                             .push(
-                                Text::new(&format!(
-                                    "{}; FPS: {}",
-                                    self.game_state.savegame_file.as_ref().to_string_lossy(),
-                                    self.fps
-                                        .map(|fps| format!("{:.0}", fps))
-                                        .unwrap_or_else(|| "-".to_string())
-                                ))
-                                .size(12),
+                                view_debug_overlay(&preferences(), &self.game_state, self.fps)
+                                    .unwrap_or_else(|| {
+                                        Space::new(Length::Shrink, Length::Shrink).into()
+                                    }),
                             ),
                     )
                     .push(self.main_view_state.view(&self.game_state)),
@@ -303,8 +514,227 @@ impl RunningState {
     }
 }
 
+fn view_offline_summary<'a>(
+    offline_summary: &OfflineProgressSummary,
+    game_state: &GameState,
+) -> Element<'a, Message> {
+    let mut rows = Column::new()
+        .spacing(5)
+        .push(
+            Text::new(format!(
+                "While you were away ({})",
+                time_span(offline_summary.passed_time)
+            ))
+            .size(scaled(SECTION_TITLE_SIZE)),
+        )
+        .push(Text::new(format!(
+            "Levels gained: {}",
+            offline_summary.levels_gained
+        )))
+        .push(
+            Row::new()
+                .spacing(5)
+                .push(Text::new("Currency earned:"))
+                .push(currency(offline_summary.currency_earned, false)),
+        );
+
+    let gains = offline_summary.attribute_gains;
+    let attribute_gains: Vec<_> = [
+        ("STR", gains.strength),
+        ("STA", gains.stamina),
+        ("DEX", gains.dexterity),
+        ("INT", gains.intelligence),
+        ("WIS", gains.wisdom),
+        ("CHR", gains.charisma),
+    ]
+    .into_iter()
+    .filter(|(_, gain)| *gain > 0)
+    .map(|(name, gain)| format!("{name} +{gain}"))
+    .collect();
+    if !attribute_gains.is_empty() {
+        rows = rows.push(Text::new(format!(
+            "Attributes: {}",
+            attribute_gains.join(", ")
+        )));
+    }
+
+    if !offline_summary.completed_quests.is_empty() {
+        rows = rows.push(Text::new("Quests completed:"));
+        for quest_id in &offline_summary.completed_quests {
+            rows = rows.push(Text::new(format!(
+                "  {}",
+                game_state.story.quest(*quest_id).title
+            )));
+        }
+    }
+
+    rows = rows.push(
+        Button::new(Text::new("Dismiss"))
+            .on_press(RunningMessage::DismissOfflineSummary.into())
+            .style(ButtonStyleSheet::style_sheet()),
+    );
+
+    Container::new(rows)
+        .padding(10)
+        .width(Length::Fill)
+        .style(FramedContainer)
+        .into()
+}
+
+fn format_fps(fps: Option<f32>) -> String {
+    fps.map(|fps| formatted_decimal(f64::from(fps)))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// The savegame path and frame rate, or `None` if the debug overlay is disabled in the preferences.
+fn view_debug_overlay<'a>(
+    preferences: &Preferences,
+    game_state: &GameState,
+    fps: Option<f32>,
+) -> Option<Element<'a, Message>> {
+    preferences.show_debug_overlay.then(|| {
+        Text::new(&format!(
+            "{}; FPS: {}",
+            game_state.savegame_file.as_ref().to_string_lossy(),
+            format_fps(fps)
+        ))
+        .size(scaled(TINY_TEXT_SIZE))
+        .into()
+    })
+}
+
+/// The title and progress of the pinned quests, or nothing if no quest is pinned.
+fn view_goal_tracker<'a>(game_state: &GameState) -> Element<'a, Message> {
+    let mut rows = Column::new().spacing(5);
+    let mut is_empty = true;
+    for quest in game_state.pinned_quests() {
+        is_empty = false;
+        let (progress, goal) = quest
+            .active_stage()
+            .and_then(|stage| game_state.triggers.progress(stage.completion_condition))
+            .unwrap_or((1.0, 1.0));
+        rows = rows
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .align_items(Alignment::Center)
+                    .push(
+                        Text::new(&quest.title)
+                            .size(scaled(SMALL_TEXT_SIZE))
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        Button::new(Text::new("Unpin").size(scaled(TINY_TEXT_SIZE)))
+                            .on_press(GameStateMessage::TogglePinnedQuest(quest.id).into())
+                            .style(ButtonStyleSheet::style_sheet()),
+                    ),
+            )
+            .push(trigger_progress_bar(progress, goal, 6));
+    }
+
+    if is_empty {
+        Space::new(Length::Shrink, Length::Shrink).into()
+    } else {
+        Column::new()
+            .spacing(5)
+            .push(Space::new(Length::Shrink, Length::Units(20)))
+            .push(
+                Text::new("Goals")
+                    .size(scaled(SECTION_TITLE_SIZE))
+                    .horizontal_alignment(Horizontal::Center)
+                    .width(Length::Fill),
+            )
+            .push(rows)
+            .into()
+    }
+}
+
+impl Autosave {
+    pub fn new(current_time: DateTime<Utc>) -> Self {
+        Self {
+            last_save: current_time,
+            save_in_flight: false,
+        }
+    }
+
+    /// Returns true if a save should be started after an update of the given kind.
+    /// No save is started while another one is in flight, and none on the tick of a long update,
+    /// which would only make that tick longer. Such a save is deferred to the following tick instead.
+    pub fn start(&mut self, current_time: DateTime<Utc>, update_kind: UpdateKind) -> bool {
+        if self.save_in_flight
+            || update_kind != UpdateKind::Normal
+            || current_time - self.last_save < *AUTOSAVE_INTERVAL
+        {
+            return false;
+        }
+
+        self.last_save = current_time;
+        self.save_in_flight = true;
+        true
+    }
+
+    /// Marks the save in flight as finished, whether it succeeded or not.
+    pub fn finish(&mut self) {
+        self.save_in_flight = false;
+    }
+}
+
 impl From<GameStateMessage> for Message {
     fn from(message: GameStateMessage) -> Self {
         Message::Running(RunningMessage::GameState(message))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{view_debug_overlay, Autosave, AUTOSAVE_INTERVAL};
+    use crate::game_state::tests::create_test_game_state;
+    use crate::preferences::Preferences;
+    use crate::UpdateKind;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_debug_overlay_toggle() {
+        let game_state = create_test_game_state();
+        let mut preferences = Preferences {
+            show_debug_overlay: false,
+            ..Default::default()
+        };
+        assert!(view_debug_overlay(&preferences, &game_state, Some(60.0)).is_none());
+
+        preferences.show_debug_overlay = true;
+        assert!(view_debug_overlay(&preferences, &game_state, Some(60.0)).is_some());
+        assert!(view_debug_overlay(&preferences, &game_state, None).is_some());
+    }
+
+    #[test]
+    fn test_overlapping_autosaves() {
+        let start = Utc::now();
+        let mut autosave = Autosave::new(start);
+        assert!(!autosave.start(start, UpdateKind::Normal));
+
+        let due = start + *AUTOSAVE_INTERVAL;
+        assert!(autosave.start(due, UpdateKind::Normal));
+        // the first save is still in flight
+        assert!(!autosave.start(due, UpdateKind::Normal));
+        assert!(!autosave.start(due + *AUTOSAVE_INTERVAL * 2, UpdateKind::Normal));
+
+        autosave.finish();
+        assert!(autosave.start(due + *AUTOSAVE_INTERVAL * 2, UpdateKind::Normal));
+        autosave.finish();
+        assert!(!autosave.start(
+            due + *AUTOSAVE_INTERVAL * 2 + Duration::seconds(1),
+            UpdateKind::Normal
+        ));
+    }
+
+    #[test]
+    fn test_autosave_deferred_after_long_update() {
+        let start = Utc::now();
+        let mut autosave = Autosave::new(start);
+        let due = start + *AUTOSAVE_INTERVAL;
+        assert!(!autosave.start(due, UpdateKind::Long));
+        assert!(!autosave.start(due, UpdateKind::Bulk));
+        assert!(autosave.start(due + Duration::milliseconds(16), UpdateKind::Normal));
+    }
+}
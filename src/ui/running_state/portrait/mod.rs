@@ -0,0 +1,161 @@
+use crate::game_state::character::CharacterRace;
+use crate::io::{load_bytes, LoadError};
+use crate::ui::running_state::RunningMessage;
+use crate::ui::style::{scaled, SMALL_TEXT_SIZE};
+use crate::ui::Message;
+use crate::RunConfiguration;
+use async_std::sync::Arc;
+use iced::alignment::Horizontal;
+use iced::widget::image::Handle;
+use iced::widget::{Column, Image, Text};
+use iced::{Alignment, Command, Element, Length};
+use log::{debug, info, warn};
+
+/// The portrait of the character's race shown next to the character name.
+/// Falls back to the name of the race while the image is not loaded or if it cannot be loaded.
+#[derive(Debug, Clone)]
+pub struct PortraitState {
+    race: CharacterRace,
+    url: String,
+    handle: Option<Handle>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PortraitMessage {
+    Init,
+    LoadedImage {
+        url: String,
+        bytes: Result<Vec<u8>, LoadError>,
+    },
+}
+
+impl PortraitState {
+    pub fn new(race: CharacterRace) -> Self {
+        Self {
+            race,
+            url: race.image_url(),
+            handle: None,
+        }
+    }
+
+    pub fn has_image(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    pub fn update(
+        &mut self,
+        configuration: Arc<RunConfiguration>,
+        message: PortraitMessage,
+    ) -> Command<Message> {
+        match message {
+            PortraitMessage::Init => {
+                let url = self.url.clone();
+                debug!("Creating load_bytes command for url {url:?}");
+                Command::perform(load_bytes(configuration, url.clone()), move |bytes| {
+                    PortraitMessage::LoadedImage {
+                        url: url.clone(),
+                        bytes,
+                    }
+                    .into()
+                })
+            }
+            PortraitMessage::LoadedImage { url, bytes } => {
+                self.loaded_image(url, bytes);
+                Command::none()
+            }
+        }
+    }
+
+    fn loaded_image(&mut self, url: String, bytes: Result<Vec<u8>, LoadError>) {
+        match bytes {
+            Ok(bytes) => {
+                if url == self.url {
+                    info!("Loaded portrait {url:?}");
+                    self.handle = Some(Handle::from_memory(bytes));
+                } else {
+                    debug!("Portrait was loaded too late, url is now {:?}", self.url);
+                }
+            }
+            Err(error) => {
+                warn!("Could not load portrait {url:?}, showing the race name instead: {error:?}");
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let race = Text::new(&self.race.to_string()).horizontal_alignment(Horizontal::Center);
+        if let Some(handle) = self.handle.clone() {
+            Column::new()
+                .width(Length::Fill)
+                .align_items(Alignment::Center)
+                .push(Image::new(handle).height(Length::Units(64)))
+                .push(race.size(scaled(SMALL_TEXT_SIZE)))
+                .into()
+        } else {
+            race.into()
+        }
+    }
+}
+
+impl From<PortraitMessage> for Message {
+    fn from(message: PortraitMessage) -> Self {
+        Message::Running(RunningMessage::Portrait(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PortraitState;
+    use crate::game_state::character::CharacterRace;
+    use crate::io::load_bytes;
+    use crate::RunConfiguration;
+    use async_std::path::PathBuf;
+    use async_std::sync::Arc;
+
+    #[test]
+    fn test_image_url() {
+        assert_eq!(CharacterRace::Human.image_url(), "races/human.png");
+        assert_eq!(CharacterRace::Dwarf.image_url(), "races/dwarf.png");
+        assert_eq!(PortraitState::new(CharacterRace::Elf).url, "races/elf.png");
+    }
+
+    #[test]
+    fn test_fallback_for_present_and_absent_assets() {
+        let static_prefix_directory = PathBuf::from(std::env::temp_dir())
+            .join(format!("iced-rs-test-portrait-{}", std::process::id()));
+        std::fs::create_dir_all(static_prefix_directory.join("races")).unwrap();
+        std::fs::write(static_prefix_directory.join("races/orc.png"), [1, 2, 3]).unwrap();
+        let configuration = Arc::new(RunConfiguration {
+            static_prefix_directory: static_prefix_directory.clone(),
+            ..RunConfiguration::wasm_default()
+        });
+
+        let load = |race: CharacterRace| {
+            let mut portrait = PortraitState::new(race);
+            let bytes =
+                async_std::task::block_on(load_bytes(configuration.clone(), portrait.url.clone()));
+            portrait.loaded_image(portrait.url.clone(), bytes);
+            portrait
+        };
+        let present = load(CharacterRace::Orc);
+        let absent = load(CharacterRace::Elf);
+        std::fs::remove_dir_all(&static_prefix_directory).unwrap();
+
+        assert!(present.has_image());
+        assert!(!absent.has_image());
+    }
+
+    #[test]
+    fn test_ignores_failed_and_stale_loads() {
+        let mut portrait = PortraitState::new(CharacterRace::Human);
+        portrait.loaded_image(
+            "races/human.png".to_string(),
+            Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()),
+        );
+        assert!(!portrait.has_image());
+        portrait.loaded_image("races/orc.png".to_string(), Ok(vec![1, 2, 3]));
+        assert!(!portrait.has_image());
+        portrait.loaded_image("races/human.png".to_string(), Ok(vec![1, 2, 3]));
+        assert!(portrait.has_image());
+    }
+}
@@ -1,5 +1,9 @@
+use crate::preferences::Preferences;
+use crate::utils::number::{format_decimal, format_number, format_progress};
 use iced::{application, Background, Color, Vector};
 use iced::widget::{button, container, radio, text};
+use lazy_static::lazy_static;
+use std::sync::RwLock;
 
 pub const WHITE: Color = Color::from_rgb(1.0, 1.0, 1.0);
 pub const LIGHT_GREY: Color = Color::from_rgb(0.9, 0.9, 0.9);
@@ -7,6 +11,78 @@ pub const GREY: Color = Color::from_rgb(0.8, 0.8, 0.8);
 pub const BLACK: Color = Color::from_rgb(0.0, 0.0, 0.0);
 pub const RED: Color = Color::from_rgb(0.9, 0.1, 0.1);
 pub const ERROR_COLOR: Color = Color::from_rgb8(220, 10, 10);
+pub const COPPER_COLOR: Color = Color::from_rgb8(184, 115, 51);
+pub const SILVER_COLOR: Color = Color::from_rgb8(171, 175, 183);
+pub const GOLD_COLOR: Color = Color::from_rgb8(212, 175, 55);
+
+pub const HIGH_CONTRAST_ERROR_COLOR: Color = Color::from_rgb8(150, 0, 0);
+pub const HIGH_CONTRAST_COPPER_COLOR: Color = Color::from_rgb8(120, 55, 0);
+pub const HIGH_CONTRAST_SILVER_COLOR: Color = Color::from_rgb8(75, 75, 85);
+pub const HIGH_CONTRAST_GOLD_COLOR: Color = Color::from_rgb8(130, 95, 0);
+
+pub const TITLE_SIZE: u16 = 100;
+pub const HEADING_SIZE: u16 = 40;
+pub const SECTION_TITLE_SIZE: u16 = 25;
+pub const SUBHEADING_SIZE: u16 = 24;
+pub const BUTTON_SYMBOL_SIZE: u16 = 20;
+pub const SMALL_TEXT_SIZE: u16 = 16;
+pub const TINY_TEXT_SIZE: u16 = 12;
+
+lazy_static! {
+    static ref PREFERENCES: RwLock<Preferences> = RwLock::new(Preferences::default());
+}
+
+pub fn preferences() -> Preferences {
+    *PREFERENCES.read().unwrap()
+}
+
+pub fn set_preferences(preferences: Preferences) {
+    *PREFERENCES.write().unwrap() = preferences;
+}
+
+/// Formats a number with the number format from the preferences.
+pub fn formatted_number(number: impl Into<i128>) -> String {
+    format_number(number, preferences().number_format)
+}
+
+/// Formats the progress towards a goal with the progress format from the preferences.
+pub fn formatted_progress(progress: f64, goal: f64) -> String {
+    format_progress(progress, goal, preferences().progress_format)
+}
+
+/// Formats a fractional number with the decimal precision from the preferences.
+pub fn formatted_decimal(number: f64) -> String {
+    format_decimal(number, preferences().decimal_precision)
+}
+
+/// Scales an element size by the UI scale from the preferences.
+pub fn scaled(size: u16) -> u16 {
+    preferences().scale(size)
+}
+
+fn palette_color(color: Color, high_contrast_color: Color) -> Color {
+    if preferences().high_contrast {
+        high_contrast_color
+    } else {
+        color
+    }
+}
+
+pub fn error_color() -> Color {
+    palette_color(ERROR_COLOR, HIGH_CONTRAST_ERROR_COLOR)
+}
+
+pub fn copper_color() -> Color {
+    palette_color(COPPER_COLOR, HIGH_CONTRAST_COPPER_COLOR)
+}
+
+pub fn silver_color() -> Color {
+    palette_color(SILVER_COLOR, HIGH_CONTRAST_SILVER_COLOR)
+}
+
+pub fn gold_color() -> Color {
+    palette_color(GOLD_COLOR, HIGH_CONTRAST_GOLD_COLOR)
+}
 
 #[derive(Default)]
 pub struct ApplicationStyleSheet;
@@ -29,7 +105,7 @@ impl text::StyleSheet for RedText {
 
     fn appearance(&self, _style: Self::Style) -> text::Appearance {
         text::Appearance {
-            color: Some(RED),
+            color: Some(palette_color(RED, HIGH_CONTRAST_ERROR_COLOR)),
         }
     }
 }
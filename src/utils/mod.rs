@@ -1,2 +1,5 @@
+pub mod number;
 pub mod text;
 pub mod ui;
+pub mod view_cache;
+pub mod weighted;
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+
+const SUFFIXES: [(u128, &str); 3] = [(1_000_000_000, "B"), (1_000_000, "M"), (1_000, "K")];
+
+/// How large numbers are displayed.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NumberFormat {
+    /// All digits, grouped by thousands, e.g. `1,234,567`.
+    #[default]
+    Grouped,
+    /// Numbers from a thousand upwards with one truncated decimal and a suffix, e.g. `1.2M`.
+    Short,
+}
+
+/// How the progress towards a goal is labelled.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ProgressFormat {
+    /// The progress and the goal, e.g. `3/5`.
+    #[default]
+    Steps,
+    /// The truncated percentage of the goal, e.g. `60%`.
+    Percent,
+}
+
+pub fn format_number(number: impl Into<i128>, number_format: NumberFormat) -> String {
+    let number = number.into();
+    let sign = if number < 0 { "-" } else { "" };
+    let absolute = number.unsigned_abs();
+    match number_format {
+        NumberFormat::Grouped => format!("{sign}{}", group_digits(absolute)),
+        NumberFormat::Short => {
+            for (threshold, suffix) in SUFFIXES {
+                if absolute >= threshold {
+                    // truncate instead of rounding, such that e.g. 999,999 does not become 1000.0K
+                    let tenths = absolute / (threshold / 10);
+                    return format!(
+                        "{sign}{}.{}{suffix}",
+                        group_digits(tenths / 10),
+                        tenths % 10
+                    );
+                }
+            }
+            format!("{sign}{absolute}")
+        }
+    }
+}
+
+/// Formats the progress towards the goal, where both may be fractional.
+/// A goal of zero or less is always reached.
+pub fn format_progress(progress: f64, goal: f64, progress_format: ProgressFormat) -> String {
+    let goal = goal.max(0.0);
+    let progress = progress.clamp(0.0, goal);
+    match progress_format {
+        ProgressFormat::Steps => format!("{}/{}", format_step(progress), format_step(goal)),
+        ProgressFormat::Percent => {
+            let percent = if goal > 0.0 {
+                // truncate instead of rounding, such that an unfinished goal never shows 100%
+                (progress / goal * 100.0).floor()
+            } else {
+                100.0
+            };
+            format!("{percent:.0}%")
+        }
+    }
+}
+
+/// Formats a fractional number, such as a rate, rounded to the given number of decimals.
+pub fn format_decimal(number: f64, precision: usize) -> String {
+    format!("{number:.precision$}")
+}
+
+/// Formats a step count with at most two decimals and without trailing zeros.
+fn format_step(step: f64) -> String {
+    let formatted = format!("{step:.2}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn group_digits(number: u128) -> String {
+    let digits = number.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_decimal, format_number, format_progress, NumberFormat, ProgressFormat};
+
+    #[test]
+    fn test_grouped() {
+        for (number, expected) in [
+            (0, "0"),
+            (7, "7"),
+            (999, "999"),
+            (1_000, "1,000"),
+            (12_345, "12,345"),
+            (123_456, "123,456"),
+            (1_234_567, "1,234,567"),
+            (-999, "-999"),
+            (-1_000, "-1,000"),
+        ] {
+            assert_eq!(format_number(number, NumberFormat::Grouped), expected);
+        }
+    }
+
+    #[test]
+    fn test_short() {
+        for (number, expected) in [
+            (0_i64, "0"),
+            (999, "999"),
+            (1_000, "1.0K"),
+            (1_099, "1.0K"),
+            (1_100, "1.1K"),
+            (999_999, "999.9K"),
+            (1_000_000, "1.0M"),
+            (12_345_678, "12.3M"),
+            (999_999_999, "999.9M"),
+            (1_000_000_000, "1.0B"),
+            (1_234_000_000_000, "1,234.0B"),
+            (-999, "-999"),
+            (-1_500, "-1.5K"),
+        ] {
+            assert_eq!(format_number(number, NumberFormat::Short), expected);
+        }
+    }
+
+    #[test]
+    fn test_extreme_values() {
+        assert_eq!(
+            format_number(u64::MAX, NumberFormat::Grouped),
+            "18,446,744,073,709,551,615"
+        );
+        assert_eq!(
+            format_number(u64::MAX, NumberFormat::Short),
+            "18,446,744,073.7B"
+        );
+        let minimum = format_number(i128::MIN, NumberFormat::Short);
+        assert!(minimum.starts_with('-') && minimum.ends_with('B'));
+    }
+
+    #[test]
+    fn test_progress_steps() {
+        for (progress, goal, expected) in [
+            (0.0, 5.0, "0/5"),
+            (3.0, 5.0, "3/5"),
+            (5.0, 5.0, "5/5"),
+            (0.25, 1.0, "0.25/1"),
+            (1.5, 2.0, "1.5/2"),
+            (7.0, 5.0, "5/5"),
+            (-1.0, 5.0, "0/5"),
+            (0.0, 0.0, "0/0"),
+        ] {
+            assert_eq!(
+                format_progress(progress, goal, ProgressFormat::Steps),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_progress_percent() {
+        for (progress, goal, expected) in [
+            (0.0, 5.0, "0%"),
+            (3.0, 5.0, "60%"),
+            (5.0, 5.0, "100%"),
+            (0.999, 1.0, "99%"),
+            (2.0, 3.0, "66%"),
+            (7.0, 5.0, "100%"),
+            (0.0, 0.0, "100%"),
+            (1.0, -1.0, "100%"),
+        ] {
+            assert_eq!(
+                format_progress(progress, goal, ProgressFormat::Percent),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_decimal_precision() {
+        for (number, precision, expected) in [
+            (59.7, 0, "60"),
+            (0.0, 0, "0"),
+            (1234.5678, 0, "1235"),
+            (59.74, 1, "59.7"),
+            (0.05, 1, "0.1"),
+            (2.0, 1, "2.0"),
+            (12.3456, 2, "12.35"),
+            (0.0, 2, "0.00"),
+            (-1.005, 2, "-1.00"),
+        ] {
+            assert_eq!(format_decimal(number, precision), expected);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// Data that a view derives from the game state, rebuilt only when the key it was built for changes.
+/// The key usually consists of the [`GameState::revision`](crate::game_state::GameState::revision)
+/// and the options of the view that the data depends on.
+pub struct ViewCache<Key, Data> {
+    cached: RefCell<Option<(Key, Arc<Data>)>>,
+}
+
+impl<Key: PartialEq, Data> ViewCache<Key, Data> {
+    /// Returns the cached data if it was built for the given key, and builds and caches it otherwise.
+    pub fn get(&self, key: Key, build: impl FnOnce() -> Data) -> Arc<Data> {
+        let mut cached = self.cached.borrow_mut();
+        if let Some((cached_key, data)) = &*cached {
+            if *cached_key == key {
+                return data.clone();
+            }
+        }
+
+        let data = Arc::new(build());
+        *cached = Some((key, data.clone()));
+        data
+    }
+}
+
+impl<Key, Data> Default for ViewCache<Key, Data> {
+    fn default() -> Self {
+        Self {
+            cached: RefCell::new(None),
+        }
+    }
+}
+
+impl<Key: Clone, Data> Clone for ViewCache<Key, Data> {
+    fn clone(&self) -> Self {
+        Self {
+            cached: RefCell::new(self.cached.borrow().clone()),
+        }
+    }
+}
+
+impl<Key, Data> Debug for ViewCache<Key, Data> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ViewCache({})",
+            if self.cached.borrow().is_some() {
+                "cached"
+            } else {
+                "empty"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ViewCache;
+    use crate::game_state::tests::{create_test_game_state, real_milliseconds};
+    use crate::game_state::time::GameTime;
+    use crate::game_state::{GameState, GameStateMessage};
+    use std::cell::Cell;
+
+    #[test]
+    fn test_quest_column_is_only_rebuilt_after_state_changes() {
+        let mut game_state = create_test_game_state();
+        let cache = ViewCache::default();
+        let build_count = Cell::new(0);
+        // stands in for the quest column, which is built from the quests in the same way
+        let quest_column = |game_state: &GameState| {
+            cache.get(game_state.revision(), || {
+                build_count.set(build_count.get() + 1);
+                game_state
+                    .story
+                    .iter_active_quests_by_activation_time()
+                    .map(|quest| quest.title.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let titles = quest_column(&game_state);
+        for _ in 0..10 {
+            assert_eq!(quest_column(&game_state), titles);
+        }
+        assert_eq!(build_count.get(), 1);
+
+        // passing time without completing an action dispatches no events
+        game_state.update(1);
+        quest_column(&game_state);
+        assert_eq!(build_count.get(), 1);
+
+        let action = game_state.actions.list_choosable().next().unwrap().id;
+        assert!(game_state.handle_message(&GameStateMessage::ActionChanged(action)));
+        quest_column(&game_state);
+        quest_column(&game_state);
+        assert_eq!(build_count.get(), 2);
+
+        game_state.update(real_milliseconds(GameTime::from_days(1)));
+        quest_column(&game_state);
+        quest_column(&game_state);
+        assert_eq!(build_count.get(), 3);
+    }
+
+    #[test]
+    fn test_changed_key_rebuilds() {
+        let cache = ViewCache::default();
+        assert_eq!(*cache.get((0, "a"), || 1), 1);
+        assert_eq!(*cache.get((0, "a"), || 2), 1);
+        assert_eq!(*cache.get((0, "b"), || 3), 3);
+        assert_eq!(*cache.get((1, "b"), || 4), 4);
+        assert_eq!(*cache.clone().get((1, "b"), || 5), 4);
+    }
+}
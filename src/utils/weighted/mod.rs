@@ -0,0 +1,92 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightedChoiceError {
+    NoItems,
+    /// The weight is negative, infinite or NaN.
+    IllegalWeight(f64),
+    AllWeightsZero,
+}
+
+/// Chooses one of the items with a probability proportional to its weight.
+/// Like the weights in game templates, all weights must be finite and non-negative, and at least one must be positive.
+/// Items with weight zero are never chosen.
+pub fn weighted_choice<'items, T>(
+    items: &'items [(f64, T)],
+    rng: &mut impl Rng,
+) -> Result<&'items T, WeightedChoiceError> {
+    if items.is_empty() {
+        return Err(WeightedChoiceError::NoItems);
+    }
+    if let Some((weight, _)) = items
+        .iter()
+        .find(|(weight, _)| !weight.is_finite() || *weight < 0.0)
+    {
+        return Err(WeightedChoiceError::IllegalWeight(*weight));
+    }
+    if items.iter().all(|(weight, _)| *weight == 0.0) {
+        return Err(WeightedChoiceError::AllWeightsZero);
+    }
+
+    let index = WeightedIndex::new(items.iter().map(|(weight, _)| *weight))
+        .unwrap_or_else(|error| panic!("Weights were validated, but got {error:?}"));
+    Ok(&items[index.sample(rng)].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{weighted_choice, WeightedChoiceError};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro512PlusPlus;
+
+    #[test]
+    fn test_distribution() {
+        let mut rng = Xoshiro512PlusPlus::seed_from_u64(0);
+        let items = [(1.0, 0), (3.0, 1), (0.0, 2), (6.0, 3)];
+        let mut counts = [0i32; 4];
+        for _ in 0..10_000 {
+            counts[*weighted_choice(&items, &mut rng).unwrap()] += 1;
+        }
+
+        assert_eq!(counts[2], 0);
+        for (count, expected) in [(counts[0], 1_000), (counts[1], 3_000), (counts[3], 6_000)] {
+            assert!((count - expected).abs() < 200, "{counts:?}");
+        }
+    }
+
+    #[test]
+    fn test_single_positive_weight() {
+        let mut rng = Xoshiro512PlusPlus::seed_from_u64(0);
+        let items = [(0.0, "never"), (0.5, "always"), (0.0, "never")];
+        for _ in 0..100 {
+            assert_eq!(weighted_choice(&items, &mut rng), Ok(&"always"));
+        }
+    }
+
+    #[test]
+    fn test_invalid_weights() {
+        let mut rng = Xoshiro512PlusPlus::seed_from_u64(0);
+        let no_items: [(f64, ()); 0] = [];
+        assert_eq!(
+            weighted_choice(&no_items, &mut rng),
+            Err(WeightedChoiceError::NoItems)
+        );
+        assert_eq!(
+            weighted_choice(&[(0.0, ()), (0.0, ())], &mut rng),
+            Err(WeightedChoiceError::AllWeightsZero)
+        );
+        assert_eq!(
+            weighted_choice(&[(1.0, ()), (-1.0, ())], &mut rng),
+            Err(WeightedChoiceError::IllegalWeight(-1.0))
+        );
+        assert_eq!(
+            weighted_choice(&[(f64::INFINITY, ())], &mut rng),
+            Err(WeightedChoiceError::IllegalWeight(f64::INFINITY))
+        );
+        assert!(matches!(
+            weighted_choice(&[(f64::NAN, ())], &mut rng),
+            Err(WeightedChoiceError::IllegalWeight(weight)) if weight.is_nan()
+        ));
+    }
+}